@@ -25,6 +25,10 @@ impl CSTestResult {
             message: self.message,
             exception: self.error_stack_trace,
             points: self.points,
+            // the C# runner does not report durations or capture output per test
+            duration_ms: None,
+            stdout: None,
+            stderr: None,
         }
     }
 }
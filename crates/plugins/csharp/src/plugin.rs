@@ -11,7 +11,7 @@ use std::{
     time::Duration,
 };
 use tmc_langs_framework::{
-    Archive, CommandError, ExerciseDesc, Language, LanguagePlugin, RunResult, RunStatus,
+    Archive, CommandError, ExerciseDesc, Language, LanguagePlugin, RunResult, RunStatus, Sandbox,
     StyleValidationResult, StyleValidationStrategy, TestDesc, TestResult, TmcCommand, TmcError,
     nom::{IResult, Parser, bytes, character, combinator, sequence},
     nom_language::error::VerboseError,
@@ -134,6 +134,7 @@ impl LanguagePlugin for CSharpPlugin {
     const DEFAULT_SANDBOX_IMAGE: &'static str = "eu.gcr.io/moocfi-public/tmc-sandbox-csharp:latest";
     const LINE_COMMENT: &'static str = "//";
     const BLOCK_COMMENT: Option<(&'static str, &'static str)> = Some(("/*", "*/"));
+    const SOURCE_FILE_EXTENSIONS: &'static [&'static str] = &["cs"];
     type StudentFilePolicy = CSharpStudentFilePolicy;
 
     fn is_exercise_type_correct(path: &Path) -> bool {
@@ -222,10 +223,14 @@ impl LanguagePlugin for CSharpPlugin {
     }
 
     /// Runs --run-tests and parses the resulting .tmc_test_results.json.
+    ///
+    /// The bundled tmc-csharp-runner only supports --generate-points-file and --run-tests and cannot select the tests to run,
+    /// so filtered runs use the default run_tests_filtered_with_timeout that runs all of the tests and removes the results that were not selected.
     fn run_tests_with_timeout(
         &self,
         path: &Path,
         timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
     ) -> Result<RunResult, TmcError> {
         // clean old file
         let test_results_path = path.join(".tmc_test_results.json");
@@ -235,7 +240,7 @@ impl LanguagePlugin for CSharpPlugin {
 
         // run command
         let bootstrap_path = Self::get_bootstrap_path()?;
-        let command = TmcCommand::piped("dotnet")
+        let command = TmcCommand::piped_in("dotnet", sandbox)
             .with(|e| e.cwd(path).arg(bootstrap_path).arg("--run-tests"));
         let output = if let Some(timeout) = timeout {
             command.output_with_timeout(timeout)
@@ -257,6 +262,7 @@ impl LanguagePlugin for CSharpPlugin {
                         status: RunStatus::CompileFailed,
                         test_results: vec![],
                         logs,
+                        execution_stats: Some(output.stats),
                     });
                 }
 
@@ -281,9 +287,15 @@ impl LanguagePlugin for CSharpPlugin {
                     status,
                     test_results,
                     logs,
+                    execution_stats: Some(output.stats),
                 })
             }
-            Err(TmcError::Command(CommandError::TimeOut { stdout, stderr, .. })) => {
+            Err(TmcError::Command(CommandError::TimeOut {
+                stdout,
+                stderr,
+                stats,
+                ..
+            })) => {
                 let mut logs = HashMap::new();
                 logs.insert("stdout".to_string(), stdout);
                 logs.insert("stderr".to_string(), stderr);
@@ -297,8 +309,12 @@ impl LanguagePlugin for CSharpPlugin {
                         "Tests timed out.\nMake sure you don't have an infinite loop in your code."
                             .to_string(),
                     exception: vec![],
+                    duration_ms: None,
+                    stdout: None,
+                    stderr: None,
                 }],
                     logs,
+                    execution_stats: Some(stats),
                 })
             }
             Err(error) => Err(error),
@@ -386,6 +402,7 @@ mod test {
     use once_cell::sync::Lazy;
     use std::sync::{Mutex, Once};
     use tempfile::TempDir;
+    use tmc_langs_framework::TestFilter;
     use zip::write::SimpleFileOptions;
 
     static INIT_RUNNER: Once = Once::new();
@@ -619,6 +636,26 @@ mod test {
         assert!(res.logs.get("stderr").unwrap().is_empty());
     }
 
+    #[test]
+    fn runs_tests_filtered() {
+        init();
+        let _lock = MUTEX.lock().unwrap();
+
+        let temp = dir_to_temp("tests/data/partially-passing");
+        let plugin = CSharpPlugin::new();
+        let filter = TestFilter::new(vec![], vec!["1.2".to_string()]);
+        let res = plugin.run_tests_filtered(temp.path(), &filter).unwrap();
+        assert_eq!(res.status, RunStatus::Passed);
+        assert_eq!(res.test_results.len(), 1);
+        assert!(res.test_results[0].name.contains("ReturnsString"));
+
+        let filter = TestFilter::new(vec!["TestReturnsTrue".to_string()], vec![]);
+        let res = plugin.run_tests_filtered(temp.path(), &filter).unwrap();
+        assert_eq!(res.status, RunStatus::TestsFailed);
+        assert_eq!(res.test_results.len(), 1);
+        assert!(!res.test_results[0].successful);
+    }
+
     #[test]
     fn runs_tests_compile_err() {
         init();
@@ -646,7 +683,7 @@ mod test {
         let temp = dir_to_temp("tests/data/passing-exercise");
         let plugin = CSharpPlugin::new();
         let res = plugin
-            .run_tests_with_timeout(temp.path(), Some(std::time::Duration::from_nanos(1)))
+            .run_tests_with_timeout(temp.path(), Some(std::time::Duration::from_nanos(1)), None)
             .unwrap();
         assert_eq!(res.status, RunStatus::TestsFailed);
     }
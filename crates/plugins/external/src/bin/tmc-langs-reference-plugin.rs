@@ -0,0 +1,194 @@
+//! A reference implementation of the external plugin protocol, used to test the protocol end to end.
+//!
+//! Exercises for this plugin contain a `reference.tests` file with one test per line in the format
+//! `name | points | file | expected contents`, for example `adds numbers | 1.1 1.2 | src/answer.txt | 42`.
+//! A test passes if the file exists and its trimmed contents equal the expected contents.
+//! All files in `src` are student files, and `.txt` files use `#` for comments.
+
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+use tmc_langs_external::protocol::{
+    PROTOCOL_VERSION, PluginInfo, Request, RequestMessage, Response,
+};
+use tmc_langs_framework::{
+    CommentSyntax, ExerciseDesc, RunResult, RunStatus, TestDesc, TestResult,
+};
+use tmc_langs_util::{deserialize, serialize};
+
+const TESTS_FILE: &str = "reference.tests";
+const RUN_LOG_FILE: &str = ".reference-run.log";
+
+fn main() {
+    let mut input = String::new();
+    if let Err(err) = std::io::stdin().read_to_string(&mut input) {
+        respond::<()>(Err(format!("failed to read request: {err}")));
+        return;
+    }
+    let message: RequestMessage = match deserialize::json_from_str(&input) {
+        Ok(message) => message,
+        Err(err) => {
+            respond::<()>(Err(format!("invalid request: {err}")));
+            return;
+        }
+    };
+    if message.protocol_version != PROTOCOL_VERSION {
+        respond::<()>(Err(format!(
+            "unsupported protocol version {}",
+            message.protocol_version
+        )));
+        return;
+    }
+
+    match message.request {
+        Request::Info => respond(Ok(PluginInfo {
+            name: "reference".to_string(),
+            protocol_version: PROTOCOL_VERSION,
+            default_sandbox_image: "eu.gcr.io/moocfi-public/tmc-sandbox-python:latest".to_string(),
+            source_file_extensions: vec!["txt".to_string()],
+            comment_syntax: CommentSyntax::new(Some("#"), None),
+        })),
+        Request::IsExerciseTypeCorrect { path } => respond(Ok(path.join(TESTS_FILE).is_file())),
+        Request::FindProjectDir { files } => {
+            let project_dir = files
+                .iter()
+                .filter(|f| f.file_name() == Some(TESTS_FILE.as_ref()))
+                .filter_map(|f| f.parent())
+                .min_by_key(|p| p.components().count())
+                .map(Path::to_path_buf);
+            respond(Ok(project_dir))
+        }
+        Request::ScanExercise {
+            path,
+            exercise_name,
+        } => respond(read_tests(&path).map(|tests| {
+            ExerciseDesc {
+                name: exercise_name,
+                tests: tests
+                    .into_iter()
+                    .map(|t| TestDesc {
+                        name: t.name,
+                        points: t.points,
+                    })
+                    .collect(),
+            }
+        })),
+        Request::RunTests { path, .. } => respond(run_tests(&path)),
+        Request::Clean { path } => {
+            let log = path.join(RUN_LOG_FILE);
+            let result = if log.exists() {
+                std::fs::remove_file(log).map_err(|e| e.to_string())
+            } else {
+                Ok(())
+            };
+            respond(result)
+        }
+        Request::StudentFiles { files, .. } => {
+            let student_files = files
+                .into_iter()
+                .filter(|f| f.starts_with("src"))
+                .collect::<Vec<_>>();
+            respond(Ok(student_files))
+        }
+        Request::AvailablePoints { path } => respond(read_tests(&path).map(|tests| {
+            let mut points = vec![];
+            for point in tests.into_iter().flat_map(|t| t.points) {
+                if !points.contains(&point) {
+                    points.push(point);
+                }
+            }
+            points
+        })),
+    }
+}
+
+struct ReferenceTest {
+    name: String,
+    points: Vec<String>,
+    file: PathBuf,
+    expected: String,
+}
+
+fn read_tests(path: &Path) -> Result<Vec<ReferenceTest>, String> {
+    let contents = std::fs::read_to_string(path.join(TESTS_FILE))
+        .map_err(|e| format!("failed to read {TESTS_FILE}: {e}"))?;
+    let mut tests = vec![];
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parts = line.split('|').map(str::trim).collect::<Vec<_>>();
+        let [name, points, file, expected] = parts.as_slice() else {
+            return Err(format!("invalid test line: {line}"));
+        };
+        tests.push(ReferenceTest {
+            name: name.to_string(),
+            points: points.split_whitespace().map(str::to_string).collect(),
+            file: PathBuf::from(file),
+            expected: expected.to_string(),
+        });
+    }
+    Ok(tests)
+}
+
+fn run_tests(path: &Path) -> Result<RunResult, String> {
+    let tests = read_tests(path)?;
+    let mut status = RunStatus::Passed;
+    let mut test_results = vec![];
+    let mut log = String::new();
+    for test in tests {
+        let message = match std::fs::read_to_string(path.join(&test.file)) {
+            Ok(contents) if contents.trim() == test.expected => None,
+            Ok(contents) => Some(format!(
+                "expected {} to contain '{}', found '{}'",
+                test.file.display(),
+                test.expected,
+                contents.trim()
+            )),
+            Err(err) => Some(format!("failed to read {}: {err}", test.file.display())),
+        };
+        if message.is_some() {
+            status = RunStatus::TestsFailed;
+        }
+        log.push_str(&format!(
+            "{}: {}\n",
+            test.name,
+            if message.is_none() { "ok" } else { "failed" }
+        ));
+        test_results.push(TestResult {
+            name: test.name,
+            successful: message.is_none(),
+            points: test.points,
+            message: message.unwrap_or_default(),
+            exception: vec![],
+            duration_ms: None,
+            stdout: None,
+            stderr: None,
+        });
+    }
+    std::fs::write(path.join(RUN_LOG_FILE), &log).map_err(|e| e.to_string())?;
+
+    let mut logs = HashMap::new();
+    logs.insert("stdout".to_string(), log);
+    Ok(RunResult {
+        status,
+        test_results,
+        logs,
+        execution_stats: None,
+    })
+}
+
+fn respond<T: Serialize>(result: Result<T, String>) {
+    let response = match result {
+        Ok(result) => Response::Result(result),
+        Err(error) => Response::Error(error),
+    };
+    let json = serialize::to_json_vec(&response).expect("responses are always serializable");
+    std::io::stdout()
+        .write_all(&json)
+        .expect("failed to write response");
+}
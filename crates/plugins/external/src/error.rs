@@ -0,0 +1,43 @@
+//! External plugin error type
+
+use std::path::PathBuf;
+use thiserror::Error;
+use tmc_langs_framework::TmcError;
+use tmc_langs_util::{FileError, JsonError};
+
+#[derive(Debug, Error)]
+pub enum ExternalError {
+    #[error("Failed to serialize request for plugin {0}")]
+    SerializeRequest(PathBuf, #[source] JsonError),
+    #[error("Plugin {0} sent an invalid response: {1}")]
+    InvalidResponse(PathBuf, String, #[source] JsonError),
+    #[error("Plugin {0} returned an error: {1}")]
+    Plugin(PathBuf, String),
+    #[error(
+        "Plugin {plugin} uses protocol version {version}, but only version {supported} is supported"
+    )]
+    UnsupportedProtocolVersion {
+        plugin: PathBuf,
+        version: u32,
+        supported: u32,
+    },
+
+    #[error("File IO error")]
+    FileError(#[from] FileError),
+    #[error("Error")]
+    Tmc(#[from] TmcError),
+}
+
+// conversion from plugin error to TmcError::Plugin
+impl From<ExternalError> for TmcError {
+    fn from(err: ExternalError) -> TmcError {
+        TmcError::Plugin(Box::new(err))
+    }
+}
+
+// conversion from plugin error to a tmc result
+impl<T> From<ExternalError> for Result<T, TmcError> {
+    fn from(from: ExternalError) -> Self {
+        Err(TmcError::Plugin(Box::new(from)))
+    }
+}
@@ -0,0 +1,14 @@
+#![deny(clippy::print_stdout, clippy::print_stderr, clippy::unwrap_used)]
+
+//! Support for language plugins that run as separate executables, see the README for the protocol.
+
+mod error;
+mod plugin;
+mod policy;
+pub mod protocol;
+
+pub use self::{
+    error::ExternalError,
+    plugin::{ExternalPlugin, PLUGIN_DIR_VAR, installed_plugins},
+    policy::ExternalStudentFilePolicy,
+};
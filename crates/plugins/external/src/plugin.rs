@@ -0,0 +1,578 @@
+//! Contains the external plugin and its discovery.
+
+use crate::{
+    ExternalStudentFilePolicy,
+    error::ExternalError,
+    protocol::{PROTOCOL_VERSION, PluginInfo, Request, RequestMessage, Response},
+};
+use serde::de::DeserializeOwned;
+use std::{
+    collections::HashMap,
+    env,
+    io::{Read, Seek},
+    ops::ControlFlow::{Break, Continue},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::{Duration, SystemTime},
+};
+use tmc_langs_framework::{
+    Archive, CommandError, CommentSyntax, Compression, ExecutionStats, ExerciseDesc,
+    ExercisePackagingConfiguration, Language, RunResult, RunStatus, Sandbox, StyleValidationResult,
+    TestFilter, TestResult, TmcCommand, TmcError, extract_project_with_policy,
+    extract_student_files_with_policy, fail_if_no_tests,
+    get_exercise_packaging_configuration_with_policy, safe_find_project_dir_in_archive_with,
+    tests_timeout,
+};
+use tmc_langs_util::{deserialize, file_util, serialize};
+
+/// Overrides the directory external plugins are loaded from.
+pub const PLUGIN_DIR_VAR: &str = "TMC_LANGS_PLUGIN_DIR";
+
+/// Returns the plugins installed in the plugin directory.
+/// The directory is read from the TMC_LANGS_PLUGIN_DIR environment variable, and defaults to `tmc/plugins` in the local data directory.
+/// The plugins are discovered the first time this function is called and the same plugins are returned for the rest of the process.
+pub fn installed_plugins() -> &'static [ExternalPlugin] {
+    static PLUGINS: OnceLock<Vec<ExternalPlugin>> = OnceLock::new();
+    PLUGINS.get_or_init(|| {
+        let plugin_dir = match env::var(PLUGIN_DIR_VAR) {
+            Ok(v) => PathBuf::from(v),
+            Err(_) => match dirs::data_local_dir() {
+                Some(data_dir) => data_dir.join("tmc").join("plugins"),
+                None => return vec![],
+            },
+        };
+        ExternalPlugin::discover(&plugin_dir)
+    })
+}
+
+/// A language plugin that runs as a separate executable and communicates with tmc-langs using the protocol defined in `protocol`.
+/// Each request starts a new process for the executable.
+#[derive(Debug)]
+pub struct ExternalPlugin {
+    executable: PathBuf,
+    info: PluginInfo,
+    /// The answers to `is_exercise_type_correct`, keyed by the directory and its modification time
+    /// so that adding or removing files in the directory invalidates the answer.
+    exercise_types: Mutex<HashMap<(PathBuf, SystemTime), bool>>,
+}
+
+impl ExternalPlugin {
+    /// Loads the plugin at the given path by requesting its info.
+    pub fn load(executable: &Path) -> Result<Self, ExternalError> {
+        let info: PluginInfo = Self::send(executable, Request::Info, None)?;
+        if info.protocol_version != PROTOCOL_VERSION {
+            return Err(ExternalError::UnsupportedProtocolVersion {
+                plugin: executable.to_path_buf(),
+                version: info.protocol_version,
+                supported: PROTOCOL_VERSION,
+            });
+        }
+        log::debug!(
+            "loaded external plugin {} from {}",
+            info.name,
+            executable.display()
+        );
+        Ok(Self {
+            executable: executable.to_path_buf(),
+            info,
+            exercise_types: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Loads all the executables in the given directory as plugins, ordered by file name.
+    /// Executables that fail to load are skipped with a warning.
+    pub fn discover(plugin_dir: &Path) -> Vec<Self> {
+        let Ok(entries) = file_util::read_dir(plugin_dir) else {
+            log::debug!("no plugin directory at {}", plugin_dir.display());
+            return vec![];
+        };
+        let mut executables = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| is_executable(p))
+            .collect::<Vec<_>>();
+        executables.sort();
+
+        let mut plugins = vec![];
+        for executable in executables {
+            match Self::load(&executable) {
+                Ok(plugin) => plugins.push(plugin),
+                Err(err) => log::warn!(
+                    "Failed to load external plugin {}: {err}",
+                    executable.display()
+                ),
+            }
+        }
+        plugins
+    }
+
+    pub fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    pub fn executable(&self) -> &Path {
+        &self.executable
+    }
+
+    pub fn default_sandbox_image(&self) -> &str {
+        &self.info.default_sandbox_image
+    }
+
+    /// The extensions of the plugin's source files, which are parsed for annotations with `comment_syntax`.
+    pub fn source_file_extensions(&self) -> &[String] {
+        &self.info.source_file_extensions
+    }
+
+    pub fn comment_syntax(&self) -> &CommentSyntax {
+        &self.info.comment_syntax
+    }
+
+    /// Checks if the plugin recognizes the directory as an exercise. Errors are logged and treated as a negative answer.
+    /// The answer is cached until the directory is modified, as detecting the type of an exercise is done often.
+    pub fn is_exercise_type_correct(&self, path: &Path) -> bool {
+        let key = path
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .map(|modified| (path.to_path_buf(), modified));
+        if let Some(key) = &key {
+            let exercise_types = self
+                .exercise_types
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if let Some(correct) = exercise_types.get(key) {
+                return *correct;
+            }
+        }
+
+        let request = Request::IsExerciseTypeCorrect {
+            path: path.to_path_buf(),
+        };
+        let correct = match self.request(request, None) {
+            Ok(correct) => correct,
+            Err(err) => {
+                log::warn!("{err}");
+                return false;
+            }
+        };
+        // directories that do not exist are not cached, they may be created later
+        if let Some(key) = key {
+            self.exercise_types
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .insert(key, correct);
+        }
+        correct
+    }
+
+    /// Sends the paths of the files in the archive to the plugin to find the project directory.
+    pub fn find_project_dir_in_archive<R: Read + Seek>(
+        &self,
+        archive: &mut Archive<R>,
+    ) -> Result<PathBuf, TmcError> {
+        let files = archive_files(archive)?;
+        self.find_project_dir(files)
+    }
+
+    fn find_project_dir(&self, files: Vec<PathBuf>) -> Result<PathBuf, TmcError> {
+        let project_dir: Option<PathBuf> = self.request(Request::FindProjectDir { files }, None)?;
+        project_dir.ok_or(TmcError::NoProjectDirInArchive)
+    }
+
+    pub fn safe_find_project_dir_in_archive<R: Read + Seek>(
+        &self,
+        archive: &mut Archive<R>,
+    ) -> PathBuf {
+        safe_find_project_dir_in_archive_with(archive, |archive| {
+            self.find_project_dir_in_archive(archive)
+        })
+    }
+
+    pub fn is_archive_type_correct<R: Read + Seek>(&self, archive: &mut Archive<R>) -> bool {
+        self.find_project_dir_in_archive(archive).is_ok()
+    }
+
+    pub fn scan_exercise(
+        &self,
+        path: &Path,
+        exercise_name: String,
+    ) -> Result<ExerciseDesc, TmcError> {
+        let request = Request::ScanExercise {
+            path: path.to_path_buf(),
+            exercise_name,
+        };
+        Ok(self.request(request, None)?)
+    }
+
+    /// Runs the tests with the timeout from the project config.
+    /// Like with the built-in plugins, a run without any test results is considered a failure.
+    pub fn run_tests(&self, path: &Path) -> Result<RunResult, TmcError> {
+        self.run_tests_in_sandbox(path, &TestFilter::default(), None)
+    }
+
+    /// Runs the tests and removes the results of the tests that were not selected by the filter,
+    /// because the protocol has no way to select the tests to run.
+    pub fn run_tests_filtered(
+        &self,
+        path: &Path,
+        filter: &TestFilter,
+    ) -> Result<RunResult, TmcError> {
+        self.run_tests_in_sandbox(path, filter, None)
+    }
+
+    /// Runs the tests like `run_tests_filtered`, with the plugin executable in the sandbox if one is given.
+    pub fn run_tests_in_sandbox(
+        &self,
+        path: &Path,
+        filter: &TestFilter,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        let timeout = tests_timeout::<ExternalStudentFilePolicy>(path)?;
+        let result = self.run_tests_with_timeout(path, timeout, sandbox)?;
+        if filter.is_empty() {
+            Ok(fail_if_no_tests(result, "No tests found."))
+        } else {
+            let result = filter.filter_run_result(result);
+            Ok(fail_if_no_tests(result, "No tests matched the filter."))
+        }
+    }
+
+    pub fn run_tests_with_timeout(
+        &self,
+        path: &Path,
+        timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        let request = Request::RunTests {
+            path: path.to_path_buf(),
+            timeout_ms: timeout.map(|t| t.as_millis().try_into().unwrap_or(u64::MAX)),
+        };
+        match Self::send_with_stats::<RunResult>(&self.executable, request, timeout, sandbox) {
+            Ok((mut run_result, stats)) => {
+                // plugins may report their own stats for the process that actually ran the tests
+                if run_result.execution_stats.is_none() {
+                    run_result.execution_stats = Some(stats);
+                }
+                Ok(run_result)
+            }
+            Err(ExternalError::Tmc(TmcError::Command(CommandError::TimeOut {
+                stdout,
+                stderr,
+                stats,
+                ..
+            }))) => {
+                let mut logs = HashMap::new();
+                logs.insert("stdout".to_string(), stdout);
+                logs.insert("stderr".to_string(), stderr);
+                Ok(RunResult {
+                    status: RunStatus::TestsFailed,
+                    test_results: vec![TestResult {
+                        name: "Timeout test".to_string(),
+                        successful: false,
+                        points: vec![],
+                        message:
+                            "Tests timed out.\nMake sure you don't have an infinite loop in your code."
+                                .to_string(),
+                        exception: vec![],
+                        duration_ms: None,
+                        stdout: None,
+                        stderr: None,
+                    }],
+                    logs,
+                    execution_stats: Some(stats),
+                })
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Code style checks are not part of the protocol.
+    pub fn check_code_style(
+        &self,
+        _path: &Path,
+        _locale: Language,
+    ) -> Result<Option<StyleValidationResult>, TmcError> {
+        Ok(None)
+    }
+
+    pub fn clean(&self, path: &Path) -> Result<(), TmcError> {
+        let request = Request::Clean {
+            path: path.to_path_buf(),
+        };
+        Ok(self.request(request, None)?)
+    }
+
+    /// Returns the given files that the plugin considers to be student files. The files should be relative to the project directory.
+    pub fn student_files(
+        &self,
+        path: &Path,
+        files: Vec<PathBuf>,
+    ) -> Result<Vec<PathBuf>, ExternalError> {
+        let request = Request::StudentFiles {
+            path: path.to_path_buf(),
+            files,
+        };
+        self.request(request, None)
+    }
+
+    pub fn student_file_policy(
+        &'static self,
+        path: &Path,
+    ) -> Result<ExternalStudentFilePolicy, TmcError> {
+        ExternalStudentFilePolicy::with_plugin(self, path)
+    }
+
+    pub fn get_available_points(&self, exercise_path: &Path) -> Result<Vec<String>, TmcError> {
+        let request = Request::AvailablePoints {
+            path: exercise_path.to_path_buf(),
+        };
+        Ok(self.request(request, None)?)
+    }
+
+    pub fn get_exercise_packaging_configuration(
+        &'static self,
+        path: &Path,
+    ) -> Result<ExercisePackagingConfiguration, TmcError> {
+        let policy = self.student_file_policy(path)?;
+        get_exercise_packaging_configuration_with_policy(path, &policy)
+    }
+
+    /// The files in the archive are classified with a single request instead of one request per file.
+    pub fn extract_project<R: Read + Seek>(
+        &'static self,
+        archive: &mut Archive<R>,
+        target_location: &Path,
+        clean: bool,
+    ) -> Result<(), TmcError> {
+        let files = archive_files(archive)?;
+        let project_dir = self.find_project_dir(files.clone())?;
+        extract_project_with_policy(archive, target_location, clean, &project_dir, |target| {
+            let policy = self.student_file_policy(target)?;
+            policy.classify(relative_files(files, &project_dir))?;
+            Ok(policy)
+        })
+    }
+
+    /// The files in the archive are classified with a single request instead of one request per file.
+    pub fn extract_student_files(
+        &'static self,
+        compressed_project: impl Read + Seek,
+        compression: Compression,
+        target_location: &Path,
+    ) -> Result<(), TmcError> {
+        let mut archive = Archive::new(compressed_project, compression)?;
+        let project_dir = self.safe_find_project_dir_in_archive(&mut archive);
+        let policy = self.student_file_policy(target_location)?;
+        policy.classify(relative_files(archive_files(&mut archive)?, &project_dir))?;
+        extract_student_files_with_policy(&mut archive, &project_dir, target_location, &policy)
+    }
+
+    fn request<T: DeserializeOwned>(
+        &self,
+        request: Request,
+        timeout: Option<Duration>,
+    ) -> Result<T, ExternalError> {
+        Self::send(&self.executable, request, timeout)
+    }
+
+    fn send<T: DeserializeOwned>(
+        executable: &Path,
+        request: Request,
+        timeout: Option<Duration>,
+    ) -> Result<T, ExternalError> {
+        Self::send_with_stats(executable, request, timeout, None).map(|(result, _stats)| result)
+    }
+
+    // runs the executable with the request in stdin and parses the response from stdout
+    fn send_with_stats<T: DeserializeOwned>(
+        executable: &Path,
+        request: Request,
+        timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<(T, ExecutionStats), ExternalError> {
+        let message = RequestMessage {
+            protocol_version: PROTOCOL_VERSION,
+            request,
+        };
+        let message = serialize::to_json_string(&message)
+            .map_err(|e| ExternalError::SerializeRequest(executable.to_path_buf(), e))?;
+
+        let command = TmcCommand::piped_in(executable, sandbox).set_stdin_data(message);
+        let output = match timeout {
+            Some(timeout) => command.output_with_timeout(timeout)?,
+            None => command.output()?,
+        };
+        if !output.stderr.is_empty() {
+            log::debug!(
+                "{} stderr: {}",
+                executable.display(),
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let response: Response<T> = deserialize::json_from_str(&stdout).map_err(|e| {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            ExternalError::InvalidResponse(
+                executable.to_path_buf(),
+                format!("stdout: {stdout}\nstderr: {stderr}"),
+                e,
+            )
+        })?;
+        match response {
+            Response::Result(result) => Ok((result, output.stats)),
+            Response::Error(error) => Err(ExternalError::Plugin(executable.to_path_buf(), error)),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| ["exe", "bat", "cmd"].contains(&e.to_ascii_lowercase().as_str()))
+            .unwrap_or(false)
+}
+
+// the paths of all the files in the archive
+fn archive_files<R: Read + Seek>(archive: &mut Archive<R>) -> Result<Vec<PathBuf>, TmcError> {
+    let mut files = vec![];
+    let mut iter = archive.iter()?;
+    loop {
+        let next = iter.with_next::<(), _>(|file| {
+            files.push(file.path()?);
+            Ok(Continue(()))
+        });
+        match next? {
+            Continue(_) => continue,
+            Break(_) => break,
+        }
+    }
+    Ok(files)
+}
+
+// the files that are inside the project dir, relative to it
+fn relative_files(files: Vec<PathBuf>, project_dir: &Path) -> Vec<PathBuf> {
+    files
+        .into_iter()
+        .filter_map(|f| f.strip_prefix(project_dir).ok().map(Path::to_path_buf))
+        .filter(|f| !f.as_os_str().is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    fn init() {
+        use log::*;
+        use simple_logger::*;
+        let _ = SimpleLogger::new().with_level(LevelFilter::Debug).init();
+    }
+
+    #[test]
+    fn discovers_nothing_in_missing_dir() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        let plugins = ExternalPlugin::discover(&temp.path().join("missing"));
+        assert!(plugins.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skips_invalid_executables() {
+        use std::os::unix::fs::PermissionsExt;
+
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        // not executable
+        std::fs::write(temp.path().join("data.json"), "{}").unwrap();
+        // executable, but doesn't follow the protocol
+        let script = temp.path().join("plugin.sh");
+        std::fs::write(&script, "#!/bin/sh\necho hello\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let plugins = ExternalPlugin::discover(temp.path());
+        assert!(plugins.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_unsupported_protocol_version() {
+        use std::os::unix::fs::PermissionsExt;
+
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        let script = temp.path().join("plugin.sh");
+        std::fs::write(
+            &script,
+            r#"#!/bin/sh
+cat > /dev/null
+echo '{"result":{"name":"future","protocol_version":999,"default_sandbox_image":"image"}}'
+"#,
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let err = ExternalPlugin::load(&script).unwrap_err();
+        assert!(matches!(
+            err,
+            ExternalError::UnsupportedProtocolVersion { version: 999, .. }
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn caches_exercise_type_until_dir_changes() {
+        use std::os::unix::fs::PermissionsExt;
+
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        let requests = temp.path().join("requests");
+        let script = temp.path().join("plugin.sh");
+        std::fs::write(
+            &script,
+            format!(
+                r#"#!/bin/sh
+request=$(cat)
+echo "$request" >> {}
+case "$request" in
+    *'"request":"info"'*) echo '{{"result":{{"name":"counting","protocol_version":1,"default_sandbox_image":"image"}}}}' ;;
+    *) echo '{{"result":true}}' ;;
+esac
+"#,
+                requests.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let exercise = temp.path().join("exercise");
+        std::fs::create_dir(&exercise).unwrap();
+
+        let plugin = ExternalPlugin::load(&script).unwrap();
+        let request_count = || std::fs::read_to_string(&requests).unwrap().lines().count();
+        assert!(plugin.is_exercise_type_correct(&exercise));
+        assert!(plugin.is_exercise_type_correct(&exercise));
+        assert_eq!(request_count(), 2);
+
+        std::thread::sleep(Duration::from_millis(10));
+        std::fs::write(exercise.join("file"), "").unwrap();
+        assert!(plugin.is_exercise_type_correct(&exercise));
+        assert_eq!(request_count(), 3);
+    }
+}
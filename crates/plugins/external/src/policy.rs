@@ -0,0 +1,134 @@
+//! Contains the external plugin student file policy.
+
+use crate::ExternalPlugin;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use tmc_langs_framework::{StudentFilePolicy, TmcError, TmcProjectYml};
+use walkdir::WalkDir;
+
+/// Asks the plugin which files are student files. The existing files in the project are classified with a single request when the policy is created,
+/// other files are classified one at a time as needed.
+pub struct ExternalStudentFilePolicy {
+    project_config: TmcProjectYml,
+    plugin: Option<(&'static ExternalPlugin, PathBuf)>,
+    student_files: Mutex<HashMap<PathBuf, bool>>,
+}
+
+impl ExternalStudentFilePolicy {
+    /// Creates a policy for the project at the given path.
+    pub fn with_plugin(
+        plugin: &'static ExternalPlugin,
+        project_dir: &Path,
+    ) -> Result<Self, TmcError> {
+        let project_config = TmcProjectYml::load_or_default(project_dir)?;
+
+        // the project dir may not exist yet when extracting a project
+        let mut files = vec![];
+        if project_dir.exists() {
+            for entry in WalkDir::new(project_dir).min_depth(1) {
+                let entry = entry?;
+                let relative = entry
+                    .path()
+                    .strip_prefix(project_dir)
+                    .expect("all entries are inside the project dir");
+                files.push(relative.to_path_buf());
+            }
+        }
+        let student_files = if files.is_empty() {
+            vec![]
+        } else {
+            plugin.student_files(project_dir, files.clone())?
+        };
+        let student_files = files
+            .into_iter()
+            .map(|f| {
+                let is_student_file = student_files.contains(&f);
+                (f, is_student_file)
+            })
+            .collect();
+
+        Ok(Self {
+            project_config,
+            plugin: Some((plugin, project_dir.to_path_buf())),
+            student_files: Mutex::new(student_files),
+        })
+    }
+
+    /// Classifies the given files that have not been classified yet with a single request, so that checking them later does not need a request per file.
+    /// The files should be relative to the project directory.
+    pub fn classify(&self, files: Vec<PathBuf>) -> Result<(), TmcError> {
+        let Some((plugin, project_dir)) = &self.plugin else {
+            return Ok(());
+        };
+
+        let mut student_files = self
+            .student_files
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut files = files
+            .into_iter()
+            .filter(|f| !student_files.contains_key(f))
+            .collect::<Vec<_>>();
+        files.sort();
+        files.dedup();
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        let classified = plugin.student_files(project_dir, files.clone())?;
+        for file in files {
+            let is_student_file = classified.contains(&file);
+            student_files.insert(file, is_student_file);
+        }
+        Ok(())
+    }
+}
+
+impl StudentFilePolicy for ExternalStudentFilePolicy {
+    /// The policy needs to know the plugin, so a policy created with this constructor only considers the extra student files from the config to be student files.
+    /// `ExternalStudentFilePolicy::with_plugin` should be used instead.
+    fn new_with_project_config(project_config: TmcProjectYml) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            project_config,
+            plugin: None,
+            student_files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get_project_config(&self) -> &TmcProjectYml {
+        &self.project_config
+    }
+
+    fn is_non_extra_student_file(&self, path: &Path) -> bool {
+        let Some((plugin, project_dir)) = &self.plugin else {
+            return false;
+        };
+
+        let mut student_files = self
+            .student_files
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(is_student_file) = student_files.get(path) {
+            return *is_student_file;
+        }
+
+        let is_student_file = match plugin.student_files(project_dir, vec![path.to_path_buf()]) {
+            Ok(student_files) => student_files.iter().any(|f| f == path),
+            Err(err) => {
+                log::warn!(
+                    "Failed to check if {} is a student file: {err}",
+                    path.display()
+                );
+                false
+            }
+        };
+        student_files.insert(path.to_path_buf(), is_student_file);
+        is_student_file
+    }
+}
@@ -0,0 +1,159 @@
+//! Contains the messages exchanged with external plugins.
+//!
+//! Each request is sent to a new plugin process as a single JSON object on stdin, after which stdin is closed.
+//! The plugin writes a single JSON object to stdout as its response and exits.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tmc_langs_framework::CommentSyntax;
+
+/// The version of the protocol. Incremented on every incompatible change.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// A request and the version of the protocol it was sent with, e.g.
+/// `{"protocol_version":1,"request":"scan_exercise","path":"/ex","exercise_name":"ex"}`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RequestMessage {
+    pub protocol_version: u32,
+    #[serde(flatten)]
+    pub request: Request,
+}
+
+/// The requests that can be sent to an external plugin.
+/// All paths are absolute unless stated otherwise.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "request", rename_all = "snake_case")]
+pub enum Request {
+    /// Responded to with `PluginInfo`.
+    Info,
+    /// Responded to with a boolean.
+    IsExerciseTypeCorrect { path: PathBuf },
+    /// Contains the relative paths of all the files in an archive.
+    /// Responded to with the relative path of the project directory in the archive, or null if there is no project for the plugin in the archive.
+    FindProjectDir { files: Vec<PathBuf> },
+    /// Responded to with an `ExerciseDesc`.
+    ScanExercise {
+        path: PathBuf,
+        exercise_name: String,
+    },
+    /// Responded to with a `RunResult`. The timeout is enforced by killing the plugin process, it is included for plugins that can handle it more gracefully.
+    RunTests {
+        path: PathBuf,
+        timeout_ms: Option<u64>,
+    },
+    /// Responded to with null.
+    Clean { path: PathBuf },
+    /// Contains paths relative to the project directory, some of which may not exist yet.
+    /// Responded to with the paths that are student files.
+    /// The .tmcproject.yml options for extra student and exercise files are handled by tmc-langs.
+    StudentFiles { path: PathBuf, files: Vec<PathBuf> },
+    /// Responded to with a list of the points that are available in the exercise.
+    AvailablePoints { path: PathBuf },
+}
+
+/// The response to a request, e.g. `{"result":true}` or `{"error":"something went wrong"}`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Response<T> {
+    Result(T),
+    Error(String),
+}
+
+/// Information about a plugin, sent as a response to `Request::Info`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PluginInfo {
+    /// The name of the plugin, e.g. "reference".
+    pub name: String,
+    /// The protocol version the plugin implements.
+    pub protocol_version: u32,
+    /// The sandbox image used to run the tests on the server.
+    pub default_sandbox_image: String,
+    /// The extensions of the plugin's source files, e.g. "py", which are parsed for annotations with the comment syntax
+    /// unless the extension has a built-in comment syntax.
+    #[serde(default)]
+    pub source_file_extensions: Vec<String>,
+    /// The comment syntax used in the source files, e.g. `{"line":"#"}`.
+    #[serde(default)]
+    pub comment_syntax: CommentSyntax,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use tmc_langs_util::{deserialize, serialize};
+
+    #[test]
+    fn serializes_requests() {
+        let message = RequestMessage {
+            protocol_version: PROTOCOL_VERSION,
+            request: Request::Info,
+        };
+        assert_eq!(
+            serialize::to_json_string(&message).unwrap(),
+            r#"{"protocol_version":1,"request":"info"}"#
+        );
+
+        let message = RequestMessage {
+            protocol_version: PROTOCOL_VERSION,
+            request: Request::RunTests {
+                path: PathBuf::from("/ex"),
+                timeout_ms: Some(1000),
+            },
+        };
+        assert_eq!(
+            serialize::to_json_string(&message).unwrap(),
+            r#"{"protocol_version":1,"request":"run_tests","path":"/ex","timeout_ms":1000}"#
+        );
+    }
+
+    #[test]
+    fn deserializes_requests() {
+        let message: RequestMessage = deserialize::json_from_str(
+            r#"{"protocol_version":1,"request":"student_files","path":"/ex","files":["src/a.txt"]}"#,
+        )
+        .unwrap();
+        assert_eq!(message.protocol_version, 1);
+        assert_eq!(
+            message.request,
+            Request::StudentFiles {
+                path: PathBuf::from("/ex"),
+                files: vec![PathBuf::from("src/a.txt")],
+            }
+        );
+    }
+
+    #[test]
+    fn deserializes_responses() {
+        let response: Response<bool> = deserialize::json_from_str(r#"{"result":true}"#).unwrap();
+        assert!(matches!(response, Response::Result(true)));
+
+        let response: Response<()> = deserialize::json_from_str(r#"{"result":null}"#).unwrap();
+        assert!(matches!(response, Response::Result(())));
+
+        let response: Response<Option<PathBuf>> =
+            deserialize::json_from_str(r#"{"error":"oops"}"#).unwrap();
+        assert!(matches!(response, Response::Error(e) if e == "oops"));
+    }
+
+    #[test]
+    fn deserializes_info() {
+        let info: PluginInfo = deserialize::json_from_str(
+            r#"{"name":"p","protocol_version":1,"default_sandbox_image":"image","source_file_extensions":["lua"],"comment_syntax":{"line":"--","block":["--[[","]]"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(info.source_file_extensions, ["lua"]);
+        assert_eq!(
+            info.comment_syntax,
+            CommentSyntax::new(Some("--"), Some(("--[[", "]]")))
+        );
+
+        // the source files are optional
+        let info: PluginInfo = deserialize::json_from_str(
+            r#"{"name":"p","protocol_version":1,"default_sandbox_image":"image"}"#,
+        )
+        .unwrap();
+        assert!(info.source_file_extensions.is_empty());
+        assert_eq!(info.comment_syntax, CommentSyntax::default());
+    }
+}
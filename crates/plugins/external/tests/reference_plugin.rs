@@ -0,0 +1,181 @@
+//! Drives the external plugin protocol end to end with the reference plugin.
+
+use std::{
+    io::{Cursor, Write},
+    path::{Path, PathBuf},
+};
+use tmc_langs_external::ExternalPlugin;
+use tmc_langs_framework::{Archive, RunStatus, TestFilter};
+use zip::{ZipWriter, write::SimpleFileOptions};
+
+const REFERENCE_PLUGIN: &str = env!("CARGO_BIN_EXE_tmc-langs-reference-plugin");
+
+const TESTS: &str = "\
+# name | points | file | expected
+first | 1.1 | src/answer.txt | 42
+second | 1.1 1.2 | src/other.txt | hello
+";
+
+fn file_to(target_dir: &Path, target_relative: &str, contents: &str) -> PathBuf {
+    let target = target_dir.join(target_relative);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).unwrap();
+    }
+    std::fs::write(&target, contents).unwrap();
+    target
+}
+
+/// Installs the reference plugin into a plugin directory and loads it.
+fn load_plugin(plugin_dir: &Path) -> &'static ExternalPlugin {
+    let executable = Path::new(REFERENCE_PLUGIN);
+    std::fs::copy(executable, plugin_dir.join(executable.file_name().unwrap())).unwrap();
+    let mut plugins = ExternalPlugin::discover(plugin_dir);
+    assert_eq!(plugins.len(), 1);
+    Box::leak(Box::new(plugins.remove(0)))
+}
+
+fn exercise(dir: &Path) {
+    file_to(dir, "reference.tests", TESTS);
+    file_to(dir, "src/answer.txt", "41\n");
+    file_to(dir, "src/other.txt", "hello\n");
+    file_to(dir, "README.md", "readme");
+}
+
+#[test]
+fn loads_plugin_info() {
+    let plugin_dir = tempfile::tempdir().unwrap();
+    let plugin = load_plugin(plugin_dir.path());
+    assert_eq!(plugin.name(), "reference");
+    assert!(plugin.default_sandbox_image().contains("tmc-sandbox"));
+    assert_eq!(plugin.source_file_extensions(), ["txt"]);
+    assert_eq!(plugin.comment_syntax().line.as_deref(), Some("#"));
+}
+
+#[test]
+fn detects_and_scans_exercise() {
+    let plugin_dir = tempfile::tempdir().unwrap();
+    let plugin = load_plugin(plugin_dir.path());
+    let exercise_dir = tempfile::tempdir().unwrap();
+    exercise(exercise_dir.path());
+
+    assert!(plugin.is_exercise_type_correct(exercise_dir.path()));
+    assert!(!plugin.is_exercise_type_correct(&exercise_dir.path().join("src")));
+
+    let desc = plugin
+        .scan_exercise(exercise_dir.path(), "ex".to_string())
+        .unwrap();
+    assert_eq!(desc.name, "ex");
+    assert_eq!(desc.tests.len(), 2);
+    assert_eq!(desc.tests[1].name, "second");
+    assert_eq!(desc.tests[1].points, ["1.1", "1.2"]);
+
+    let points = plugin.get_available_points(exercise_dir.path()).unwrap();
+    assert_eq!(points, ["1.1", "1.2"]);
+}
+
+#[test]
+fn runs_tests_and_cleans() {
+    let plugin_dir = tempfile::tempdir().unwrap();
+    let plugin = load_plugin(plugin_dir.path());
+    let exercise_dir = tempfile::tempdir().unwrap();
+    exercise(exercise_dir.path());
+
+    let run_result = plugin.run_tests(exercise_dir.path()).unwrap();
+    assert_eq!(run_result.status, RunStatus::TestsFailed);
+    assert!(!run_result.test_results[0].successful);
+    assert!(run_result.test_results[0].message.contains("found '41'"));
+    assert!(run_result.test_results[1].successful);
+
+    file_to(exercise_dir.path(), "src/answer.txt", "42\n");
+    let run_result = plugin.run_tests(exercise_dir.path()).unwrap();
+    assert_eq!(run_result.status, RunStatus::Passed);
+    assert!(exercise_dir.path().join(".reference-run.log").exists());
+
+    plugin.clean(exercise_dir.path()).unwrap();
+    assert!(!exercise_dir.path().join(".reference-run.log").exists());
+}
+
+#[test]
+fn filters_test_results() {
+    let plugin_dir = tempfile::tempdir().unwrap();
+    let plugin = load_plugin(plugin_dir.path());
+    let exercise_dir = tempfile::tempdir().unwrap();
+    exercise(exercise_dir.path());
+
+    let filter = TestFilter::new(vec![], vec!["1.2".to_string()]);
+    let run_result = plugin
+        .run_tests_filtered(exercise_dir.path(), &filter)
+        .unwrap();
+    assert_eq!(run_result.status, RunStatus::Passed);
+    assert_eq!(run_result.test_results.len(), 1);
+    assert_eq!(run_result.test_results[0].name, "second");
+}
+
+#[test]
+fn classifies_student_files() {
+    let plugin_dir = tempfile::tempdir().unwrap();
+    let plugin = load_plugin(plugin_dir.path());
+    let exercise_dir = tempfile::tempdir().unwrap();
+    exercise(exercise_dir.path());
+    file_to(
+        exercise_dir.path(),
+        ".tmcproject.yml",
+        "extra_student_files:\n  - README.md\n",
+    );
+
+    let config = plugin
+        .get_exercise_packaging_configuration(exercise_dir.path())
+        .unwrap();
+    assert!(
+        config
+            .student_file_paths
+            .contains(Path::new("src/answer.txt"))
+    );
+    assert!(config.student_file_paths.contains(Path::new("README.md")));
+    assert!(
+        config
+            .exercise_file_paths
+            .contains(Path::new("reference.tests"))
+    );
+}
+
+#[test]
+fn extracts_project_from_archive() {
+    let plugin_dir = tempfile::tempdir().unwrap();
+    let plugin = load_plugin(plugin_dir.path());
+
+    let mut zip = ZipWriter::new(Cursor::new(vec![]));
+    for (path, contents) in [
+        ("outer/ex/reference.tests", TESTS),
+        ("outer/ex/src/answer.txt", "42\n"),
+        ("outer/ex/lib/helper.txt", "new helper"),
+    ] {
+        zip.start_file(path, SimpleFileOptions::default()).unwrap();
+        zip.write_all(contents.as_bytes()).unwrap();
+    }
+    let zip = zip.finish().unwrap().into_inner();
+
+    let mut archive = Archive::zip(Cursor::new(zip.clone())).unwrap();
+    assert_eq!(
+        plugin.find_project_dir_in_archive(&mut archive).unwrap(),
+        Path::new("outer/ex")
+    );
+
+    // student files are kept, exercise files are overwritten
+    let target = tempfile::tempdir().unwrap();
+    file_to(target.path(), "src/answer.txt", "student answer");
+    file_to(target.path(), "lib/helper.txt", "old helper");
+    let mut archive = Archive::zip(Cursor::new(zip)).unwrap();
+    plugin
+        .extract_project(&mut archive, target.path(), false)
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(target.path().join("src/answer.txt")).unwrap(),
+        "student answer"
+    );
+    assert_eq!(
+        std::fs::read_to_string(target.path().join("lib/helper.txt")).unwrap(),
+        "new helper"
+    );
+    assert!(target.path().join("reference.tests").exists());
+}
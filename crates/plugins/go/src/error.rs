@@ -0,0 +1,31 @@
+//! Go plugin error type
+
+use std::path::PathBuf;
+use thiserror::Error;
+use tmc_langs_framework::TmcError;
+use tmc_langs_util::FileError;
+
+#[derive(Debug, Error)]
+pub enum GoError {
+    #[error("No Go exercise found at {0}")]
+    NoExerciseFound(PathBuf),
+
+    #[error("File IO error")]
+    FileError(#[from] FileError),
+    #[error("Error")]
+    Tmc(#[from] TmcError),
+}
+
+// conversion from plugin error to TmcError::Plugin
+impl From<GoError> for TmcError {
+    fn from(err: GoError) -> TmcError {
+        TmcError::Plugin(Box::new(err))
+    }
+}
+
+// conversion from plugin error to a tmc result
+impl<T> From<GoError> for Result<T, TmcError> {
+    fn from(from: GoError) -> Self {
+        Err(TmcError::Plugin(Box::new(from)))
+    }
+}
@@ -0,0 +1,296 @@
+//! Contains types that model the output of `go test -json`.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use tmc_langs_framework::{RunStatus, TestResult};
+
+/// A single line of `go test -json` output.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GoTestEvent {
+    pub action: GoTestAction,
+    pub package: Option<String>,
+    pub test: Option<String>,
+    pub output: Option<String>,
+    /// Set on a package's fail event if the package failed to build (Go 1.24+).
+    pub failed_build: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GoTestAction {
+    Start,
+    Run,
+    Pause,
+    Cont,
+    Pass,
+    Fail,
+    Skip,
+    Output,
+    /// Compiler output (Go 1.24+). Older versions write it to stderr.
+    BuildOutput,
+    BuildFail,
+    #[serde(other)]
+    Other,
+}
+
+/// The test results collected from the output of a `go test -json` run.
+#[derive(Debug)]
+pub struct GoTestRun {
+    pub status: RunStatus,
+    pub test_results: Vec<TestResult>,
+    /// The output of the run as it would have been printed without -json, including any compiler output.
+    pub output: String,
+}
+
+impl GoTestRun {
+    /// Collects the results from the given events. The points map should contain a mapping from package import paths and test function names to a list of points, e.g.
+    /// ("example.com/exercise", "TestOne") => ["1.1", "1.2"].
+    /// Subtests are not reported separately, their output is included in the results of the top-level test instead.
+    pub fn from_events(
+        events: impl IntoIterator<Item = GoTestEvent>,
+        point_map: &HashMap<(String, String), Vec<String>>,
+    ) -> Self {
+        let mut build_failed = false;
+        let mut package_failed = false;
+        let mut output = String::new();
+        let mut tests: Vec<GoTest> = vec![];
+
+        for event in events {
+            if let Some(event_output) = &event.output {
+                output.push_str(event_output);
+            }
+            match (&event.action, &event.test) {
+                (GoTestAction::BuildFail, _) => build_failed = true,
+                (GoTestAction::Output, None) => {
+                    let event_output = event.output.as_deref().unwrap_or_default();
+                    // older versions of Go only report build failures in the output
+                    if event_output.contains("[build failed]")
+                        || event_output.contains("[setup failed]")
+                    {
+                        build_failed = true;
+                    }
+                }
+                (GoTestAction::Fail, None) => {
+                    if event.failed_build.is_some() {
+                        build_failed = true;
+                    }
+                    package_failed = true;
+                }
+                (action, Some(test_name)) => {
+                    let (top_level, is_subtest) = match test_name.split_once('/') {
+                        Some((top_level, _)) => (top_level, true),
+                        None => (test_name.as_str(), false),
+                    };
+                    // tests in different packages may have the same name
+                    let index = match tests
+                        .iter()
+                        .position(|t| t.package == event.package && t.name == top_level)
+                    {
+                        Some(index) => index,
+                        None => {
+                            tests.push(GoTest {
+                                package: event.package.clone(),
+                                name: top_level.to_string(),
+                                successful: None,
+                                output: vec![],
+                            });
+                            tests.len() - 1
+                        }
+                    };
+                    let test = &mut tests[index];
+                    match action {
+                        GoTestAction::Output => {
+                            if let Some(event_output) = event.output {
+                                test.output.push(event_output);
+                            }
+                        }
+                        GoTestAction::Pass if !is_subtest => test.successful = Some(true),
+                        GoTestAction::Fail if !is_subtest => test.successful = Some(false),
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut status = RunStatus::Passed;
+        let mut test_results = vec![];
+        for test in tests {
+            // tests that were skipped or never finished are not reported
+            let Some(successful) = test.successful else {
+                continue;
+            };
+            if !successful {
+                status = RunStatus::TestsFailed;
+            }
+
+            let message = if successful {
+                String::new()
+            } else {
+                // the status lines are left out, leaving the test's own output such as t.Errorf messages
+                test.output
+                    .iter()
+                    .flat_map(|o| o.lines())
+                    .filter(|l| {
+                        let trimmed = l.trim_start();
+                        !trimmed.starts_with("=== ")
+                            && !trimmed.starts_with("--- PASS")
+                            && !trimmed.starts_with("--- FAIL")
+                            && !trimmed.starts_with("--- SKIP")
+                    })
+                    .map(str::trim)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            let points = point_map
+                .get(&(test.package.unwrap_or_default(), test.name.clone()))
+                .cloned()
+                .unwrap_or_default();
+            test_results.push(TestResult {
+                name: test.name,
+                successful,
+                points,
+                message,
+                exception: vec![],
+                duration_ms: None,
+                stdout: None,
+                stderr: None,
+            });
+        }
+
+        if build_failed {
+            status = RunStatus::CompileFailed;
+        } else if package_failed && status == RunStatus::Passed {
+            // a package failed without any failing tests, e.g. due to os.Exit in a test
+            status = RunStatus::TestrunInterrupted;
+        }
+
+        Self {
+            status,
+            test_results,
+            output,
+        }
+    }
+}
+
+/// A top-level test and the output of it and its subtests.
+struct GoTest {
+    package: Option<String>,
+    name: String,
+    /// None if the test was skipped or did not finish.
+    successful: Option<bool>,
+    output: Vec<String>,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use tmc_langs_util::deserialize;
+
+    fn parse(events: &str) -> impl Iterator<Item = GoTestEvent> + '_ {
+        events
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(|l| deserialize::json_from_str::<GoTestEvent>(l).unwrap())
+    }
+
+    #[test]
+    fn parses_test_results() {
+        let events = r#"
+{"Time":"2024-01-01T00:00:00Z","Action":"start","Package":"example.com/ex"}
+{"Time":"2024-01-01T00:00:00Z","Action":"run","Package":"example.com/ex","Test":"TestAdd"}
+{"Time":"2024-01-01T00:00:00Z","Action":"output","Package":"example.com/ex","Test":"TestAdd","Output":"=== RUN   TestAdd\n"}
+{"Time":"2024-01-01T00:00:00Z","Action":"output","Package":"example.com/ex","Test":"TestAdd","Output":"--- PASS: TestAdd (0.00s)\n"}
+{"Time":"2024-01-01T00:00:00Z","Action":"pass","Package":"example.com/ex","Test":"TestAdd","Elapsed":0}
+{"Time":"2024-01-01T00:00:00Z","Action":"run","Package":"example.com/ex","Test":"TestSub"}
+{"Time":"2024-01-01T00:00:00Z","Action":"output","Package":"example.com/ex","Test":"TestSub","Output":"=== RUN   TestSub\n"}
+{"Time":"2024-01-01T00:00:00Z","Action":"run","Package":"example.com/ex","Test":"TestSub/negative"}
+{"Time":"2024-01-01T00:00:00Z","Action":"output","Package":"example.com/ex","Test":"TestSub/negative","Output":"=== RUN   TestSub/negative\n"}
+{"Time":"2024-01-01T00:00:00Z","Action":"output","Package":"example.com/ex","Test":"TestSub/negative","Output":"    sub_test.go:12: expected -3, got 0\n"}
+{"Time":"2024-01-01T00:00:00Z","Action":"output","Package":"example.com/ex","Test":"TestSub/negative","Output":"    --- FAIL: TestSub/negative (0.00s)\n"}
+{"Time":"2024-01-01T00:00:00Z","Action":"fail","Package":"example.com/ex","Test":"TestSub/negative","Elapsed":0}
+{"Time":"2024-01-01T00:00:00Z","Action":"output","Package":"example.com/ex","Test":"TestSub","Output":"--- FAIL: TestSub (0.00s)\n"}
+{"Time":"2024-01-01T00:00:00Z","Action":"fail","Package":"example.com/ex","Test":"TestSub","Elapsed":0}
+{"Time":"2024-01-01T00:00:00Z","Action":"output","Package":"example.com/ex","Output":"FAIL\n"}
+{"Time":"2024-01-01T00:00:00Z","Action":"output","Package":"example.com/ex","Output":"FAIL\texample.com/ex\t0.001s\n"}
+{"Time":"2024-01-01T00:00:00Z","Action":"fail","Package":"example.com/ex","Elapsed":0.001}
+"#;
+        let mut point_map = HashMap::new();
+        point_map.insert(
+            ("example.com/ex".to_string(), "TestAdd".to_string()),
+            vec!["1.1".to_string()],
+        );
+        point_map.insert(
+            ("example.com/ex".to_string(), "TestSub".to_string()),
+            vec!["1.2".to_string()],
+        );
+        let run = GoTestRun::from_events(parse(events), &point_map);
+
+        assert_eq!(run.status, RunStatus::TestsFailed);
+        assert_eq!(run.test_results.len(), 2);
+        assert_eq!(run.test_results[0].name, "TestAdd");
+        assert!(run.test_results[0].successful);
+        assert_eq!(run.test_results[0].points, &["1.1"]);
+        assert_eq!(run.test_results[1].name, "TestSub");
+        assert!(!run.test_results[1].successful);
+        assert_eq!(run.test_results[1].points, &["1.2"]);
+        assert_eq!(
+            run.test_results[1].message,
+            "sub_test.go:12: expected -3, got 0"
+        );
+        assert!(run.output.contains("--- FAIL: TestSub"));
+    }
+
+    #[test]
+    fn matches_points_by_package() {
+        let events = r#"
+{"Time":"2024-01-01T00:00:00Z","Action":"run","Package":"example.com/ex","Test":"TestAdd"}
+{"Time":"2024-01-01T00:00:00Z","Action":"pass","Package":"example.com/ex","Test":"TestAdd","Elapsed":0}
+{"Time":"2024-01-01T00:00:00Z","Action":"run","Package":"example.com/ex/sub","Test":"TestAdd"}
+{"Time":"2024-01-01T00:00:00Z","Action":"pass","Package":"example.com/ex/sub","Test":"TestAdd","Elapsed":0}
+"#;
+        let mut point_map = HashMap::new();
+        point_map.insert(
+            ("example.com/ex".to_string(), "TestAdd".to_string()),
+            vec!["1.1".to_string()],
+        );
+        point_map.insert(
+            ("example.com/ex/sub".to_string(), "TestAdd".to_string()),
+            vec!["2.1".to_string()],
+        );
+        let run = GoTestRun::from_events(parse(events), &point_map);
+
+        assert_eq!(run.status, RunStatus::Passed);
+        assert_eq!(run.test_results.len(), 2);
+        assert_eq!(run.test_results[0].points, &["1.1"]);
+        assert_eq!(run.test_results[1].points, &["2.1"]);
+    }
+
+    #[test]
+    fn parses_build_failure() {
+        // Go 1.24+
+        let events = r##"
+{"ImportPath":"example.com/ex [example.com/ex.test]","Action":"build-output","Output":"# example.com/ex [example.com/ex.test]\n"}
+{"ImportPath":"example.com/ex [example.com/ex.test]","Action":"build-output","Output":"./add.go:4:14: syntax error: unexpected }\n"}
+{"ImportPath":"example.com/ex [example.com/ex.test]","Action":"build-fail"}
+{"Time":"2024-01-01T00:00:00Z","Action":"start","Package":"example.com/ex"}
+{"Time":"2024-01-01T00:00:00Z","Action":"output","Package":"example.com/ex","Output":"FAIL\texample.com/ex [build failed]\n"}
+{"Time":"2024-01-01T00:00:00Z","Action":"fail","Package":"example.com/ex","Elapsed":0,"FailedBuild":"example.com/ex [example.com/ex.test]"}
+"##;
+        let run = GoTestRun::from_events(parse(events), &HashMap::new());
+        assert_eq!(run.status, RunStatus::CompileFailed);
+        assert!(run.test_results.is_empty());
+        assert!(run.output.contains("syntax error"));
+
+        // older versions write the compiler output to stderr
+        let events = r#"
+{"Time":"2024-01-01T00:00:00Z","Action":"start","Package":"example.com/ex"}
+{"Time":"2024-01-01T00:00:00Z","Action":"output","Package":"example.com/ex","Output":"FAIL\texample.com/ex [build failed]\n"}
+{"Time":"2024-01-01T00:00:00Z","Action":"fail","Package":"example.com/ex","Elapsed":0}
+"#;
+        let run = GoTestRun::from_events(parse(events), &HashMap::new());
+        assert_eq!(run.status, RunStatus::CompileFailed);
+    }
+}
@@ -0,0 +1,10 @@
+#![deny(clippy::print_stdout, clippy::print_stderr, clippy::unwrap_used)]
+
+//! TMC language plugin for Go modules.
+
+mod error;
+mod go_test_result;
+mod plugin;
+mod policy;
+
+pub use self::{error::GoError, plugin::GoPlugin, policy::GoStudentFilePolicy};
@@ -0,0 +1,791 @@
+//! Contains the LanguagePlugin implementation for Go.
+
+use crate::{
+    error::GoError,
+    go_test_result::{GoTestEvent, GoTestRun},
+    policy::GoStudentFilePolicy,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    io::{Read, Seek},
+    ops::ControlFlow::{Break, Continue},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tmc_langs_framework::{
+    Archive, CommandError, ExerciseDesc, LanguagePlugin, RunResult, RunStatus, Sandbox, TestDesc,
+    TestResult, TmcCommand, TmcError,
+    nom::{self, IResult, Parser, branch, bytes, character, combinator, multi, sequence},
+    nom_language::error::VerboseError,
+};
+use tmc_langs_util::{deserialize, file_util, parse_util, path_util};
+
+#[derive(Default)]
+pub struct GoPlugin {}
+
+impl GoPlugin {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Scans the exercise's test files for the parts that are relevant to test and point detection.
+    /// The scans are grouped by the relative path of the test file.
+    fn scan_test_files(path: &Path) -> Result<Vec<(PathBuf, Vec<Scan>)>, TmcError> {
+        let config = Self::get_exercise_packaging_configuration(path)?;
+
+        let mut test_file_paths = config
+            .exercise_file_paths
+            .into_iter()
+            .filter(|p| {
+                !p.starts_with("vendor")
+                    && p.file_name()
+                        .and_then(|n| n.to_str())
+                        .map(|n| n.ends_with("_test.go"))
+                        .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        test_file_paths.sort();
+
+        let mut scanned = vec![];
+        for relative_path in test_file_paths {
+            let test_file_path = path.join(&relative_path);
+            log::trace!("scanning tests from {}", test_file_path.display());
+            let file_contents = file_util::read_file_to_string_lossy(&test_file_path)?;
+
+            let mut parser = multi::many0(branch::alt((
+                combinator::map(comment_points_parser, |points| {
+                    Scan::CommentPoints(points.into_iter().map(str::to_string).collect())
+                }),
+                combinator::map(helper_points_parser, |points| {
+                    Scan::HelperPoints(points.into_iter().map(str::to_string).collect())
+                }),
+                // comments
+                combinator::value(
+                    Scan::Other,
+                    (
+                        bytes::complete::tag("//"),
+                        character::complete::not_line_ending,
+                    ),
+                ),
+                combinator::value(
+                    Scan::Other,
+                    (
+                        bytes::complete::tag("/*"),
+                        bytes::complete::take_until("*/"),
+                        bytes::complete::tag("*/"),
+                    ),
+                ),
+                // functions
+                combinator::map(
+                    sequence::preceded(
+                        (
+                            bytes::complete::tag("func"),
+                            character::complete::multispace1,
+                        ),
+                        identifier,
+                    ),
+                    |name| Scan::Function(name.to_string()),
+                ),
+                // whole identifiers are skipped so that func is only recognized at the start of a word
+                combinator::value(Scan::Other, identifier),
+                combinator::value(Scan::Other, character::complete::anychar),
+            )));
+
+            let res: IResult<_, _, VerboseError<&str>> = parser.parse(&file_contents);
+            match res {
+                Ok((_, file_scanned)) => scanned.push((relative_path, file_scanned)),
+                Err(nom::Err::Incomplete(_)) => unreachable!("this should never happen"),
+                Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                    return Err(TmcError::PointParse(
+                        test_file_path,
+                        VerboseError {
+                            errors: e
+                                .errors
+                                .into_iter()
+                                .map(|(s, k)| (s.to_string(), k))
+                                .collect(),
+                        },
+                    ));
+                }
+            };
+        }
+        Ok(scanned)
+    }
+
+    /// Finds the test functions and their points, along with the import path of the package each test is in.
+    /// A points comment applies to the function that follows it, while a tmc.Points call applies to the function it is in.
+    fn scan_tests(path: &Path) -> Result<Vec<(String, TestDesc)>, TmcError> {
+        let module = module_path(path)?;
+        let mut tests: Vec<(String, TestDesc)> = vec![];
+        for (test_file_path, scanned) in Self::scan_test_files(path)? {
+            let package = package_path(&module, &test_file_path);
+            let mut pending_points = vec![];
+            // whether the function currently being scanned is in tests
+            let mut in_test = false;
+            for scan in scanned {
+                match scan {
+                    Scan::CommentPoints(points) => pending_points.extend(points),
+                    Scan::HelperPoints(points) => {
+                        if let Some((_, test)) = tests.last_mut().filter(|_| in_test) {
+                            test.points.extend(points);
+                        }
+                    }
+                    Scan::Function(name) => {
+                        in_test = is_test_function(&name) || !pending_points.is_empty();
+                        if in_test {
+                            tests.push((
+                                package.clone(),
+                                TestDesc::new(name, std::mem::take(&mut pending_points)),
+                            ));
+                        }
+                    }
+                    Scan::Other => {}
+                }
+            }
+        }
+        Ok(tests)
+    }
+}
+
+/// Project directory:
+/// Contains a go.mod file.
+impl LanguagePlugin for GoPlugin {
+    const PLUGIN_NAME: &'static str = "go";
+    const DEFAULT_SANDBOX_IMAGE: &'static str = "eu.gcr.io/moocfi-public/tmc-sandbox-go:latest";
+    const LINE_COMMENT: &'static str = "//";
+    const BLOCK_COMMENT: Option<(&'static str, &'static str)> = Some(("/*", "*/"));
+    const SOURCE_FILE_EXTENSIONS: &'static [&'static str] = &["go"];
+    type StudentFilePolicy = GoStudentFilePolicy;
+
+    fn scan_exercise(&self, path: &Path, exercise_name: String) -> Result<ExerciseDesc, TmcError> {
+        if !Self::is_exercise_type_correct(path) {
+            return GoError::NoExerciseFound(path.to_path_buf()).into();
+        }
+
+        let tests = Self::scan_tests(path)?
+            .into_iter()
+            .map(|(_, test)| test)
+            .collect();
+        Ok(ExerciseDesc {
+            name: exercise_name,
+            tests,
+        })
+    }
+
+    /// Runs go test -json for all of the packages in the module.
+    fn run_tests_with_timeout(
+        &self,
+        path: &Path,
+        timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        // tests in different packages may have the same name
+        let mut point_map = HashMap::<(String, String), Vec<String>>::new();
+        for (package, test) in Self::scan_tests(path)? {
+            point_map
+                .entry((package, test.name))
+                .or_default()
+                .extend(test.points);
+        }
+
+        let command = TmcCommand::piped_in("go", sandbox)
+            .with(|e| e.cwd(path).args(&["test", "-json", "./..."]));
+        let output = if let Some(timeout) = timeout {
+            command.output_with_timeout(timeout)
+        } else {
+            command.output()
+        };
+
+        match output {
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                log::trace!("stdout: {stdout}");
+                log::debug!("stderr: {stderr}");
+
+                let events = stdout.lines().filter_map(|line| {
+                    match deserialize::json_from_str::<GoTestEvent>(line) {
+                        Ok(event) => Some(event),
+                        Err(_) => {
+                            log::trace!("skipping non-JSON line {line}");
+                            None
+                        }
+                    }
+                });
+                let run = GoTestRun::from_events(events, &point_map);
+
+                let mut logs = HashMap::new();
+                logs.insert("stdout".to_string(), run.output);
+                logs.insert("stderr".to_string(), stderr.into_owned());
+
+                let status = if !output.status.success()
+                    && run.status == RunStatus::Passed
+                    && run.test_results.is_empty()
+                {
+                    // go failed before running anything, e.g. due to an invalid go.mod
+                    RunStatus::CompileFailed
+                } else {
+                    run.status
+                };
+
+                // remove points associated with any failed tests
+                let mut test_results = run.test_results;
+                let mut failed_points = HashSet::new();
+                for test_result in &test_results {
+                    if !test_result.successful {
+                        failed_points.extend(test_result.points.iter().cloned());
+                    }
+                }
+                for test_result in &mut test_results {
+                    test_result.points.retain(|p| !failed_points.contains(p));
+                }
+
+                Ok(RunResult {
+                    status,
+                    test_results,
+                    logs,
+                    execution_stats: Some(output.stats),
+                })
+            }
+            Err(TmcError::Command(CommandError::TimeOut {
+                stdout,
+                stderr,
+                stats,
+                ..
+            })) => {
+                let mut logs = HashMap::new();
+                logs.insert("stdout".to_string(), stdout);
+                logs.insert("stderr".to_string(), stderr);
+                Ok(RunResult {
+                    status: RunStatus::TestsFailed,
+                    test_results: vec![TestResult {
+                        name: "Timeout test".to_string(),
+                        successful: false,
+                        points: vec![],
+                        message:
+                            "Tests timed out.\nMake sure you don't have an infinite loop in your code."
+                                .to_string(),
+                        exception: vec![],
+                        duration_ms: None,
+                        stdout: None,
+                        stderr: None,
+                    }],
+                    logs,
+                    execution_stats: Some(stats),
+                })
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Finds the shallowest directory with a go.mod file.
+    fn find_project_dir_in_archive<R: Read + Seek>(
+        archive: &mut Archive<R>,
+    ) -> Result<PathBuf, TmcError> {
+        let mut iter = archive.iter()?;
+        let mut shallowest_module_parent: Option<PathBuf> = None;
+        loop {
+            let next = iter.with_next::<(), _>(|file| {
+                let file_path = file.path()?;
+
+                if file.is_file()
+                    && !file_path
+                        .components()
+                        .any(|c| c.as_os_str() == "__MACOSX" || c.as_os_str() == "vendor")
+                {
+                    if let Some(parent) = path_util::get_parent_of_named(&file_path, "go.mod") {
+                        let is_shallower = shallowest_module_parent
+                            .as_ref()
+                            .map(|shallowest| {
+                                shallowest.components().count() > parent.components().count()
+                            })
+                            .unwrap_or(true);
+                        if is_shallower {
+                            shallowest_module_parent = Some(parent);
+                        }
+                    }
+                }
+                Ok(Continue(()))
+            });
+            match next? {
+                Continue(_) => continue,
+                Break(_) => break,
+            }
+        }
+
+        match shallowest_module_parent {
+            Some(project_dir) => Ok(project_dir),
+            None => Err(TmcError::NoProjectDirInArchive),
+        }
+    }
+
+    /// Checks if the directory contains a go.mod file.
+    fn is_exercise_type_correct(path: &Path) -> bool {
+        path.join("go.mod").is_file()
+    }
+
+    /// Runs go clean.
+    fn clean(&self, path: &Path) -> Result<(), TmcError> {
+        TmcCommand::piped("go")
+            .with(|e| e.cwd(path).arg("clean"))
+            .output_checked()?;
+        Ok(())
+    }
+
+    fn get_default_student_file_paths() -> Vec<PathBuf> {
+        vec![PathBuf::from(".")]
+    }
+
+    fn get_default_exercise_file_paths() -> Vec<PathBuf> {
+        vec![PathBuf::from("tmc")]
+    }
+
+    /// Overridden because the default implementation skips comments, which would skip the points comments.
+    fn get_available_points(exercise_path: &Path) -> Result<Vec<String>, TmcError> {
+        let mut points = vec![];
+        for (_, scanned) in Self::scan_test_files(exercise_path)? {
+            for scan in scanned {
+                if let Scan::CommentPoints(p) | Scan::HelperPoints(p) = scan {
+                    points.extend(p);
+                }
+            }
+        }
+        Ok(points)
+    }
+
+    /// Parses comments like // @points 1.1 1.2 or calls like tmc.Points(t, "1.1", "1.2").
+    fn points_parser(i: &str) -> IResult<&str, Vec<&str>, VerboseError<&str>> {
+        branch::alt((comment_points_parser, helper_points_parser)).parse(i)
+    }
+}
+
+/// The interesting parts of a Go test file when scanning for tests.
+#[derive(Debug, Clone)]
+enum Scan {
+    CommentPoints(Vec<String>),
+    HelperPoints(Vec<String>),
+    Function(String),
+    Other,
+}
+
+/// Reads the module path from the exercise's go.mod, e.g. example.com/exercise.
+fn module_path(path: &Path) -> Result<String, TmcError> {
+    let go_mod = file_util::read_file_to_string_lossy(path.join("go.mod"))?;
+    let module = go_mod
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("module "))
+        .map(|module| module.trim().trim_matches('"').to_string())
+        .unwrap_or_default();
+    Ok(module)
+}
+
+/// The import path of the package a file in the module is in, as reported by go test -json, e.g. example.com/exercise/sub.
+fn package_path(module: &str, file_path: &Path) -> String {
+    let mut package = module.to_string();
+    for component in file_path.parent().into_iter().flat_map(Path::components) {
+        package.push('/');
+        package.push_str(&component.as_os_str().to_string_lossy());
+    }
+    package
+}
+
+/// Checks if the function is a test according to the rules of go test: the name starts with Test, followed by something other than a lowercase letter.
+fn is_test_function(name: &str) -> bool {
+    match name.strip_prefix("Test") {
+        Some(rest) => !rest.starts_with(|c: char| c.is_lowercase()),
+        None => false,
+    }
+}
+
+/// Parses a comment like // @points 1.1 1.2
+fn comment_points_parser(i: &str) -> IResult<&str, Vec<&str>, VerboseError<&str>> {
+    combinator::map(
+        sequence::preceded(
+            (
+                bytes::complete::tag("//"),
+                character::complete::space0,
+                bytes::complete::tag("@points"),
+                character::complete::space1,
+            ),
+            character::complete::not_line_ending,
+        ),
+        |points: &str| points.split_whitespace().collect(),
+    )
+    .parse(i)
+}
+
+/// Parses a call like tmc.Points(t, "1.1", "1.2")
+fn helper_points_parser(i: &str) -> IResult<&str, Vec<&str>, VerboseError<&str>> {
+    combinator::map(
+        sequence::delimited(
+            (
+                combinator::opt((identifier, character::complete::char('.'))),
+                bytes::complete::tag("Points"),
+                character::complete::multispace0,
+                character::complete::char('('),
+                character::complete::multispace0,
+                identifier,
+                character::complete::multispace0,
+                character::complete::char(','),
+                character::complete::multispace0,
+            ),
+            parse_util::comma_separated_strings,
+            (
+                character::complete::multispace0,
+                combinator::opt(character::complete::char(',')),
+                character::complete::multispace0,
+                character::complete::char(')'),
+            ),
+        ),
+        // splits each point by whitespace
+        |points| {
+            points
+                .into_iter()
+                .flat_map(|p| p.split_whitespace())
+                .collect()
+        },
+    )
+    .parse(i)
+}
+
+/// Parses a Go identifier.
+fn identifier(i: &str) -> IResult<&str, &str, VerboseError<&str>> {
+    combinator::recognize((
+        character::complete::satisfy(|c| c.is_alphabetic() || c == '_'),
+        bytes::complete::take_while(|c: char| c.is_alphanumeric() || c == '_'),
+    ))
+    .parse(i)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use zip::write::SimpleFileOptions;
+
+    fn init() {
+        use log::*;
+        use simple_logger::*;
+        let _ = SimpleLogger::new().with_level(LevelFilter::Debug).init();
+    }
+
+    fn file_to(
+        target_dir: impl AsRef<std::path::Path>,
+        target_relative: impl AsRef<std::path::Path>,
+        contents: impl AsRef<[u8]>,
+    ) -> PathBuf {
+        let target = target_dir.as_ref().join(target_relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(&target, contents.as_ref()).unwrap();
+        target
+    }
+
+    fn dir_to_zip(source_dir: impl AsRef<std::path::Path>) -> Vec<u8> {
+        use std::io::Write;
+
+        let mut target = vec![];
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(&mut target));
+
+        for entry in walkdir::WalkDir::new(&source_dir)
+            .min_depth(1)
+            .sort_by(|a, b| a.path().cmp(b.path()))
+        {
+            let entry = entry.unwrap();
+            let rela = entry
+                .path()
+                .strip_prefix(&source_dir)
+                .unwrap()
+                .to_str()
+                .unwrap();
+            if entry.path().is_dir() {
+                zip.add_directory(rela, SimpleFileOptions::default())
+                    .unwrap();
+            } else if entry.path().is_file() {
+                zip.start_file(rela, SimpleFileOptions::default()).unwrap();
+                let bytes = std::fs::read(entry.path()).unwrap();
+                zip.write_all(&bytes).unwrap();
+            }
+        }
+
+        zip.finish().unwrap();
+        target
+    }
+
+    /// Writes a Go module with a tmc helper package, the given source file and the given tests.
+    fn exercise_to(target_dir: impl AsRef<std::path::Path>, src: &str, tests: &str) {
+        file_to(
+            &target_dir,
+            "go.mod",
+            "module example.com/exercise\n\ngo 1.21\n",
+        );
+        file_to(
+            &target_dir,
+            "tmc/points.go",
+            r#"package tmc
+
+import "testing"
+
+func Points(t *testing.T, points ...string) {}
+"#,
+        );
+        file_to(&target_dir, "add.go", src);
+        file_to(&target_dir, "add_test.go", tests);
+    }
+
+    const TESTS: &str = r#"package exercise
+
+import (
+	"testing"
+
+	"example.com/exercise/tmc"
+)
+
+// @points 1.1
+func TestAdd(t *testing.T) {
+	if got := Add(1, 2); got != 3 {
+		t.Errorf("expected 3, got %d", got)
+	}
+}
+
+func TestAddNegative(t *testing.T) {
+	tmc.Points(t, "1.1", "1.2")
+	if got := Add(-1, -2); got != -3 {
+		t.Errorf("expected -3, got %d", got)
+	}
+}
+
+func helper() {}
+
+/*
+// @points 2
+func TestCommented(t *testing.T) {}
+*/
+"#;
+
+    const SRC: &str = r#"package exercise
+
+func Add(a, b int) int {
+	return a + b
+}
+"#;
+
+    #[test]
+    fn parses_points() {
+        init();
+
+        assert!(GoPlugin::points_parser("asd").is_err());
+        assert!(GoPlugin::points_parser("// points 1").is_err());
+        assert!(GoPlugin::points_parser("tmc.Points(\"1\")").is_err());
+
+        let res = GoPlugin::points_parser("// @points 1").unwrap();
+        assert_eq!(res.1, &["1"]);
+
+        let res = GoPlugin::points_parser("//@points  1 2\nfunc").unwrap();
+        assert_eq!(res.1, &["1", "2"]);
+        assert_eq!(res.0, "\nfunc");
+
+        let res = GoPlugin::points_parser("tmc.Points(t, \"1\")").unwrap();
+        assert_eq!(res.1, &["1"]);
+
+        let res = GoPlugin::points_parser("Points( t , \"1\", \"2 3\", )").unwrap();
+        assert_eq!(res.1, &["1", "2", "3"]);
+    }
+
+    #[test]
+    fn detects_test_functions() {
+        assert!(is_test_function("Test"));
+        assert!(is_test_function("TestAdd"));
+        assert!(is_test_function("Test_add"));
+        assert!(!is_test_function("Testify"));
+        assert!(!is_test_function("helper"));
+    }
+
+    #[test]
+    fn gets_available_points() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, SRC, TESTS);
+        let points = GoPlugin::get_available_points(temp.path()).unwrap();
+        assert_eq!(points, &["1.1", "1.1", "1.2"]);
+    }
+
+    #[test]
+    fn scans_exercise() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, SRC, TESTS);
+        let plugin = GoPlugin::new();
+        let desc = plugin.scan_exercise(temp.path(), "ex".to_string()).unwrap();
+        assert_eq!(desc.name, "ex");
+        assert_eq!(
+            desc.tests,
+            &[
+                TestDesc::new("TestAdd".to_string(), vec!["1.1".to_string()]),
+                TestDesc::new(
+                    "TestAddNegative".to_string(),
+                    vec!["1.1".to_string(), "1.2".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn scans_tests_by_package() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, SRC, TESTS);
+        file_to(
+            &temp,
+            "sub/sub_test.go",
+            r#"package sub
+
+import "testing"
+
+// @points 2.1
+func TestAdd(t *testing.T) {}
+"#,
+        );
+        let tests = GoPlugin::scan_tests(temp.path()).unwrap();
+        let tests = tests
+            .iter()
+            .map(|(package, test)| (package.as_str(), test.name.as_str(), test.points.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tests,
+            &[
+                ("example.com/exercise", "TestAdd", vec!["1.1".to_string()]),
+                (
+                    "example.com/exercise",
+                    "TestAddNegative",
+                    vec!["1.1".to_string(), "1.2".to_string()]
+                ),
+                (
+                    "example.com/exercise/sub",
+                    "TestAdd",
+                    vec!["2.1".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_tests_passing() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, SRC, TESTS);
+        let plugin = GoPlugin::new();
+        let res = plugin.run_tests(temp.path()).unwrap();
+        log::debug!("{res:#?}");
+        assert_eq!(res.status, RunStatus::Passed);
+        assert_eq!(res.test_results.len(), 2);
+        for test_result in &res.test_results {
+            assert!(test_result.successful);
+        }
+        assert_eq!(res.test_results[0].points, &["1.1"]);
+    }
+
+    #[test]
+    fn runs_tests_failing() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(
+            &temp,
+            r#"package exercise
+
+func Add(a, b int) int {
+	if a < 0 {
+		return 0
+	}
+	return a + b
+}
+"#,
+            TESTS,
+        );
+        let plugin = GoPlugin::new();
+        let res = plugin.run_tests(temp.path()).unwrap();
+        log::debug!("{res:#?}");
+        assert_eq!(res.status, RunStatus::TestsFailed);
+        assert_eq!(res.test_results.len(), 2);
+        let failed = res
+            .test_results
+            .iter()
+            .find(|t| t.name == "TestAddNegative")
+            .unwrap();
+        assert!(!failed.successful);
+        assert!(failed.message.contains("expected -3, got 0"));
+        // 1.1 is shared with the failed test so it is not awarded
+        for test_result in &res.test_results {
+            assert!(test_result.points.is_empty());
+        }
+    }
+
+    #[test]
+    fn runs_tests_compile_err() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(
+            &temp,
+            r#"package exercise
+
+func Add(a, b int) int {
+	return a +
+}
+"#,
+            TESTS,
+        );
+        let plugin = GoPlugin::new();
+        let res = plugin.run_tests(temp.path()).unwrap();
+        log::debug!("{res:#?}");
+        assert_eq!(res.status, RunStatus::CompileFailed);
+        assert!(res.test_results.is_empty());
+        let logs = format!("{}{}", res.logs["stdout"], res.logs["stderr"]);
+        assert!(logs.contains("add.go"));
+    }
+
+    #[test]
+    fn runs_tests_timeout() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, SRC, TESTS);
+        let plugin = GoPlugin::new();
+        let res = plugin
+            .run_tests_with_timeout(temp.path(), Some(std::time::Duration::from_nanos(1)), None)
+            .unwrap();
+        assert_eq!(res.status, RunStatus::TestsFailed);
+        assert_eq!(res.test_results[0].name, "Timeout test");
+    }
+
+    #[test]
+    fn finds_project_dir_in_zip() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(temp.path().join("outer/exercise"), SRC, TESTS);
+        file_to(&temp, "outer/exercise/vendor/example.com/dep/go.mod", "");
+        let zip = dir_to_zip(&temp);
+        let mut zip = Archive::zip(std::io::Cursor::new(zip)).unwrap();
+        let dir = GoPlugin::find_project_dir_in_archive(&mut zip).unwrap();
+        assert_eq!(dir, Path::new("outer/exercise"));
+    }
+
+    #[test]
+    fn doesnt_find_project_dir_in_zip() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        file_to(&temp, "outer/exercise/add.go", SRC);
+        file_to(&temp, "outer/__MACOSX/go.mod", "");
+        let zip = dir_to_zip(&temp);
+        let mut zip = Archive::zip(std::io::Cursor::new(zip)).unwrap();
+        assert!(GoPlugin::find_project_dir_in_archive(&mut zip).is_err());
+    }
+}
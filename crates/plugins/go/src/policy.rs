@@ -0,0 +1,58 @@
+//! Contains the Go student file policy.
+
+use std::{ffi::OsStr, path::Path};
+use tmc_langs_framework::{StudentFilePolicy, TmcProjectYml};
+
+pub struct GoStudentFilePolicy {
+    project_config: TmcProjectYml,
+}
+
+impl StudentFilePolicy for GoStudentFilePolicy {
+    fn new_with_project_config(project_config: TmcProjectYml) -> Self
+    where
+        Self: Sized,
+    {
+        Self { project_config }
+    }
+
+    fn get_project_config(&self) -> &TmcProjectYml {
+        &self.project_config
+    }
+
+    fn is_non_extra_student_file(&self, path: &Path) -> bool {
+        // Go packages are directories with both the code and its tests in them,
+        // so all Go files except tests are student files.
+        // vendored dependencies and the tmc helper package are excluded
+        let excluded = path.starts_with("vendor") || path.starts_with("tmc");
+        let is_go = path.extension() == Some(OsStr::new("go"));
+        let is_test = path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .map(|name| name.ends_with("_test.go"))
+            .unwrap_or_default();
+        !excluded && is_go && !is_test
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn go_file_is_student_file() {
+        let policy = GoStudentFilePolicy::new(Path::new(".")).unwrap();
+        assert!(policy.is_student_file(Path::new("main.go")));
+        assert!(policy.is_student_file(Path::new("internal/calc/add.go")));
+    }
+
+    #[test]
+    fn tests_and_other_files_are_not_student_files() {
+        let policy = GoStudentFilePolicy::new(Path::new(".")).unwrap();
+        assert!(!policy.is_student_file(Path::new("main_test.go")));
+        assert!(!policy.is_student_file(Path::new("internal/calc/add_test.go")));
+        assert!(!policy.is_student_file(Path::new("go.mod")));
+        assert!(!policy.is_student_file(Path::new("vendor/example.com/dep/dep.go")));
+        assert!(!policy.is_student_file(Path::new("tmc/points.go")));
+    }
+}
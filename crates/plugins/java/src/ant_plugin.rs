@@ -5,7 +5,6 @@ use crate::{
     java_plugin::JavaPlugin,
 };
 use std::{
-    env,
     ffi::OsStr,
     io::{Read, Seek},
     ops::ControlFlow::{Break, Continue},
@@ -13,8 +12,8 @@ use std::{
     time::Duration,
 };
 use tmc_langs_framework::{
-    Archive, ExerciseDesc, Language, LanguagePlugin, RunResult, StyleValidationResult, TmcCommand,
-    TmcError, nom::IResult, nom_language::error::VerboseError,
+    Archive, ExerciseDesc, Language, LanguagePlugin, RunResult, Sandbox, StyleValidationResult,
+    TestFilter, TmcCommand, TmcError, nom::IResult, nom_language::error::VerboseError,
 };
 use tmc_langs_util::{file_util, path_util};
 use walkdir::WalkDir;
@@ -72,6 +71,7 @@ impl LanguagePlugin for AntPlugin {
     const DEFAULT_SANDBOX_IMAGE: &'static str = "eu.gcr.io/moocfi-public/tmc-sandbox-java:latest";
     const LINE_COMMENT: &'static str = "//";
     const BLOCK_COMMENT: Option<(&'static str, &'static str)> = Some(("/*", "*/"));
+    const SOURCE_FILE_EXTENSIONS: &'static [&'static str] = &["java"];
     type StudentFilePolicy = AntStudentFilePolicy;
 
     fn check_code_style(
@@ -96,8 +96,20 @@ impl LanguagePlugin for AntPlugin {
         &self,
         project_root_path: &Path,
         timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
     ) -> Result<RunResult, TmcError> {
-        Ok(self.run_java_tests(project_root_path, timeout)?)
+        Ok(self.run_java_tests(project_root_path, timeout, &TestFilter::default(), sandbox)?)
+    }
+
+    /// Only passes the selected tests to the test runner.
+    fn run_tests_filtered_with_timeout(
+        &self,
+        project_root_path: &Path,
+        filter: &TestFilter,
+        timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        Ok(self.run_java_tests(project_root_path, timeout, filter, sandbox)?)
     }
 
     fn find_project_dir_in_archive<R: Read + Seek>(
@@ -265,72 +277,18 @@ impl JavaPlugin for AntPlugin {
         path: &Path,
         timeout: Option<Duration>,
         compile_result: CompileResult,
+        filter: &TestFilter,
+        sandbox: Option<&Sandbox>,
     ) -> Result<TestRun, JavaError> {
         log::info!("running tests for project at {}", path.display());
-
-        // build java args
-        let mut arguments = vec![];
-        // JVM args
-        if let Ok(jvm_options) = env::var("JVM_OPTIONS") {
-            arguments.extend(
-                jvm_options
-                    .split(" +")
-                    .map(|s| s.trim())
-                    .filter(|s| !s.is_empty())
-                    .map(|s| s.to_string()),
-            )
-        }
-        // TMC args
-        let test_dir = path.join("test");
-        let result_file_name = "results.txt";
-        let result_file = path.join(result_file_name);
-        arguments.push(format!("-Dtmc.test_class_dir={}", test_dir.display()));
-        // we want to use path relative to the exercise path for the java command,
-        // and a path relative to the current directory for the rest of the program
-        arguments.push(format!("-Dtmc.results_file={result_file_name}"));
-        // TODO: endorsed libs?
-        let endorsed_libs_path = path.join("lib/endorsed");
-        if endorsed_libs_path.exists() {
-            arguments.push(format!(
-                "-Djava.endorsed.dirs={}",
-                endorsed_libs_path.display()
-            ));
-        }
-        // scan needs to be before getting class path
-        let exercise = self.scan_exercise_with_compile_result(
+        self.run_tmc_junit_runner(
             path,
-            format!("{}{}", path.display(), "/test"), // TODO: ?
+            &path.join("test"),
+            timeout,
             compile_result,
-        )?;
-        // classpath
-        arguments.push("-cp".to_string());
-        let class_path = self.get_project_class_path(path)?;
-        arguments.push(class_path);
-        // main
-        arguments.push("fi.helsinki.cs.tmc.testrunner.Main".to_string());
-        // ?
-        for desc in exercise.tests {
-            let mut s = String::new();
-            s.push_str(&desc.name.replace(' ', "."));
-            s.push('{');
-            s.push_str(&desc.points.join(","));
-            s.push('}');
-            arguments.push(s);
-        }
-
-        log::debug!("java args '{}' in {}", arguments.join(" "), path.display());
-        let command = TmcCommand::piped("java").with(|e| e.cwd(path).args(&arguments));
-        let output = if let Some(timeout) = timeout {
-            command.output_with_timeout(timeout)?
-        } else {
-            command.output()?
-        };
-
-        Ok(TestRun {
-            test_results: result_file,
-            stdout: output.stdout,
-            stderr: output.stderr,
-        })
+            filter,
+            sandbox,
+        )
     }
 }
 
@@ -504,7 +462,13 @@ mod test {
         let plugin = AntPlugin::new().unwrap();
         let compile_result = plugin.build(temp_dir.path()).unwrap();
         let test_run = plugin
-            .create_run_result_file(temp_dir.path(), None, compile_result)
+            .create_run_result_file(
+                temp_dir.path(),
+                None,
+                compile_result,
+                &TestFilter::default(),
+                None,
+            )
             .unwrap();
         log::trace!("stdout: {}", String::from_utf8_lossy(&test_run.stdout));
         log::debug!("stderr: {}", String::from_utf8_lossy(&test_run.stderr));
@@ -587,7 +551,7 @@ mod test {
         let temp_dir = dir_to_temp("tests/data/ant-exercise");
         let plugin = AntPlugin::new().unwrap();
         let test_result = plugin
-            .run_tests_with_timeout(Path::new(temp_dir.path()), None)
+            .run_tests_with_timeout(Path::new(temp_dir.path()), None, None)
             .unwrap();
         log::debug!("{test_result:?}");
         assert_eq!(
@@ -596,6 +560,21 @@ mod test {
         );
     }
 
+    #[test]
+    fn runs_filtered_tests() {
+        init();
+
+        let temp_dir = dir_to_temp("tests/data/ant-exercise");
+        let plugin = AntPlugin::new().unwrap();
+        let filter = TestFilter::new(vec!["ArithTest testAdd".to_string()], vec![]);
+        let test_result = plugin
+            .run_tests_filtered(Path::new(temp_dir.path()), &filter)
+            .unwrap();
+        log::debug!("{test_result:?}");
+        assert_eq!(test_result.test_results.len(), 1);
+        assert_eq!(test_result.test_results[0].name, "ArithTest testAdd");
+    }
+
     #[test]
     fn runs_tests_with_timeout() {
         init();
@@ -603,7 +582,11 @@ mod test {
         let temp_dir = dir_to_temp("tests/data/ant-exercise");
         let plugin = AntPlugin::new().unwrap();
         let test_result_err = plugin
-            .run_tests_with_timeout(Path::new(temp_dir.path()), Some(Duration::from_nanos(1)))
+            .run_tests_with_timeout(
+                Path::new(temp_dir.path()),
+                Some(Duration::from_nanos(1)),
+                None,
+            )
             .unwrap_err();
         log::debug!("{test_result_err:?}");
 
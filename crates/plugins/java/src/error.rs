@@ -13,6 +13,8 @@ pub enum JavaError {
     NoJavaHome,
     #[error("Maven did not output any class path")]
     NoMvnClassPath,
+    #[error("Gradle did not output any class path")]
+    NoGradleClassPath,
     #[error("{0} did not contain a valid exercise")]
     InvalidExercise(PathBuf),
     #[error("Failed to write temporary .jar file {0}")]
@@ -0,0 +1,798 @@
+//! Java Gradle plugin.
+
+use crate::{
+    CompileResult, GradleStudentFilePolicy, JvmWrapper, SEPARATOR, TestRun, error::JavaError,
+    java_plugin::JavaPlugin,
+};
+use std::{
+    ffi::OsStr,
+    io::{Read, Seek},
+    ops::ControlFlow::{Break, Continue},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tmc_langs_framework::{
+    Archive, ExerciseDesc, Language, LanguagePlugin, RunResult, Sandbox, StyleValidationResult,
+    TestDesc, TestFilter, TmcCommand, TmcError,
+    nom::{self, IResult, Parser, branch, bytes, character, combinator, multi, sequence},
+    nom_language::error::VerboseError,
+};
+use tmc_langs_util::{file_util, path_util};
+use walkdir::WalkDir;
+
+const BUILD_FILES: &[&str] = &["build.gradle", "build.gradle.kts"];
+
+/// Init script that adds a task for writing the test runtime class path of the root project into a file.
+/// Used instead of parsing the output of Gradle, which depends on the Gradle version and the project's configuration.
+const CLASS_PATH_INIT_SCRIPT: &str = r#"
+rootProject {
+    afterEvaluate { project ->
+        project.tasks.register("tmcTestClassPath") {
+            def classPathFile = project.file(project.property("tmcClassPathFile"))
+            def classPath = project.sourceSets.test.runtimeClasspath
+            doLast {
+                classPathFile.text = classPath.asPath
+            }
+        }
+    }
+}
+"#;
+
+pub struct GradlePlugin {
+    jvm: JvmWrapper,
+}
+
+impl GradlePlugin {
+    pub fn new() -> Result<Self, JavaError> {
+        let jvm = crate::instantiate_jvm()?;
+        Ok(Self { jvm })
+    }
+
+    /// Returns a Gradle command for the project at the given path.
+    /// The project's Gradle wrapper is preferred so that the exercise is built with the Gradle version it was made for.
+    fn get_gradle_command(path: &Path) -> TmcCommand {
+        let command = if cfg!(windows) {
+            let wrapper = path.join("gradlew.bat");
+            if wrapper.is_file() {
+                TmcCommand::piped(wrapper)
+            } else {
+                TmcCommand::piped("gradle.bat")
+            }
+        } else {
+            let wrapper = path.join("gradlew");
+            if wrapper.is_file() {
+                // the wrapper may have lost its executable bit when the exercise was packaged
+                TmcCommand::piped("sh").with(|e| e.arg(wrapper))
+            } else {
+                TmcCommand::piped("gradle")
+            }
+        };
+        command.with(|e| e.cwd(path).arg("--console=plain").arg("--no-daemon"))
+    }
+
+    /// Scans the Kotlin test files in src/test for test functions and the points annotated on them and their classes.
+    /// The bundled test scanner only reads Java sources, so Kotlin tests are scanned here.
+    fn scan_kotlin_tests(path: &Path) -> Result<Vec<TestDesc>, JavaError> {
+        let mut tests = vec![];
+        for entry in WalkDir::new(path.join("src").join("test")).sort_by_file_name() {
+            let entry = entry?;
+            if !entry.file_type().is_file() || entry.path().extension() != Some(OsStr::new("kt")) {
+                continue;
+            }
+            log::trace!("scanning tests from {}", entry.path().display());
+            let file_contents = file_util::read_file_to_string_lossy(entry.path())?;
+
+            let mut parser = multi::many0(branch::alt((
+                // comments and literals
+                combinator::value(
+                    KotlinScan::Other,
+                    (
+                        bytes::complete::tag("//"),
+                        character::complete::not_line_ending,
+                    ),
+                ),
+                combinator::value(
+                    KotlinScan::Other,
+                    (
+                        bytes::complete::tag("/*"),
+                        bytes::complete::take_until("*/"),
+                        bytes::complete::tag("*/"),
+                    ),
+                ),
+                combinator::value(KotlinScan::Other, kotlin_string_literal),
+                combinator::value(
+                    KotlinScan::Other,
+                    (
+                        character::complete::char('\''),
+                        branch::alt((
+                            combinator::recognize((
+                                character::complete::char('\\'),
+                                bytes::complete::is_not("'"),
+                            )),
+                            combinator::recognize(character::complete::anychar),
+                        )),
+                        character::complete::char('\''),
+                    ),
+                ),
+                // annotations
+                combinator::map(Self::java_points_parser, |points| {
+                    KotlinScan::Points(points.into_iter().map(str::to_string).collect())
+                }),
+                combinator::value(
+                    KotlinScan::Test,
+                    (
+                        character::complete::char('@'),
+                        multi::many0_count((identifier, character::complete::char('.'))),
+                        bytes::complete::tag("Test"),
+                        combinator::not(character::complete::satisfy(|c| {
+                            c.is_alphanumeric() || c == '_'
+                        })),
+                    ),
+                ),
+                // declarations
+                combinator::map(
+                    sequence::preceded(
+                        (bytes::complete::tag("package"), character::complete::space1),
+                        combinator::recognize(multi::separated_list1(
+                            character::complete::char('.'),
+                            identifier,
+                        )),
+                    ),
+                    |package| KotlinScan::Package(package.to_string()),
+                ),
+                combinator::map(
+                    sequence::preceded(
+                        (
+                            branch::alt((
+                                bytes::complete::tag("class"),
+                                bytes::complete::tag("object"),
+                            )),
+                            character::complete::multispace1,
+                        ),
+                        identifier,
+                    ),
+                    |name| KotlinScan::Class(name.to_string()),
+                ),
+                combinator::map(
+                    sequence::preceded(
+                        (
+                            bytes::complete::tag("fun"),
+                            character::complete::multispace1,
+                        ),
+                        branch::alt((
+                            combinator::map(identifier, Some),
+                            // names in backticks can contain spaces, which the test runner's arguments do not support
+                            combinator::value(
+                                None,
+                                (
+                                    character::complete::char('`'),
+                                    bytes::complete::is_not("`"),
+                                    character::complete::char('`'),
+                                ),
+                            ),
+                        )),
+                    ),
+                    |name| KotlinScan::Function(name.map(str::to_string)),
+                ),
+                combinator::value(KotlinScan::OpenBrace, character::complete::char('{')),
+                combinator::value(KotlinScan::CloseBrace, character::complete::char('}')),
+                // whole identifiers are skipped so that keywords are only recognized at the start of a word
+                combinator::value(KotlinScan::Other, identifier),
+                combinator::value(KotlinScan::Other, character::complete::anychar),
+            )));
+
+            let res: IResult<_, _, VerboseError<&str>> = parser.parse(&file_contents);
+            let scanned = match res {
+                Ok((_, scanned)) => scanned,
+                Err(nom::Err::Incomplete(_)) => unreachable!("this should never happen"),
+                Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                    return Err(TmcError::PointParse(
+                        entry.path().to_path_buf(),
+                        VerboseError {
+                            errors: e
+                                .errors
+                                .into_iter()
+                                .map(|(s, k)| (s.to_string(), k))
+                                .collect(),
+                        },
+                    )
+                    .into());
+                }
+            };
+
+            let mut package = None;
+            let mut is_test = false;
+            let mut points = vec![];
+            // a class whose body has not been opened yet
+            let mut pending_class = None;
+            // the classes that are currently open, the brace depth they were opened at and their points
+            let mut classes: Vec<(usize, String, Vec<String>)> = vec![];
+            let mut depth = 0;
+            for scan in scanned {
+                match scan {
+                    KotlinScan::Package(p) => package = Some(p),
+                    KotlinScan::Points(p) => points.extend(p),
+                    KotlinScan::Test => is_test = true,
+                    KotlinScan::Class(name) => {
+                        pending_class = Some((name, std::mem::take(&mut points)));
+                        is_test = false;
+                    }
+                    KotlinScan::Function(name) => {
+                        pending_class = None;
+                        let method_points = std::mem::take(&mut points);
+                        if is_test && !classes.is_empty() {
+                            match name {
+                                Some(name) => {
+                                    // nested classes are separated by $ in their binary names
+                                    let class_name = classes
+                                        .iter()
+                                        .map(|(_, class, _)| class.as_str())
+                                        .collect::<Vec<_>>()
+                                        .join("$");
+                                    let class_name = match &package {
+                                        Some(package) => format!("{package}.{class_name}"),
+                                        None => class_name,
+                                    };
+                                    let points = classes
+                                        .iter()
+                                        .flat_map(|(_, _, points)| points.iter().cloned())
+                                        .chain(method_points)
+                                        .collect();
+                                    tests.push(TestDesc::new(
+                                        format!("{class_name} {name}"),
+                                        points,
+                                    ));
+                                }
+                                None => log::warn!(
+                                    "skipping test with a name in backticks in {}",
+                                    entry.path().display()
+                                ),
+                            }
+                        }
+                        is_test = false;
+                    }
+                    KotlinScan::OpenBrace => {
+                        depth += 1;
+                        if let Some((name, points)) = pending_class.take() {
+                            classes.push((depth, name, points));
+                        }
+                    }
+                    KotlinScan::CloseBrace => {
+                        if classes.last().map(|(d, _, _)| *d == depth).unwrap_or(false) {
+                            classes.pop();
+                        }
+                        depth = depth.saturating_sub(1);
+                    }
+                    KotlinScan::Other => {}
+                }
+            }
+        }
+        Ok(tests)
+    }
+}
+
+/// Project directory:
+/// Contains a build.gradle or build.gradle.kts file.
+impl LanguagePlugin for GradlePlugin {
+    const PLUGIN_NAME: &'static str = "gradle";
+    const DEFAULT_SANDBOX_IMAGE: &'static str = "eu.gcr.io/moocfi-public/tmc-sandbox-java:latest";
+    const LINE_COMMENT: &'static str = "//";
+    const BLOCK_COMMENT: Option<(&'static str, &'static str)> = Some(("/*", "*/"));
+    const SOURCE_FILE_EXTENSIONS: &'static [&'static str] = &["java", "kt", "kts"];
+    type StudentFilePolicy = GradleStudentFilePolicy;
+
+    fn check_code_style(
+        &self,
+        path: &Path,
+        locale: Language,
+    ) -> Result<Option<StyleValidationResult>, TmcError> {
+        Ok(Some(self.run_checkstyle(&locale, path)?))
+    }
+
+    fn scan_exercise(&self, path: &Path, exercise_name: String) -> Result<ExerciseDesc, TmcError> {
+        if !Self::is_exercise_type_correct(path) {
+            return JavaError::InvalidExercise(path.to_path_buf()).into();
+        }
+
+        let compile_result = self.build(path)?;
+        Ok(self.scan_exercise_with_compile_result(path, exercise_name, compile_result)?)
+    }
+
+    fn run_tests_with_timeout(
+        &self,
+        project_root_path: &Path,
+        timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        Ok(self.run_java_tests(project_root_path, timeout, &TestFilter::default(), sandbox)?)
+    }
+
+    /// Only passes the selected tests to the test runner.
+    fn run_tests_filtered_with_timeout(
+        &self,
+        project_root_path: &Path,
+        filter: &TestFilter,
+        timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        Ok(self.run_java_tests(project_root_path, timeout, filter, sandbox)?)
+    }
+
+    /// Finds the shallowest directory with a Gradle build file, subprojects of a multi-project build have their own build files.
+    fn find_project_dir_in_archive<R: Read + Seek>(
+        archive: &mut Archive<R>,
+    ) -> Result<PathBuf, TmcError> {
+        let mut iter = archive.iter()?;
+        let mut shallowest_build_parent: Option<PathBuf> = None;
+        loop {
+            let next = iter.with_next::<(), _>(|file| {
+                let file_path = file.path()?;
+
+                if file.is_file() {
+                    if let Some(parent) = BUILD_FILES
+                        .iter()
+                        .find_map(|name| path_util::get_parent_of_named(&file_path, name))
+                    {
+                        let is_shallower = shallowest_build_parent
+                            .as_ref()
+                            .map(|shallowest| {
+                                shallowest.components().count() > parent.components().count()
+                            })
+                            .unwrap_or(true);
+                        if is_shallower {
+                            shallowest_build_parent = Some(parent);
+                        }
+                    }
+                }
+                Ok(Continue(()))
+            });
+            match next? {
+                Continue(_) => continue,
+                Break(_) => break,
+            }
+        }
+
+        match shallowest_build_parent {
+            Some(project_dir) => Ok(project_dir),
+            None => Err(TmcError::NoProjectDirInArchive),
+        }
+    }
+
+    /// Checks if the directory has a build.gradle or build.gradle.kts file.
+    fn is_exercise_type_correct(path: &Path) -> bool {
+        BUILD_FILES.iter().any(|name| path.join(name).is_file())
+    }
+
+    /// Runs the Gradle clean task.
+    fn clean(&self, path: &Path) -> Result<(), TmcError> {
+        log::info!("Cleaning gradle project at {}", path.display());
+
+        let _output = Self::get_gradle_command(path)
+            .with(|e| e.arg("--quiet").arg("clean"))
+            .output_checked()?;
+
+        Ok(())
+    }
+
+    fn get_default_student_file_paths() -> Vec<PathBuf> {
+        vec![PathBuf::from("src/main")]
+    }
+
+    fn get_default_exercise_file_paths() -> Vec<PathBuf> {
+        vec![PathBuf::from("src/test")]
+    }
+
+    fn points_parser(i: &str) -> IResult<&str, Vec<&str>, VerboseError<&str>> {
+        Self::java_points_parser(i)
+    }
+}
+
+impl JavaPlugin for GradlePlugin {
+    const TEST_DIR: &'static str = "src";
+
+    fn jvm(&self) -> &JvmWrapper {
+        &self.jvm
+    }
+
+    /// Constructs the class path from the test runtime class path of the project and the bundled tmc-junit-runner.
+    fn get_project_class_path(&self, path: &Path) -> Result<String, JavaError> {
+        // canonicalize root path to avoid issues where the cwd and project root are different directories
+        let path = file_util::canonicalize(path)?;
+        log::info!(
+            "Building classpath for gradle project at {}",
+            path.display()
+        );
+
+        let temp = tempfile::tempdir().map_err(JavaError::TempDir)?;
+        let init_script_path = temp.path().join("tmc-init.gradle");
+        file_util::write_to_file(CLASS_PATH_INIT_SCRIPT.as_bytes(), &init_script_path)?;
+        let class_path_file = temp.path().join("cp.txt");
+
+        let _output = Self::get_gradle_command(&path)
+            .with(|e| {
+                e.arg("--quiet")
+                    .arg("--init-script")
+                    .arg(&init_script_path)
+                    .arg(format!("-PtmcClassPathFile={}", class_path_file.display()))
+                    .arg("tmcTestClassPath")
+            })
+            .output_checked()?;
+
+        let class_path = file_util::read_file_to_string(&class_path_file)?;
+        if class_path.is_empty() {
+            return Err(JavaError::NoGradleClassPath);
+        }
+
+        // the runner is written into the build directory so that it gets removed by clean
+        let runner_path = path.join("build").join("tmc").join("tmc-junit-runner.jar");
+        if !runner_path.exists() {
+            log::debug!("writing tmc-junit-runner to {}", runner_path.display());
+            file_util::write_to_file(crate::TMC_JUNIT_RUNNER_BYTES, &runner_path)?;
+        }
+
+        let class_path = [class_path, runner_path.to_string_lossy().into_owned()];
+        Ok(class_path.join(SEPARATOR))
+    }
+
+    fn scan_non_java_tests(&self, path: &Path) -> Result<Vec<TestDesc>, JavaError> {
+        Self::scan_kotlin_tests(path)
+    }
+
+    fn build(&self, project_root_path: &Path) -> Result<CompileResult, JavaError> {
+        log::info!("Building gradle project at {}", project_root_path.display());
+
+        let output = Self::get_gradle_command(project_root_path)
+            .with(|e| e.arg("clean").arg("testClasses"))
+            .output()?;
+
+        Ok(CompileResult {
+            status_code: output.status,
+            stdout: output.stdout,
+            stderr: output.stderr,
+        })
+    }
+
+    /// Runs the tests with the bundled tmc-junit-runner.
+    /// The compiled Java and Kotlin test classes are loaded from the project's class path.
+    fn create_run_result_file(
+        &self,
+        path: &Path,
+        timeout: Option<Duration>,
+        compile_result: CompileResult,
+        filter: &TestFilter,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<TestRun, JavaError> {
+        log::info!("Running tests for gradle project at {}", path.display());
+        self.run_tmc_junit_runner(
+            path,
+            &path.join("src").join("test"),
+            timeout,
+            compile_result,
+            filter,
+            sandbox,
+        )
+    }
+}
+
+/// The interesting parts of a Kotlin source file when scanning for tests.
+#[derive(Debug, Clone)]
+enum KotlinScan {
+    Package(String),
+    Points(Vec<String>),
+    Test,
+    Class(String),
+    Function(Option<String>),
+    OpenBrace,
+    CloseBrace,
+    Other,
+}
+
+/// Parses a Kotlin identifier.
+fn identifier(i: &str) -> IResult<&str, &str, VerboseError<&str>> {
+    combinator::recognize((
+        character::complete::satisfy(|c| c.is_alphabetic() || c == '_'),
+        bytes::complete::take_while(|c: char| c.is_alphanumeric() || c == '_'),
+    ))
+    .parse(i)
+}
+
+/// Parses a Kotlin string literal, including raw strings in triple quotes.
+fn kotlin_string_literal(i: &str) -> IResult<&str, &str, VerboseError<&str>> {
+    branch::alt((
+        combinator::recognize((
+            bytes::complete::tag("\"\"\""),
+            bytes::complete::take_until("\"\"\""),
+            bytes::complete::tag("\"\"\""),
+        )),
+        combinator::recognize((
+            character::complete::char('"'),
+            multi::many0_count(branch::alt((
+                combinator::recognize((
+                    character::complete::char('\\'),
+                    character::complete::anychar,
+                )),
+                bytes::complete::is_not("\\\"\n"),
+            ))),
+            character::complete::char('"'),
+        )),
+    ))
+    .parse(i)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use once_cell::sync::Lazy;
+    use std::sync::{Mutex, MutexGuard};
+    use tmc_langs_framework::Archive;
+    use zip::write::SimpleFileOptions;
+
+    static GRADLE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+    fn init() {
+        use log::*;
+        use simple_logger::*;
+        let _ = SimpleLogger::new()
+            .with_level(LevelFilter::Debug)
+            .with_module_level("j4rs", LevelFilter::Warn)
+            .init();
+    }
+
+    /// The tests that run Gradle share the Gradle user home, so they are run one at a time.
+    fn get_gradle() -> (GradlePlugin, MutexGuard<'static, ()>) {
+        (GradlePlugin::new().unwrap(), GRADLE_LOCK.lock().unwrap())
+    }
+
+    fn file_to(
+        target_dir: impl AsRef<std::path::Path>,
+        target_relative: impl AsRef<std::path::Path>,
+        contents: impl AsRef<[u8]>,
+    ) -> PathBuf {
+        let target = target_dir.as_ref().join(target_relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(&target, contents.as_ref()).unwrap();
+        target
+    }
+
+    fn dir_to_temp(source_dir: impl AsRef<std::path::Path>) -> tempfile::TempDir {
+        let temp = tempfile::TempDir::new().unwrap();
+        for entry in walkdir::WalkDir::new(&source_dir).min_depth(1) {
+            let entry = entry.unwrap();
+            let rela = entry.path().strip_prefix(&source_dir).unwrap();
+            let target = temp.path().join(rela);
+            if entry.path().is_dir() {
+                std::fs::create_dir(target).unwrap();
+            } else if entry.path().is_file() {
+                std::fs::copy(entry.path(), target).unwrap();
+            }
+        }
+        temp
+    }
+
+    fn dir_to_zip(source_dir: impl AsRef<std::path::Path>) -> Vec<u8> {
+        use std::io::Write;
+
+        let mut target = vec![];
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(&mut target));
+
+        for entry in walkdir::WalkDir::new(&source_dir)
+            .min_depth(1)
+            .sort_by(|a, b| a.path().cmp(b.path()))
+        {
+            let entry = entry.unwrap();
+            let rela = entry
+                .path()
+                .strip_prefix(&source_dir)
+                .unwrap()
+                .to_str()
+                .unwrap();
+            if entry.path().is_dir() {
+                zip.add_directory(rela, SimpleFileOptions::default())
+                    .unwrap();
+            } else if entry.path().is_file() {
+                zip.start_file(rela, SimpleFileOptions::default()).unwrap();
+                let bytes = std::fs::read(entry.path()).unwrap();
+                zip.write_all(&bytes).unwrap();
+            }
+        }
+
+        zip.finish().unwrap();
+        target
+    }
+
+    #[test]
+    fn exercise_type_is_correct() {
+        init();
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        file_to(temp_dir.path(), "build.gradle", "");
+        assert!(GradlePlugin::is_exercise_type_correct(temp_dir.path()));
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        file_to(temp_dir.path(), "build.gradle.kts", "");
+        assert!(GradlePlugin::is_exercise_type_correct(temp_dir.path()));
+    }
+
+    #[test]
+    fn exercise_type_is_incorrect() {
+        init();
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        file_to(temp_dir.path(), "build", "");
+        file_to(temp_dir.path(), "settings.gradle", "");
+        file_to(temp_dir.path(), "dir/build.gradle", "");
+        assert!(!GradlePlugin::is_exercise_type_correct(temp_dir.path()));
+    }
+
+    #[test]
+    fn finds_project_dir_in_zip() {
+        init();
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        file_to(
+            &temp_dir,
+            "Outer/Inner/gradle-exercise/build.gradle.kts",
+            "",
+        );
+        file_to(
+            &temp_dir,
+            "Outer/Inner/gradle-exercise/sub/build.gradle",
+            "",
+        );
+
+        let zip_contents = dir_to_zip(&temp_dir);
+        let mut zip = Archive::zip(std::io::Cursor::new(zip_contents)).unwrap();
+        let dir = GradlePlugin::find_project_dir_in_archive(&mut zip).unwrap();
+        assert_eq!(dir, Path::new("Outer/Inner/gradle-exercise"));
+    }
+
+    #[test]
+    fn doesnt_find_project_dir_in_zip() {
+        init();
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        file_to(
+            &temp_dir,
+            "Outer/Inner/gradle-exercise/build.gradle.bak",
+            "",
+        );
+        file_to(&temp_dir, "__MACOSX/gradle-exercise/build.gradle", "");
+
+        let zip_contents = dir_to_zip(&temp_dir);
+        let mut zip = Archive::zip(std::io::Cursor::new(zip_contents)).unwrap();
+        let dir = GradlePlugin::find_project_dir_in_archive(&mut zip);
+        assert!(dir.is_err());
+    }
+
+    #[test]
+    fn scans_kotlin_tests() {
+        init();
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        file_to(
+            &temp_dir,
+            "src/test/kotlin/fi/helsinki/cs/AppTest.kt",
+            r#"
+package fi.helsinki.cs
+
+import fi.helsinki.cs.tmc.edutestutils.Points
+import org.junit.Test
+
+data class Pair(val a: Int, val b: Int)
+
+@Points("1")
+class AppTest {
+    // @Points("2")
+    @Test
+    @Points("1.1 1.2")
+    fun adds() {
+        val s = "} {"
+        assertEquals('}', s[0])
+    }
+
+    @Test
+    fun `has spaces`() {}
+
+    fun helper() {}
+
+    @Points("3")
+    class Inner {
+        @org.junit.Test
+        fun nested() {}
+    }
+}
+"#,
+        );
+        file_to(&temp_dir, "src/test/java/fi/helsinki/cs/Other.kt.bak", "");
+
+        let tests = GradlePlugin::scan_kotlin_tests(temp_dir.path()).unwrap();
+        assert_eq!(
+            tests,
+            &[
+                TestDesc::new(
+                    "fi.helsinki.cs.AppTest adds".to_string(),
+                    vec!["1".to_string(), "1.1".to_string(), "1.2".to_string()]
+                ),
+                TestDesc::new(
+                    "fi.helsinki.cs.AppTest$Inner nested".to_string(),
+                    vec!["1".to_string(), "3".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn builds() {
+        init();
+
+        let temp_dir = dir_to_temp("tests/data/gradle-exercise");
+        let (plugin, _lock) = get_gradle();
+        let compile_result = plugin.build(temp_dir.path()).unwrap();
+        assert!(compile_result.status_code.success());
+        assert!(
+            temp_dir
+                .path()
+                .join("build/classes/java/test/fi/helsinki/cs/gradletest/AppTest.class")
+                .exists()
+        );
+    }
+
+    #[test]
+    fn gets_project_class_path() {
+        init();
+
+        let temp_dir = dir_to_temp("tests/data/gradle-exercise");
+        let (plugin, _lock) = get_gradle();
+        let class_path = plugin.get_project_class_path(temp_dir.path()).unwrap();
+        assert!(class_path.contains("junit-4.13.2.jar"), "{class_path}");
+        assert!(class_path.contains("edu-test-utils"), "{class_path}");
+        assert!(class_path.ends_with("tmc-junit-runner.jar"), "{class_path}");
+    }
+
+    #[test]
+    fn scans_exercise() {
+        init();
+
+        let temp_dir = dir_to_temp("tests/data/gradle-exercise");
+        let (plugin, _lock) = get_gradle();
+        let exercises = plugin
+            .scan_exercise(temp_dir.path(), "test".to_string())
+            .unwrap();
+        assert_eq!(exercises.name, "test");
+        assert_eq!(exercises.tests.len(), 1);
+        assert_eq!(
+            exercises.tests[0].name,
+            "fi.helsinki.cs.gradletest.AppTest trol"
+        );
+        assert_eq!(exercises.tests[0].points, ["gradle-exercise"]);
+    }
+
+    #[test]
+    fn runs_tests() {
+        init();
+
+        let temp_dir = dir_to_temp("tests/data/gradle-exercise");
+        let (plugin, _lock) = get_gradle();
+        let res = plugin.run_tests(temp_dir.path()).unwrap();
+        log::debug!("{res:#?}");
+        assert_eq!(res.status, tmc_langs_framework::RunStatus::TestsFailed);
+        assert_eq!(res.test_results.len(), 1);
+        assert_eq!(res.test_results[0].points, ["gradle-exercise"]);
+    }
+
+    #[test]
+    fn cleans() {
+        init();
+
+        let temp_dir = dir_to_temp("tests/data/gradle-exercise");
+        file_to(&temp_dir, "build/output file", "");
+
+        let (plugin, _lock) = get_gradle();
+        plugin.clean(temp_dir.path()).unwrap();
+        assert!(!temp_dir.path().join("build/output file").exists());
+        assert!(temp_dir.path().join("src").exists());
+    }
+}
@@ -0,0 +1,50 @@
+//! Gradle student file policy
+
+use std::{ffi::OsStr, path::Path};
+use tmc_langs_framework::{StudentFilePolicy, TmcProjectYml};
+
+pub struct GradleStudentFilePolicy {
+    project_config: TmcProjectYml,
+}
+
+impl StudentFilePolicy for GradleStudentFilePolicy {
+    fn new_with_project_config(project_config: TmcProjectYml) -> Self
+    where
+        Self: Sized,
+    {
+        Self { project_config }
+    }
+
+    fn get_project_config(&self) -> &TmcProjectYml {
+        &self.project_config
+    }
+
+    fn is_non_extra_student_file(&self, path: &Path) -> bool {
+        let ext = path.extension();
+        path.starts_with("src/main")
+            && (ext == Some(OsStr::new("java")) || ext == Some(OsStr::new("kt")))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn is_student_file() {
+        let policy = GradleStudentFilePolicy::new(Path::new(".")).unwrap();
+        assert!(policy.is_student_file(Path::new("src/main/java/file.java")));
+        assert!(policy.is_student_file(Path::new("src/main/kotlin/dir/file.kt")));
+    }
+
+    #[test]
+    fn is_not_student_source_file() {
+        let policy = GradleStudentFilePolicy::new(Path::new(".")).unwrap();
+        assert!(!policy.is_student_file(Path::new("file.java")));
+        assert!(!policy.is_student_file(Path::new("build.gradle")));
+        assert!(!policy.is_student_file(Path::new("src/main/resources/file.txt")));
+        assert!(!policy.is_student_file(Path::new("src/test/java/file.java")));
+        assert!(!policy.is_student_file(Path::new("dir/src/main/file.java")));
+    }
+}
@@ -8,13 +8,14 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     convert::TryFrom,
+    env,
     ffi::OsStr,
     path::{Path, PathBuf},
     time::Duration,
 };
 use tmc_langs_framework::{
-    ExerciseDesc, Language, LanguagePlugin, RunResult, RunStatus, StyleValidationError,
-    StyleValidationResult, StyleValidationStrategy, TestDesc, TestResult, TmcCommand,
+    ExerciseDesc, Language, LanguagePlugin, RunResult, RunStatus, Sandbox, StyleValidationError,
+    StyleValidationResult, StyleValidationStrategy, TestDesc, TestFilter, TestResult, TmcCommand,
     nom::{IResult, Parser, bytes, character, combinator, sequence},
     nom_language::error::VerboseError,
 };
@@ -33,11 +34,13 @@ pub(crate) trait JavaPlugin: LanguagePlugin {
     /// Builds the Java project.
     fn build(&self, project_root_path: &Path) -> Result<CompileResult, JavaError>;
 
-    /// Runs the tests for the given project.
+    /// Runs the tests for the given project that are selected by the filter.
     fn run_java_tests(
         &self,
         project_root_path: &Path,
         timeout: Option<Duration>,
+        filter: &TestFilter,
+        sandbox: Option<&Sandbox>,
     ) -> Result<RunResult, JavaError> {
         log::info!(
             "running tests for project at {}",
@@ -49,8 +52,13 @@ pub(crate) trait JavaPlugin: LanguagePlugin {
             return Ok(self.run_result_from_failed_compilation(compile_result));
         }
 
-        let test_result =
-            self.create_run_result_file(project_root_path, timeout, compile_result)?;
+        let test_result = self.create_run_result_file(
+            project_root_path,
+            timeout,
+            compile_result,
+            filter,
+            sandbox,
+        )?;
         let result = self.parse_test_result(&test_result);
         if let Err(err) = file_util::remove_file(&test_result.test_results) {
             log::warn!("Failed to remove test results file: {err}");
@@ -85,6 +93,7 @@ pub(crate) trait JavaPlugin: LanguagePlugin {
             status,
             test_results,
             logs,
+            execution_stats: Some(results.stats.clone()),
         })
     }
 
@@ -114,6 +123,10 @@ pub(crate) trait JavaPlugin: LanguagePlugin {
             points,
             message,
             exception: exceptions,
+            // the JUnit runner does not report durations or capture output per test
+            duration_ms: None,
+            stdout: None,
+            stderr: None,
         }
     }
 
@@ -143,14 +156,96 @@ pub(crate) trait JavaPlugin: LanguagePlugin {
         }
     }
 
-    /// Runs tests and writes the results into a file.
+    /// Runs the tests selected by the filter and writes the results into a file.
+    /// Test runners that cannot select tests may run all of them.
     fn create_run_result_file(
         &self,
         path: &Path,
         timeout: Option<Duration>,
         compile_result: CompileResult,
+        filter: &TestFilter,
+        sandbox: Option<&Sandbox>,
     ) -> Result<TestRun, JavaError>;
 
+    /// Runs the tests of a compiled project that are selected by the filter with the bundled tmc-junit-runner and writes the results into results.txt in the project directory.
+    fn run_tmc_junit_runner(
+        &self,
+        path: &Path,
+        test_dir: &Path,
+        timeout: Option<Duration>,
+        compile_result: CompileResult,
+        filter: &TestFilter,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<TestRun, JavaError> {
+        // build java args
+        let mut arguments = vec![];
+        // JVM args
+        if let Ok(jvm_options) = env::var("JVM_OPTIONS") {
+            arguments.extend(
+                jvm_options
+                    .split(" +")
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.to_string()),
+            )
+        }
+        // TMC args
+        let result_file_name = "results.txt";
+        let result_file = path.join(result_file_name);
+        arguments.push(format!("-Dtmc.test_class_dir={}", test_dir.display()));
+        // we want to use path relative to the exercise path for the java command,
+        // and a path relative to the current directory for the rest of the program
+        arguments.push(format!("-Dtmc.results_file={result_file_name}"));
+        // TODO: endorsed libs?
+        let endorsed_libs_path = path.join("lib/endorsed");
+        if endorsed_libs_path.exists() {
+            arguments.push(format!(
+                "-Djava.endorsed.dirs={}",
+                endorsed_libs_path.display()
+            ));
+        }
+        // scan needs to be before getting class path
+        let exercise = self.scan_exercise_with_compile_result(
+            path,
+            format!("{}{}", path.display(), "/test"), // TODO: ?
+            compile_result,
+        )?;
+        // classpath
+        arguments.push("-cp".to_string());
+        let class_path = self.get_project_class_path(path)?;
+        arguments.push(class_path);
+        // main
+        arguments.push("fi.helsinki.cs.tmc.testrunner.Main".to_string());
+        // the tests to run in the format Class.method{point1,point2}
+        for desc in exercise
+            .tests
+            .into_iter()
+            .filter(|desc| filter.matches(&desc.name, &desc.points))
+        {
+            let mut s = String::new();
+            s.push_str(&desc.name.replace(' ', "."));
+            s.push('{');
+            s.push_str(&desc.points.join(","));
+            s.push('}');
+            arguments.push(s);
+        }
+
+        log::debug!("java args '{}' in {}", arguments.join(" "), path.display());
+        let command = TmcCommand::piped_in("java", sandbox).with(|e| e.cwd(path).args(&arguments));
+        let output = if let Some(timeout) = timeout {
+            command.output_with_timeout(timeout)?
+        } else {
+            command.output()?
+        };
+
+        Ok(TestRun {
+            test_results: result_file,
+            stdout: output.stdout,
+            stderr: output.stderr,
+            stats: output.stats,
+        })
+    }
+
     /// Checks the compile result and scans an exercise.
     fn scan_exercise_with_compile_result(
         &self,
@@ -206,13 +301,14 @@ pub(crate) trait JavaPlugin: LanguagePlugin {
             Ok(scan_results)
         })?;
 
-        let tests = scan_results
+        let mut tests: Vec<TestDesc> = scan_results
             .into_iter()
             .map(|s| TestDesc {
                 name: format!("{} {}", s.class_name, s.method_name),
                 points: s.points,
             })
             .collect();
+        tests.extend(self.scan_non_java_tests(path)?);
 
         Ok(ExerciseDesc {
             name: exercise_name,
@@ -220,6 +316,11 @@ pub(crate) trait JavaPlugin: LanguagePlugin {
         })
     }
 
+    /// Scans the tests in test sources that the bundled test scanner cannot read, in the same format as the Java tests.
+    fn scan_non_java_tests(&self, _path: &Path) -> Result<Vec<TestDesc>, JavaError> {
+        Ok(vec![])
+    }
+
     /// Creates a run result from a failed compilation.
     fn run_result_from_failed_compilation(&self, compile_result: CompileResult) -> RunResult {
         let mut logs = HashMap::new();
@@ -235,6 +336,7 @@ pub(crate) trait JavaPlugin: LanguagePlugin {
             status: RunStatus::CompileFailed,
             test_results: vec![],
             logs,
+            execution_stats: None,
         }
     }
 
@@ -361,7 +463,7 @@ mod test {
     use super::*;
     use crate::SEPARATOR;
     use std::io::{Read, Seek};
-    use tmc_langs_framework::{Archive, TmcError};
+    use tmc_langs_framework::{Archive, ExecutionStats, TmcError};
 
     fn init() {
         use log::*;
@@ -418,6 +520,7 @@ mod test {
             &self,
             _path: &Path,
             _timeout: Option<Duration>,
+            _sandbox: Option<&Sandbox>,
         ) -> Result<RunResult, TmcError> {
             unimplemented!()
         }
@@ -476,6 +579,8 @@ mod test {
             path: &Path,
             _timeout: Option<Duration>,
             _compile_result: CompileResult,
+            _filter: &TestFilter,
+            _sandbox: Option<&Sandbox>,
         ) -> Result<TestRun, JavaError> {
             let path = path.join("runresult");
             std::fs::write(
@@ -501,6 +606,7 @@ mod test {
                 test_results: path,
                 stdout: vec![],
                 stderr: vec![],
+                stats: ExecutionStats::default(),
             })
         }
     }
@@ -511,7 +617,9 @@ mod test {
 
         let temp_dir = tempfile::tempdir().unwrap();
         let plugin = Stub::new();
-        let result = plugin.run_java_tests(temp_dir.path(), None).unwrap();
+        let result = plugin
+            .run_java_tests(temp_dir.path(), None, &TestFilter::default(), None)
+            .unwrap();
         assert_eq!(result.status, RunStatus::TestsFailed);
     }
 
@@ -546,6 +654,7 @@ mod test {
             test_results: temp_file.path().to_path_buf(),
             stdout: vec![],
             stderr: vec![],
+            stats: ExecutionStats::default(),
         };
         let run_result = plugin.parse_test_result(&test_run).unwrap();
         assert_eq!(run_result.status, RunStatus::TestsFailed);
@@ -1,6 +1,6 @@
 #![deny(clippy::print_stdout, clippy::print_stderr, clippy::unwrap_used)]
 
-//! Java plugins for ant and maven
+//! Java plugins for ant, gradle and maven
 
 #[cfg(target_env = "musl")]
 compile_error!("The Java plugin does not work on musl");
@@ -8,19 +8,22 @@ compile_error!("The Java plugin does not work on musl");
 mod ant_plugin;
 mod ant_policy;
 mod error;
+mod gradle_plugin;
+mod gradle_policy;
 mod java_plugin;
 mod maven_plugin;
 mod maven_policy;
 
 pub use self::{
     ant_plugin::AntPlugin, ant_policy::AntStudentFilePolicy, error::JavaError,
-    maven_plugin::MavenPlugin, maven_policy::MavenStudentFilePolicy,
+    gradle_plugin::GradlePlugin, gradle_policy::GradleStudentFilePolicy, maven_plugin::MavenPlugin,
+    maven_policy::MavenStudentFilePolicy,
 };
 use j4rs::{ClasspathEntry, Instance, InvocationArg, Jvm, JvmBuilder, errors::J4RsError};
 use serde::Deserialize;
 use std::{fmt::Display, path::PathBuf};
 use tempfile::TempPath;
-use tmc_langs_framework::ExitStatus;
+use tmc_langs_framework::{ExecutionStats, ExitStatus};
 use tmc_langs_util::file_util;
 
 #[cfg(windows)]
@@ -253,6 +256,7 @@ struct TestRun {
     pub test_results: PathBuf,
     pub stdout: Vec<u8>,
     pub stderr: Vec<u8>,
+    pub stats: ExecutionStats,
 }
 
 #[derive(Debug, Deserialize)]
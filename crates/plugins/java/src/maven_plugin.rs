@@ -14,8 +14,8 @@ use std::{
 };
 use tar::Archive as Tar;
 use tmc_langs_framework::{
-    Archive, ExerciseDesc, Language, LanguagePlugin, RunResult, StyleValidationResult, TmcCommand,
-    TmcError, nom::IResult, nom_language::error::VerboseError,
+    Archive, ExerciseDesc, Language, LanguagePlugin, RunResult, Sandbox, StyleValidationResult,
+    TestFilter, TmcCommand, TmcError, nom::IResult, nom_language::error::VerboseError,
 };
 use tmc_langs_util::file_util;
 
@@ -101,6 +101,7 @@ impl LanguagePlugin for MavenPlugin {
     const DEFAULT_SANDBOX_IMAGE: &'static str = "eu.gcr.io/moocfi-public/tmc-sandbox-java:latest";
     const LINE_COMMENT: &'static str = "//";
     const BLOCK_COMMENT: Option<(&'static str, &'static str)> = Some(("/*", "*/"));
+    const SOURCE_FILE_EXTENSIONS: &'static [&'static str] = &["java"];
     type StudentFilePolicy = MavenStudentFilePolicy;
 
     fn check_code_style(
@@ -124,8 +125,20 @@ impl LanguagePlugin for MavenPlugin {
         &self,
         project_root_path: &Path,
         timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
     ) -> Result<RunResult, TmcError> {
-        Ok(self.run_java_tests(project_root_path, timeout)?)
+        Ok(self.run_java_tests(project_root_path, timeout, &TestFilter::default(), sandbox)?)
+    }
+
+    /// Only passes the selected tests to the test runner.
+    fn run_tests_filtered_with_timeout(
+        &self,
+        project_root_path: &Path,
+        filter: &TestFilter,
+        timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        Ok(self.run_java_tests(project_root_path, timeout, filter, sandbox)?)
     }
 
     fn find_project_dir_in_archive<R: Read + Seek>(
@@ -301,16 +314,30 @@ impl JavaPlugin for MavenPlugin {
     }
 
     /// Runs the tmc-maven-plugin.
+    /// The tmc-maven-plugin cannot select tests, so the selected tests are run with the bundled tmc-junit-runner instead,
+    /// using the class path resolved by Maven.
     fn create_run_result_file(
         &self,
         path: &Path,
         timeout: Option<Duration>,
-        _compile_result: CompileResult,
+        compile_result: CompileResult,
+        filter: &TestFilter,
+        sandbox: Option<&Sandbox>,
     ) -> Result<TestRun, JavaError> {
         log::info!("Running tests for maven project at {}", path.display());
+        if !filter.is_empty() {
+            return self.run_tmc_junit_runner(
+                path,
+                &path.join("src").join("test"),
+                timeout,
+                compile_result,
+                filter,
+                sandbox,
+            );
+        }
 
         let mvn_path = Self::get_mvn_command()?;
-        let command = TmcCommand::piped(mvn_path).with(|e| {
+        let command = TmcCommand::piped_in(mvn_path, sandbox).with(|e| {
             e.cwd(path)
                 .arg("--batch-mode")
                 .arg("fi.helsinki.cs.tmc:tmc-maven-plugin:1.12:test")
@@ -325,6 +352,7 @@ impl JavaPlugin for MavenPlugin {
             test_results: path.join("target/test_output.txt"),
             stdout: output.stdout,
             stderr: output.stderr,
+            stats: output.stats,
         })
     }
 }
@@ -499,6 +527,22 @@ mod test {
         assert_eq!(res.status, tmc_langs_framework::RunStatus::TestsFailed);
     }
 
+    #[test]
+    fn runs_filtered_tests() {
+        init();
+
+        let temp_dir = dir_to_temp("tests/data/maven-exercise");
+        let (plugin, _lock) = get_maven();
+        let filter = TestFilter::new(vec![], vec!["maven-exercise".to_string()]);
+        let res = plugin.run_tests_filtered(temp_dir.path(), &filter).unwrap();
+        log::debug!("{res:#?}");
+        assert_eq!(res.test_results.len(), 1);
+        assert_eq!(
+            res.test_results[0].name,
+            "fi.helsinki.cs.maventest.AppTest trol"
+        );
+    }
+
     #[test]
     fn runs_tests_timeout() {
         init();
@@ -506,7 +550,11 @@ mod test {
         let temp_dir = dir_to_temp("tests/data/maven-exercise");
         let (plugin, _lock) = get_maven();
         let test_result_err = plugin
-            .run_tests_with_timeout(temp_dir.path(), Some(std::time::Duration::from_nanos(1)))
+            .run_tests_with_timeout(
+                temp_dir.path(),
+                Some(std::time::Duration::from_nanos(1)),
+                None,
+            )
             .unwrap_err();
         log::debug!("{test_result_err:#?}");
 
@@ -619,7 +667,13 @@ mod test {
         let (plugin, _lock) = get_maven();
         let compile_result = plugin.build(test_path).unwrap();
         let test_run = plugin
-            .create_run_result_file(test_path, None, compile_result)
+            .create_run_result_file(
+                test_path,
+                None,
+                compile_result,
+                &TestFilter::default(),
+                None,
+            )
             .unwrap();
         let test_result: Vec<TestCase> =
             deserialize::json_from_str(&fs::read_to_string(test_run.test_results).unwrap())
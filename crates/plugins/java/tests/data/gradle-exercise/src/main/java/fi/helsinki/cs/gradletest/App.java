@@ -0,0 +1,7 @@
+package fi.helsinki.cs.gradletest;
+
+public class App {
+        public static void main(String[] args) {
+        //System.out.println("Hello Gradle!");
+    }
+}
@@ -0,0 +1,24 @@
+package fi.helsinki.cs.gradletest;
+
+import fi.helsinki.cs.tmc.edutestutils.MockStdio;
+import fi.helsinki.cs.tmc.edutestutils.Points;
+import fi.helsinki.cs.tmc.edutestutils.ReflectionUtils;
+import org.junit.Test;
+import static org.junit.Assert.*;
+import org.junit.Rule;
+
+public class AppTest {
+    @Rule
+    public MockStdio mio = new MockStdio();
+    
+    @Test
+    @Points("gradle-exercise")
+    public void trol() {
+        App.main(null);
+        assertEquals("Hello Gradle!\n", mio.getSysOut());
+        ReflectionUtils.newInstanceOfClass("fi.helsinki.cs.gradletest.App");
+
+
+        System.out.println("Tests executed");
+    }
+}
@@ -35,12 +35,16 @@ impl CheckLog {
 
                 let points = point_map.remove(&test.id).unwrap_or_default();
                 let exceptions = vec![];
+                let duration_ms = test.duration_ms();
                 test_results.push(TestResult {
                     name: test.description,
                     successful,
                     points,
                     message: test.message,
                     exception: exceptions,
+                    duration_ms,
+                    stdout: None,
+                    stderr: None,
                 });
             }
         }
@@ -48,6 +52,7 @@ impl CheckLog {
             status,
             test_results,
             logs,
+            execution_stats: None,
         }
     }
 }
@@ -74,8 +79,19 @@ pub struct Test {
     pub id: String,
     #[allow(dead_code)]
     pub iteration: String,
-    #[allow(dead_code)]
     pub duration: String,
     pub description: String,
     pub message: String,
 }
+
+impl Test {
+    // the duration is in seconds, or -1 if it was not measured
+    fn duration_ms(&self) -> Option<u32> {
+        self.duration
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|duration| *duration >= 0.0)
+            .map(|duration| (duration * 1000.0).round() as u32)
+    }
+}
@@ -14,7 +14,7 @@ use std::{
 };
 use tmc_langs_framework::{
     Archive, CommandError, ExerciseDesc, LanguagePlugin, Output, PopenError, RunResult, RunStatus,
-    TestDesc, TmcCommand, TmcError, TmcProjectYml,
+    Sandbox, TestDesc, TestFilter, TmcCommand, TmcError, TmcProjectYml,
     nom::{IResult, Parser, bytes, character, combinator, sequence},
     nom_language::error::VerboseError,
 };
@@ -61,12 +61,15 @@ impl MakePlugin {
     }
 
     /// Runs tests with or without valgrind according to the argument.
+    /// If a test case is given, Check only runs the test case with that name.
     /// Returns an error if the command finishes unsuccessfully.
     /// TODO: no option for timeout
     fn run_tests_with_valgrind(
         &self,
         path: &Path,
         run_valgrind: bool,
+        run_case: Option<&str>,
+        sandbox: Option<&Sandbox>,
     ) -> Result<Output, MakeError> {
         let arg = if run_valgrind {
             "run-test-with-valgrind"
@@ -75,8 +78,14 @@ impl MakePlugin {
         };
         log::info!("Running make {arg}");
 
-        let output = TmcCommand::piped("make")
-            .with(|e| e.cwd(path).arg(arg))
+        let output = TmcCommand::piped_in("make", sandbox)
+            .with(|e| {
+                let e = e.cwd(path).arg(arg);
+                match run_case {
+                    Some(run_case) => e.env("CK_RUN_CASE", run_case),
+                    None => e,
+                }
+            })
             .output()?;
 
         log::trace!("stdout: {}", String::from_utf8_lossy(&output.stdout));
@@ -110,41 +119,29 @@ impl MakePlugin {
 
         Ok(output)
     }
-}
-
-/// Project directory:
-/// Contains a src directory and a Makefile file
-impl LanguagePlugin for MakePlugin {
-    const PLUGIN_NAME: &'static str = "make";
-    const DEFAULT_SANDBOX_IMAGE: &'static str = "eu.gcr.io/moocfi-public/tmc-sandbox-make:latest";
-    const LINE_COMMENT: &'static str = "//";
-    const BLOCK_COMMENT: Option<(&'static str, &'static str)> = Some(("/*", "*/"));
-    type StudentFilePolicy = MakeStudentFilePolicy;
-
-    fn scan_exercise(&self, path: &Path, exercise_name: String) -> Result<ExerciseDesc, TmcError> {
-        if !Self::is_exercise_type_correct(path) {
-            return MakeError::NoExerciseFound(path.to_path_buf()).into();
-        }
-
-        self.run_tests_with_valgrind(path, false)?;
 
+    /// Lists the tests of a built project without running them.
+    fn list_tests(
+        &self,
+        path: &Path,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<Vec<TestDesc>, MakeError> {
+        // the test binary writes the available points before running the tests,
+        // and Check does not run any tests when the selected test case does not exist
+        self.run_tests_with_valgrind(path, false, Some("tmc-langs-list-tests"), sandbox)?;
         let available_points_path = path.join("test/tmc_available_points.txt");
-
         if !available_points_path.exists() {
-            return MakeError::CantFindAvailablePoints(available_points_path).into();
+            return Err(MakeError::CantFindAvailablePoints(available_points_path));
         }
-
-        let tests = self.parse_available_points(&available_points_path)?;
-        Ok(ExerciseDesc {
-            name: exercise_name,
-            tests,
-        })
+        self.parse_available_points(&available_points_path)
     }
 
-    fn run_tests_with_timeout(
+    /// Runs the tests, or only the test case with the given name.
+    fn run_tests_in_case(
         &self,
         path: &Path,
-        _timeout: Option<Duration>,
+        run_case: Option<&str>,
+        sandbox: Option<&Sandbox>,
     ) -> Result<RunResult, TmcError> {
         let output = self.build(path)?;
         if !output.status.success() {
@@ -161,6 +158,7 @@ impl LanguagePlugin for MakePlugin {
                 status: RunStatus::CompileFailed,
                 test_results: vec![],
                 logs,
+                execution_stats: None,
             });
         }
 
@@ -171,7 +169,7 @@ impl LanguagePlugin for MakePlugin {
 
         // try to run valgrind
         let mut ran_valgrind = true;
-        let valgrind_run = self.run_tests_with_valgrind(path, true);
+        let valgrind_run = self.run_tests_with_valgrind(path, true, run_case, sandbox);
         let output = match valgrind_run {
             Ok(output) => output,
             Err(error) => {
@@ -187,7 +185,7 @@ impl LanguagePlugin for MakePlugin {
                             {
                                 // failed due to lacking permissions, try to clean and rerun
                                 self.clean(path)?;
-                                match self.run_tests_with_valgrind(path, false) {
+                                match self.run_tests_with_valgrind(path, false, run_case, sandbox) {
                                     Ok(output) => output,
                                     Err(err) => {
                                         log::error!(
@@ -195,21 +193,23 @@ impl LanguagePlugin for MakePlugin {
                                         );
                                         ran_valgrind = false;
                                         log::info!("Running without valgrind");
-                                        self.run_tests_with_valgrind(path, false)?
+                                        self.run_tests_with_valgrind(
+                                            path, false, run_case, sandbox,
+                                        )?
                                     }
                                 }
                             }
                             _ => {
                                 ran_valgrind = false;
                                 log::info!("Running without valgrind");
-                                self.run_tests_with_valgrind(path, false)?
+                                self.run_tests_with_valgrind(path, false, run_case, sandbox)?
                             }
                         }
                     }
                     MakeError::RunningTestsWithValgrind(..) => {
                         ran_valgrind = false;
                         log::info!("Running without valgrind");
-                        self.run_tests_with_valgrind(path, false)?
+                        self.run_tests_with_valgrind(path, false, run_case, sandbox)?
                     }
                     err => {
                         log::warn!("unexpected error {err:?}");
@@ -260,6 +260,7 @@ impl LanguagePlugin for MakePlugin {
             String::from_utf8_lossy(&output.stdout).into_owned(),
         );
         let mut run_result = check_log.into_run_result(ids_to_points, logs);
+        run_result.execution_stats = Some(output.stats);
 
         if let Some(valgrind_log) = valgrind_log {
             if valgrind_log.errors {
@@ -283,6 +284,72 @@ impl LanguagePlugin for MakePlugin {
 
         Ok(run_result)
     }
+}
+
+/// Project directory:
+/// Contains a src directory and a Makefile file
+impl LanguagePlugin for MakePlugin {
+    const PLUGIN_NAME: &'static str = "make";
+    const DEFAULT_SANDBOX_IMAGE: &'static str = "eu.gcr.io/moocfi-public/tmc-sandbox-make:latest";
+    const LINE_COMMENT: &'static str = "//";
+    const BLOCK_COMMENT: Option<(&'static str, &'static str)> = Some(("/*", "*/"));
+    const SOURCE_FILE_EXTENSIONS: &'static [&'static str] = &["c", "h"];
+    type StudentFilePolicy = MakeStudentFilePolicy;
+
+    fn scan_exercise(&self, path: &Path, exercise_name: String) -> Result<ExerciseDesc, TmcError> {
+        if !Self::is_exercise_type_correct(path) {
+            return MakeError::NoExerciseFound(path.to_path_buf()).into();
+        }
+
+        self.run_tests_with_valgrind(path, false, None, None)?;
+
+        let available_points_path = path.join("test/tmc_available_points.txt");
+
+        if !available_points_path.exists() {
+            return MakeError::CantFindAvailablePoints(available_points_path).into();
+        }
+
+        let tests = self.parse_available_points(&available_points_path)?;
+        Ok(ExerciseDesc {
+            name: exercise_name,
+            tests,
+        })
+    }
+
+    fn run_tests_with_timeout(
+        &self,
+        path: &Path,
+        _timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        self.run_tests_in_case(path, None, sandbox)
+    }
+
+    /// Check can only select a single test case, so the tests are only selected by Check
+    /// when the filter matches exactly one test. Otherwise, all of the tests are run and the results are filtered.
+    fn run_tests_filtered_with_timeout(
+        &self,
+        path: &Path,
+        filter: &TestFilter,
+        _timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        // the tests can only be listed if the project builds, running all of them reports the compilation failure otherwise
+        if self.build(path)?.status.success() {
+            let selected = self
+                .list_tests(path, sandbox)?
+                .into_iter()
+                .filter(|t| filter.matches(&t.name, &t.points))
+                .collect::<Vec<_>>();
+            match selected.as_slice() {
+                [] => return Ok(RunResult::new(RunStatus::Passed, vec![], HashMap::new())),
+                [test] => return self.run_tests_in_case(path, Some(&test.name), sandbox),
+                _ => {}
+            }
+        }
+        let run_result = self.run_tests_in_case(path, None, sandbox)?;
+        Ok(filter.filter_run_result(run_result))
+    }
 
     fn find_project_dir_in_archive<R: Read + Seek>(
         archive: &mut Archive<R>,
@@ -571,6 +638,20 @@ test [invalid] point6
         assert_eq!(points[0], "1.1");
     }
 
+    #[test]
+    fn runs_filtered_tests() {
+        init();
+
+        let temp = dir_to_temp("tests/data/valgrind-failing-exercise");
+        let plugin = MakePlugin::new();
+        let filter = TestFilter::new(vec![], vec!["1.2".to_string()]);
+        let run_result = plugin.run_tests_filtered(temp.path(), &filter).unwrap();
+        let test_results = &run_result.test_results;
+        assert_eq!(test_results.len(), 1);
+        assert_eq!(test_results[0].name, "test_two");
+        assert_eq!(test_results[0].points, ["1.2"]);
+    }
+
     // if this test causes problems just disable it, valgrind might be writing the results in a random order
     #[test]
     fn runs_tests_failing_valgrind() {
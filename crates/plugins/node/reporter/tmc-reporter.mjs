@@ -0,0 +1,75 @@
+// A reporter for the Node.js test runner that writes the results in the same JSON format as Jest.
+// Used by tmc-langs with `node --test --test-reporter=./.tmc_reporter.mjs`.
+
+export default async function* tmcReporter(source) {
+  // file => { name, status, message, assertionResults, ancestors }
+  const files = new Map();
+  const getFile = (name) => {
+    if (!files.has(name)) {
+      files.set(name, {
+        name,
+        status: "passed",
+        message: "",
+        assertionResults: [],
+        ancestors: [],
+      });
+    }
+    return files.get(name);
+  };
+
+  for await (const event of source) {
+    const data = event.data ?? {};
+    const file = getFile(data.file ?? "");
+    switch (event.type) {
+      case "test:start":
+        // tests within a file are reported sequentially, so the currently running suites can be tracked by nesting
+        file.ancestors.length = data.nesting;
+        file.ancestors.push(data.name);
+        break;
+      case "test:stderr":
+        file.message += data.message;
+        break;
+      case "test:pass":
+      case "test:fail": {
+        if (data.details?.type === "suite") {
+          break;
+        }
+        if (data.nesting === 0 && data.name === data.file) {
+          // the file itself failed to run, e.g. due to a syntax error
+          if (event.type === "test:fail") {
+            file.status = "failed";
+          }
+          break;
+        }
+        const error = data.details?.error;
+        const cause = error?.cause ?? error;
+        let status = event.type === "test:pass" ? "passed" : "failed";
+        if (data.skip || data.todo) {
+          status = "pending";
+        }
+        if (status === "failed") {
+          file.status = "failed";
+        }
+        file.assertionResults.push({
+          ancestorTitles: file.ancestors.slice(0, data.nesting),
+          title: data.name,
+          status,
+          failureMessages: status === "failed" ? [String(cause?.stack ?? cause?.message ?? cause)] : [],
+        });
+        break;
+      }
+      default:
+        break;
+    }
+  }
+
+  const testResults = [...files.values()]
+    .filter((file) => file.name !== "")
+    .map(({ ancestors, ...file }) => {
+      if (file.status === "passed") {
+        file.message = "";
+      }
+      return file;
+    });
+  yield JSON.stringify({ testResults });
+}
@@ -0,0 +1,43 @@
+//! Node plugin error type
+
+use std::path::PathBuf;
+use thiserror::Error;
+use tmc_langs_framework::TmcError;
+use tmc_langs_util::{FileError, JsonError};
+
+#[derive(Debug, Error)]
+pub enum NodeError {
+    #[error("No Node exercise found at {0}")]
+    NoExerciseFound(PathBuf),
+    #[error("Failed to deserialize file at {0} to JSON")]
+    Deserialize(PathBuf, #[source] JsonError),
+    #[error(
+        "Failed to locate test results at {path}
+    stdout: {stdout}
+    stderr: {stderr}"
+    )]
+    MissingTestResults {
+        path: PathBuf,
+        stdout: String,
+        stderr: String,
+    },
+
+    #[error("File IO error")]
+    FileError(#[from] FileError),
+    #[error("Error")]
+    Tmc(#[from] TmcError),
+}
+
+// conversion from plugin error to TmcError::Plugin
+impl From<NodeError> for TmcError {
+    fn from(err: NodeError) -> TmcError {
+        TmcError::Plugin(Box::new(err))
+    }
+}
+
+// conversion from plugin error to a tmc result
+impl<T> From<NodeError> for Result<T, TmcError> {
+    fn from(from: NodeError) -> Self {
+        Err(TmcError::Plugin(Box::new(from)))
+    }
+}
@@ -0,0 +1,10 @@
+#![deny(clippy::print_stdout, clippy::print_stderr, clippy::unwrap_used)]
+
+//! TMC language plugin for JavaScript and TypeScript projects tested with Jest, Vitest or the Node.js test runner.
+
+mod error;
+mod node_test_result;
+mod plugin;
+mod policy;
+
+pub use self::{error::NodeError, plugin::NodePlugin, policy::NodeStudentFilePolicy};
@@ -0,0 +1,226 @@
+//! Contains types that model the JSON test report written by Jest, Vitest and the bundled reporter for the Node.js test runner.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use tmc_langs_framework::{RunStatus, TestResult};
+
+/// The JSON report. Vitest and the bundled reporter both use the same format as Jest.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeTestReport {
+    pub test_results: Vec<NodeTestFileResult>,
+}
+
+/// The results of a single test file.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeTestFileResult {
+    pub name: String,
+    pub status: NodeTestStatus,
+    /// Contains the error if the file failed to run.
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub assertion_results: Vec<NodeAssertionResult>,
+}
+
+/// The result of a single test case.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeAssertionResult {
+    /// The titles of the describe blocks the test is in, outermost first.
+    #[serde(default)]
+    pub ancestor_titles: Vec<String>,
+    pub title: String,
+    pub status: NodeTestStatus,
+    #[serde(default)]
+    pub failure_messages: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeTestStatus {
+    Passed,
+    Failed,
+    /// Skipped, pending, todo etc.
+    #[serde(other)]
+    Other,
+}
+
+/// The test results collected from a report.
+#[derive(Debug)]
+pub struct NodeTestRun {
+    pub status: RunStatus,
+    pub test_results: Vec<TestResult>,
+    /// Errors from test files that failed to run at all, e.g. due to syntax or type errors.
+    pub file_errors: Vec<String>,
+}
+
+impl NodeTestRun {
+    /// Collects the results from the given report. The points map should contain a mapping from test and describe block titles to a list of points, e.g.
+    /// "adds numbers" => ["1.1", "1.2"]. Each test gets the points of its own title and the titles of the describe blocks around it.
+    pub fn from_report(report: NodeTestReport, point_map: &HashMap<String, Vec<String>>) -> Self {
+        let mut status = RunStatus::Passed;
+        let mut test_results = vec![];
+        let mut file_errors = vec![];
+
+        for file in report.test_results {
+            if file.status == NodeTestStatus::Failed && file.assertion_results.is_empty() {
+                file_errors.push(format!("{}\n{}", file.name, file.message.trim()));
+                continue;
+            }
+
+            for assertion in file.assertion_results {
+                let successful = match assertion.status {
+                    NodeTestStatus::Passed => true,
+                    NodeTestStatus::Failed => false,
+                    NodeTestStatus::Other => continue,
+                };
+                if !successful {
+                    status = RunStatus::TestsFailed;
+                }
+
+                let mut points = vec![];
+                for title in assertion
+                    .ancestor_titles
+                    .iter()
+                    .chain(std::iter::once(&assertion.title))
+                {
+                    for point in point_map.get(title).into_iter().flatten() {
+                        if !points.contains(point) {
+                            points.push(point.clone());
+                        }
+                    }
+                }
+
+                // the failure messages contain the error message followed by a stack trace
+                let mut message = vec![];
+                let mut exception = vec![];
+                for line in assertion.failure_messages.iter().flat_map(|m| m.lines()) {
+                    let trimmed = line.trim();
+                    if trimmed.starts_with("at ") {
+                        exception.push(trimmed.to_string());
+                    } else {
+                        message.push(line);
+                    }
+                }
+
+                let name = assertion
+                    .ancestor_titles
+                    .into_iter()
+                    .chain(std::iter::once(assertion.title))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                test_results.push(TestResult {
+                    name,
+                    successful,
+                    points,
+                    message: message.join("\n").trim().to_string(),
+                    exception,
+                    duration_ms: None,
+                    stdout: None,
+                    stderr: None,
+                });
+            }
+        }
+
+        if !file_errors.is_empty() {
+            status = RunStatus::CompileFailed;
+        }
+        Self {
+            status,
+            test_results,
+            file_errors,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use tmc_langs_util::deserialize;
+
+    #[test]
+    fn deserializes_jest_report() {
+        // trimmed output of jest --json
+        let report = r#"
+{
+  "numFailedTests": 1,
+  "success": false,
+  "testResults": [
+    {
+      "assertionResults": [
+        {
+          "ancestorTitles": ["add"],
+          "failureMessages": [],
+          "fullName": "add adds",
+          "status": "passed",
+          "title": "adds"
+        },
+        {
+          "ancestorTitles": ["add"],
+          "failureMessages": [
+            "Error: expect(received).toBe(expected) // Object.is equality\n\nExpected: 4\nReceived: 3\n    at Object.toBe (/ex/test/add.test.js:8:22)\n    at processTicksAndRejections (node:internal/process/task_queues:95:5)"
+          ],
+          "fullName": "add adds negative",
+          "status": "failed",
+          "title": "adds negative"
+        },
+        {
+          "ancestorTitles": [],
+          "failureMessages": [],
+          "fullName": "skipped",
+          "status": "pending",
+          "title": "skipped"
+        }
+      ],
+      "message": "",
+      "name": "/ex/test/add.test.js",
+      "status": "failed"
+    }
+  ]
+}
+"#;
+        let report: NodeTestReport = deserialize::json_from_str(report).unwrap();
+        let mut point_map = HashMap::new();
+        point_map.insert("add".to_string(), vec!["1".to_string()]);
+        point_map.insert("adds".to_string(), vec!["1.1".to_string()]);
+        let run = NodeTestRun::from_report(report, &point_map);
+
+        assert_eq!(run.status, RunStatus::TestsFailed);
+        assert!(run.file_errors.is_empty());
+        assert_eq!(run.test_results.len(), 2);
+        assert_eq!(run.test_results[0].name, "add adds");
+        assert_eq!(run.test_results[0].points, &["1", "1.1"]);
+        assert_eq!(run.test_results[1].name, "add adds negative");
+        assert!(!run.test_results[1].successful);
+        assert_eq!(
+            run.test_results[1].message,
+            "Error: expect(received).toBe(expected) // Object.is equality\n\nExpected: 4\nReceived: 3"
+        );
+        assert_eq!(run.test_results[1].exception.len(), 2);
+    }
+
+    #[test]
+    fn file_errors_are_compile_failures() {
+        let report = r#"
+{
+  "testResults": [
+    {
+      "assertionResults": [],
+      "message": "SyntaxError: Unexpected end of input",
+      "name": "/ex/test/add.test.js",
+      "status": "failed"
+    }
+  ]
+}
+"#;
+        let report: NodeTestReport = deserialize::json_from_str(report).unwrap();
+        let run = NodeTestRun::from_report(report, &HashMap::new());
+
+        assert_eq!(run.status, RunStatus::CompileFailed);
+        assert!(run.test_results.is_empty());
+        assert!(run.file_errors[0].contains("SyntaxError"));
+    }
+}
@@ -0,0 +1,791 @@
+//! Contains the LanguagePlugin implementation for Node.
+
+use crate::{
+    error::NodeError,
+    node_test_result::{NodeTestReport, NodeTestRun},
+    policy::NodeStudentFilePolicy,
+};
+use serde::{Deserialize, de::IgnoredAny};
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    io::{Read, Seek},
+    ops::ControlFlow::{Break, Continue},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tmc_langs_framework::{
+    Archive, CommandError, ExerciseDesc, LanguagePlugin, RunResult, RunStatus, Sandbox, TestDesc,
+    TestResult, TmcCommand, TmcError,
+    nom::{self, IResult, Parser, branch, bytes, character, combinator, multi, sequence},
+    nom_language::error::VerboseError,
+};
+use tmc_langs_util::{deserialize, file_util, parse_util, path_util};
+use walkdir::WalkDir;
+
+/// Reporter for the Node.js test runner that writes the results in the same format as Jest.
+const NODE_TEST_REPORTER: &str = include_str!("../reporter/tmc-reporter.mjs");
+const TEST_RESULTS_FILE: &str = ".tmc_test_results.json";
+const REPORTER_FILE: &str = ".tmc_reporter.mjs";
+
+#[derive(Default)]
+pub struct NodePlugin {}
+
+impl NodePlugin {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Scans the exercise's test files for test cases and the points annotated on them.
+    /// A points call applies to the test or describe block that follows it.
+    fn scan_tests(path: &Path) -> Result<Vec<TestDesc>, TmcError> {
+        let config = Self::get_exercise_packaging_configuration(path)?;
+
+        let mut tests = vec![];
+        for exercise_file_path in config.exercise_file_paths {
+            let is_source_file = exercise_file_path
+                .extension()
+                .and_then(OsStr::to_str)
+                .map(|ext| Self::SOURCE_FILE_EXTENSIONS.contains(&ext))
+                .unwrap_or_default();
+            if !is_source_file
+                || exercise_file_path
+                    .components()
+                    .any(|c| c.as_os_str() == "node_modules")
+            {
+                continue;
+            }
+            let exercise_file_path = path.join(exercise_file_path);
+            log::trace!("scanning tests from {}", exercise_file_path.display());
+            let file_contents = file_util::read_file_to_string_lossy(&exercise_file_path)?;
+
+            let mut parser = multi::many0(branch::alt((
+                // comments
+                combinator::value(
+                    Scan::Other,
+                    (
+                        bytes::complete::tag("//"),
+                        character::complete::not_line_ending,
+                    ),
+                ),
+                combinator::value(
+                    Scan::Other,
+                    (
+                        bytes::complete::tag("/*"),
+                        bytes::complete::take_until("*/"),
+                        bytes::complete::tag("*/"),
+                    ),
+                ),
+                combinator::map(Self::points_parser, |points| {
+                    Scan::Points(points.into_iter().map(str::to_string).collect())
+                }),
+                // test("title", ...), it.only("title", ...), describe("title", ...) etc.
+                combinator::map(
+                    (
+                        combinator::verify(identifier, |s: &str| {
+                            matches!(s, "test" | "it" | "describe")
+                        }),
+                        combinator::opt((character::complete::char('.'), identifier)),
+                        character::complete::multispace0,
+                        character::complete::char('('),
+                        character::complete::multispace0,
+                        string,
+                    ),
+                    |(kind, _, _, _, _, title)| {
+                        if kind == "describe" {
+                            Scan::Describe(title.to_string())
+                        } else {
+                            Scan::Test(title.to_string())
+                        }
+                    },
+                ),
+                // whole identifiers are skipped so that the test functions are only recognized at the start of a word
+                combinator::value(Scan::Other, identifier),
+                combinator::value(Scan::Other, character::complete::anychar),
+            )));
+
+            let res: IResult<_, _, VerboseError<&str>> = parser.parse(&file_contents);
+            let scanned = match res {
+                Ok((_, scanned)) => scanned,
+                Err(nom::Err::Incomplete(_)) => unreachable!("this should never happen"),
+                Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                    return Err(TmcError::PointParse(
+                        exercise_file_path,
+                        VerboseError {
+                            errors: e
+                                .errors
+                                .into_iter()
+                                .map(|(s, k)| (s.to_string(), k))
+                                .collect(),
+                        },
+                    ));
+                }
+            };
+
+            let mut points = vec![];
+            for scan in scanned {
+                match scan {
+                    Scan::Points(p) => points.extend(p),
+                    Scan::Test(title) => {
+                        tests.push(TestDesc::new(title, std::mem::take(&mut points)));
+                    }
+                    Scan::Describe(title) => {
+                        // describe blocks are only interesting if they give points to the tests inside them
+                        if !points.is_empty() {
+                            tests.push(TestDesc::new(title, std::mem::take(&mut points)));
+                        }
+                    }
+                    Scan::Other => {}
+                }
+            }
+        }
+        Ok(tests)
+    }
+
+    /// Checks the dependencies in package.json to see which test runner the exercise uses.
+    fn get_test_runner(path: &Path) -> Result<TestRunner, NodeError> {
+        let package_json_path = path.join("package.json");
+        let package_json = file_util::read_file_to_string(&package_json_path)?;
+        let package_json: PackageJson = deserialize::json_from_str(&package_json)
+            .map_err(|e| NodeError::Deserialize(package_json_path, e))?;
+
+        let has_dependency = |name: &str| {
+            package_json.dependencies.contains_key(name)
+                || package_json.dev_dependencies.contains_key(name)
+        };
+        let runner = if has_dependency("vitest") {
+            TestRunner::Vitest
+        } else if has_dependency("jest") {
+            TestRunner::Jest
+        } else {
+            TestRunner::NodeTest
+        };
+        Ok(runner)
+    }
+
+    /// Parses the test report and removes it.
+    fn parse_test_report(test_results_json: &Path) -> Result<NodeTestReport, NodeError> {
+        let results = file_util::read_file_to_string(test_results_json)?;
+        let report = deserialize::json_from_str(&results)
+            .map_err(|e| NodeError::Deserialize(test_results_json.to_path_buf(), e))?;
+        file_util::remove_file(test_results_json)?;
+        Ok(report)
+    }
+}
+
+/// Project directory:
+/// Contains a package.json file.
+impl LanguagePlugin for NodePlugin {
+    const PLUGIN_NAME: &'static str = "node";
+    const DEFAULT_SANDBOX_IMAGE: &'static str = "eu.gcr.io/moocfi-public/tmc-sandbox-node:latest";
+    const LINE_COMMENT: &'static str = "//";
+    const BLOCK_COMMENT: Option<(&'static str, &'static str)> = Some(("/*", "*/"));
+    const SOURCE_FILE_EXTENSIONS: &'static [&'static str] =
+        &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts"];
+    type StudentFilePolicy = NodeStudentFilePolicy;
+
+    fn scan_exercise(&self, path: &Path, exercise_name: String) -> Result<ExerciseDesc, TmcError> {
+        if !Self::is_exercise_type_correct(path) {
+            return NodeError::NoExerciseFound(path.to_path_buf()).into();
+        }
+
+        let tests = Self::scan_tests(path)?;
+        Ok(ExerciseDesc {
+            name: exercise_name,
+            tests,
+        })
+    }
+
+    /// Runs the tests with Vitest or Jest if the exercise depends on one of them, or with the Node.js test runner otherwise.
+    /// The runners are expected to be installed in the exercise's node_modules, they are never downloaded.
+    fn run_tests_with_timeout(
+        &self,
+        path: &Path,
+        timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        let mut point_map = HashMap::<String, Vec<String>>::new();
+        for test in Self::scan_tests(path)? {
+            point_map.entry(test.name).or_default().extend(test.points);
+        }
+
+        let test_results_json = path.join(TEST_RESULTS_FILE);
+        if test_results_json.exists() {
+            file_util::remove_file(&test_results_json)?;
+        }
+
+        let runner = Self::get_test_runner(path)?;
+        log::info!("running tests with {runner:?}");
+        let command = match runner {
+            TestRunner::Vitest => TmcCommand::piped_in("npx", sandbox).with(|e| {
+                e.args(&["--no", "vitest", "run", "--reporter=json"])
+                    .arg(format!("--outputFile={TEST_RESULTS_FILE}"))
+            }),
+            TestRunner::Jest => TmcCommand::piped_in("npx", sandbox).with(|e| {
+                e.args(&["--no", "jest", "--json"])
+                    .arg(format!("--outputFile={TEST_RESULTS_FILE}"))
+            }),
+            TestRunner::NodeTest => {
+                file_util::write_to_file(NODE_TEST_REPORTER, path.join(REPORTER_FILE))?;
+                TmcCommand::piped_in("node", sandbox).with(|e| {
+                    e.arg("--test")
+                        .arg(format!("--test-reporter=./{REPORTER_FILE}"))
+                        .arg(format!("--test-reporter-destination={TEST_RESULTS_FILE}"))
+                })
+            }
+        }
+        .with(|e| e.cwd(path).env("CI", "true").env("NO_COLOR", "1"));
+
+        let output = if let Some(timeout) = timeout {
+            command.output_with_timeout(timeout)
+        } else {
+            command.output()
+        };
+        if runner == TestRunner::NodeTest {
+            file_util::remove_file(path.join(REPORTER_FILE))?;
+        }
+
+        match output {
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                log::trace!("stdout: {stdout}");
+                log::debug!("stderr: {stderr}");
+
+                if !test_results_json.exists() {
+                    return Err(NodeError::MissingTestResults {
+                        path: test_results_json,
+                        stdout: stdout.into_owned(),
+                        stderr: stderr.into_owned(),
+                    }
+                    .into());
+                }
+                let report = Self::parse_test_report(&test_results_json)?;
+                let run = NodeTestRun::from_report(report, &point_map);
+
+                let mut logs = HashMap::new();
+                logs.insert("stdout".to_string(), stdout.into_owned());
+                let mut stderr = stderr.into_owned();
+                for file_error in &run.file_errors {
+                    stderr.push('\n');
+                    stderr.push_str(file_error);
+                }
+                logs.insert("stderr".to_string(), stderr);
+
+                // remove points associated with any failed tests
+                let mut test_results = run.test_results;
+                let mut failed_points = HashSet::new();
+                for test_result in &test_results {
+                    if !test_result.successful {
+                        failed_points.extend(test_result.points.iter().cloned());
+                    }
+                }
+                for test_result in &mut test_results {
+                    test_result.points.retain(|p| !failed_points.contains(p));
+                }
+
+                Ok(RunResult {
+                    status: run.status,
+                    test_results,
+                    logs,
+                    execution_stats: Some(output.stats),
+                })
+            }
+            Err(TmcError::Command(CommandError::TimeOut {
+                stdout,
+                stderr,
+                stats,
+                ..
+            })) => {
+                let mut logs = HashMap::new();
+                logs.insert("stdout".to_string(), stdout);
+                logs.insert("stderr".to_string(), stderr);
+                Ok(RunResult {
+                    status: RunStatus::TestsFailed,
+                    test_results: vec![TestResult {
+                        name: "Timeout test".to_string(),
+                        successful: false,
+                        points: vec![],
+                        message:
+                            "Tests timed out.\nMake sure you don't have an infinite loop in your code."
+                                .to_string(),
+                        exception: vec![],
+                        duration_ms: None,
+                        stdout: None,
+                        stderr: None,
+                    }],
+                    logs,
+                    execution_stats: Some(stats),
+                })
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Finds the shallowest directory with a package.json file, ignoring node_modules.
+    fn find_project_dir_in_archive<R: Read + Seek>(
+        archive: &mut Archive<R>,
+    ) -> Result<PathBuf, TmcError> {
+        let mut iter = archive.iter()?;
+        let mut shallowest_package_parent: Option<PathBuf> = None;
+        loop {
+            let next = iter.with_next::<(), _>(|file| {
+                let file_path = file.path()?;
+
+                if file.is_file()
+                    && !file_path
+                        .components()
+                        .any(|c| c.as_os_str() == "__MACOSX" || c.as_os_str() == "node_modules")
+                {
+                    if let Some(parent) = path_util::get_parent_of_named(&file_path, "package.json")
+                    {
+                        let is_shallower = shallowest_package_parent
+                            .as_ref()
+                            .map(|shallowest| {
+                                shallowest.components().count() > parent.components().count()
+                            })
+                            .unwrap_or(true);
+                        if is_shallower {
+                            shallowest_package_parent = Some(parent);
+                        }
+                    }
+                }
+                Ok(Continue(()))
+            });
+            match next? {
+                Continue(_) => continue,
+                Break(_) => break,
+            }
+        }
+
+        match shallowest_package_parent {
+            Some(project_dir) => Ok(project_dir),
+            None => Err(TmcError::NoProjectDirInArchive),
+        }
+    }
+
+    /// Checks if the directory contains a package.json file.
+    fn is_exercise_type_correct(path: &Path) -> bool {
+        path.join("package.json").is_file()
+    }
+
+    /// Removes files left over from test runs. node_modules is left as is since the exercise may depend on it being installed.
+    fn clean(&self, path: &Path) -> Result<(), TmcError> {
+        for entry in WalkDir::new(path)
+            .into_iter()
+            .filter_entry(|e| e.file_name() != "node_modules")
+            .filter_map(|e| e.ok())
+        {
+            if entry.file_name() == TEST_RESULTS_FILE || entry.file_name() == REPORTER_FILE {
+                file_util::remove_file(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn get_default_student_file_paths() -> Vec<PathBuf> {
+        vec![PathBuf::from("src")]
+    }
+
+    fn get_default_exercise_file_paths() -> Vec<PathBuf> {
+        vec![
+            PathBuf::from("test"),
+            PathBuf::from("tests"),
+            PathBuf::from("__tests__"),
+        ]
+    }
+
+    /// Parses calls like points("1.1") or points('1.1', '1.2').
+    fn points_parser(i: &str) -> IResult<&str, Vec<&str>, VerboseError<&str>> {
+        combinator::map(
+            sequence::delimited(
+                (
+                    bytes::complete::tag("points"),
+                    character::complete::multispace0,
+                    character::complete::char('('),
+                    character::complete::multispace0,
+                ),
+                parse_util::comma_separated_strings_either,
+                (
+                    character::complete::multispace0,
+                    combinator::opt(character::complete::char(',')),
+                    character::complete::multispace0,
+                    character::complete::char(')'),
+                ),
+            ),
+            // splits each point by whitespace
+            |points| {
+                points
+                    .into_iter()
+                    .flat_map(|p| p.split_whitespace())
+                    .collect()
+            },
+        )
+        .parse(i)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TestRunner {
+    Jest,
+    Vitest,
+    NodeTest,
+}
+
+/// The parts of package.json that are used to detect the test runner.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageJson {
+    #[serde(default)]
+    dependencies: HashMap<String, IgnoredAny>,
+    #[serde(default)]
+    dev_dependencies: HashMap<String, IgnoredAny>,
+}
+
+/// The interesting parts of a test file when scanning for tests.
+#[derive(Debug, Clone)]
+enum Scan {
+    Points(Vec<String>),
+    Test(String),
+    Describe(String),
+    Other,
+}
+
+/// Parses a JavaScript identifier.
+fn identifier(i: &str) -> IResult<&str, &str, VerboseError<&str>> {
+    combinator::recognize((
+        character::complete::satisfy(|c| c.is_alphabetic() || c == '_' || c == '$'),
+        bytes::complete::take_while(|c: char| c.is_alphanumeric() || c == '_' || c == '$'),
+    ))
+    .parse(i)
+}
+
+/// Parses a string literal delimited by double quotes, single quotes or backticks.
+fn string(i: &str) -> IResult<&str, &str, VerboseError<&str>> {
+    branch::alt((
+        sequence::delimited(
+            character::complete::char('"'),
+            bytes::complete::is_not("\""),
+            character::complete::char('"'),
+        ),
+        sequence::delimited(
+            character::complete::char('\''),
+            bytes::complete::is_not("'"),
+            character::complete::char('\''),
+        ),
+        sequence::delimited(
+            character::complete::char('`'),
+            bytes::complete::is_not("`"),
+            character::complete::char('`'),
+        ),
+    ))
+    .parse(i)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use zip::write::SimpleFileOptions;
+
+    fn init() {
+        use log::*;
+        use simple_logger::*;
+        let _ = SimpleLogger::new().with_level(LevelFilter::Debug).init();
+    }
+
+    fn file_to(
+        target_dir: impl AsRef<std::path::Path>,
+        target_relative: impl AsRef<std::path::Path>,
+        contents: impl AsRef<[u8]>,
+    ) -> PathBuf {
+        let target = target_dir.as_ref().join(target_relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(&target, contents.as_ref()).unwrap();
+        target
+    }
+
+    fn dir_to_zip(source_dir: impl AsRef<std::path::Path>) -> Vec<u8> {
+        use std::io::Write;
+
+        let mut target = vec![];
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(&mut target));
+
+        for entry in walkdir::WalkDir::new(&source_dir)
+            .min_depth(1)
+            .sort_by(|a, b| a.path().cmp(b.path()))
+        {
+            let entry = entry.unwrap();
+            let rela = entry
+                .path()
+                .strip_prefix(&source_dir)
+                .unwrap()
+                .to_str()
+                .unwrap();
+            if entry.path().is_dir() {
+                zip.add_directory(rela, SimpleFileOptions::default())
+                    .unwrap();
+            } else if entry.path().is_file() {
+                zip.start_file(rela, SimpleFileOptions::default()).unwrap();
+                let bytes = std::fs::read(entry.path()).unwrap();
+                zip.write_all(&bytes).unwrap();
+            }
+        }
+
+        zip.finish().unwrap();
+        target
+    }
+
+    /// Writes an exercise that is tested with the Node.js test runner, using the given source and test files.
+    fn exercise_to(target_dir: impl AsRef<std::path::Path>, src: &str, tests: &str) {
+        file_to(
+            &target_dir,
+            "package.json",
+            r#"{ "name": "exercise", "type": "module" }"#,
+        );
+        file_to(
+            &target_dir,
+            "tmc/points.mjs",
+            "export const points = (...points) => {};\n",
+        );
+        file_to(&target_dir, "src/add.mjs", src);
+        file_to(&target_dir, "test/add.test.mjs", tests);
+    }
+
+    const TESTS: &str = r#"
+import { test, it, describe } from "node:test";
+import assert from "node:assert";
+import { points } from "../tmc/points.mjs";
+import { add } from "../src/add.mjs";
+
+points("1");
+describe("add", () => {
+  points("1.1");
+  test("adds", () => {
+    assert.strictEqual(add(1, 2), 3);
+  });
+
+  points('1.1 1.2');
+  it("adds negative", () => {
+    assert.strictEqual(add(-1, -2), -3);
+  });
+});
+
+// points("2")
+test(`unannotated`, () => {});
+
+test.skip("skipped", () => {});
+"#;
+
+    #[test]
+    fn parses_points() {
+        init();
+
+        assert!(NodePlugin::points_parser("asd").is_err());
+        assert!(NodePlugin::points_parser("points(\"1\"").is_err());
+
+        let res = NodePlugin::points_parser("points(\"1\")").unwrap();
+        assert_eq!(res.1, &["1"]);
+
+        let res = NodePlugin::points_parser("points ( '1' , \"2 3\", )").unwrap();
+        assert_eq!(res.1, &["1", "2", "3"]);
+    }
+
+    #[test]
+    fn gets_available_points() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, "", TESTS);
+        let points = NodePlugin::get_available_points(temp.path()).unwrap();
+        assert_eq!(points, &["1", "1.1", "1.1", "1.2"]);
+    }
+
+    #[test]
+    fn scans_exercise() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, "", TESTS);
+        let plugin = NodePlugin::new();
+        let desc = plugin.scan_exercise(temp.path(), "ex".to_string()).unwrap();
+        assert_eq!(desc.name, "ex");
+        assert_eq!(
+            desc.tests,
+            &[
+                TestDesc::new("add".to_string(), vec!["1".to_string()]),
+                TestDesc::new("adds".to_string(), vec!["1.1".to_string()]),
+                TestDesc::new(
+                    "adds negative".to_string(),
+                    vec!["1.1".to_string(), "1.2".to_string()]
+                ),
+                TestDesc::new("unannotated".to_string(), vec![]),
+                TestDesc::new("skipped".to_string(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_test_runner() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        file_to(&temp, "package.json", r#"{ "name": "exercise" }"#);
+        assert_eq!(
+            NodePlugin::get_test_runner(temp.path()).unwrap(),
+            TestRunner::NodeTest
+        );
+
+        file_to(
+            &temp,
+            "package.json",
+            r#"{ "devDependencies": { "jest": "^29.0.0" } }"#,
+        );
+        assert_eq!(
+            NodePlugin::get_test_runner(temp.path()).unwrap(),
+            TestRunner::Jest
+        );
+
+        file_to(
+            &temp,
+            "package.json",
+            r#"{ "dependencies": { "react": "^18.0.0" }, "devDependencies": { "vitest": "^1.0.0" } }"#,
+        );
+        assert_eq!(
+            NodePlugin::get_test_runner(temp.path()).unwrap(),
+            TestRunner::Vitest
+        );
+    }
+
+    #[test]
+    fn runs_tests_passing() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, "export const add = (a, b) => a + b;\n", TESTS);
+        let plugin = NodePlugin::new();
+        let res = plugin.run_tests(temp.path()).unwrap();
+        log::debug!("{res:#?}");
+        assert_eq!(res.status, RunStatus::Passed);
+        assert_eq!(res.test_results.len(), 3);
+        for test_result in &res.test_results {
+            assert!(test_result.successful);
+        }
+        let adds = res
+            .test_results
+            .iter()
+            .find(|t| t.name == "add adds")
+            .unwrap();
+        assert_eq!(adds.points, &["1", "1.1"]);
+        assert!(!temp.path().join(TEST_RESULTS_FILE).exists());
+        assert!(!temp.path().join(REPORTER_FILE).exists());
+    }
+
+    #[test]
+    fn runs_tests_failing() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(
+            &temp,
+            "export const add = (a, b) => a < 0 ? 0 : a + b;\n",
+            TESTS,
+        );
+        let plugin = NodePlugin::new();
+        let res = plugin.run_tests(temp.path()).unwrap();
+        log::debug!("{res:#?}");
+        assert_eq!(res.status, RunStatus::TestsFailed);
+        let failed = res
+            .test_results
+            .iter()
+            .find(|t| t.name == "add adds negative")
+            .unwrap();
+        assert!(!failed.successful);
+        assert!(failed.message.contains("-3"));
+        assert!(!failed.exception.is_empty());
+        let unannotated = res
+            .test_results
+            .iter()
+            .find(|t| t.name == "unannotated")
+            .unwrap();
+        assert!(unannotated.successful);
+        // the points are shared with the failed test so they are not awarded
+        for test_result in &res.test_results {
+            assert!(test_result.points.is_empty());
+        }
+    }
+
+    #[test]
+    fn runs_tests_compile_err() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, "export const add = (a, b) => a +;\n", TESTS);
+        let plugin = NodePlugin::new();
+        let res = plugin.run_tests(temp.path()).unwrap();
+        log::debug!("{res:#?}");
+        assert_eq!(res.status, RunStatus::CompileFailed);
+        assert!(res.test_results.is_empty());
+        assert!(res.logs.get("stderr").unwrap().contains("SyntaxError"));
+    }
+
+    #[test]
+    fn runs_tests_timeout() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, "export const add = (a, b) => a + b;\n", TESTS);
+        let plugin = NodePlugin::new();
+        let res = plugin
+            .run_tests_with_timeout(temp.path(), Some(std::time::Duration::from_nanos(1)), None)
+            .unwrap();
+        assert_eq!(res.status, RunStatus::TestsFailed);
+        assert_eq!(res.test_results[0].name, "Timeout test");
+    }
+
+    #[test]
+    fn cleans() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        let f1 = file_to(&temp, TEST_RESULTS_FILE, "");
+        let f2 = file_to(&temp, REPORTER_FILE, "");
+        let f3 = file_to(&temp, "node_modules/dep/.tmc_test_results.json", "");
+        let f4 = file_to(&temp, "src/add.mjs", "");
+
+        NodePlugin::new().clean(temp.path()).unwrap();
+
+        assert!(!f1.exists());
+        assert!(!f2.exists());
+        assert!(f3.exists());
+        assert!(f4.exists());
+    }
+
+    #[test]
+    fn finds_project_dir_in_zip() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(temp.path().join("outer/exercise"), "", "");
+        file_to(&temp, "outer/exercise/node_modules/dep/package.json", "{}");
+        let zip = dir_to_zip(&temp);
+        let mut zip = Archive::zip(std::io::Cursor::new(zip)).unwrap();
+        let dir = NodePlugin::find_project_dir_in_archive(&mut zip).unwrap();
+        assert_eq!(dir, Path::new("outer/exercise"));
+    }
+
+    #[test]
+    fn doesnt_find_project_dir_in_zip() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        file_to(&temp, "outer/exercise/src/add.mjs", "");
+        file_to(&temp, "outer/__MACOSX/package.json", "");
+        file_to(&temp, "outer/node_modules/package.json", "");
+        let zip = dir_to_zip(&temp);
+        let mut zip = Archive::zip(std::io::Cursor::new(zip)).unwrap();
+        assert!(NodePlugin::find_project_dir_in_archive(&mut zip).is_err());
+    }
+}
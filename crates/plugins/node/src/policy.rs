@@ -0,0 +1,57 @@
+//! Contains the Node student file policy.
+
+use std::path::Path;
+use tmc_langs_framework::{StudentFilePolicy, TmcProjectYml};
+
+pub struct NodeStudentFilePolicy {
+    project_config: TmcProjectYml,
+}
+
+impl StudentFilePolicy for NodeStudentFilePolicy {
+    fn new_with_project_config(project_config: TmcProjectYml) -> Self
+    where
+        Self: Sized,
+    {
+        Self { project_config }
+    }
+
+    fn get_project_config(&self) -> &TmcProjectYml {
+        &self.project_config
+    }
+
+    fn is_non_extra_student_file(&self, path: &Path) -> bool {
+        // test files are often placed next to the code they test,
+        // so files like src/add.test.js and src/__tests__/add.js are excluded
+        let in_tests_dir = path.components().any(|c| c.as_os_str() == "__tests__");
+        let is_test_file = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(|stem| stem.ends_with(".test") || stem.ends_with(".spec"))
+            .unwrap_or_default();
+        path.starts_with("src") && !in_tests_dir && !is_test_file
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn file_in_src_is_student_file() {
+        let policy = NodeStudentFilePolicy::new(Path::new(".")).unwrap();
+        assert!(policy.is_student_file(Path::new("src/index.js")));
+        assert!(policy.is_student_file(Path::new("src/components/App.tsx")));
+    }
+
+    #[test]
+    fn test_files_are_not_student_files() {
+        let policy = NodeStudentFilePolicy::new(Path::new(".")).unwrap();
+        assert!(!policy.is_student_file(Path::new("src/index.test.js")));
+        assert!(!policy.is_student_file(Path::new("src/App.spec.tsx")));
+        assert!(!policy.is_student_file(Path::new("src/__tests__/index.js")));
+        assert!(!policy.is_student_file(Path::new("test/index.test.mjs")));
+        assert!(!policy.is_student_file(Path::new("package.json")));
+        assert!(!policy.is_student_file(Path::new("node_modules/jest/package.json")));
+    }
+}
@@ -9,8 +9,8 @@ use std::{
     time::Duration,
 };
 use tmc_langs_framework::{
-    Archive, ExerciseDesc, LanguagePlugin, RunResult, RunStatus, StudentFilePolicy, TestDesc,
-    TestResult, TmcError, TmcProjectYml,
+    Archive, ExerciseDesc, LanguagePlugin, RunResult, RunStatus, Sandbox, StudentFilePolicy,
+    TestDesc, TestResult, TmcError, TmcProjectYml,
     nom::{self, IResult},
     nom_language::error::VerboseError,
 };
@@ -59,6 +59,7 @@ impl LanguagePlugin for NoTestsPlugin {
         &self,
         path: &Path,
         _timeout: Option<Duration>,
+        _sandbox: Option<&Sandbox>,
     ) -> Result<RunResult, TmcError> {
         Ok(RunResult {
             status: RunStatus::Passed,
@@ -68,8 +69,12 @@ impl LanguagePlugin for NoTestsPlugin {
                 points: Self::get_points(path),
                 message: "".to_string(),
                 exception: vec![],
+                duration_ms: None,
+                stdout: None,
+                stderr: None,
             }],
             logs: HashMap::new(),
+            execution_stats: None,
         })
     }
 
@@ -206,6 +211,7 @@ no-tests:
             .run_tests_with_timeout(
                 Path::new("/nonexistent"),
                 Some(std::time::Duration::from_nanos(1)),
+                None,
             )
             .unwrap();
         assert_eq!(run_result.status, RunStatus::Passed);
@@ -1,7 +1,9 @@
 //! Contains the Python3Plugin struct
 
 use crate::{
-    error::PythonError, policy::Python3StudentFilePolicy, python_test_result::PythonTestResult,
+    error::PythonError,
+    policy::Python3StudentFilePolicy,
+    python_test_result::{PythonTestResult, parse_test_name},
 };
 use hmac::{Hmac, Mac};
 use once_cell::sync::Lazy;
@@ -18,7 +20,7 @@ use std::{
 };
 use tmc_langs_framework::{
     Archive, CommandError, ExerciseDesc, LanguagePlugin, Output, PythonVer, RunResult, RunStatus,
-    TestDesc, TestResult, TmcCommand, TmcError, TmcProjectYml,
+    Sandbox, TestDesc, TestFilter, TestResult, TmcCommand, TmcError, TmcProjectYml,
     nom::{IResult, Parser, bytes, character, combinator, sequence},
     nom_language::error::VerboseError,
 };
@@ -36,7 +38,7 @@ impl Python3Plugin {
         Self
     }
 
-    fn get_local_python_command() -> TmcCommand {
+    fn get_local_python_command(sandbox: Option<&Sandbox>) -> TmcCommand {
         // the correct python command is platform-dependent
         static LOCAL_PY: Lazy<LocalPy> = Lazy::new(|| {
             if let Ok(python_exec) = env::var("TMC_LANGS_PYTHON_EXEC") {
@@ -71,15 +73,15 @@ impl Python3Plugin {
         }
 
         match &*LOCAL_PY {
-            LocalPy::Unix => TmcCommand::piped("python3"),
-            LocalPy::Windows => TmcCommand::piped("py").with(|e| e.arg("-3")),
-            LocalPy::WindowsConda { conda_path } => TmcCommand::piped(conda_path),
-            LocalPy::Custom { python_exec } => TmcCommand::piped(python_exec),
+            LocalPy::Unix => TmcCommand::piped_in("python3", sandbox),
+            LocalPy::Windows => TmcCommand::piped_in("py", sandbox).with(|e| e.arg("-3")),
+            LocalPy::WindowsConda { conda_path } => TmcCommand::piped_in(conda_path, sandbox),
+            LocalPy::Custom { python_exec } => TmcCommand::piped_in(python_exec, sandbox),
         }
     }
 
     fn get_local_python_ver() -> Result<(u32, u32, u32), PythonError> {
-        let output = Self::get_local_python_command()
+        let output = Self::get_local_python_command(None)
         .with(|e| e.args(&["-c", "import sys; print(sys.version_info.major); print(sys.version_info.minor); print(sys.version_info.micro);"]))
         .output_checked()?;
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -111,6 +113,7 @@ impl Python3Plugin {
         extra_args: &[&str],
         timeout: Option<Duration>,
         stdin: Option<String>,
+        sandbox: Option<&Sandbox>,
     ) -> Result<Output, PythonError> {
         let minimum = TmcProjectYml::load_or_default(path)?
             .minimum_python_version
@@ -138,7 +141,7 @@ impl Python3Plugin {
         log::debug!("running tmc command at {}", path.display());
         let common_args = ["-m", "tmc"];
 
-        let command = Self::get_local_python_command();
+        let command = Self::get_local_python_command(sandbox);
         let command = command.with(|e| e.args(&common_args).args(extra_args).cwd(path));
         let command = if let Some(stdin) = stdin {
             command.set_stdin_data(stdin)
@@ -209,62 +212,21 @@ impl Python3Plugin {
             .collect();
         Ok((status, test_results))
     }
-}
-
-impl Default for Python3Plugin {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-/// Project directory:
-/// Contains setup.py, requirements.txt, test/__init__.py, or tmc/__main__.py
-/// OR
-/// Contains an .ipynb file. This is given lower priority than the prior rule, and if there are multiple .ipynb files, the shallowest directory is returned.
-impl LanguagePlugin for Python3Plugin {
-    const PLUGIN_NAME: &'static str = "python3";
-    const DEFAULT_SANDBOX_IMAGE: &'static str = "eu.gcr.io/moocfi-public/tmc-sandbox-python:latest";
-    const LINE_COMMENT: &'static str = "#";
-    const BLOCK_COMMENT: Option<(&'static str, &'static str)> = Some(("\"\"\"", "\"\"\""));
-    type StudentFilePolicy = Python3StudentFilePolicy;
 
-    fn scan_exercise(
-        &self,
-        exercise_directory: &Path,
-        exercise_name: String,
-    ) -> Result<ExerciseDesc, TmcError> {
-        let available_points_json = exercise_directory.join(".available_points.json");
-        // remove any existing points json
-        if available_points_json.exists() {
-            file_util::remove_file(&available_points_json)?;
-        }
-
-        if let Err(error) =
-            Self::run_tmc_command(exercise_directory, &["available_points"], None, None)
-        {
-            log::error!("Failed to scan exercise. {error}");
-        }
-
-        let test_descs_res = Self::parse_exercise_description(&available_points_json);
-        // remove file regardless of parse success
-        if available_points_json.exists() {
-            file_util::remove_file(&available_points_json)?;
-        }
-        let tests = test_descs_res?;
-        Ok(ExerciseDesc::new(exercise_name, tests))
-    }
-
-    fn run_tests_with_timeout(
-        &self,
+    /// Runs the tests with the given names, or all of the tests if no names are given.
+    fn run_selected_tests(
         exercise_directory: &Path,
+        test_names: &[String],
         timeout: Option<Duration>,
-    ) -> Result<RunResult, TmcError> {
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, PythonError> {
         let test_results_json = exercise_directory.join(".tmc_test_results.json");
         // remove any existing results json
         if test_results_json.exists() {
             file_util::remove_file(&test_results_json)?;
         }
 
+        let mut args = test_names.iter().map(String::as_str).collect::<Vec<_>>();
         let (output, random_string) = if exercise_directory.join("tmc/hmac_writer.py").exists() {
             // has hmac writer
             let random_string: String = rand::rng()
@@ -272,15 +234,17 @@ impl LanguagePlugin for Python3Plugin {
                 .take(32)
                 .map(char::from)
                 .collect();
+            args.insert(0, "--wait-for-secret");
             let output = Self::run_tmc_command(
                 exercise_directory,
-                &["--wait-for-secret"],
+                &args,
                 timeout,
                 Some(random_string.clone()),
+                sandbox,
             );
             (output, Some(random_string))
         } else {
-            let output = Self::run_tmc_command(exercise_directory, &[], timeout, None);
+            let output = Self::run_tmc_command(exercise_directory, &args, timeout, None, sandbox);
             (output, None)
         };
 
@@ -306,8 +270,7 @@ impl LanguagePlugin for Python3Plugin {
                         path: test_results_json,
                         stdout: stdout.into_owned(),
                         stderr: stderr.into_owned(),
-                    }
-                    .into());
+                    });
                 }
                 let (status, mut test_results) =
                     Self::parse_and_verify_test_result(&test_results_json, hmac_data)?;
@@ -331,11 +294,13 @@ impl LanguagePlugin for Python3Plugin {
                     status,
                     test_results,
                     logs,
+                    execution_stats: Some(output.stats),
                 })
             }
             Err(PythonError::Tmc(TmcError::Command(CommandError::TimeOut {
                 stdout,
                 stderr,
+                stats,
                 ..
             }))) => {
                 let mut logs = HashMap::new();
@@ -351,12 +316,105 @@ impl LanguagePlugin for Python3Plugin {
                             "Tests timed out.\nMake sure you don't have an infinite loop in your code."
                                 .to_string(),
                         exception: vec![],
+                        duration_ms: None,
+                        stdout: None,
+                        stderr: None,
                     }],
                     logs,
+                    execution_stats: Some(stats),
                 })
             }
-            Err(error) => Err(error.into()),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+impl Default for Python3Plugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Project directory:
+/// Contains setup.py, requirements.txt, test/__init__.py, or tmc/__main__.py
+/// OR
+/// Contains an .ipynb file. This is given lower priority than the prior rule, and if there are multiple .ipynb files, the shallowest directory is returned.
+impl LanguagePlugin for Python3Plugin {
+    const PLUGIN_NAME: &'static str = "python3";
+    const DEFAULT_SANDBOX_IMAGE: &'static str = "eu.gcr.io/moocfi-public/tmc-sandbox-python:latest";
+    const LINE_COMMENT: &'static str = "#";
+    const BLOCK_COMMENT: Option<(&'static str, &'static str)> = Some(("\"\"\"", "\"\"\""));
+    const SOURCE_FILE_EXTENSIONS: &'static [&'static str] = &["py"];
+    type StudentFilePolicy = Python3StudentFilePolicy;
+
+    fn scan_exercise(
+        &self,
+        exercise_directory: &Path,
+        exercise_name: String,
+    ) -> Result<ExerciseDesc, TmcError> {
+        let available_points_json = exercise_directory.join(".available_points.json");
+        // remove any existing points json
+        if available_points_json.exists() {
+            file_util::remove_file(&available_points_json)?;
+        }
+
+        if let Err(error) =
+            Self::run_tmc_command(exercise_directory, &["available_points"], None, None, None)
+        {
+            log::error!("Failed to scan exercise. {error}");
+        }
+
+        let test_descs_res = Self::parse_exercise_description(&available_points_json);
+        // remove file regardless of parse success
+        if available_points_json.exists() {
+            file_util::remove_file(&available_points_json)?;
         }
+        let tests = test_descs_res?;
+        Ok(ExerciseDesc::new(exercise_name, tests))
+    }
+
+    fn run_tests_with_timeout(
+        &self,
+        exercise_directory: &Path,
+        timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        Ok(Self::run_selected_tests(
+            exercise_directory,
+            &[],
+            timeout,
+            sandbox,
+        )?)
+    }
+
+    /// Passes the names of the selected tests to unittest, which then only runs them.
+    fn run_tests_filtered_with_timeout(
+        &self,
+        exercise_directory: &Path,
+        filter: &TestFilter,
+        timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        // the results use a shorter form of the names, so both forms are checked
+        let selected = self
+            .scan_exercise(exercise_directory, String::new())?
+            .tests
+            .into_iter()
+            .filter(|t| {
+                filter.matches(&t.name, &t.points)
+                    || filter.matches(&parse_test_name(t.name.clone()), &t.points)
+            })
+            .map(|t| t.name)
+            .collect::<Vec<_>>();
+        if selected.is_empty() {
+            return Ok(RunResult::new(RunStatus::Passed, vec![], HashMap::new()));
+        }
+        Ok(Self::run_selected_tests(
+            exercise_directory,
+            &selected,
+            timeout,
+            sandbox,
+        )?)
     }
 
     fn find_project_dir_in_archive<R: Read + Seek>(
@@ -601,7 +659,7 @@ mod test {
     fn gets_local_python_command() {
         init();
 
-        let _cmd = Python3Plugin::get_local_python_command();
+        let _cmd = Python3Plugin::get_local_python_command(None);
     }
 
     #[test]
@@ -710,6 +768,55 @@ class TestFailing(unittest.TestCase):
         assert_eq!(run_result.test_results.len(), 1);
     }
 
+    #[test]
+    fn runs_filtered_tests() {
+        init();
+
+        let temp_dir = temp_with_tmc();
+        file_to(&temp_dir, "test/__init__.py", "");
+        file_to(
+            &temp_dir,
+            "test/test_file.py",
+            r#"
+import unittest
+from tmc import points
+
+class TestFiltered(unittest.TestCase):
+    @points('1.1')
+    def test_first(self):
+        self.assertEqual("a", "a")
+
+    @points('1.2')
+    def test_second(self):
+        self.assertEqual("a", "b")
+
+    def test_third(self):
+        self.assertEqual("a", "a")
+"#,
+        );
+
+        let plugin = Python3Plugin::new();
+        let filter = TestFilter::new(vec!["test_third".to_string()], vec!["1.1".to_string()]);
+        let run_result = plugin.run_tests_filtered(temp_dir.path(), &filter).unwrap();
+        assert_eq!(run_result.status, RunStatus::Passed);
+        let mut names = run_result
+            .test_results
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        assert_eq!(
+            names,
+            ["TestFiltered: test_first", "TestFiltered: test_third"]
+        );
+
+        let filter = TestFilter::new(vec!["TestFiltered: test_second".to_string()], vec![]);
+        let run_result = plugin.run_tests_filtered(temp_dir.path(), &filter).unwrap();
+        assert_eq!(run_result.status, RunStatus::TestsFailed);
+        assert_eq!(run_result.test_results.len(), 1);
+        assert_eq!(run_result.test_results[0].points.len(), 0);
+    }
+
     #[test]
     fn runs_tests_erroring() {
         init();
@@ -763,7 +870,11 @@ class TestErroring(unittest.TestCase):
 
         let plugin = Python3Plugin::new();
         let run_result = plugin
-            .run_tests_with_timeout(temp_dir.path(), Some(std::time::Duration::from_nanos(1)))
+            .run_tests_with_timeout(
+                temp_dir.path(),
+                Some(std::time::Duration::from_nanos(1)),
+                None,
+            )
             .unwrap();
         assert_eq!(run_result.status, RunStatus::TestsFailed);
         assert_eq!(run_result.test_results[0].name, "Timeout test");
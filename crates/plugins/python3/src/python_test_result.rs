@@ -12,6 +12,13 @@ pub struct PythonTestResult {
     pub message: String,
     #[serde(default)]
     pub backtrace: Vec<String>,
+    /// The duration of the test in seconds, if reported by the runner, as are stdout and stderr.
+    #[serde(default)]
+    pub duration: Option<f64>,
+    #[serde(default)]
+    pub stdout: Option<String>,
+    #[serde(default)]
+    pub stderr: Option<String>,
 }
 
 impl PythonTestResult {
@@ -23,12 +30,15 @@ impl PythonTestResult {
             message: parse_test_message(self.message),
             points: self.points,
             exception: self.backtrace,
+            duration_ms: self.duration.map(|d| (d * 1000.0).round() as u32),
+            stdout: self.stdout.filter(|s| !s.is_empty()),
+            stderr: self.stderr.filter(|s| !s.is_empty()),
         }
     }
 }
 
 // parses a four part test name a.b.c.d into c: d
-fn parse_test_name(test_name: String) -> String {
+pub(crate) fn parse_test_name(test_name: String) -> String {
     let parts: Vec<_> = test_name.split('.').collect();
     if parts.len() == 4 {
         format!("{}: {}", parts[2], parts[3])
@@ -65,6 +75,27 @@ mod test {
         assert_eq!(parsed, "some.other.test");
     }
 
+    #[test]
+    fn converts_into_test_result() {
+        let python_test_result = PythonTestResult {
+            name: "test.test_file.TestCase.test_method".to_string(),
+            passed: true,
+            points: vec!["1.1".to_string(), "1.2".to_string()],
+            message: "".to_string(),
+            backtrace: vec![],
+            duration: Some(0.0126),
+            stdout: Some("printed\n".to_string()),
+            stderr: Some("".to_string()),
+        };
+        let failed_points = HashSet::from(["1.2".to_string()]);
+        let test_result = python_test_result.into_test_result(&failed_points);
+        assert_eq!(test_result.name, "TestCase: test_method");
+        assert_eq!(test_result.points, ["1.1"]);
+        assert_eq!(test_result.duration_ms, Some(13));
+        assert_eq!(test_result.stdout.as_deref(), Some("printed\n"));
+        assert_eq!(test_result.stderr, None);
+    }
+
     #[test]
     fn parses_test_message() {
         let parsed = parse_test_message("True is not False :   !MessagE!    ".to_string());
@@ -10,7 +10,7 @@ use std::{
     time::Duration,
 };
 use tmc_langs_framework::{
-    Archive, ExerciseDesc, LanguagePlugin, RunResult, TestDesc, TmcCommand, TmcError,
+    Archive, ExerciseDesc, LanguagePlugin, RunResult, Sandbox, TestDesc, TmcCommand, TmcError,
     nom::{IResult, Parser, branch, bytes, character, sequence},
     nom_language::error::VerboseError,
 };
@@ -32,6 +32,7 @@ impl LanguagePlugin for RPlugin {
     const DEFAULT_SANDBOX_IMAGE: &'static str = "eu.gcr.io/moocfi-public/tmc-sandbox-r:latest";
     const LINE_COMMENT: &'static str = "#";
     const BLOCK_COMMENT: Option<(&'static str, &'static str)> = None;
+    const SOURCE_FILE_EXTENSIONS: &'static [&'static str] = &["R", "r"];
     type StudentFilePolicy = RStudentFilePolicy;
 
     fn scan_exercise(&self, path: &Path, exercise_name: String) -> Result<ExerciseDesc, TmcError> {
@@ -65,6 +66,7 @@ impl LanguagePlugin for RPlugin {
         &self,
         path: &Path,
         timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
     ) -> Result<RunResult, TmcError> {
         // delete results json
         let results_path = path.join(".results.json");
@@ -80,11 +82,11 @@ impl LanguagePlugin for RPlugin {
         };
 
         let output = if let Some(timeout) = timeout {
-            TmcCommand::piped("Rscript")
+            TmcCommand::piped_in("Rscript", sandbox)
                 .with(|e| e.cwd(path).args(args))
                 .output_with_timeout_checked(timeout)?
         } else {
-            TmcCommand::piped("Rscript")
+            TmcCommand::piped_in("Rscript", sandbox)
                 .with(|e| e.cwd(path).args(args))
                 .output_checked()?
         };
@@ -111,7 +113,9 @@ impl LanguagePlugin for RPlugin {
         })?;
         file_util::remove_file(&results_path)?;
 
-        Ok(run_result.into())
+        let mut run_result = RunResult::from(run_result);
+        run_result.execution_stats = Some(output.stats);
+        Ok(run_result)
     }
 
     /// Checks if the directory contains R or tests/testthat
@@ -419,7 +423,11 @@ test("sample", c("r1.1"), {
 
         let plugin = RPlugin::new();
         let run = plugin
-            .run_tests_with_timeout(temp_dir.path(), Some(std::time::Duration::from_nanos(1)))
+            .run_tests_with_timeout(
+                temp_dir.path(),
+                Some(std::time::Duration::from_nanos(1)),
+                None,
+            )
             .unwrap_err();
         use std::error::Error;
         let mut source = run.source();
@@ -50,6 +50,7 @@ impl From<RRunResult> for RunResult {
                 .map(|t| t.into())
                 .collect(),
             logs,
+            execution_stats: None,
         }
     }
 }
@@ -70,6 +71,10 @@ struct RTestResult {
     message: String,
     backtrace: Vec<String>,
     points: Vec<String>,
+    /// The elapsed time in seconds, if reported by the runner.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time: Option<f64>,
 }
 
 impl From<RTestResult> for TestResult {
@@ -80,6 +85,11 @@ impl From<RTestResult> for TestResult {
             points: r_test_result.points,
             message: r_test_result.message,
             exception: r_test_result.backtrace,
+            duration_ms: r_test_result
+                .time
+                .map(|time| (time * 1000.0).round() as u32),
+            stdout: None,
+            stderr: None,
         }
     }
 }
@@ -92,6 +102,7 @@ enum RTestStatus {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod test {
     use super::*;
 
@@ -113,4 +124,21 @@ mod test {
         let run_result: RunResult = r_run_result.into();
         assert_eq!(run_result.status, RunStatus::Passed);
     }
+
+    #[test]
+    fn converts_test_duration() {
+        init();
+
+        let r_run_result: RRunResult = tmc_langs_util::deserialize::json_from_str(
+            r#"{"runStatus":"success","backtrace":[],"testResults":[
+                {"status":"pass","name":"t1","message":"","backtrace":[],"points":["1"],"time":0.0214},
+                {"status":"fail","name":"t2","message":"m","backtrace":[],"points":[]}
+            ]}"#,
+        )
+        .unwrap();
+        let run_result: RunResult = r_run_result.into();
+        assert_eq!(run_result.status, RunStatus::TestsFailed);
+        assert_eq!(run_result.test_results[0].duration_ms, Some(21));
+        assert_eq!(run_result.test_results[1].duration_ms, None);
+    }
 }
@@ -0,0 +1,147 @@
+//! Contains types that model the JSON output of libtest, the test harness used by `cargo test`.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use tmc_langs_framework::{RunStatus, TestResult};
+
+/// A single line of libtest's `--format json` output.
+#[derive(Debug, Deserialize)]
+pub struct CargoTestEvent {
+    #[serde(rename = "type")]
+    pub kind: EventKind,
+    pub event: EventStatus,
+    pub name: Option<String>,
+    /// Captured output of a failed test, including the panic message.
+    pub stdout: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    Suite,
+    Test,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventStatus {
+    Started,
+    Ok,
+    Failed,
+    Ignored,
+    Timeout,
+    #[serde(other)]
+    Other,
+}
+
+/// The test results collected from the output of a `cargo test` run.
+#[derive(Debug)]
+pub struct CargoTestRun {
+    /// Whether any test suite started, i.e. whether the tests compiled.
+    pub started: bool,
+    pub status: RunStatus,
+    pub test_results: Vec<TestResult>,
+}
+
+impl CargoTestRun {
+    /// Collects the results from the given events. The points map should contain a mapping from test paths to a list of points, e.g.
+    /// "tests::test_one" => ["1.1", "1.2"].
+    pub fn from_events(
+        events: impl IntoIterator<Item = CargoTestEvent>,
+        point_map: &HashMap<String, Vec<String>>,
+    ) -> Self {
+        let mut started = false;
+        let mut status = RunStatus::Passed;
+        let mut test_results = vec![];
+
+        for event in events {
+            match event.kind {
+                EventKind::Suite => started = true,
+                EventKind::Test => {
+                    let successful = match event.event {
+                        EventStatus::Ok => true,
+                        EventStatus::Failed => false,
+                        // only the final result of each test is interesting
+                        _ => continue,
+                    };
+                    let Some(name) = event.name else {
+                        continue;
+                    };
+                    if !successful {
+                        status = RunStatus::TestsFailed;
+                    }
+
+                    // test names contain the module path, e.g. tests::test_one
+                    let points = point_map.get(&name).cloned().unwrap_or_default();
+                    let message = event
+                        .stdout
+                        .or(event.message)
+                        .map(|s| s.trim().to_string())
+                        .unwrap_or_default();
+                    test_results.push(TestResult {
+                        name,
+                        successful,
+                        points,
+                        message,
+                        exception: vec![],
+                        duration_ms: None,
+                        stdout: None,
+                        stderr: None,
+                    });
+                }
+                EventKind::Other => {}
+            }
+        }
+
+        Self {
+            started,
+            status,
+            test_results,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use tmc_langs_util::deserialize;
+
+    #[test]
+    fn deserializes() {
+        let events = r#"
+{ "type": "suite", "event": "started", "test_count": 2 }
+{ "type": "test", "event": "started", "name": "passing" }
+{ "type": "test", "event": "started", "name": "tests::failing" }
+{ "type": "test", "name": "passing", "event": "ok" }
+{ "type": "test", "name": "tests::failing", "event": "failed", "stdout": "thread 'tests::failing' panicked at tests/tests.rs:8:5:\nassertion failed: false\n" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.001 }
+"#;
+        let events = events
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(|l| deserialize::json_from_str::<CargoTestEvent>(l).unwrap());
+
+        let mut point_map = HashMap::new();
+        point_map.insert("passing".to_string(), vec!["1.1".to_string()]);
+        point_map.insert("tests::failing".to_string(), vec!["1.2".to_string()]);
+        let run = CargoTestRun::from_events(events, &point_map);
+
+        assert!(run.started);
+        assert_eq!(run.status, RunStatus::TestsFailed);
+        assert_eq!(run.test_results.len(), 2);
+        assert!(run.test_results[0].successful);
+        assert_eq!(run.test_results[0].points, &["1.1"]);
+        assert!(!run.test_results[1].successful);
+        assert_eq!(run.test_results[1].name, "tests::failing");
+        assert_eq!(run.test_results[1].points, &["1.2"]);
+        assert!(
+            run.test_results[1]
+                .message
+                .contains("assertion failed: false")
+        );
+    }
+}
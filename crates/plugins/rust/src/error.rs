@@ -0,0 +1,31 @@
+//! Error type for the Rust plugin.
+
+use std::path::PathBuf;
+use thiserror::Error;
+use tmc_langs_framework::TmcError;
+use tmc_langs_util::FileError;
+
+#[derive(Debug, Error)]
+pub enum RustError {
+    #[error("No exercise found at {0}")]
+    NoExerciseFound(PathBuf),
+
+    #[error("File IO error")]
+    FileError(#[from] FileError),
+    #[error(transparent)]
+    Tmc(#[from] TmcError),
+}
+
+// conversion from plugin error to TmcError::Plugin
+impl From<RustError> for TmcError {
+    fn from(err: RustError) -> TmcError {
+        TmcError::Plugin(Box::new(err))
+    }
+}
+
+// conversion from plugin error to a tmc result
+impl<T> From<RustError> for Result<T, TmcError> {
+    fn from(from: RustError) -> Self {
+        Err(TmcError::Plugin(Box::new(from)))
+    }
+}
@@ -0,0 +1,10 @@
+#![deny(clippy::print_stdout, clippy::print_stderr, clippy::unwrap_used)]
+
+//! TMC language plugin for Rust projects built with Cargo.
+
+mod cargo_test_result;
+mod error;
+mod plugin;
+mod policy;
+
+pub use self::{error::RustError, plugin::RustPlugin, policy::RustStudentFilePolicy};
@@ -0,0 +1,859 @@
+//! Contains the LanguagePlugin implementation for Rust.
+
+use crate::{
+    cargo_test_result::{CargoTestEvent, CargoTestRun},
+    error::RustError,
+    policy::RustStudentFilePolicy,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    io::{Read, Seek},
+    ops::ControlFlow::{Break, Continue},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tmc_langs_framework::{
+    Archive, CommandError, ExerciseDesc, LanguagePlugin, RunResult, RunStatus, Sandbox, TestDesc,
+    TestResult, TmcCommand, TmcError,
+    nom::{self, IResult, Parser, branch, bytes, character, combinator, multi, sequence},
+    nom_language::error::VerboseError,
+};
+use tmc_langs_util::{deserialize, file_util, parse_util, path_util};
+
+#[derive(Default)]
+pub struct RustPlugin {}
+
+impl RustPlugin {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Scans the exercise's Rust source files for test functions and the points annotated on them.
+    /// A points annotation applies to the function that follows it.
+    /// The tests are named by their full module path, e.g. tests::test_one, the same way as in the test harness's output.
+    fn scan_tests(path: &Path) -> Result<Vec<TestDesc>, TmcError> {
+        let config = Self::get_exercise_packaging_configuration(path)?;
+
+        let mut tests = vec![];
+        for exercise_file_path in config.exercise_file_paths {
+            if exercise_file_path.starts_with("target")
+                || exercise_file_path.extension() != Some(OsStr::new("rs"))
+            {
+                continue;
+            }
+            let file_module_path = module_path_of_file(&exercise_file_path);
+            let exercise_file_path = path.join(exercise_file_path);
+            log::trace!("scanning tests from {}", exercise_file_path.display());
+            let file_contents = file_util::read_file_to_string_lossy(&exercise_file_path)?;
+
+            let mut parser = multi::many0(branch::alt((
+                // comments
+                combinator::value(
+                    Scan::Other,
+                    (
+                        bytes::complete::tag("//"),
+                        character::complete::not_line_ending,
+                    ),
+                ),
+                combinator::value(
+                    Scan::Other,
+                    (
+                        bytes::complete::tag("/*"),
+                        bytes::complete::take_until("*/"),
+                        bytes::complete::tag("*/"),
+                    ),
+                ),
+                // literals, so that braces inside them are not counted
+                combinator::value(Scan::Other, string_literal),
+                combinator::value(Scan::Other, char_literal),
+                // annotations
+                combinator::map(Self::points_parser, |points| {
+                    Scan::Points(points.into_iter().map(str::to_string).collect())
+                }),
+                combinator::value(
+                    Scan::Test,
+                    (
+                        character::complete::char('#'),
+                        character::complete::multispace0,
+                        character::complete::char('['),
+                        character::complete::multispace0,
+                        bytes::complete::tag("test"),
+                        character::complete::multispace0,
+                        character::complete::char(']'),
+                    ),
+                ),
+                // functions
+                combinator::map(
+                    sequence::preceded(
+                        (bytes::complete::tag("fn"), character::complete::multispace1),
+                        identifier,
+                    ),
+                    |name| Scan::Function(name.to_string()),
+                ),
+                // inline modules
+                combinator::map(
+                    sequence::delimited(
+                        (
+                            bytes::complete::tag("mod"),
+                            character::complete::multispace1,
+                        ),
+                        identifier,
+                        (
+                            character::complete::multispace0,
+                            character::complete::char('{'),
+                        ),
+                    ),
+                    |name| Scan::Module(name.to_string()),
+                ),
+                combinator::value(Scan::OpenBrace, character::complete::char('{')),
+                combinator::value(Scan::CloseBrace, character::complete::char('}')),
+                // whole identifiers are skipped so that fn is only recognized at the start of a word
+                combinator::value(Scan::Other, identifier),
+                combinator::value(Scan::Other, character::complete::anychar),
+            )));
+
+            let res: IResult<_, _, VerboseError<&str>> = parser.parse(&file_contents);
+            let scanned = match res {
+                Ok((_, scanned)) => scanned,
+                Err(nom::Err::Incomplete(_)) => unreachable!("this should never happen"),
+                Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                    return Err(TmcError::PointParse(
+                        exercise_file_path,
+                        VerboseError {
+                            errors: e
+                                .errors
+                                .into_iter()
+                                .map(|(s, k)| (s.to_string(), k))
+                                .collect(),
+                        },
+                    ));
+                }
+            };
+
+            let mut is_test = false;
+            let mut points = vec![];
+            // the inline modules that are currently open and the brace depth they were opened at
+            let mut modules: Vec<(usize, String)> = vec![];
+            let mut depth = 0;
+            for scan in scanned {
+                match scan {
+                    Scan::Points(p) => points.extend(p),
+                    Scan::Test => is_test = true,
+                    Scan::Function(name) => {
+                        if is_test || !points.is_empty() {
+                            let test_path = file_module_path
+                                .iter()
+                                .map(String::as_str)
+                                .chain(modules.iter().map(|(_, module)| module.as_str()))
+                                .chain(std::iter::once(name.as_str()))
+                                .collect::<Vec<_>>()
+                                .join("::");
+                            tests.push(TestDesc::new(test_path, std::mem::take(&mut points)));
+                        }
+                        is_test = false;
+                    }
+                    Scan::Module(name) => {
+                        depth += 1;
+                        modules.push((depth, name));
+                    }
+                    Scan::OpenBrace => depth += 1,
+                    Scan::CloseBrace => {
+                        if modules.last().map(|(d, _)| *d == depth).unwrap_or(false) {
+                            modules.pop();
+                        }
+                        depth = depth.saturating_sub(1);
+                    }
+                    Scan::Other => {}
+                }
+            }
+        }
+        Ok(tests)
+    }
+
+    /// Parses the JSON lines written by libtest to stdout. Any other lines are ignored.
+    fn parse_test_output(stdout: &str, point_map: &HashMap<String, Vec<String>>) -> CargoTestRun {
+        let events = stdout.lines().filter_map(|line| {
+            match deserialize::json_from_str::<CargoTestEvent>(line) {
+                Ok(event) => Some(event),
+                Err(_) => {
+                    log::trace!("skipping non-JSON line {line}");
+                    None
+                }
+            }
+        });
+        CargoTestRun::from_events(events, point_map)
+    }
+}
+
+/// Project directory:
+/// Contains a Cargo.toml file.
+impl LanguagePlugin for RustPlugin {
+    const PLUGIN_NAME: &'static str = "rust";
+    const DEFAULT_SANDBOX_IMAGE: &'static str = "eu.gcr.io/moocfi-public/tmc-sandbox-rust:latest";
+    const LINE_COMMENT: &'static str = "//";
+    const BLOCK_COMMENT: Option<(&'static str, &'static str)> = Some(("/*", "*/"));
+    const SOURCE_FILE_EXTENSIONS: &'static [&'static str] = &["rs"];
+    type StudentFilePolicy = RustStudentFilePolicy;
+
+    fn scan_exercise(&self, path: &Path, exercise_name: String) -> Result<ExerciseDesc, TmcError> {
+        if !Self::is_exercise_type_correct(path) {
+            return RustError::NoExerciseFound(path.to_path_buf()).into();
+        }
+
+        let tests = Self::scan_tests(path)?;
+        Ok(ExerciseDesc {
+            name: exercise_name,
+            tests,
+        })
+    }
+
+    /// Runs cargo test with libtest's JSON output. The JSON output is unstable, so RUSTC_BOOTSTRAP is set to allow using it on stable toolchains.
+    fn run_tests_with_timeout(
+        &self,
+        path: &Path,
+        timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        let mut point_map = HashMap::<String, Vec<String>>::new();
+        for test in Self::scan_tests(path)? {
+            point_map.entry(test.name).or_default().extend(test.points);
+        }
+
+        let command = TmcCommand::piped_in("cargo", sandbox).with(|e| {
+            e.cwd(path).env("RUSTC_BOOTSTRAP", "1").args(&[
+                "test",
+                "--no-fail-fast",
+                "--",
+                "-Z",
+                "unstable-options",
+                "--format",
+                "json",
+            ])
+        });
+        let output = if let Some(timeout) = timeout {
+            command.output_with_timeout(timeout)
+        } else {
+            command.output()
+        };
+
+        match output {
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                log::trace!("stdout: {stdout}");
+                log::debug!("stderr: {stderr}");
+
+                let run = Self::parse_test_output(&stdout, &point_map);
+                let mut logs = HashMap::new();
+                logs.insert("stdout".to_string(), stdout.into_owned());
+                logs.insert("stderr".to_string(), stderr.into_owned());
+
+                if !run.started && !output.status.success() {
+                    // cargo failed before running any tests
+                    return Ok(RunResult {
+                        status: RunStatus::CompileFailed,
+                        test_results: vec![],
+                        logs,
+                        execution_stats: Some(output.stats),
+                    });
+                }
+
+                let status = if run.status == RunStatus::Passed && !output.status.success() {
+                    // every test that finished passed, but something crashed along the way
+                    RunStatus::TestrunInterrupted
+                } else {
+                    run.status
+                };
+
+                // remove points associated with any failed tests
+                let mut test_results = run.test_results;
+                let mut failed_points = HashSet::new();
+                for test_result in &test_results {
+                    if !test_result.successful {
+                        failed_points.extend(test_result.points.iter().cloned());
+                    }
+                }
+                for test_result in &mut test_results {
+                    test_result.points.retain(|p| !failed_points.contains(p));
+                }
+
+                Ok(RunResult {
+                    status,
+                    test_results,
+                    logs,
+                    execution_stats: Some(output.stats),
+                })
+            }
+            Err(TmcError::Command(CommandError::TimeOut {
+                stdout,
+                stderr,
+                stats,
+                ..
+            })) => {
+                let mut logs = HashMap::new();
+                logs.insert("stdout".to_string(), stdout);
+                logs.insert("stderr".to_string(), stderr);
+                Ok(RunResult {
+                    status: RunStatus::TestsFailed,
+                    test_results: vec![TestResult {
+                        name: "Timeout test".to_string(),
+                        successful: false,
+                        points: vec![],
+                        message:
+                            "Tests timed out.\nMake sure you don't have an infinite loop in your code."
+                                .to_string(),
+                        exception: vec![],
+                        duration_ms: None,
+                        stdout: None,
+                        stderr: None,
+                    }],
+                    logs,
+                    execution_stats: Some(stats),
+                })
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Finds the shallowest directory with a Cargo.toml file.
+    fn find_project_dir_in_archive<R: Read + Seek>(
+        archive: &mut Archive<R>,
+    ) -> Result<PathBuf, TmcError> {
+        let mut iter = archive.iter()?;
+        let mut shallowest_manifest_parent: Option<PathBuf> = None;
+        loop {
+            let next = iter.with_next::<(), _>(|file| {
+                let file_path = file.path()?;
+
+                if file.is_file() && !file_path.components().any(|c| c.as_os_str() == "__MACOSX") {
+                    if let Some(parent) = path_util::get_parent_of_named(&file_path, "Cargo.toml") {
+                        let is_shallower = shallowest_manifest_parent
+                            .as_ref()
+                            .map(|shallowest| {
+                                shallowest.components().count() > parent.components().count()
+                            })
+                            .unwrap_or(true);
+                        if is_shallower {
+                            shallowest_manifest_parent = Some(parent);
+                        }
+                    }
+                }
+                Ok(Continue(()))
+            });
+            match next? {
+                Continue(_) => continue,
+                Break(_) => break,
+            }
+        }
+
+        match shallowest_manifest_parent {
+            Some(project_dir) => Ok(project_dir),
+            None => Err(TmcError::NoProjectDirInArchive),
+        }
+    }
+
+    /// Checks if the directory contains a Cargo.toml file.
+    fn is_exercise_type_correct(path: &Path) -> bool {
+        path.join("Cargo.toml").is_file()
+    }
+
+    /// Runs cargo clean.
+    fn clean(&self, path: &Path) -> Result<(), TmcError> {
+        TmcCommand::piped("cargo")
+            .with(|e| e.cwd(path).arg("clean"))
+            .output_checked()?;
+        Ok(())
+    }
+
+    fn get_default_student_file_paths() -> Vec<PathBuf> {
+        vec![PathBuf::from("src")]
+    }
+
+    fn get_default_exercise_file_paths() -> Vec<PathBuf> {
+        vec![PathBuf::from("tests")]
+    }
+
+    /// Parses attributes like #[points("1.1")] or #[tmc::points("1.1", "1.2")].
+    fn points_parser(i: &str) -> IResult<&str, Vec<&str>, VerboseError<&str>> {
+        combinator::map(
+            sequence::delimited(
+                (
+                    character::complete::char('#'),
+                    character::complete::multispace0,
+                    character::complete::char('['),
+                    character::complete::multispace0,
+                    // optional path to the attribute
+                    multi::many0_count((identifier, bytes::complete::tag("::"))),
+                    bytes::complete::tag("points"),
+                    character::complete::multispace0,
+                    character::complete::char('('),
+                    character::complete::multispace0,
+                ),
+                parse_util::comma_separated_strings,
+                (
+                    character::complete::multispace0,
+                    character::complete::char(')'),
+                    character::complete::multispace0,
+                    character::complete::char(']'),
+                ),
+            ),
+            // splits each point by whitespace
+            |points| {
+                points
+                    .into_iter()
+                    .flat_map(|p| p.split_whitespace())
+                    .collect()
+            },
+        )
+        .parse(i)
+    }
+}
+
+/// The interesting parts of a Rust source file when scanning for tests.
+#[derive(Debug, Clone)]
+enum Scan {
+    Points(Vec<String>),
+    Test,
+    Function(String),
+    Module(String),
+    OpenBrace,
+    CloseBrace,
+    Other,
+}
+
+/// The module path of a source file relative to its crate root, e.g. src/a/b.rs => a::b.
+/// Files directly inside tests, benches and examples, src/lib.rs, src/main.rs and the files in src/bin are crate roots.
+fn module_path_of_file(relative_path: &Path) -> Vec<String> {
+    let mut components = relative_path
+        .iter()
+        .map(|c| c.to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    if components.is_empty() {
+        return vec![];
+    }
+    let top = components.remove(0);
+    if top == "src" && components.first().map(String::as_str) == Some("bin") {
+        // each binary is its own crate, either src/bin/x.rs or src/bin/x/main.rs
+        components.remove(0);
+        if !components.is_empty() {
+            components.remove(0);
+        }
+        return match components.as_slice() {
+            [] => vec![],
+            [file] if file == "main.rs" => vec![],
+            _ => module_path_of_components(&components),
+        };
+    }
+    match components.as_slice() {
+        [] => vec![],
+        [file] if top != "src" || file == "lib.rs" || file == "main.rs" => vec![],
+        _ => module_path_of_components(&components),
+    }
+}
+
+// a::b.rs => a::b, a::mod.rs => a
+fn module_path_of_components(components: &[String]) -> Vec<String> {
+    let mut path = components.to_vec();
+    if let Some(file) = path.pop() {
+        if file != "mod.rs" {
+            let stem = Path::new(&file)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or(file);
+            path.push(stem);
+        }
+    }
+    path
+}
+
+/// Parses a string literal, including raw and byte strings.
+fn string_literal(i: &str) -> IResult<&str, &str, VerboseError<&str>> {
+    branch::alt((
+        // raw strings end at a quote followed by the same number of hashes they started with
+        |i| {
+            let (rest, (_, hashes, _)) = (
+                (
+                    combinator::opt(character::complete::char('b')),
+                    character::complete::char('r'),
+                ),
+                bytes::complete::take_while(|c| c == '#'),
+                character::complete::char('"'),
+            )
+                .parse(i)?;
+            let end = format!("\"{hashes}");
+            combinator::recognize((
+                bytes::complete::take_until(end.as_str()),
+                bytes::complete::tag(end.as_str()),
+            ))
+            .parse(rest)
+        },
+        combinator::recognize((
+            combinator::opt(character::complete::char('b')),
+            character::complete::char('"'),
+            multi::many0_count(branch::alt((
+                combinator::recognize((
+                    character::complete::char('\\'),
+                    character::complete::anychar,
+                )),
+                bytes::complete::is_not("\\\""),
+            ))),
+            character::complete::char('"'),
+        )),
+    ))
+    .parse(i)
+}
+
+/// Parses a character literal. Lifetimes are not matched as they have no closing quote.
+fn char_literal(i: &str) -> IResult<&str, &str, VerboseError<&str>> {
+    combinator::recognize((
+        combinator::opt(character::complete::char('b')),
+        character::complete::char('\''),
+        branch::alt((
+            combinator::recognize((
+                character::complete::char('\\'),
+                bytes::complete::is_not("'"),
+            )),
+            combinator::recognize(character::complete::anychar),
+        )),
+        character::complete::char('\''),
+    ))
+    .parse(i)
+}
+
+/// Parses a Rust identifier.
+fn identifier(i: &str) -> IResult<&str, &str, VerboseError<&str>> {
+    combinator::recognize((
+        character::complete::satisfy(|c| c.is_alphabetic() || c == '_'),
+        bytes::complete::take_while(|c: char| c.is_alphanumeric() || c == '_'),
+    ))
+    .parse(i)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use zip::write::SimpleFileOptions;
+
+    fn init() {
+        use log::*;
+        use simple_logger::*;
+        let _ = SimpleLogger::new().with_level(LevelFilter::Debug).init();
+    }
+
+    fn file_to(
+        target_dir: impl AsRef<std::path::Path>,
+        target_relative: impl AsRef<std::path::Path>,
+        contents: impl AsRef<[u8]>,
+    ) -> PathBuf {
+        let target = target_dir.as_ref().join(target_relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(&target, contents.as_ref()).unwrap();
+        target
+    }
+
+    fn dir_to_zip(source_dir: impl AsRef<std::path::Path>) -> Vec<u8> {
+        use std::io::Write;
+
+        let mut target = vec![];
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(&mut target));
+
+        for entry in walkdir::WalkDir::new(&source_dir)
+            .min_depth(1)
+            .sort_by(|a, b| a.path().cmp(b.path()))
+        {
+            let entry = entry.unwrap();
+            let rela = entry
+                .path()
+                .strip_prefix(&source_dir)
+                .unwrap()
+                .to_str()
+                .unwrap();
+            if entry.path().is_dir() {
+                zip.add_directory(rela, SimpleFileOptions::default())
+                    .unwrap();
+            } else if entry.path().is_file() {
+                zip.start_file(rela, SimpleFileOptions::default()).unwrap();
+                let bytes = std::fs::read(entry.path()).unwrap();
+                zip.write_all(&bytes).unwrap();
+            }
+        }
+
+        zip.finish().unwrap();
+        target
+    }
+
+    /// Writes a cargo project with a no-op points attribute macro, the given library source and the given tests.
+    fn exercise_to(target_dir: impl AsRef<std::path::Path>, lib: &str, tests: &str) {
+        file_to(
+            &target_dir,
+            "Cargo.toml",
+            r#"
+[package]
+name = "exercise"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+points = { path = "points" }
+
+[workspace]
+"#,
+        );
+        file_to(
+            &target_dir,
+            "points/Cargo.toml",
+            r#"
+[package]
+name = "points"
+version = "0.1.0"
+edition = "2021"
+
+[lib]
+proc-macro = true
+"#,
+        );
+        file_to(
+            &target_dir,
+            "points/src/lib.rs",
+            r#"
+use proc_macro::TokenStream;
+
+#[proc_macro_attribute]
+pub fn points(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+"#,
+        );
+        file_to(&target_dir, "src/lib.rs", lib);
+        file_to(&target_dir, "tests/tests.rs", tests);
+    }
+
+    const TESTS: &str = r#"
+use points::points;
+
+#[test]
+#[points("1.1")]
+fn adds() {
+    assert_eq!(exercise::add(1, 2), 3);
+}
+
+#[test]
+#[points("1.1 1.2")]
+fn adds_negative() {
+    assert_eq!(exercise::add(-1, -2), -3);
+}
+
+// #[points("2")]
+fn helper() {}
+"#;
+
+    #[test]
+    fn parses_points() {
+        init();
+
+        assert!(RustPlugin::points_parser("asd").is_err());
+        assert!(RustPlugin::points_parser("#[points(\"1\")").is_err());
+
+        let res = RustPlugin::points_parser("#[points(\"1\")]").unwrap();
+        assert_eq!(res.1, &["1"]);
+
+        let res = RustPlugin::points_parser("#  [  points  (  \"  1  \"  )  ]").unwrap();
+        assert_eq!(res.1, &["1"]);
+
+        let res = RustPlugin::points_parser("#[tmc::points(\"1\", \"2 3\")]").unwrap();
+        assert_eq!(res.1, &["1", "2", "3"]);
+    }
+
+    #[test]
+    fn gets_available_points() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, "", TESTS);
+        let points = RustPlugin::get_available_points(temp.path()).unwrap();
+        assert_eq!(points, &["1.1", "1.1", "1.2"]);
+    }
+
+    #[test]
+    fn scans_exercise() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, "", TESTS);
+        let plugin = RustPlugin::new();
+        let desc = plugin.scan_exercise(temp.path(), "ex".to_string()).unwrap();
+        assert_eq!(desc.name, "ex");
+        assert_eq!(
+            desc.tests,
+            &[
+                TestDesc::new("adds".to_string(), vec!["1.1".to_string()]),
+                TestDesc::new(
+                    "adds_negative".to_string(),
+                    vec!["1.1".to_string(), "1.2".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn scans_tests_by_module_path() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        let tests = format!(
+            "{TESTS}{}",
+            r##"
+mod a {
+    #[test]
+    #[points("2")]
+    fn adds() { let _ = format!("{{{}", '{'); }
+}
+
+mod b {
+    #[test]
+    #[points("3")]
+    fn adds() { let _ = r#"}"#; }
+}
+"##
+        );
+        exercise_to(&temp, "", &tests);
+        file_to(
+            &temp,
+            "tests/common/mod.rs",
+            "mod c {\n    #[test]\n    #[points(\"4\")]\n    fn adds() {}\n}\n",
+        );
+        let mut tests = RustPlugin::scan_tests(temp.path()).unwrap();
+        tests.sort_by(|a, b| a.name.cmp(&b.name));
+        let tests = tests
+            .into_iter()
+            .map(|t| (t.name, t.points))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tests,
+            &[
+                ("a::adds".to_string(), vec!["2".to_string()]),
+                ("adds".to_string(), vec!["1.1".to_string()]),
+                (
+                    "adds_negative".to_string(),
+                    vec!["1.1".to_string(), "1.2".to_string()]
+                ),
+                ("b::adds".to_string(), vec!["3".to_string()]),
+                ("common::c::adds".to_string(), vec!["4".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_tests_passing() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, "pub fn add(a: i32, b: i32) -> i32 { a + b }", TESTS);
+        let plugin = RustPlugin::new();
+        let res = plugin.run_tests(temp.path()).unwrap();
+        log::debug!("{res:#?}");
+        assert_eq!(res.status, RunStatus::Passed);
+        assert_eq!(res.test_results.len(), 2);
+        for test_result in &res.test_results {
+            assert!(test_result.successful);
+        }
+        let adds = res.test_results.iter().find(|t| t.name == "adds").unwrap();
+        assert_eq!(adds.points, &["1.1"]);
+    }
+
+    #[test]
+    fn runs_tests_failing() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(
+            &temp,
+            "pub fn add(a: i32, b: i32) -> i32 { if a < 0 { 0 } else { a + b } }",
+            TESTS,
+        );
+        let plugin = RustPlugin::new();
+        let res = plugin.run_tests(temp.path()).unwrap();
+        log::debug!("{res:#?}");
+        assert_eq!(res.status, RunStatus::TestsFailed);
+        assert_eq!(res.test_results.len(), 2);
+        let failed = res
+            .test_results
+            .iter()
+            .find(|t| t.name == "adds_negative")
+            .unwrap();
+        assert!(!failed.successful);
+        assert!(failed.message.contains("assertion"));
+        // 1.1 is shared with the failed test so it is not awarded
+        for test_result in &res.test_results {
+            assert!(test_result.points.is_empty());
+        }
+    }
+
+    #[test]
+    fn runs_tests_compile_err() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, "pub fn add(a: i32, b: i32) -> i32 { a + }", TESTS);
+        let plugin = RustPlugin::new();
+        let res = plugin.run_tests(temp.path()).unwrap();
+        assert_eq!(res.status, RunStatus::CompileFailed);
+        assert!(res.test_results.is_empty());
+        assert!(res.logs.get("stderr").unwrap().contains("error"));
+    }
+
+    #[test]
+    fn runs_tests_timeout() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, "pub fn add(a: i32, b: i32) -> i32 { a + b }", TESTS);
+        let plugin = RustPlugin::new();
+        let res = plugin
+            .run_tests_with_timeout(temp.path(), Some(std::time::Duration::from_nanos(1)), None)
+            .unwrap();
+        assert_eq!(res.status, RunStatus::TestsFailed);
+        assert_eq!(res.test_results[0].name, "Timeout test");
+    }
+
+    #[test]
+    fn cleans() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(&temp, "pub fn add(a: i32, b: i32) -> i32 { a + b }", TESTS);
+        let plugin = RustPlugin::new();
+        plugin.run_tests(temp.path()).unwrap();
+        assert!(temp.path().join("target").exists());
+        plugin.clean(temp.path()).unwrap();
+        assert!(!temp.path().join("target").exists());
+    }
+
+    #[test]
+    fn finds_project_dir_in_zip() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        exercise_to(temp.path().join("outer/exercise"), "", "");
+        let zip = dir_to_zip(&temp);
+        let mut zip = Archive::zip(std::io::Cursor::new(zip)).unwrap();
+        let dir = RustPlugin::find_project_dir_in_archive(&mut zip).unwrap();
+        assert_eq!(dir, Path::new("outer/exercise"));
+    }
+
+    #[test]
+    fn doesnt_find_project_dir_in_zip() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        file_to(&temp, "outer/exercise/src/lib.rs", "");
+        file_to(&temp, "outer/__MACOSX/Cargo.toml", "");
+        let zip = dir_to_zip(&temp);
+        let mut zip = Archive::zip(std::io::Cursor::new(zip)).unwrap();
+        assert!(RustPlugin::find_project_dir_in_archive(&mut zip).is_err());
+    }
+}
@@ -0,0 +1,47 @@
+//! Contains the Rust student file policy.
+
+use std::path::Path;
+use tmc_langs_framework::{StudentFilePolicy, TmcProjectYml};
+
+pub struct RustStudentFilePolicy {
+    project_config: TmcProjectYml,
+}
+
+impl StudentFilePolicy for RustStudentFilePolicy {
+    fn new_with_project_config(project_config: TmcProjectYml) -> Self
+    where
+        Self: Sized,
+    {
+        Self { project_config }
+    }
+
+    fn get_project_config(&self) -> &TmcProjectYml {
+        &self.project_config
+    }
+
+    fn is_non_extra_student_file(&self, path: &Path) -> bool {
+        path.starts_with("src")
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn file_in_src_is_student_file() {
+        let policy = RustStudentFilePolicy::new(Path::new(".")).unwrap();
+        assert!(policy.is_student_file(Path::new("src/lib.rs")));
+        assert!(policy.is_student_file(Path::new("src/bin/main.rs")));
+    }
+
+    #[test]
+    fn tests_and_manifest_are_not_student_files() {
+        let policy = RustStudentFilePolicy::new(Path::new(".")).unwrap();
+        assert!(!policy.is_student_file(Path::new("tests/tests.rs")));
+        assert!(!policy.is_student_file(Path::new("Cargo.toml")));
+        assert!(!policy.is_student_file(Path::new("Cargo.lock")));
+        assert!(!policy.is_student_file(Path::new("target/debug/exercise")));
+    }
+}
@@ -2,9 +2,11 @@ export type Locale = string;
 
 export type CliOutput = { "output-kind": "output-data" } & OutputData | { "output-kind": "status-update" } & StatusUpdateData | { "output-kind": "notification" } & Notification;
 
-export type DataKind = { "output-data-kind": "error", "output-data": { kind: Kind, trace: Array<string>, } } | { "output-data-kind": "validation", "output-data": StyleValidationResult | null } | { "output-data-kind": "available-points", "output-data": Array<string> } | { "output-data-kind": "exercises", "output-data": Array<string> } | { "output-data-kind": "exercise-packaging-configuration", "output-data": ExercisePackagingConfiguration } | { "output-data-kind": "local-tmc-exercises", "output-data": Array<LocalTmcExercise> } | { "output-data-kind": "local-mooc-exercises", "output-data": Array<LocalMoocExercise> } | { "output-data-kind": "refresh-result", "output-data": RefreshData } | { "output-data-kind": "test-result", "output-data": RunResult } | { "output-data-kind": "exercise-desc", "output-data": ExerciseDesc } | { "output-data-kind": "updated-exercises", "output-data": Array<UpdatedExercise> } | { "output-data-kind": "tmc-exercise-download", "output-data": DownloadOrUpdateTmcCourseExercisesResult } | { "output-data-kind": "mooc-exercise-download", "output-data": DownloadOrUpdateMoocCourseExercisesResult } | { "output-data-kind": "combined-course-data", "output-data": CombinedCourseData } | { "output-data-kind": "course-details", "output-data": CourseDetails } | { "output-data-kind": "course-exercises", "output-data": Array<CourseExercise> } | { "output-data-kind": "course-data", "output-data": CourseData } | { "output-data-kind": "courses", "output-data": Array<Course> } | { "output-data-kind": "exercise-details", "output-data": ExerciseDetails } | { "output-data-kind": "submissions", "output-data": Array<Submission> } | { "output-data-kind": "update-result", "output-data": UpdateResult } | { "output-data-kind": "organization", "output-data": Organization } | { "output-data-kind": "organizations", "output-data": Array<Organization> } | { "output-data-kind": "reviews", "output-data": Array<Review> } | { "output-data-kind": "token", "output-data": unknown } | { "output-data-kind": "new-submission", "output-data": NewSubmission } | { "output-data-kind": "submission-feedback-response", "output-data": SubmissionFeedbackResponse } | { "output-data-kind": "submission-finished", "output-data": SubmissionFinished } | { "output-data-kind": "config-value", "output-data": ConfigValue } | { "output-data-kind": "tmc-config", "output-data": TmcConfig } | { "output-data-kind": "compressed-project-hash", "output-data": string } | { "output-data-kind": "submission-sandbox", "output-data": string } | { "output-data-kind": "mooc-course-instances", "output-data": Array<CourseInstance> } | { "output-data-kind": "mooc-exercise-slides", "output-data": Array<TmcExerciseSlide> } | { "output-data-kind": "mooc-exercise-slide", "output-data": TmcExerciseSlide } | { "output-data-kind": "mooc-submission-finished", "output-data": ExerciseTaskSubmissionResult };
+export type DataKind = { "output-data-kind": "error", "output-data": { kind: Kind, trace: Array<string>, } } | { "output-data-kind": "validation", "output-data": StyleValidationResult | null } | { "output-data-kind": "course-check", "output-data": Array<ExerciseCheck> } | { "output-data-kind": "exercise-lint", "output-data": Array<LintIssue> } | { "output-data-kind": "exercise-preview", "output-data": Array<FilePreview> } | { "output-data-kind": "available-points", "output-data": Array<string> } | { "output-data-kind": "exercises", "output-data": Array<string> } | { "output-data-kind": "exercise-packaging-configuration", "output-data": ExercisePackagingConfiguration } | { "output-data-kind": "local-tmc-exercises", "output-data": Array<LocalTmcExercise> } | { "output-data-kind": "local-mooc-exercises", "output-data": Array<LocalMoocExercise> } | { "output-data-kind": "refresh-result", "output-data": RefreshData } | { "output-data-kind": "test-result", "output-data": RunResult } | { "output-data-kind": "exercise-desc", "output-data": ExerciseDesc } | { "output-data-kind": "updated-exercises", "output-data": Array<UpdatedExercise> } | { "output-data-kind": "tmc-exercise-download", "output-data": DownloadOrUpdateTmcCourseExercisesResult } | { "output-data-kind": "mooc-exercise-download", "output-data": DownloadOrUpdateMoocCourseExercisesResult } | { "output-data-kind": "combined-course-data", "output-data": CombinedCourseData } | { "output-data-kind": "course-details", "output-data": CourseDetails } | { "output-data-kind": "course-exercises", "output-data": Array<CourseExercise> } | { "output-data-kind": "course-data", "output-data": CourseData } | { "output-data-kind": "courses", "output-data": Array<Course> } | { "output-data-kind": "exercise-details", "output-data": ExerciseDetails } | { "output-data-kind": "submissions", "output-data": Array<Submission> } | { "output-data-kind": "update-result", "output-data": UpdateResult } | { "output-data-kind": "organization", "output-data": Organization } | { "output-data-kind": "organizations", "output-data": Array<Organization> } | { "output-data-kind": "reviews", "output-data": Array<Review> } | { "output-data-kind": "token", "output-data": unknown } | { "output-data-kind": "new-submission", "output-data": NewSubmission } | { "output-data-kind": "queued-submission", "output-data": QueuedSubmission } | { "output-data-kind": "queued-submissions", "output-data": Array<QueuedSubmission> } | { "output-data-kind": "synced-submissions", "output-data": Array<SyncedSubmission<NewSubmission>> } | { "output-data-kind": "submission-feedback-response", "output-data": SubmissionFeedbackResponse } | { "output-data-kind": "submission-finished", "output-data": SubmissionFinished } | { "output-data-kind": "config-value", "output-data": ConfigValue } | { "output-data-kind": "tmc-config", "output-data": TmcConfig } | { "output-data-kind": "compressed-project-hash", "output-data": string } | { "output-data-kind": "submission-sandbox", "output-data": string } | { "output-data-kind": "graded-submission", "output-data": GradedSubmission } | { "output-data-kind": "mooc-course-instances", "output-data": Array<CourseInstance> } | { "output-data-kind": "mooc-exercise-slides", "output-data": Array<TmcExerciseSlide> } | { "output-data-kind": "mooc-exercise-slide", "output-data": TmcExerciseSlide } | { "output-data-kind": "mooc-submission-finished", "output-data": ExerciseTaskSubmissionResult } | { "output-data-kind": "mooc-synced-submissions", "output-data": Array<SyncedSubmission<ExerciseTaskSubmissionResult>> };
 
-export type Kind = "generic" | "forbidden" | "not-logged-in" | "connection-error" | "obsolete-client" | "invalid-token" | { "failed-exercise-download": { completed: Array<TmcExerciseDownload>, skipped: Array<TmcExerciseDownload>, failed: Array<[TmcExerciseDownload, Array<string>]>, } };
+export type Kind = "generic" | "forbidden" | "not-logged-in" | "connection-error" | "obsolete-client" | "invalid-token" | { "authorization-error": { hint: AuthorizationHint, } } | { "failed-exercise-download": { completed: Array<TmcExerciseDownload>, skipped: Array<TmcExerciseDownload>, failed: Array<[TmcExerciseDownload, Array<string>]>, } };
+
+export type AuthorizationHint = "reauthenticate";
 
 export type OutputData = { status: Status, message: string, result: OutputResult, data: DataKind | null, }
 
@@ -12,7 +14,7 @@ export type OutputResult = "logged-in" | "logged-out" | "not-logged-in" | "error
 
 export type Status = "finished" | "crashed";
 
-export type StatusUpdateData = { "update-data-kind": "client-update-data" } & StatusUpdate<ClientUpdateData> | { "update-data-kind": "none" } & StatusUpdate<null>;
+export type StatusUpdateData = { "update-data-kind": "client-update-data" } & StatusUpdate<ClientUpdateData> | { "update-data-kind": "run-result" } & StatusUpdate<RunResult> | { "update-data-kind": "none" } & StatusUpdate<null>;
 
 export type Notification = { "notification-kind": NotificationKind, message: string, }
 
@@ -22,6 +24,36 @@ export type StatusUpdate<T> = { finished: boolean, message: string, "percent-don
 
 export type ClientUpdateData = { "client-update-data-kind": "exercise-download", id: number, path: string, } | { "client-update-data-kind": "posted-submission" } & NewSubmission;
 
+export type ExerciseCheck = { 
+/**
+ * Path to the exercise relative to the course directory.
+ */
+exercise_path: string, 
+/**
+ * Running the tests on the stub. The tests should fail, but some exercises are meant to pass on the stub, so passing is only a warning.
+ */
+stub: CheckResult, 
+/**
+ * Running the tests on the model solution. The tests should pass.
+ */
+solution: CheckResult, 
+/**
+ * Grading the model solution packaged as a submission, see `grade_submission`. The tests should pass.
+ */
+submission: CheckResult, }
+
+export type CheckResult = { status: CheckStatus, 
+/**
+ * The status of the test run, if the tests could be run.
+ */
+run_status: RunStatus | null, 
+/**
+ * Describes the problem if the status is not ok.
+ */
+message: string | null, }
+
+export type CheckStatus = "ok" | "warning" | "error";
+
 export type StyleValidationResult = { strategy: StyleValidationStrategy, validation_errors: Record<string, Array<StyleValidationError>> | null, }
 
 export type StyleValidationError = { column: number, line: number, message: string, source_name: string, }
@@ -38,15 +70,85 @@ student_file_paths: Array<string>,
  */
 exercise_file_paths: Array<string>, }
 
+export type GradedSubmission = { 
+/**
+ * The sandbox image the server would run the tests in.
+ */
+sandbox_image: string, 
+/**
+ * The points the server would award for the submission, sorted by name.
+ */
+awarded_points: Array<string>, run_result: RunResult, }
+
 export type LocalExercise = { "tmc": LocalTmcExercise } | { "mooc": LocalMoocExercise };
 
 export type LocalTmcExercise = { "exercise-slug": string, "exercise-path": string, }
 
 export type LocalMoocExercise = { "exercise-id": string, "exercise-path": string, }
 
+export type LintIssue = { 
+/**
+ * Path to the file relative to the exercise directory.
+ */
+path: string,
+/**
+ * The notebook cell of the annotation, starting from 1, if the file is a notebook.
+ */
+cell?: number,
+/**
+ * The line of the annotation, starting from 1. In notebooks, the line is counted from the start of the cell.
+ */
+line: number,
+/**
+ * The column where the annotation's comment starts, starting from 1.
+ */
+column: number, kind: AnnotationIssueKind, message: string, }
+
+export type AnnotationIssueKind = "unclosed" | "nested" | "mismatched" | "unmatched" | "unrecognized" | "invalid-variant";
+
 export type Compression = "tar" | "zip" | "zstd";
 
-export type RefreshData = { "new-cache-path": string, "course-options": object, exercises: Array<RefreshExercise>, }
+export type FilePreview = { 
+/**
+ * Path to the file relative to the exercise directory.
+ */
+path: string, 
+/**
+ * Whether the file is included in the stub.
+ */
+in_stub: boolean, 
+/**
+ * Whether the file is included in the model solution.
+ */
+in_solution: boolean, 
+/**
+ * The diff from the template to the stub, if they differ.
+ */
+stub_diff: string | null, 
+/**
+ * The diff from the template to the model solution, if they differ.
+ */
+solution_diff: string | null, 
+/**
+ * The diff from the stub to the model solution, if they differ.
+ */
+stub_to_solution_diff: string | null, }
+
+export type RefreshData = { "new-cache-path": string, 
+/**
+ * The commit the course was refreshed from, if the source was a git repository.
+ * None for a local directory with uncommitted changes or untracked files, as they are not part of any commit.
+ */
+commit: string | null, 
+/**
+ * The options in the course's course_options.yml, with the ones under `courses.<course_name>` merged over the general ones.
+ */
+"course-options": object, exercises: Array<RefreshExercise>, 
+/**
+ * The names of the exercises that were processed during this refresh.
+ * The rest were unchanged since the previous refresh and reused from the previous cache.
+ */
+"rebuilt-exercises": Array<string>, }
 
 export type RefreshExercise = { name: string, checksum: string, points: Array<string>, "sandbox-image": string, "tmcproject-yml": TmcProjectYml | null, }
 
@@ -80,13 +182,46 @@ minimum_python_version?: PythonVer,
  * Overrides the default sandbox image. e.g. `eu.gcr.io/moocfi-public/tmc-sandbox-python:latest`
  */
 sandbox_image?: string,
+/**
+ * Resource limits for running the tests in a sandbox.
+ */
+sandbox_limits?: SandboxLimits,
 /**
  * Overrides the default archive size limit (500 Mb).
  */
-submission_size_limit_mb?: number, }
+submission_size_limit_mb?: number, 
+/**
+ * Maps file extensions to the comment syntaxes used to find annotations such as `BEGIN SOLUTION` in files with them.
+ * Overrides the built-in and language plugin syntaxes for the extension.
+ */
+comment_syntaxes?: Record<string, CommentSyntax>, }
 
 export type PythonVer = { major: number, minor: number | null, patch: number | null, }
 
+export type SandboxLimits = { 
+/**
+ * The maximum amount of memory used by all of the command's processes together in megabytes, without swap.
+ */
+memory_mb?: number,
+/**
+ * The maximum number of processes for the user running the tests.
+ */
+max_processes?: number,
+/**
+ * The maximum CPU time for each process in seconds.
+ */
+cpu_time_s?: number, }
+
+export type CommentSyntax = { 
+/**
+ * Starts a comment that lasts until the end of the line, e.g. `//`.
+ */
+line?: string, 
+/**
+ * The start and end of a comment that can span multiple lines, like the block comments in C.
+ */
+block?: [string, string], }
+
 export type RunResult = { 
 /**
  * The overall status of a test run.
@@ -100,7 +235,37 @@ testResults: Array<TestResult>,
  * Logs from the test run.
  * The key may be an arbitrary string identifying the type of log.
  */
-logs: Record<string, string>, }
+logs: Record<string, string>,
+/**
+ * Statistics about the execution of the command that ran the tests.
+ */
+executionStats?: ExecutionStats, }
+
+export type ExecutionStats = { 
+/**
+ * How long the command ran in milliseconds.
+ */
+wallTimeMs: number,
+/**
+ * The CPU time used by the command and the processes it waited for in milliseconds. Only measured on Unix.
+ */
+cpuTimeMs: number | null,
+/**
+ * The peak resident set size in kilobytes of the largest process among the command and the processes it waited for. Only measured on Unix.
+ */
+peakRssKb: number | null,
+/**
+ * The signal that terminated the command, if any.
+ */
+exitSignal: number | null,
+/**
+ * Whether the command was stopped because it ran longer than its timeout.
+ */
+timedOut: boolean,
+/**
+ * Whether the command was killed for exceeding the sandbox's memory limit. Only detected for commands run in a sandbox with a memory limit.
+ */
+memoryLimitExceeded: boolean, }
 
 export type RunStatus = "PASSED" | "TESTS_FAILED" | "COMPILE_FAILED" | "TESTRUN_INTERRUPTED" | "GENERIC_ERROR";
 
@@ -108,7 +273,23 @@ export type TestResult = { name: string, successful: boolean,
 /**
  * List of points that were received from the exercise from passed tests.
  */
-points: Array<string>, message: string, exception: Array<string>, }
+points: Array<string>, message: string, exception: Array<string>,
+/**
+ * How long the test took to run in milliseconds, if reported by the test runner.
+ * Read from the Check results of C exercises, and from the results of Python and R runners
+ * and external plugins that include it. The bundled Java and C# runners do not report it.
+ */
+durationMs?: number,
+/**
+ * What the test wrote to stdout, if captured by the test runner.
+ * Read from the results of Python runners and external plugins that include it.
+ */
+stdout?: string,
+/**
+ * What the test wrote to stderr, if captured by the test runner.
+ * Read from the results of Python runners and external plugins that include it.
+ */
+stderr?: string, }
 
 export type ExerciseDesc = { 
 /**
@@ -268,6 +449,22 @@ export type TmcStyleValidationError = { column: number, line: number, message: s
 
 export type TmcStyleValidationStrategy = "FAIL" | "WARN" | "DISABLED";
 
+export type QueuedSubmission = { id: string, target: SubmissionTarget, queued_at: string,
+/**
+ * The BLAKE3 hash of the archive, checked before the archive is sent.
+ */
+hash: string, }
+
+export type SubmissionTarget = { "kind": "tmc", exercise_id: number,
+/**
+ * ISO 639-3 code of the locale the submission was made with.
+ */
+locale: string | null, } | { "kind": "mooc", exercise_id: string, slide_id: string, task_id: string, };
+
+export type SyncedSubmission<T> = { submission: QueuedSubmission, result: SyncResult<T>, }
+
+export type SyncResult<T> = { "status": "sent", "data": T } | { "status": "failed", "data": string } | { "status": "pending" };
+
 export type SubmissionFinished = { api_version: number, all_tests_passed: boolean | null, user_id: number, login: string, course: string, exercise_name: string, status: SubmissionStatus, points: Array<string>, valgrind: string | null,
 /**
  * https://tmc.mooc.fi/submissions/{submission_id}}
@@ -294,7 +491,15 @@ export type SubmissionFeedbackKind = "Text" | { "IntRange": { lower: number, upp
 
 export type ConfigValue = unknown | null | string;
 
-export type TmcConfig = { projects_dir: string, }
+export type TmcConfig = { projects_dir: string, profiles?: Record<string, Profile>,
+/**
+ * Where the credentials are stored, see `credential_store::selected_backend`.
+ */
+credential_store?: CredentialBackend, }
+
+export type Profile = { tmc_root_url?: string, mooc_root_url?: string, projects_dir?: string, }
+
+export type CredentialBackend = "secret-service" | "encrypted-file" | "plaintext-file";
 
 export type CourseInstance = { id: string, course_id: string, course_slug: string, course_name: string, course_description: string | null, instance_name: string | null, instance_description: string | null, }
 
@@ -1,13 +1,15 @@
 //! Create clap app
 
 use anyhow::Context;
+use chrono::{DateTime, FixedOffset};
 use clap::Parser;
 use schemars::JsonSchema;
 use std::{path::PathBuf, str::FromStr};
 use tmc_langs::{
-    CombinedCourseData, Compression, DownloadOrUpdateTmcCourseExercisesResult, ExerciseDesc,
-    ExercisePackagingConfiguration, Language, LocalExercise, RunResult, StyleValidationResult,
-    UpdatedExercise,
+    CombinedCourseData, Compression, DownloadOrUpdateTmcCourseExercisesResult, ExerciseCheck,
+    ExerciseDesc, ExercisePackagingConfiguration, ExportFormat, FilePreview, GradedSubmission,
+    Language, LintIssue, LocalExercise, QueuedSubmission, RunResult, StyleValidationResult,
+    SyncedSubmission, UpdatedExercise,
     mooc::CourseInstance,
     tmc::{
         UpdateResult,
@@ -32,12 +34,29 @@ pub struct Cli {
     /// Pretty-prints all output
     #[clap(long, short)]
     pub pretty: bool,
+    /// Uses the named profile from the client's config, which has its own credentials and can set its own root URLs and projects directory.
+    #[clap(long, global = true)]
+    pub profile: Option<String>,
     #[clap(subcommand)]
     pub command: Command,
 }
 
 #[derive(Parser)]
 pub enum Command {
+    /// Checks each exercise in a course repository: the tests should fail on the stub, pass on the model solution, and pass when the model solution is packaged and graded as a submission
+    #[clap(long_about = schema_leaked::<Vec<ExerciseCheck>>())]
+    CheckCourse {
+        /// Path to the course repository.
+        #[clap(long)]
+        course_path: PathBuf,
+        /// How many exercises to check in parallel.
+        #[clap(long, default_value_t = 1)]
+        jobs: usize,
+        /// If defined, the report will be written to this path. Overwritten if it already exists.
+        #[clap(long)]
+        output_path: Option<PathBuf>,
+    },
+
     /// Checks the code style for the given exercise
     #[clap(long_about = schema_leaked::<Option<StyleValidationResult>>())]
     Checkstyle {
@@ -133,6 +152,44 @@ pub enum Command {
         output_path: Option<PathBuf>,
     },
 
+    /// Grades a submission locally the way the server does. The submission is prepared like in prepare-submission, the tests are run with the timeout from the exercise's .tmcproject.yml, and the points are awarded, including the no-tests points from .tmcproject.yml.
+    /// The tests are not run in a sandbox.
+    #[clap(long_about = schema_leaked::<GradedSubmission>())]
+    GradeSubmission {
+        /// Path to exercise's clone path, where the unmodified test files will be copied from.
+        #[clap(long)]
+        clone_path: PathBuf,
+        /// If defined, the grading result will be written to this path. Overwritten if it already exists.
+        #[clap(long)]
+        output_path: Option<PathBuf>,
+        /// Path to the submission archive.
+        #[clap(long)]
+        submission_path: PathBuf,
+        /// Compression algorithm used for the submission.
+        #[clap(long, default_value_t = Compression::Zip)]
+        submission_compression: Compression,
+        /// If set, the submission is extracted without trying to find a project directory inside it. This can be useful if the submission is minimal and doesn't contain enough files to detect the project.
+        #[clap(long)]
+        extract_submission_naively: bool,
+        /// A key-value pair in the form key=value to be written into .tmcparams. If multiple pairs with the same key are given, the values are collected into an array.
+        #[clap(long)]
+        tmc_param: Vec<String>,
+    },
+
+    /// Checks the stub and solution annotations in the exercise's files, reporting unclosed, nested or mismatched blocks and unrecognized annotations
+    #[clap(long_about = schema_leaked::<Vec<LintIssue>>())]
+    LintExercise {
+        /// Path to the directory where the exercise resides.
+        #[clap(long)]
+        exercise_path: PathBuf,
+        /// If given, the issues will be written to this path. Overwritten if it already exists.
+        #[clap(long)]
+        output_path: Option<PathBuf>,
+        /// The variant of the exercise to check the annotations for. Annotations limited to a variant are errors if none is given.
+        #[clap(long)]
+        variant: Option<String>,
+    },
+
     /// Returns a list of local exercises for the given course
     #[clap(long_about = schema_leaked::<Vec<LocalExercise>>())]
     ListLocalTmcCourseExercises {
@@ -144,6 +201,14 @@ pub enum Command {
         course_slug: String,
     },
 
+    /// Returns the submissions that were queued because the server could not be reached, in the order they were queued in
+    #[clap(long_about = schema_leaked::<Vec<QueuedSubmission>>())]
+    ListQueuedSubmissions {
+        /// The client name of which the queued submissions should be listed.
+        #[clap(long)]
+        client_name: String,
+    },
+
     /// Processes the exercise files in exercise-path, removing all code marked as stubs
     #[clap(long_about = SCHEMA_NULL)]
     PrepareSolution {
@@ -153,6 +218,9 @@ pub enum Command {
         /// Path to the directory where the processed files will be written.
         #[clap(long)]
         output_path: PathBuf,
+        /// The variant of the exercise to prepare the solution for.
+        #[clap(long)]
+        variant: Option<String>,
     },
 
     /// Processes the exercise files in exercise-path, removing all code marked as solutions
@@ -164,6 +232,9 @@ pub enum Command {
         /// Path to the directory where the processed files will be written.
         #[clap(long)]
         output_path: PathBuf,
+        /// The variant of the exercise to prepare the stub for.
+        #[clap(long)]
+        variant: Option<String>,
     },
 
     /// Takes a submission archive and turns it into an archive with reset test files, and tmc-params, ready for further processing.
@@ -204,6 +275,23 @@ pub enum Command {
         no_archive_prefix: bool,
     },
 
+    /// Processes the exercise files in memory the same way as prepare-stub and prepare-solution and returns unified diffs between the template, the stub and the model solution for each file that changes, including files that are left out of the stub or solution
+    #[clap(long_about = schema_leaked::<Vec<FilePreview>>())]
+    PreviewExercise {
+        /// Path to the directory where the exercise resides.
+        #[clap(long)]
+        exercise_path: PathBuf,
+        /// If given, the previews will be written to this path. Overwritten if it already exists.
+        #[clap(long)]
+        output_path: Option<PathBuf>,
+        /// If set, the previews are written to the output path as an HTML fragment that can be pasted into a pull request instead of as JSON.
+        #[clap(long, requires = "output_path")]
+        html: bool,
+        /// The variant of the exercise to preview.
+        #[clap(long)]
+        variant: Option<String>,
+    },
+
     /// Refresh the given course
     RefreshCourse {
         /// Path to the cached course.
@@ -216,11 +304,34 @@ pub enum Command {
         #[clap(long)]
         course_name: String,
         /// Version control branch.
-        #[clap(long)]
-        git_branch: String,
+        #[clap(long, conflicts_with = "git_ref", required_unless_present_any = ["git_ref", "source_bundle", "source_path"])]
+        git_branch: Option<String>,
+        /// Version control branch, tag or commit SHA. Used with --source-url or --source-bundle.
+        #[clap(long)]
+        git_ref: Option<String>,
+        /// How many exercises to process in parallel.
+        #[clap(long, default_value_t = 1)]
+        jobs: usize,
+        /// Path to a git bundle file to refresh the course from.
+        #[clap(long, conflicts_with_all = ["source_path", "source_url"])]
+        source_bundle: Option<PathBuf>,
+        /// Path to a local directory, such as a working copy, to refresh the course from. Uncommitted changes are included, the .git directory and files ignored by git are not.
+        #[clap(long, conflicts_with_all = ["source_url", "git_branch", "git_ref"])]
+        source_path: Option<PathBuf>,
         /// Version control URL or path.
+        #[clap(long, required_unless_present_any = ["source_bundle", "source_path"])]
+        source_url: Option<String>,
+    },
+
+    /// Removes a submission from the queue without sending it, such as one the server keeps rejecting
+    #[clap(long_about = schema_leaked::<QueuedSubmission>())]
+    RemoveQueuedSubmission {
+        /// The client name of which the queued submission should be removed.
+        #[clap(long)]
+        client_name: String,
+        /// The ID of the queued submission.
         #[clap(long)]
-        source_url: String,
+        submission_id: Uuid,
     },
 
     /// Run the tests for the exercise using the appropriate language plugin
@@ -238,6 +349,15 @@ pub enum Command {
         /// If defined, the test results will be written to this path. Overwritten if it already exists.
         #[clap(long)]
         output_path: Option<PathBuf>,
+        /// Only runs the tests that give this point. Multiple points can be given. The C# and R test runners cannot select tests, so for them all of the tests are run and only the selected ones are reported.
+        #[clap(long)]
+        point: Vec<String>,
+        /// If set, the tests are run in a sandbox without network access that only sees the toolchains and can only write to the exercise directory, with the limits from the exercise's .tmcproject.yml. Requires bubblewrap (bwrap) and prlimit, and systemd-run for the memory limit.
+        #[clap(long)]
+        sandbox: bool,
+        /// Only runs the tests whose name contains this string. Multiple names can be given, in which case the tests that match any of them or any of the points are run.
+        #[clap(long)]
+        test: Vec<String>,
         /// If defined, the command will wait for a string to be written to stdin, used for signing the output file with jwt.
         #[clap(long)]
         wait_for_secret: bool,
@@ -255,10 +375,20 @@ pub enum Command {
         #[clap(long)]
         output_path: Option<PathBuf>,
     },
+
+    /// Runs the tests for the exercise and re-runs them whenever its student files change. Each run result is printed as a status update. Runs until stopped.
+    Watch {
+        /// How long to wait in milliseconds for the student files to stop changing before re-running the tests.
+        #[clap(long, default_value_t = 300)]
+        debounce_ms: u64,
+        /// Path to the directory where the exercise resides.
+        #[clap(long)]
+        exercise_path: PathBuf,
+    },
 }
 
 /// Various commands that communicate with the TestMyCode server.
-#[derive(Parser)]
+#[derive(Parser, Clone)]
 #[clap(subcommand_required(true), arg_required_else_help(true))]
 pub struct TestMyCode {
     /// Name used to differentiate between different frontends (e.g. the VSCode extension).
@@ -271,7 +401,7 @@ pub struct TestMyCode {
     pub command: TestMyCodeCommand,
 }
 
-#[derive(Parser)]
+#[derive(Parser, Clone)]
 pub enum TestMyCodeCommand {
     /// Checks for updates to any exercises that exist locally.
     #[clap(long_about = schema_leaked::<Vec<UpdatedExercise>>())]
@@ -316,6 +446,25 @@ pub enum TestMyCodeCommand {
         exercise_id: Vec<u32>,
     },
 
+    /// Exports the points and submissions of every student in the course as a table with a row for each student and exercise they have submitted to or have points for.
+    /// Students with no submissions or points, such as enrolled students who have not started, are not included, as the server has no list of a course's students. Requires teacher or admin rights to the course
+    #[clap(long_about = SCHEMA_NULL)]
+    ExportCourseResults {
+        /// The ID of the course.
+        #[clap(long)]
+        course_id: u32,
+        /// The format of the table, either csv or ndjson (a JSON object on each line).
+        #[clap(long, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// Path to where the table will be written.
+        #[clap(long)]
+        output_path: PathBuf,
+        /// If set, only the rows with new submissions or points since the given RFC 3339 timestamp are exported, e.g. 2024-01-31T12:00:00+02:00.
+        /// This only filters the output: all of the course's submissions and points are still fetched from the server.
+        #[clap(long)]
+        since: Option<DateTime<FixedOffset>>,
+    },
+
     ///Fetches course data. Combines course details, course exercises and course settings
     #[clap(long_about = schema_leaked::<CombinedCourseData>())]
     GetCourseData {
@@ -519,6 +668,10 @@ pub enum TestMyCodeCommand {
         exercise_id: u32,
     },
 
+    /// Sends the submissions that were queued because the server could not be reached, in the order they were queued in
+    #[clap(long_about = schema_leaked::<Vec<SyncedSubmission<NewSubmission>>>())]
+    SyncSubmissions,
+
     /// Updates all local exercises that have been updated on the server
     #[clap(long_about = SCHEMA_NULL)]
     UpdateExercises,
@@ -532,16 +685,19 @@ pub enum TestMyCodeCommand {
     },
 }
 
-#[derive(Parser)]
+#[derive(Parser, Clone)]
 pub struct Mooc {
     /// Name used to differentiate between different frontends (e.g. the VSCode extension).
     #[clap(long, short)]
     pub client_name: String,
+    /// Client version, used when refreshing the login token with the TestMyCode server. Defaults to the CLI's version.
+    #[clap(long, short = 'v')]
+    pub client_version: Option<String>,
     #[clap(subcommand)]
     pub command: MoocCommand,
 }
 
-#[derive(Parser)]
+#[derive(Parser, Clone)]
 pub enum MoocCommand {
     /// Fetches information about a course instance.
     CourseInstance {
@@ -582,6 +738,8 @@ pub enum MoocCommand {
         #[clap(long)]
         submission_path: PathBuf,
     },
+    /// Sends the submissions that were queued because the server could not be reached, in the order they were queued in.
+    SyncSubmissions,
 }
 
 /// Configure the CLI
@@ -604,7 +762,7 @@ pub enum SettingsCommand {
     },
     /// Prints every key=value pair in the settings file
     List,
-    /// Migrates an exercise on disk into the langs project directory
+    /// Migrates an exercise on disk into the langs project directory, or the projects directory of the profile given with --profile
     Migrate {
         /// Path to the directory where the project resides.
         #[clap(long)]
@@ -622,7 +780,8 @@ pub enum SettingsCommand {
         #[clap(long)]
         exercise_checksum: String,
     },
-    /// Change the projects-dir setting, moving the contents into the new directory
+    /// Change the projects-dir setting, moving the contents into the new directory.
+    /// With --profile, the profile's projects directory is moved if it has its own one
     MoveProjectsDir {
         /// The directory where the projects should be moved.
         dir: PathBuf,
@@ -718,6 +877,19 @@ mod base_test {
         );
     }
 
+    #[test]
+    fn check_course() {
+        get_matches(&[
+            "check-course",
+            "--course-path",
+            "path",
+            "--jobs",
+            "4",
+            "--output-path",
+            "path",
+        ]);
+    }
+
     #[test]
     fn checkstyle() {
         get_matches(&[
@@ -792,6 +964,31 @@ mod base_test {
         ]);
     }
 
+    #[test]
+    fn grade_submission() {
+        get_matches(&[
+            "grade-submission",
+            "--clone-path",
+            "path",
+            "--submission-path",
+            "path",
+            "--tmc-param",
+            "a=b",
+        ]);
+    }
+
+    #[test]
+    fn lint_exercise() {
+        get_matches(&["lint-exercise", "--exercise-path", "path"]);
+        get_matches(&[
+            "lint-exercise",
+            "--exercise-path",
+            "path",
+            "--variant",
+            "advanced",
+        ]);
+    }
+
     #[test]
     fn list_local_course_exercises() {
         get_matches(&[
@@ -803,6 +1000,11 @@ mod base_test {
         ]);
     }
 
+    #[test]
+    fn list_queued_submissions() {
+        get_matches(&["list-queued-submissions", "--client-name", "client"]);
+    }
+
     #[test]
     fn prepare_solutions() {
         get_matches(&[
@@ -846,6 +1048,20 @@ mod base_test {
         ]);
     }
 
+    #[test]
+    fn preview_exercise() {
+        get_matches(&[
+            "preview-exercise",
+            "--exercise-path",
+            "path",
+            "--output-path",
+            "path",
+            "--html",
+            "--variant",
+            "advanced",
+        ]);
+    }
+
     #[test]
     fn refresh_course() {
         get_matches(&[
@@ -858,9 +1074,46 @@ mod base_test {
             "name",
             "--git-branch",
             "main",
+            "--jobs",
+            "4",
             "--source-url",
             "http://example.com",
         ]);
+        get_matches(&[
+            "refresh-course",
+            "--cache-path",
+            "path",
+            "--cache-root",
+            "path",
+            "--course-name",
+            "name",
+            "--git-ref",
+            "v1.0",
+            "--source-bundle",
+            "course.bundle",
+        ]);
+        get_matches(&[
+            "refresh-course",
+            "--cache-path",
+            "path",
+            "--cache-root",
+            "path",
+            "--course-name",
+            "name",
+            "--source-path",
+            "path",
+        ]);
+    }
+
+    #[test]
+    fn remove_queued_submission() {
+        get_matches(&[
+            "remove-queued-submission",
+            "--client-name",
+            "client",
+            "--submission-id",
+            "e8d2b25f-8ed8-4b1a-8ad8-5bfd3e1d3de6",
+        ]);
     }
 
     #[test]
@@ -875,6 +1128,13 @@ mod base_test {
             "fi",
             "--output-path",
             "path",
+            "--point",
+            "1.1",
+            "--point",
+            "1.2",
+            "--sandbox",
+            "--test",
+            "name",
         ]);
     }
 
@@ -888,6 +1148,11 @@ mod base_test {
             "path",
         ]);
     }
+
+    #[test]
+    fn watch() {
+        get_matches(&["watch", "--debounce-ms", "100", "--exercise-path", "path"]);
+    }
 }
 
 #[cfg(test)]
@@ -916,6 +1181,23 @@ mod core_test {
         get_matches_tmc(&["check-exercise-updates"]);
     }
 
+    #[test]
+    fn profile() {
+        Cli::try_parse_from([
+            "tmc-langs-cli",
+            "--profile",
+            "staging",
+            "tmc",
+            "--client-name",
+            "client",
+            "--client-version",
+            "version",
+            "check-exercise-updates",
+        ])
+        .unwrap();
+        get_matches_tmc(&["--profile", "staging", "check-exercise-updates"]);
+    }
+
     #[test]
     fn download_model_solution() {
         get_matches_tmc(&[
@@ -958,6 +1240,21 @@ mod core_test {
         ]);
     }
 
+    #[test]
+    fn export_course_results() {
+        get_matches_tmc(&[
+            "export-course-results",
+            "--course-id",
+            "1234",
+            "--format",
+            "ndjson",
+            "--output-path",
+            "path",
+            "--since",
+            "2024-01-31T12:00:00+02:00",
+        ]);
+    }
+
     #[test]
     fn get_course_data() {
         get_matches_tmc(&["get-course-data", "--course-id", "1234"]);
@@ -1124,6 +1421,11 @@ mod core_test {
         ]);
     }
 
+    #[test]
+    fn sync_submissions() {
+        get_matches_tmc(&["sync-submissions"]);
+    }
+
     #[test]
     fn update_exercises() {
         get_matches_tmc(&["update-exercises"]);
@@ -1180,6 +1482,7 @@ mod settings_test {
     #[test]
     fn move_projects_dir() {
         get_matches_settings(&["move-projects-dir", "path"]);
+        get_matches_settings(&["--profile", "staging", "move-projects-dir", "path"]);
     }
 
     #[test]
@@ -1213,6 +1516,7 @@ mod test {
             crate::output::CliOutput,
             crate::output::DataKind,
             crate::output::Kind,
+            crate::output::AuthorizationHint,
             crate::output::OutputData,
             crate::output::OutputResult,
             crate::output::Status,
@@ -1221,25 +1525,39 @@ mod test {
             tmc_langs::notification_reporter::NotificationKind,
             tmc_langs::progress_reporter::StatusUpdate<()>,
             tmc_langs::tmc::ClientUpdateData,
+            // checkCourse
+            tmc_langs::ExerciseCheck,
+            tmc_langs::CheckResult,
+            tmc_langs::CheckStatus,
             // checkstyle
             tmc_langs::StyleValidationResult,
             tmc_langs::StyleValidationError,
             tmc_langs::StyleValidationStrategy,
             // getExercisePackagingConfiguration
             tmc_langs::ExercisePackagingConfiguration,
+            // gradeSubmission
+            tmc_langs::GradedSubmission,
             // listLocalCourseExercises
             tmc_langs::LocalExercise,
             tmc_langs::LocalTmcExercise,
             tmc_langs::LocalMoocExercise,
+            // lintExercise
+            tmc_langs::LintIssue,
+            tmc_langs::AnnotationIssueKind,
             // prepareSubmission
             tmc_langs::Compression,
+            // previewExercise
+            tmc_langs::FilePreview,
             // refreshCourse
             tmc_langs::RefreshData,
             tmc_langs::RefreshExercise,
             tmc_langs::TmcProjectYml,
             tmc_langs::PythonVer,
+            tmc_langs::SandboxLimits,
+            tmc_langs::CommentSyntax,
             // runTests
             tmc_langs::RunResult,
+            tmc_langs::ExecutionStats,
             tmc_langs::RunStatus,
             tmc_langs::TestResult,
             // scanExercise
@@ -1286,6 +1604,11 @@ mod test {
             tmc_langs::tmc::response::TmcStyleValidationResult,
             tmc_langs::tmc::response::TmcStyleValidationError,
             tmc_langs::tmc::response::TmcStyleValidationStrategy,
+            tmc_langs::QueuedSubmission,
+            tmc_langs::SubmissionTarget,
+            // syncSubmissions
+            tmc_langs::SyncedSubmission<()>,
+            tmc_langs::SyncResult<()>,
             // waitForSubmission
             tmc_langs::tmc::response::SubmissionFinished,
             tmc_langs::tmc::response::TestCase,
@@ -1295,6 +1618,8 @@ mod test {
             tmc_langs::ConfigValue,
             // settings list
             tmc_langs::TmcConfig,
+            tmc_langs::Profile,
+            tmc_langs::credential_store::CredentialBackend,
             // mooc
             tmc_langs::mooc::CourseInstance,
             tmc_langs::mooc::TmcExerciseSlide,
@@ -5,8 +5,8 @@ use thiserror::Error;
 use tmc_langs::TmcExerciseDownload;
 
 #[derive(Debug, Error)]
-#[error("Invalid token. Deleted credentials file")]
-pub struct InvalidTokenError {
+#[error("The token was rejected and could not be refreshed. Deleted credentials")]
+pub struct AuthorizationError {
     pub source: anyhow::Error,
 }
 
@@ -7,8 +7,8 @@ pub mod error;
 pub mod output;
 
 use self::{
-    error::{DownloadsFailedError, InvalidTokenError, SandboxTestError},
-    output::{CliOutput, DataKind, Kind, OutputData, OutputResult, Status},
+    error::{AuthorizationError, DownloadsFailedError, SandboxTestError},
+    output::{AuthorizationHint, CliOutput, DataKind, Kind, OutputData, OutputResult, Status},
 };
 use crate::app::{Cli, Locale};
 use anyhow::{Context, Result};
@@ -21,16 +21,20 @@ use std::{
     collections::HashMap,
     env,
     io::{self, BufReader, Cursor, Read},
+    ops::ControlFlow,
     path::{Path, PathBuf},
+    time::Duration,
 };
 use tmc_langs::{
     CommandError, Compression, Credentials, DownloadOrUpdateTmcCourseExercisesResult,
-    DownloadResult, Language, StyleValidationResult, TmcConfig, TmcProjectYml, UpdatedExercise,
+    DownloadResult, LangsError, Language, RefreshSource, RunResult, StyleValidationResult,
+    TestFilter, TmcConfig, TmcProjectYml, UpdatedExercise,
     file_util::{self, Lock, LockOptions},
     mooc::{MoocClient, MoocClientError},
     tmc::{TestMyCodeClient, TestMyCodeClientError, request::FeedbackAnswer},
 };
 use tmc_langs_util::deserialize;
+use url::Url;
 
 pub enum ParsingResult {
     Ok(Cli),
@@ -97,13 +101,15 @@ pub fn run(cli: Cli) -> Result<CliOutput, CliError> {
 /// Goes through the error chain and checks for special error types that should be indicated by the Kind.
 fn solve_error_kind(e: &anyhow::Error) -> Kind {
     for cause in e.chain() {
-        // check for invalid token
-        if cause.downcast_ref::<InvalidTokenError>().is_some() {
-            return Kind::InvalidToken;
+        // check for a token that was rejected and could not be refreshed
+        if cause.downcast_ref::<AuthorizationError>().is_some() {
+            return Kind::AuthorizationError {
+                hint: AuthorizationHint::Reauthenticate,
+            };
         }
 
         // check for tmc client errors
-        match cause.downcast_ref::<TestMyCodeClientError>() {
+        match downcast_client_error::<TestMyCodeClientError>(cause) {
             Some(TestMyCodeClientError::HttpError {
                 url: _,
                 status,
@@ -123,14 +129,19 @@ fn solve_error_kind(e: &anyhow::Error) -> Kind {
             Some(TestMyCodeClientError::NotAuthenticated) => {
                 return Kind::NotLoggedIn;
             }
+            Some(TestMyCodeClientError::TokenExpired | TestMyCodeClientError::NoRefreshToken) => {
+                return Kind::AuthorizationError {
+                    hint: AuthorizationHint::Reauthenticate,
+                };
+            }
             Some(TestMyCodeClientError::ConnectionError { .. }) => {
                 return Kind::ConnectionError;
             }
             _ => {}
         }
 
-        // check for tmc client errors
-        match cause.downcast_ref::<MoocClientError>() {
+        // check for mooc client errors
+        match downcast_client_error::<MoocClientError>(cause) {
             Some(MoocClientError::HttpError {
                 url: _,
                 status,
@@ -150,6 +161,11 @@ fn solve_error_kind(e: &anyhow::Error) -> Kind {
             Some(MoocClientError::NotAuthenticated) => {
                 return Kind::NotLoggedIn;
             }
+            Some(MoocClientError::TokenExpired) => {
+                return Kind::AuthorizationError {
+                    hint: AuthorizationHint::Reauthenticate,
+                };
+            }
             Some(MoocClientError::ConnectionError { .. }) => {
                 return Kind::ConnectionError;
             }
@@ -174,6 +190,23 @@ fn solve_error_kind(e: &anyhow::Error) -> Kind {
     Kind::Generic
 }
 
+/// Downcasts a cause to a client error. The clients return their errors boxed, so the box is checked for as well.
+/// LangsError wraps the client errors transparently, so they don't show up in the chain on their own and are checked for separately.
+fn downcast_client_error<'a, E: std::error::Error + 'static>(
+    cause: &'a (dyn std::error::Error + 'static),
+) -> Option<&'a E> {
+    let wrapped: Option<&(dyn std::error::Error + 'static)> =
+        match cause.downcast_ref::<LangsError>() {
+            Some(LangsError::TestMyCodeClient(error)) => Some(&**error),
+            Some(LangsError::MoocClient(error)) => Some(&**error),
+            _ => None,
+        };
+    cause
+        .downcast_ref::<E>()
+        .or_else(|| cause.downcast_ref::<Box<E>>().map(AsRef::as_ref))
+        .or_else(|| wrapped.and_then(|error| error.downcast_ref::<E>()))
+}
+
 /// Goes through the error chain and returns the specialized error message, if any.
 fn error_message_special_casing(e: &anyhow::Error) -> String {
     for cause in e.chain() {
@@ -200,6 +233,30 @@ fn check_sandbox_err(e: &anyhow::Error) -> Option<PathBuf> {
 
 fn run_app(cli: Cli) -> Result<CliOutput> {
     let output = match cli.command {
+        Command::CheckCourse {
+            course_path,
+            jobs,
+            output_path,
+        } => {
+            let mut lock = Lock::dir(&course_path, LockOptions::Read)?;
+            let _guard = lock.lock()?;
+
+            let checks = tmc_langs::check_course(&course_path, jobs)
+                .with_context(|| format!("Failed to check course at {}", course_path.display()))?;
+            if let Some(output_path) = output_path {
+                write_result_to_file_as_json(&checks, &output_path, cli.pretty, None)?;
+            }
+            let failed = checks.iter().filter(|c| c.has_errors()).count();
+            CliOutput::finished_with_data(
+                format!(
+                    "checked {} exercises in {}, {failed} had errors",
+                    checks.len(),
+                    course_path.display()
+                ),
+                DataKind::CourseCheck(checks),
+            )
+        }
+
         Command::Checkstyle {
             exercise_path,
             locale: Locale(locale),
@@ -248,9 +305,9 @@ fn run_app(cli: Cli) -> Result<CliOutput> {
             )
         }
 
-        Command::Tmc(tmc) => run_tmc(tmc)?,
+        Command::Tmc(tmc) => run_tmc(tmc, cli.profile.as_deref())?,
 
-        Command::Mooc(mooc) => run_mooc(mooc)?,
+        Command::Mooc(mooc) => run_mooc(mooc, cli.profile.as_deref())?,
 
         Command::ExtractProject {
             archive_path,
@@ -333,12 +390,75 @@ fn run_app(cli: Cli) -> Result<CliOutput> {
             )
         }
 
+        Command::GradeSubmission {
+            clone_path,
+            output_path,
+            submission_path,
+            submission_compression,
+            extract_submission_naively,
+            tmc_param,
+        } => {
+            let mut clone_lock = Lock::dir(&clone_path, file_util::LockOptions::Read)?;
+            let _clone_guard = clone_lock.lock()?;
+
+            let tmc_params = parse_tmc_params(&tmc_param)?;
+            let graded = tmc_langs::grade_submission(
+                tmc_langs::PrepareSubmission {
+                    archive: &submission_path,
+                    compression: submission_compression,
+                    extract_naively: extract_submission_naively,
+                },
+                tmc_params,
+                &clone_path,
+            )
+            .with_context(|| format!("Failed to grade submission {}", submission_path.display()))?;
+            if let Some(output_path) = output_path {
+                write_result_to_file_as_json(&graded, &output_path, cli.pretty, None)?;
+            }
+            CliOutput::finished_with_data(
+                format!("graded submission {}", submission_path.display()),
+                DataKind::GradedSubmission(Box::new(graded)),
+            )
+        }
+
+        Command::LintExercise {
+            exercise_path,
+            output_path,
+            variant,
+        } => {
+            let mut lock = Lock::dir(&exercise_path, file_util::LockOptions::Read)?;
+            let _guard = lock.lock()?;
+
+            let issues = tmc_langs::lint_exercise(&exercise_path, variant.as_deref())
+                .with_context(|| {
+                    format!("Failed to lint exercise at {}", exercise_path.display())
+                })?;
+
+            if let Some(output_path) = output_path {
+                write_result_to_file_as_json(&issues, &output_path, cli.pretty, None)?;
+            }
+
+            let errors = issues.iter().filter(|i| i.is_error()).count();
+            CliOutput::finished_with_data(
+                format!(
+                    "found {} errors and {} warnings in {}",
+                    errors,
+                    issues.len() - errors,
+                    exercise_path.display(),
+                ),
+                DataKind::ExerciseLint(issues),
+            )
+        }
+
         Command::ListLocalTmcCourseExercises {
             client_name,
             course_slug,
         } => {
-            let local_exercises =
-                tmc_langs::list_local_tmc_course_exercises(&client_name, &course_slug)?;
+            let local_exercises = tmc_langs::list_local_tmc_course_exercises(
+                &client_name,
+                cli.profile.as_deref(),
+                &course_slug,
+            )?;
 
             CliOutput::finished_with_data(
                 format!("listed local exercises for {course_slug}"),
@@ -346,19 +466,30 @@ fn run_app(cli: Cli) -> Result<CliOutput> {
             )
         }
 
+        Command::ListQueuedSubmissions { client_name } => {
+            let projects_dir = tmc_langs::get_projects_dir(&client_name, cli.profile.as_deref())?;
+            let queued = tmc_langs::list_queued_submissions(&projects_dir)?;
+            CliOutput::finished_with_data(
+                "listed queued submissions",
+                DataKind::QueuedSubmissions(queued),
+            )
+        }
+
         Command::PrepareSolution {
             exercise_path,
             output_path,
+            variant,
         } => {
             let mut lock = Lock::dir(&exercise_path, LockOptions::Read)?;
             let _guard = lock.lock()?;
 
-            tmc_langs::prepare_solution(&exercise_path, &output_path).with_context(|| {
-                format!(
-                    "Failed to prepare solutions for exercise at {}",
-                    exercise_path.display(),
-                )
-            })?;
+            tmc_langs::prepare_solution(&exercise_path, &output_path, variant.as_deref())
+                .with_context(|| {
+                    format!(
+                        "Failed to prepare solutions for exercise at {}",
+                        exercise_path.display(),
+                    )
+                })?;
             CliOutput::finished(format!(
                 "prepared solutions for {} at {}",
                 exercise_path.display(),
@@ -369,16 +500,18 @@ fn run_app(cli: Cli) -> Result<CliOutput> {
         Command::PrepareStub {
             exercise_path,
             output_path,
+            variant,
         } => {
             let mut lock = Lock::dir(&exercise_path, LockOptions::Read)?;
             let _guard = lock.lock()?;
 
-            tmc_langs::prepare_stub(&exercise_path, &output_path).with_context(|| {
-                format!(
-                    "Failed to prepare stubs for exercise at {}",
-                    exercise_path.display(),
-                )
-            })?;
+            tmc_langs::prepare_stub(&exercise_path, &output_path, variant.as_deref())
+                .with_context(|| {
+                    format!(
+                        "Failed to prepare stubs for exercise at {}",
+                        exercise_path.display(),
+                    )
+                })?;
             CliOutput::finished(format!(
                 "prepared stubs for {} at {}",
                 exercise_path.display(),
@@ -401,37 +534,7 @@ fn run_app(cli: Cli) -> Result<CliOutput> {
             let mut clone_lock = Lock::dir(&clone_path, file_util::LockOptions::Read)?;
             let _clone_guard = clone_lock.lock()?;
 
-            // will contain for each key all the values with that key in a list
-            let mut tmc_params_grouped = HashMap::new();
-            for value in &tmc_param {
-                let params: Vec<_> = value.split('=').collect();
-                if params.len() != 2 {
-                    app::Cli::command()
-                        .error(
-                            ErrorKind::ValueValidation,
-                            "tmc-param values should contain a single '=' as a delimiter.",
-                        )
-                        .exit();
-                }
-                let key = params[0];
-                let value = params[1];
-                let entry = tmc_params_grouped.entry(key).or_insert_with(Vec::new);
-                entry.push(value);
-            }
-            let mut tmc_params = tmc_langs::TmcParams::new();
-            for (key, values) in tmc_params_grouped {
-                if values.len() == 1 {
-                    // 1-length lists are inserted as a string
-                    tmc_params
-                        .insert_string(key, values[0])
-                        .context("invalid tmc-param key-value pair")?;
-                } else {
-                    tmc_params
-                        .insert_array(key, values)
-                        .context("invalid tmc-param key-value pair")?;
-                }
-            }
-
+            let tmc_params = parse_tmc_params(&tmc_param)?;
             let sandbox = tmc_langs::prepare_submission(
                 tmc_langs::PrepareSubmission {
                     archive: &submission_path,
@@ -455,19 +558,69 @@ fn run_app(cli: Cli) -> Result<CliOutput> {
             )
         }
 
+        Command::PreviewExercise {
+            exercise_path,
+            output_path,
+            html,
+            variant,
+        } => {
+            let mut lock = Lock::dir(&exercise_path, file_util::LockOptions::Read)?;
+            let _guard = lock.lock()?;
+
+            let previews = tmc_langs::preview_exercise(&exercise_path, variant.as_deref())
+                .with_context(|| {
+                    format!("Failed to preview exercise at {}", exercise_path.display())
+                })?;
+
+            if let Some(output_path) = output_path {
+                if html {
+                    file_util::write_to_file(tmc_langs::preview_to_html(&previews), &output_path)
+                        .with_context(|| {
+                        format!("Failed to write preview to {}", output_path.display())
+                    })?;
+                } else {
+                    write_result_to_file_as_json(&previews, &output_path, cli.pretty, None)?;
+                }
+            }
+
+            CliOutput::finished_with_data(
+                format!(
+                    "previewed {} changed files in {}",
+                    previews.len(),
+                    exercise_path.display()
+                ),
+                DataKind::ExercisePreview(previews),
+            )
+        }
+
         Command::RefreshCourse {
             cache_path,
             cache_root,
             course_name,
             git_branch,
+            git_ref,
+            jobs,
+            source_bundle,
+            source_path,
             source_url,
         } => {
+            let git_ref = git_ref.or(git_branch);
+            let source = match (source_path, source_bundle, source_url) {
+                (Some(path), _, _) => RefreshSource::Directory(path),
+                (None, Some(path), _) => RefreshSource::Bundle { path, git_ref },
+                (None, None, Some(url)) => RefreshSource::Git {
+                    url,
+                    git_ref: git_ref
+                        .context("A git branch or ref is required with a source URL")?,
+                },
+                (None, None, None) => anyhow::bail!("No source given for the course"),
+            };
             let refresh_result = tmc_langs::refresh_course(
                 course_name.clone(),
                 cache_path,
-                source_url,
-                git_branch,
+                source,
                 cache_root,
+                jobs,
             )
             .with_context(|| format!("Failed to refresh course {course_name}"))?;
             CliOutput::finished_with_data(
@@ -476,11 +629,26 @@ fn run_app(cli: Cli) -> Result<CliOutput> {
             )
         }
 
+        Command::RemoveQueuedSubmission {
+            client_name,
+            submission_id,
+        } => {
+            let projects_dir = tmc_langs::get_projects_dir(&client_name, cli.profile.as_deref())?;
+            let removed = tmc_langs::remove_queued_submission(&projects_dir, submission_id)?;
+            CliOutput::finished_with_data(
+                format!("removed queued submission {submission_id}"),
+                DataKind::QueuedSubmission(removed),
+            )
+        }
+
         Command::RunTests {
             checkstyle_output_path,
             exercise_path,
             locale,
             output_path,
+            point,
+            sandbox,
+            test,
             wait_for_secret,
         } => {
             let mut lock = Lock::dir(&exercise_path, LockOptions::Read)?;
@@ -494,7 +662,13 @@ fn run_app(cli: Cli) -> Result<CliOutput> {
                 None
             };
 
-            let test_result = tmc_langs::run_tests(&exercise_path).with_context(|| {
+            let filter = TestFilter::new(test, point);
+            let test_result = if sandbox {
+                tmc_langs::run_tests_in_sandbox(&exercise_path, &filter)
+            } else {
+                tmc_langs::run_tests_filtered(&exercise_path, &filter)
+            };
+            let test_result = test_result.with_context(|| {
                 format!(
                     "Failed to run tests for exercise at {}",
                     exercise_path.display()
@@ -529,13 +703,19 @@ fn run_app(cli: Cli) -> Result<CliOutput> {
                 )?;
             }
 
-            CliOutput::finished_with_data(
-                format!("ran tests for {}", exercise_path.display()),
-                DataKind::TestResult(test_result),
-            )
+            let mut message = format!("ran tests for {}", exercise_path.display());
+            if let Some(stats) = &test_result.execution_stats {
+                // let the user know why the run was stopped
+                if stats.timed_out {
+                    message.push_str(", the tests were stopped because they timed out");
+                } else if stats.memory_limit_exceeded {
+                    message.push_str(", the tests were stopped because they ran out of memory");
+                }
+            }
+            CliOutput::finished_with_data(message, DataKind::TestResult(test_result))
         }
 
-        Command::Settings(settings) => run_settings(settings)?,
+        Command::Settings(settings) => run_settings(settings, cli.profile.as_deref())?,
 
         Command::ScanExercise {
             exercise_path,
@@ -569,51 +749,121 @@ fn run_app(cli: Cli) -> Result<CliOutput> {
                 DataKind::ExerciseDesc(scan_result),
             )
         }
+
+        Command::Watch {
+            debounce_ms,
+            exercise_path,
+        } => {
+            tmc_langs::watch_exercise(
+                &exercise_path,
+                Duration::from_millis(debounce_ms),
+                |result| {
+                    let message = format!("running tests for {}", exercise_path.display());
+                    tmc_langs::progress_reporter::start_stage::<RunResult>(1, message, None);
+                    match result {
+                        Ok(run_result) => {
+                            let message = format!("ran tests for {}", exercise_path.display());
+                            tmc_langs::progress_reporter::finish_stage(message, Some(run_result));
+                        }
+                        Err(err) => {
+                            // keep watching, the next change may fix the error
+                            let message = format!(
+                                "Failed to run tests for exercise at {}: {:#}",
+                                exercise_path.display(),
+                                anyhow::Error::from(err)
+                            );
+                            tmc_langs::progress_reporter::finish_stage::<RunResult>(message, None);
+                        }
+                    }
+                    ControlFlow::Continue(())
+                },
+            )
+            .with_context(|| format!("Failed to watch exercise at {}", exercise_path.display()))?;
+            CliOutput::finished(format!("stopped watching {}", exercise_path.display()))
+        }
     };
     Ok(output)
 }
 
-fn run_tmc(tmc: TestMyCode) -> Result<CliOutput> {
-    let client_name = &tmc.client_name;
-    let client_version = &tmc.client_version;
-    let root_url = env::var("TMC_LANGS_TMC_ROOT_URL")
-        .unwrap_or_else(|_| "https://tmc.mooc.fi/".to_string())
-        .parse()
-        .context("Invalid TMC root url")?;
-    let (mut client, mut credentials) =
-        tmc_langs::init_testmycode_client_with_credentials(root_url, client_name, client_version)?;
-
-    match run_tmc_inner(tmc, &mut client, &mut credentials) {
-        Err(error) => {
-            for cause in error.chain() {
-                // check if the token was rejected and delete it if so
-                if let Some(TestMyCodeClientError::HttpError { status, .. }) =
-                    cause.downcast_ref::<TestMyCodeClientError>()
-                {
-                    if status.as_u16() == 401 {
-                        log::error!("Received HTTP 401 error, deleting credentials");
-                        if let Some(credentials) = credentials {
-                            credentials.remove()?;
-                        }
-                        return Err(InvalidTokenError { source: error }.into());
+fn run_tmc(tmc: TestMyCode, profile: Option<&str>) -> Result<CliOutput> {
+    let client_name = tmc.client_name.clone();
+    let (mut client, mut credentials) = tmc_langs::init_testmycode_client_with_credentials(
+        tmc_root_url()?,
+        &client_name,
+        &tmc.client_version,
+        profile,
+    )?;
+
+    let error = match run_tmc_inner(tmc.clone(), profile, &mut client, &mut credentials) {
+        Err(error) if is_unauthorized(&error) => error,
+        output => return output,
+    };
+    // the token may have been revoked or expired early, so it is refreshed once before giving up on it
+    if credentials.is_some() {
+        match tmc_langs::refresh_credentials(&mut client, &client_name, profile) {
+            Ok(refreshed) => {
+                log::info!("Received HTTP 401 error, retrying with a refreshed token");
+                credentials = Some(refreshed);
+                match run_tmc_inner(tmc, profile, &mut client, &mut credentials) {
+                    Err(error) if is_unauthorized(&error) => {
+                        return reauthenticate(error, credentials);
                     }
+                    output => return output,
                 }
             }
+            Err(err) => log::warn!("Failed to refresh token: {err}"),
+        }
+    }
+    reauthenticate(error, credentials)
+}
+
+fn tmc_root_url() -> Result<Url> {
+    env::var("TMC_LANGS_TMC_ROOT_URL")
+        .unwrap_or_else(|_| "https://tmc.mooc.fi/".to_string())
+        .parse()
+        .context("Invalid TMC root url")
+}
+
+/// Checks whether either server rejected the token with HTTP 401.
+fn is_unauthorized(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        let status = match (
+            downcast_client_error::<TestMyCodeClientError>(cause),
+            downcast_client_error::<MoocClientError>(cause),
+        ) {
+            (Some(TestMyCodeClientError::HttpError { status, .. }), _) => status.as_u16(),
+            (_, Some(MoocClientError::HttpError { status, .. })) => status.as_u16(),
+            _ => return false,
+        };
+        status == 401
+    })
+}
+
+// deletes the credentials that were rejected and could not be refreshed, so the user needs to log in again
+fn reauthenticate(error: anyhow::Error, credentials: Option<Credentials>) -> Result<CliOutput> {
+    match credentials {
+        Some(credentials) => {
+            log::error!("Received HTTP 401 error, deleting credentials");
+            credentials.remove()?;
+            Err(AuthorizationError { source: error }.into())
+        }
+        None => {
+            log::warn!("401 without credentials");
             Err(error)
         }
-        output => output,
     }
 }
 
 fn run_tmc_inner(
     tmc: TestMyCode,
+    profile: Option<&str>,
     client: &mut TestMyCodeClient,
     credentials: &mut Option<Credentials>,
 ) -> Result<CliOutput> {
     let client_name = &tmc.client_name;
     let output = match tmc.command {
         TestMyCodeCommand::CheckExerciseUpdates => {
-            let projects_dir = tmc_langs::get_projects_dir(client_name)?;
+            let projects_dir = tmc_langs::get_projects_dir(client_name, profile)?;
             let updated_exercises = tmc_langs::check_exercise_updates(client, &projects_dir)
                 .context("Failed to check exercise updates")?
                 .into_iter()
@@ -664,7 +914,7 @@ fn run_tmc_inner(
             download_template,
             exercise_id: exercise_ids,
         } => {
-            let projects_dir = tmc_langs::get_projects_dir(client_name)?;
+            let projects_dir = tmc_langs::get_projects_dir(client_name, profile)?;
             let data = match tmc_langs::download_or_update_course_exercises(
                 client,
                 &projects_dir,
@@ -695,6 +945,22 @@ fn run_tmc_inner(
             )
         }
 
+        TestMyCodeCommand::ExportCourseResults {
+            course_id,
+            format,
+            output_path,
+            since,
+        } => {
+            let results = tmc_langs::export_course_results(client, course_id, since)
+                .context("Failed to export course results")?;
+            tmc_langs::write_course_results(&results, format, &output_path)?;
+            CliOutput::finished(format!(
+                "exported {} results to {}",
+                results.len(),
+                output_path.display()
+            ))
+        }
+
         TestMyCodeCommand::GetCourseData { course_id } => {
             let data = tmc_langs::get_course_data(client, course_id)
                 .context("Failed to get course data")?;
@@ -853,7 +1119,7 @@ fn run_tmc_inner(
             };
 
             // create token file
-            Credentials::save(client_name, token)?;
+            Credentials::save(client_name, profile, token)?;
 
             CliOutput::OutputData(Box::new(OutputData {
                 status: Status::Finished,
@@ -1003,14 +1269,29 @@ fn run_tmc_inner(
 
             let locale = locale.map(|l| l.0);
             let tmc_project_yml = TmcProjectYml::load_or_default(&submission_path)?;
-            let new_submission = client
-                .submit(
-                    exercise_id,
-                    &submission_path,
-                    tmc_project_yml.get_submission_size_limit_mb(),
-                    locale,
-                )
-                .context("Failed to submit")?;
+            let new_submission = match client.submit(
+                exercise_id,
+                &submission_path,
+                tmc_project_yml.get_submission_size_limit_mb(),
+                locale,
+            ) {
+                Ok(new_submission) => new_submission,
+                Err(error) if error.is_connection_error() => {
+                    log::warn!("Failed to reach the server, queuing the submission: {error}");
+                    let projects_dir = tmc_langs::get_projects_dir(client_name, profile)?;
+                    let queued = tmc_langs::queue_tmc_submission(
+                        &projects_dir,
+                        exercise_id,
+                        &submission_path,
+                        locale,
+                    )?;
+                    return Ok(CliOutput::finished_with_data(
+                        "queued submission to be sent with sync-submissions",
+                        DataKind::QueuedSubmission(queued),
+                    ));
+                }
+                Err(error) => return Err(anyhow::Error::from(error).context("Failed to submit")),
+            };
 
             if dont_block {
                 CliOutput::finished_with_data(
@@ -1030,8 +1311,17 @@ fn run_tmc_inner(
             }
         }
 
+        TestMyCodeCommand::SyncSubmissions => {
+            let projects_dir = tmc_langs::get_projects_dir(client_name, profile)?;
+            let synced = tmc_langs::sync_tmc_submissions(client, &projects_dir)?;
+            CliOutput::finished_with_data(
+                "synced queued submissions",
+                DataKind::SyncedSubmissions(synced),
+            )
+        }
+
         TestMyCodeCommand::UpdateExercises => {
-            let projects_dir = tmc_langs::get_projects_dir(client_name)?;
+            let projects_dir = tmc_langs::get_projects_dir(client_name, profile)?;
             let data = tmc_langs::update_tmc_exercises(client, &projects_dir)?;
             CliOutput::finished_with_data(
                 "downloaded or updated exercises",
@@ -1052,39 +1342,55 @@ fn run_tmc_inner(
     Ok(output)
 }
 
-fn run_mooc(mooc: Mooc) -> Result<CliOutput> {
+fn run_mooc(mooc: Mooc, profile: Option<&str>) -> Result<CliOutput> {
     let root_url = env::var("TMC_LANGS_MOOC_ROOT_URL")
         .unwrap_or_else(|_| "https://courses.mooc.fi/".to_string())
         .parse()
         .context("Invalid TMC root url")?;
-
-    let (mut client, credentials) =
-        tmc_langs::init_mooc_client_with_credentials(root_url, &mooc.client_name)?;
-    match run_mooc_inner(mooc, &mut client) {
-        Err(error) => {
-            for cause in error.chain() {
-                // check if the token was rejected and delete it if so
-                if let Some(TestMyCodeClientError::HttpError { status, .. }) =
-                    cause.downcast_ref::<TestMyCodeClientError>()
-                {
-                    if status.as_u16() == 401 {
-                        log::error!("Received HTTP 401 error, deleting credentials");
-                        if let Some(credentials) = credentials {
-                            credentials.remove()?;
-                        }
-                        return Err(InvalidTokenError { source: error }.into());
-                    } else {
-                        log::warn!("401 without credentials");
+    let client_name = mooc.client_name.clone();
+    let client_version = mooc
+        .client_version
+        .clone()
+        .unwrap_or_else(|| env!("CARGO_PKG_VERSION").to_string());
+
+    let (mut client, mut credentials) = tmc_langs::init_mooc_client_with_credentials(
+        root_url,
+        tmc_root_url()?,
+        &client_name,
+        &client_version,
+        profile,
+    )?;
+    let error = match run_mooc_inner(mooc.clone(), profile, &mut client) {
+        Err(error) if is_unauthorized(&error) => error,
+        output => return output,
+    };
+    // the token may have been revoked or expired early, so it is refreshed once before giving up on it
+    if let Some(stored) = &credentials {
+        match tmc_langs::refresh_mooc_credentials(
+            tmc_root_url()?,
+            &client_name,
+            &client_version,
+            profile,
+            stored,
+        ) {
+            Ok(refreshed) => {
+                log::info!("Received HTTP 401 error, retrying with a refreshed token");
+                client.set_token(refreshed.token(), refreshed.expires_at());
+                credentials = Some(refreshed);
+                match run_mooc_inner(mooc, profile, &mut client) {
+                    Err(error) if is_unauthorized(&error) => {
+                        return reauthenticate(error, credentials);
                     }
+                    output => return output,
                 }
             }
-            Err(error)
+            Err(err) => log::warn!("Failed to refresh token: {err}"),
         }
-        output => output,
     }
+    reauthenticate(error, credentials)
 }
 
-fn run_mooc_inner(mooc: Mooc, client: &mut MoocClient) -> Result<CliOutput> {
+fn run_mooc_inner(mooc: Mooc, profile: Option<&str>, client: &mut MoocClient) -> Result<CliOutput> {
     let client_name = &mooc.client_name;
 
     let output = match mooc.command {
@@ -1126,7 +1432,7 @@ fn run_mooc_inner(mooc: Mooc, client: &mut MoocClient) -> Result<CliOutput> {
             CliOutput::finished("downloaded exercise")
         }
         MoocCommand::UpdateExercises => {
-            let projects_dir = tmc_langs::get_projects_dir(client_name)?;
+            let projects_dir = tmc_langs::get_projects_dir(client_name, profile)?;
             let res = tmc_langs::update_mooc_exercises(client, &projects_dir)?;
             CliOutput::finished_with_data("updated exercises", DataKind::MoocExerciseDownload(res))
         }
@@ -1148,17 +1454,43 @@ fn run_mooc_inner(mooc: Mooc, client: &mut MoocClient) -> Result<CliOutput> {
                 false,
             )?;
 
-            let result = client.submit(exercise_id, slide_id, task_id, temp.path())?;
+            let result = match client.submit(exercise_id, slide_id, task_id, temp.path()) {
+                Ok(result) => result,
+                Err(error) if error.is_connection_error() => {
+                    log::warn!("Failed to reach the server, queuing the submission: {error}");
+                    let projects_dir = tmc_langs::get_projects_dir(client_name, profile)?;
+                    let queued = tmc_langs::queue_mooc_submission(
+                        &projects_dir,
+                        exercise_id,
+                        slide_id,
+                        task_id,
+                        &submission_path,
+                    )?;
+                    return Ok(CliOutput::finished_with_data(
+                        "queued submission to be sent with sync-submissions",
+                        DataKind::QueuedSubmission(queued),
+                    ));
+                }
+                Err(error) => return Err(error.into()),
+            };
             CliOutput::finished_with_data(
                 "submitted exercise",
                 DataKind::MoocSubmissionFinished(result),
             )
         }
+        MoocCommand::SyncSubmissions => {
+            let projects_dir = tmc_langs::get_projects_dir(client_name, profile)?;
+            let synced = tmc_langs::sync_mooc_submissions(client, &projects_dir)?;
+            CliOutput::finished_with_data(
+                "synced queued submissions",
+                DataKind::MoocSyncedSubmissions(synced),
+            )
+        }
     };
     Ok(output)
 }
 
-fn run_settings(settings: Settings) -> Result<CliOutput> {
+fn run_settings(settings: Settings, profile: Option<&str>) -> Result<CliOutput> {
     let client_name = &settings.client_name;
     let output = match settings.command {
         SettingsCommand::Get { setting } => {
@@ -1181,6 +1513,7 @@ fn run_settings(settings: Settings) -> Result<CliOutput> {
             let tmc_config = TmcConfig::load(client_name)?;
             tmc_langs::migrate_exercise(
                 tmc_config,
+                profile,
                 &course_slug,
                 &exercise_slug,
                 exercise_id,
@@ -1192,7 +1525,7 @@ fn run_settings(settings: Settings) -> Result<CliOutput> {
 
         SettingsCommand::MoveProjectsDir { dir } => {
             let tmc_config = TmcConfig::load(client_name)?;
-            tmc_langs::move_projects_dir(tmc_config, dir)?;
+            tmc_langs::move_projects_dir(tmc_config, profile, dir)?;
             CliOutput::finished("moved project directory")
         }
 
@@ -1220,6 +1553,41 @@ fn run_settings(settings: Settings) -> Result<CliOutput> {
     Ok(output)
 }
 
+// parses the key=value pairs given with --tmc-param
+fn parse_tmc_params(tmc_param: &[String]) -> Result<tmc_langs::TmcParams> {
+    // will contain for each key all the values with that key in a list
+    let mut tmc_params_grouped = HashMap::new();
+    for value in tmc_param {
+        let params: Vec<_> = value.split('=').collect();
+        if params.len() != 2 {
+            app::Cli::command()
+                .error(
+                    ErrorKind::ValueValidation,
+                    "tmc-param values should contain a single '=' as a delimiter.",
+                )
+                .exit();
+        }
+        let key = params[0];
+        let value = params[1];
+        let entry = tmc_params_grouped.entry(key).or_insert_with(Vec::new);
+        entry.push(value);
+    }
+    let mut tmc_params = tmc_langs::TmcParams::new();
+    for (key, values) in tmc_params_grouped {
+        if values.len() == 1 {
+            // 1-length lists are inserted as a string
+            tmc_params
+                .insert_string(key, values[0])
+                .context("invalid tmc-param key-value pair")?;
+        } else {
+            tmc_params
+                .insert_array(key, values)
+                .context("invalid tmc-param key-value pair")?;
+        }
+    }
+    Ok(tmc_params)
+}
+
 fn write_result_to_file_as_json<T: Serialize>(
     result: &T,
     output_path: &Path,
@@ -4,7 +4,7 @@ use anyhow::{Context, Result};
 use clap::Parser;
 use log::LevelFilter;
 use std::{any::Any, fs::File, io::Write, path::PathBuf, process::ExitCode};
-use tmc_langs::{notification_reporter, progress_reporter, tmc::ClientUpdateData};
+use tmc_langs::{RunResult, notification_reporter, progress_reporter, tmc::ClientUpdateData};
 use tmc_langs_cli::{
     ParsingResult,
     app::Cli,
@@ -96,6 +96,10 @@ fn register_reporters(pretty: bool) {
         let output = CliOutput::StatusUpdate(StatusUpdateData::ClientUpdateData(update));
         let _r = print_output(&output, pretty, None);
     });
+    progress_reporter::subscribe::<RunResult, _>(move |update| {
+        let output = CliOutput::StatusUpdate(StatusUpdateData::RunResult(update));
+        let _r = print_output(&output, pretty, None);
+    });
 }
 
 fn print_panic(err: Box<dyn Any + Send>, pretty: bool) {
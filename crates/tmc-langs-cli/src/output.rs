@@ -4,9 +4,10 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use tmc_langs::{
     CombinedCourseData, ConfigValue, DownloadOrUpdateMoocCourseExercisesResult,
-    DownloadOrUpdateTmcCourseExercisesResult, ExerciseDesc, ExercisePackagingConfiguration,
-    LocalMoocExercise, LocalTmcExercise, RunResult, StyleValidationResult, TmcConfig,
-    TmcExerciseDownload, UpdatedExercise, mooc,
+    DownloadOrUpdateTmcCourseExercisesResult, ExerciseCheck, ExerciseDesc,
+    ExercisePackagingConfiguration, FilePreview, GradedSubmission, LintIssue, LocalMoocExercise,
+    LocalTmcExercise, QueuedSubmission, RunResult, StyleValidationResult, SyncedSubmission,
+    TmcConfig, TmcExerciseDownload, UpdatedExercise, mooc,
     notification_reporter::Notification,
     tmc::{
         ClientUpdateData, Token, UpdateResult,
@@ -72,6 +73,9 @@ pub enum DataKind {
         trace: Vec<String>,
     },
     Validation(Option<StyleValidationResult>),
+    CourseCheck(Vec<ExerciseCheck>),
+    ExerciseLint(Vec<LintIssue>),
+    ExercisePreview(Vec<FilePreview>),
     /// megabytes
     // FreeDiskSpace(u64),
     AvailablePoints(Vec<String>),
@@ -98,16 +102,21 @@ pub enum DataKind {
     Reviews(Vec<Review>),
     Token(#[cfg_attr(feature = "ts-rs", ts(type = "unknown"))] Token),
     NewSubmission(NewSubmission),
+    QueuedSubmission(QueuedSubmission),
+    QueuedSubmissions(Vec<QueuedSubmission>),
+    SyncedSubmissions(Vec<SyncedSubmission<NewSubmission>>),
     SubmissionFeedbackResponse(SubmissionFeedbackResponse),
     SubmissionFinished(SubmissionFinished),
     ConfigValue(ConfigValue),
     TmcConfig(TmcConfig),
     CompressedProjectHash(String),
     SubmissionSandbox(String),
+    GradedSubmission(Box<GradedSubmission>),
     MoocCourseInstances(Vec<mooc::CourseInstance>),
     MoocExerciseSlides(Vec<mooc::TmcExerciseSlide>),
     MoocExerciseSlide(mooc::TmcExerciseSlide),
     MoocSubmissionFinished(mooc::ExerciseTaskSubmissionResult),
+    MoocSyncedSubmissions(Vec<SyncedSubmission<mooc::ExerciseTaskSubmissionResult>>),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -116,6 +125,7 @@ pub enum DataKind {
 #[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
 pub enum StatusUpdateData {
     ClientUpdateData(StatusUpdate<ClientUpdateData>),
+    RunResult(StatusUpdate<RunResult>),
     None(StatusUpdate<()>),
 }
 
@@ -154,8 +164,10 @@ pub enum Kind {
     ConnectionError,
     /// Client out of date
     ObsoleteClient,
-    /// Invalid token
+    /// Invalid token. No longer reported, rejected tokens that cannot be refreshed are reported as authorization errors
     InvalidToken,
+    /// The token has expired or was rejected and could not be refreshed
+    AuthorizationError { hint: AuthorizationHint },
     /// Failed to download some or all exercises
     FailedExerciseDownload {
         completed: Vec<TmcExerciseDownload>,
@@ -164,6 +176,15 @@ pub enum Kind {
     },
 }
 
+/// What the client should do to resolve an authorization error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub enum AuthorizationHint {
+    /// Log in again
+    Reauthenticate,
+}
+
 pub use tmc_langs::ProjectsDirTmcExercise;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -210,6 +231,24 @@ mod test {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn output_data_authorization_error() {
+        let output_data = CliOutput::OutputData(Box::new(OutputData {
+            status: Status::Finished,
+            message: "The authentication token has expired".to_string(),
+            result: OutputResult::Error,
+            data: Some(DataKind::Error {
+                kind: Kind::AuthorizationError {
+                    hint: AuthorizationHint::Reauthenticate,
+                },
+                trace: vec!["Caused by: The authentication token has expired".to_string()],
+            }),
+        }));
+        let actual = serde_json::to_string_pretty(&output_data).unwrap();
+        let expected = read_api_file("output-data-authorization-error.json");
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn output_data_dl() {
         let output_data = CliOutput::OutputData(Box::new(OutputData {
@@ -4,7 +4,7 @@ use tempfile::{NamedTempFile, TempDir, tempdir};
 use tmc_langs::{Compression, RunStatus, file_util};
 use tmc_langs_cli::{
     app::Cli,
-    output::{CliOutput, DataKind, OutputResult},
+    output::{AuthorizationHint, CliOutput, DataKind, Kind, OutputResult},
 };
 use walkdir::WalkDir;
 
@@ -632,3 +632,67 @@ fn prepare_submission_passes_tests() {
         _ => panic!(),
     }
 }
+
+#[test]
+fn unrefreshable_token_is_authorization_error() {
+    use tmc_server_mock::mockito::{Matcher, Server};
+
+    let config_dir = tempdir().unwrap();
+    let mut server = Server::new();
+    // the variables are only read by this test
+    unsafe {
+        std::env::set_var("TMC_LANGS_CONFIG_DIR", config_dir.path());
+        std::env::set_var("TMC_LANGS_TMC_ROOT_URL", format!("{}/", server.url()));
+        std::env::set_var("TMC_LANGS_CREDENTIAL_STORE", "plaintext-file");
+    }
+    let credentials_path = config_dir
+        .path()
+        .join("tmc-client")
+        .join("credentials.json");
+    std::fs::create_dir_all(credentials_path.parent().unwrap()).unwrap();
+    std::fs::write(
+        &credentials_path,
+        r#"{"access_token":"token","token_type":"bearer","refresh_token":"refresh","expires_at":"2100-01-01T00:00:00Z"}"#,
+    )
+    .unwrap();
+
+    let organizations = server
+        .mock("GET", "/api/v8/org.json")
+        .match_query(Matcher::Any)
+        .with_status(401)
+        .with_body(r#"{"errors":["Invalid token"]}"#)
+        .create();
+    let _credentials = tmc_server_mock::get_credentials(&mut server);
+    let refresh = server
+        .mock("POST", "/oauth/token")
+        .with_status(401)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error":"invalid_grant"}"#)
+        .create();
+
+    let cli = Cli::parse_from([
+        "tmc-langs-cli",
+        "tmc",
+        "--client-name",
+        "client",
+        "--client-version",
+        "1",
+        "get-organizations",
+    ]);
+    let error = tmc_langs_cli::run(cli).unwrap_err();
+    organizations.assert();
+    refresh.assert();
+    let CliOutput::OutputData(output) = *error.output else {
+        panic!("expected output data");
+    };
+    assert!(matches!(
+        output.data,
+        Some(DataKind::Error {
+            kind: Kind::AuthorizationError {
+                hint: AuthorizationHint::Reauthenticate
+            },
+            ..
+        })
+    ));
+    assert!(!credentials_path.exists());
+}
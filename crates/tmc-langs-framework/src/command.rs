@@ -1,40 +1,59 @@
 //! Custom wrapper for Command that supports timeouts and contains custom error handling.
 
-use crate::{TmcError, error::CommandError};
+mod sandbox;
+
+use self::sandbox::MemoryScope;
+pub use self::sandbox::{Sandbox, SandboxLimits};
+use crate::{ExecutionStats, TmcError, error::CommandError};
 use std::{
     ffi::OsStr,
     fs::File,
     io::{Read, Write},
     thread::JoinHandle,
-    time::Duration,
+    time::{Duration, Instant},
 };
 pub use subprocess::ExitStatus;
-use subprocess::{Exec, PopenError, Redirection};
+use subprocess::{Exec, Popen, PopenError, Redirection};
+
+// how long a timed out command is given to exit after being terminated before it is killed
+const TERMINATE_GRACE_PERIOD: Duration = Duration::from_secs(1);
 
 /// Wrapper around subprocess::Exec
 #[must_use]
 pub struct TmcCommand {
     exec: Exec,
     stdin: Option<String>,
+    memory_scope: Option<MemoryScope>,
 }
 
 impl TmcCommand {
-    /// Creates a new command
+    /// Creates a new command.
     pub fn new(cmd: impl AsRef<OsStr>) -> Self {
         Self {
             exec: Exec::cmd(cmd).env("LANG", "en_US.UTF-8"),
             stdin: None,
+            memory_scope: None,
         }
     }
 
     /// Creates a new command with piped stdout/stderr.
     pub fn piped(cmd: impl AsRef<OsStr>) -> Self {
+        Self::piped_in(cmd, None)
+    }
+
+    /// Creates a new command with piped stdout/stderr that is executed in the sandbox if one is given.
+    pub fn piped_in(cmd: impl AsRef<OsStr>, sandbox: Option<&Sandbox>) -> Self {
+        let (exec, memory_scope) = match sandbox {
+            Some(sandbox) => sandbox.exec(cmd.as_ref()),
+            None => (Exec::cmd(cmd), None),
+        };
         Self {
-            exec: Exec::cmd(cmd)
+            exec: exec
                 .stdout(Redirection::Pipe)
                 .stderr(Redirection::Pipe)
                 .env("LANG", "en_US.UTF-8"),
             stdin: None,
+            memory_scope,
         }
     }
 
@@ -51,6 +70,7 @@ impl TmcCommand {
         Self {
             exec: self.exec.stdin(Redirection::Pipe),
             stdin: Some(data),
+            ..self
         }
     }
 
@@ -59,45 +79,67 @@ impl TmcCommand {
         let cmd = self.exec.to_cmdline_lossy();
         log::info!("executing {cmd}");
 
-        let Self { exec, stdin } = self;
+        let Self {
+            exec,
+            stdin,
+            memory_scope,
+        } = self;
 
         // starts executing the command
+        let start = Instant::now();
         let mut popen = exec.popen().map_err(|e| popen_to_tmc_err(cmd.clone(), e))?;
+        // on Unix the process is waited for with wait4 to get its resource usage, so subprocess must not wait for it
+        #[cfg(unix)]
+        popen.detach();
         let stdin_handle = spawn_writer(popen.stdin.take(), stdin);
         let stdout_handle = spawn_reader(popen.stdout.take());
         let stderr_handle = spawn_reader(popen.stderr.take());
 
-        let exit_status = if let Some(timeout) = timeout {
+        let exited = if let Some(timeout) = timeout {
             // timeout set
-            let exit_status = popen
-                .wait_timeout(timeout)
-                .map_err(|e| popen_to_tmc_err(cmd.clone(), e))?;
+            let exited =
+                wait_timeout(&mut popen, timeout).map_err(|e| popen_to_tmc_err(cmd.clone(), e))?;
 
-            match exit_status {
-                Some(exit_status) => exit_status,
+            match exited {
+                Some(exited) => exited,
                 None => {
                     // None means that we timed out
                     popen
                         .terminate()
                         .map_err(|e| CommandError::Terminate(cmd.clone(), e))?;
+                    // waits for the process so that its resource usage is included in the stats
+                    let exited = match wait_timeout(&mut popen, TERMINATE_GRACE_PERIOD)
+                        .map_err(|e| popen_to_tmc_err(cmd.clone(), e))?
+                    {
+                        Some(exited) => exited,
+                        None => {
+                            popen
+                                .kill()
+                                .map_err(|e| CommandError::Terminate(cmd.clone(), e))?;
+                            wait(&mut popen).map_err(|e| popen_to_tmc_err(cmd.clone(), e))?
+                        }
+                    };
                     let stdout = stdout_handle
                         .join()
                         .expect("the thread should not be able to panic");
                     let stderr = stderr_handle
                         .join()
                         .expect("the thread should not be able to panic");
+                    let stats = execution_stats(start, &exited, true, memory_scope.as_ref());
                     return Err(TmcError::Command(CommandError::TimeOut {
                         command: cmd,
                         timeout,
                         stdout: String::from_utf8_lossy(&stdout).into_owned(),
                         stderr: String::from_utf8_lossy(&stderr).into_owned(),
+                        stats,
                     }));
                 }
             }
         } else {
             // no timeout, block until done
-            popen.wait().map_err(|e| popen_to_tmc_err(cmd.clone(), e))?
+            wait(&mut popen).map_err(|e| popen_to_tmc_err(cmd.clone(), e))?
         };
+        let exit_status = exited.status;
 
         log::info!("finished executing {cmd}");
         stdin_handle
@@ -109,6 +151,8 @@ impl TmcCommand {
         let stderr = stderr_handle
             .join()
             .expect("the thread should not be able to panic");
+        let stats = execution_stats(start, &exited, false, memory_scope.as_ref());
+        log::debug!("{cmd}: {stats:?}");
 
         // on success, log stdout trace and stderr debug
         // on failure if checked, log warn
@@ -137,6 +181,7 @@ impl TmcCommand {
             status: exit_status,
             stdout,
             stderr,
+            stats,
         })
     }
 
@@ -213,6 +258,153 @@ pub struct Output {
     pub status: ExitStatus,
     pub stdout: Vec<u8>,
     pub stderr: Vec<u8>,
+    pub stats: ExecutionStats,
+}
+
+// the exit status of a process that has been waited for and the resources used by it and the processes it waited for
+struct Exited {
+    status: ExitStatus,
+    usage: Option<Usage>,
+}
+
+struct Usage {
+    cpu_time: Duration,
+    max_rss_kb: u64,
+}
+
+// waits for the process to exit
+#[cfg(unix)]
+fn wait(popen: &mut Popen) -> Result<Exited, PopenError> {
+    loop {
+        if let Some(exited) = wait4(popen, 0)? {
+            return Ok(exited);
+        }
+    }
+}
+
+// waits for the process to exit, or returns None if it did not exit within the timeout
+#[cfg(unix)]
+fn wait_timeout(popen: &mut Popen, timeout: Duration) -> Result<Option<Exited>, PopenError> {
+    let deadline = Instant::now() + timeout;
+    let mut delay = Duration::from_millis(1);
+    loop {
+        if let Some(exited) = wait4(popen, libc::WNOHANG)? {
+            return Ok(Some(exited));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        std::thread::sleep(delay.min(deadline - now));
+        delay = (delay * 2).min(Duration::from_millis(100));
+    }
+}
+
+// waits for the process with wait4, which also returns the resource usage of the process
+// returns None if the process has not exited and the options contain WNOHANG
+#[cfg(unix)]
+fn wait4(popen: &Popen, options: libc::c_int) -> Result<Option<Exited>, PopenError> {
+    let pid = popen
+        .pid()
+        .and_then(|pid| libc::pid_t::try_from(pid).ok())
+        .ok_or(PopenError::LogicError(
+            "the process has already been waited for",
+        ))?;
+    let mut status = 0;
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::zeroed();
+    let result = loop {
+        // SAFETY: wait4 only writes into the given status and struct
+        let result = unsafe { libc::wait4(pid, &mut status, options, usage.as_mut_ptr()) };
+        if result == -1 {
+            let err = std::io::Error::last_os_error();
+            if err.kind() == std::io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(PopenError::IoError(err));
+        }
+        break result;
+    };
+    if result == 0 {
+        return Ok(None);
+    }
+    // SAFETY: the struct was zeroed and then initialized by wait4
+    let usage = unsafe { usage.assume_init() };
+
+    let status = if libc::WIFEXITED(status) {
+        ExitStatus::Exited(u32::try_from(libc::WEXITSTATUS(status)).unwrap_or_default())
+    } else if libc::WIFSIGNALED(status) {
+        ExitStatus::Signaled(u8::try_from(libc::WTERMSIG(status)).unwrap_or_default())
+    } else {
+        ExitStatus::Other(status)
+    };
+    let to_duration = |time: libc::timeval| {
+        Duration::from_secs(time.tv_sec.try_into().unwrap_or_default())
+            + Duration::from_micros(time.tv_usec.try_into().unwrap_or_default())
+    };
+    // ru_maxrss is in bytes on macOS and in kilobytes elsewhere
+    let max_rss = u64::try_from(usage.ru_maxrss).unwrap_or_default();
+    let max_rss_kb = if cfg!(target_os = "macos") {
+        max_rss / 1024
+    } else {
+        max_rss
+    };
+    Ok(Some(Exited {
+        status,
+        usage: Some(Usage {
+            cpu_time: to_duration(usage.ru_utime) + to_duration(usage.ru_stime),
+            max_rss_kb,
+        }),
+    }))
+}
+
+#[cfg(not(unix))]
+fn wait(popen: &mut Popen) -> Result<Exited, PopenError> {
+    let status = popen.wait()?;
+    Ok(Exited {
+        status,
+        usage: None,
+    })
+}
+
+#[cfg(not(unix))]
+fn wait_timeout(popen: &mut Popen, timeout: Duration) -> Result<Option<Exited>, PopenError> {
+    let exited = popen.wait_timeout(timeout)?.map(|status| Exited {
+        status,
+        usage: None,
+    });
+    Ok(exited)
+}
+
+// collects the stats of a command that has exited
+fn execution_stats(
+    start: Instant,
+    exited: &Exited,
+    timed_out: bool,
+    memory_scope: Option<&MemoryScope>,
+) -> ExecutionStats {
+    let wall_time_ms = start.elapsed().as_millis().try_into().unwrap_or(u32::MAX);
+    let cpu_time_ms = exited
+        .usage
+        .as_ref()
+        .map(|usage| usage.cpu_time.as_millis().try_into().unwrap_or(u32::MAX));
+    let peak_rss_kb = exited
+        .usage
+        .as_ref()
+        .map(|usage| usage.max_rss_kb.try_into().unwrap_or(u32::MAX));
+    let exit_signal = match exited.status {
+        ExitStatus::Signaled(signal) => Some(signal),
+        _ => None,
+    };
+    // only the sandbox's memory limit is detected, a process can also be killed or fail to allocate memory for other reasons
+    let memory_limit_exceeded = memory_scope.is_some_and(MemoryScope::out_of_memory);
+    ExecutionStats {
+        wall_time_ms,
+        cpu_time_ms,
+        peak_rss_kb,
+        exit_signal,
+        timed_out,
+        memory_limit_exceeded,
+    }
 }
 
 #[cfg(test)]
@@ -228,6 +420,65 @@ mod test {
         ));
     }
 
+    #[test]
+    fn timeout_stats() {
+        let cmd = TmcCommand::piped("sleep").with(|e| e.arg("2"));
+        let Err(TmcError::Command(CommandError::TimeOut { stats, .. })) =
+            cmd.output_with_timeout(Duration::from_millis(100))
+        else {
+            panic!("expected the command to time out");
+        };
+        assert!(stats.timed_out);
+        assert!(!stats.memory_limit_exceeded);
+        assert!(stats.wall_time_ms < 2000);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn output_stats() {
+        let output = TmcCommand::piped("sh")
+            .with(|e| e.arg("-c").arg("echo MemoryError >&2; exit 1"))
+            .output()
+            .expect("sh should be available");
+        assert!(output.stats.cpu_time_ms.is_some());
+        assert!(output.stats.peak_rss_kb.is_some());
+        assert_eq!(output.stats.exit_signal, None);
+        assert!(!output.stats.timed_out);
+        // the output does not matter, only the sandbox's memory limit is detected
+        assert!(!output.stats.memory_limit_exceeded);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn signal_stats() {
+        let output = TmcCommand::piped("sh")
+            .with(|e| e.arg("-c").arg("kill -9 $$"))
+            .output()
+            .expect("sh should be available");
+        assert_eq!(output.stats.exit_signal, Some(9));
+        assert!(!output.stats.memory_limit_exceeded);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn measures_each_command_separately() {
+        // builds a string of over 100 MB
+        let large = TmcCommand::piped("awk")
+            .with(|e| e.arg(r#"BEGIN { s = "x"; while (length(s) < 100000000) s = s s }"#))
+            .output()
+            .expect("awk should be available");
+        assert!(large.status.success());
+        let large_rss = large.stats.peak_rss_kb.expect("measured on unix");
+        assert!(large_rss > 100_000, "{large_rss}");
+
+        // the peak of the previous command should not be included
+        let small = TmcCommand::piped("true")
+            .output()
+            .expect("true should be available");
+        let small_rss = small.stats.peak_rss_kb.expect("measured on unix");
+        assert!(small_rss < 100_000, "{small_rss}");
+    }
+
     #[test]
     fn not_found() {
         let cmd = TmcCommand::piped("nonexistent command");
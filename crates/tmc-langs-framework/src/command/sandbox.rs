@@ -0,0 +1,391 @@
+//! Contains the sandbox that commands can be executed in.
+
+use crate::{TmcCommand, TmcError, TmcProjectYml};
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use subprocess::Exec;
+use tmc_langs_util::file_util;
+
+// how many times and how often the result of a memory limited scope is checked while it is stopping
+const SCOPE_CHECKS: u32 = 20;
+const SCOPE_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+// the directories with the system's programs and libraries that are visible in the sandbox
+const SYSTEM_PATHS: &[&str] = &[
+    "/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc", "/opt", "/nix", "/sys",
+];
+
+// environment variables that point to toolchains that may be outside of the system paths
+const TOOLCHAIN_VARS: &[&str] = &[
+    "JAVA_HOME",
+    "CARGO_HOME",
+    "RUSTUP_HOME",
+    "GOROOT",
+    "GOPATH",
+    "DOTNET_ROOT",
+    "NVM_DIR",
+    "VIRTUAL_ENV",
+    "CONDA_PREFIX",
+];
+
+// the default locations of toolchains and their packages in the home directory
+const HOME_TOOLCHAIN_PATHS: &[&str] = &[
+    ".cargo",
+    ".rustup",
+    ".dotnet",
+    ".nuget/packages",
+    ".m2/repository",
+    ".gradle",
+    "go",
+    ".nvm",
+    ".pyenv",
+    ".sdkman",
+    ".local/bin",
+    ".local/lib",
+];
+
+/// Resource limits for commands executed in a sandbox.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub struct SandboxLimits {
+    /// The maximum amount of memory used by all of the command's processes together in megabytes, without swap.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u32>,
+
+    /// The maximum number of processes for the user running the tests.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_processes: Option<u32>,
+
+    /// The maximum CPU time for each process in seconds.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_time_s: Option<u32>,
+}
+
+/// Executes commands with bubblewrap (`bwrap`) in new namespaces without network access.
+/// Only the system directories, the toolchains (found through PATH, common environment variables such as JAVA_HOME and their default locations in the home directory)
+/// and TMC's cache directory are visible, read-only, along with a private `/tmp` and the exercise directory, which is the only writable directory.
+/// The rest of the home directory, including the stored credentials, is not visible.
+///
+/// The memory limit is applied to the whole command with a systemd scope (`systemd-run`), whose result tells whether the command ran out of memory.
+/// The other limits are applied with `prlimit`.
+/// The tools need to be installed, so the sandbox is only supported on Linux.
+///
+/// Only commands created with `TmcCommand::piped_in` with the sandbox are sandboxed, anything the plugins do in-process is not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sandbox {
+    exercise_dir: PathBuf,
+    limits: SandboxLimits,
+}
+
+impl Sandbox {
+    /// Creates a sandbox where the given exercise directory is writable.
+    pub fn new(exercise_dir: &Path, limits: SandboxLimits) -> Result<Self, TmcError> {
+        let exercise_dir = file_util::canonicalize(exercise_dir)?;
+        Ok(Self {
+            exercise_dir,
+            limits,
+        })
+    }
+
+    /// Creates a sandbox for the exercise with the limits from its .tmcproject.yml.
+    pub fn for_exercise(exercise_dir: &Path) -> Result<Self, TmcError> {
+        let limits = TmcProjectYml::load_or_default(exercise_dir)?
+            .sandbox_limits
+            .unwrap_or_default();
+        Self::new(exercise_dir, limits)
+    }
+
+    pub fn exercise_dir(&self) -> &Path {
+        &self.exercise_dir
+    }
+
+    pub fn limits(&self) -> SandboxLimits {
+        self.limits
+    }
+
+    /// Creates an Exec that runs the given command in the sandbox. Arguments added to it are passed to the command.
+    /// If there is a memory limit, the command runs in the returned systemd scope.
+    pub(crate) fn exec(&self, cmd: &OsStr) -> (Exec, Option<MemoryScope>) {
+        let scope = self.limits.memory_mb.map(MemoryScope::new);
+        let mut exec = match &scope {
+            Some(scope) => Exec::cmd("systemd-run")
+                .args(&scope.args())
+                .arg("--")
+                .arg("bwrap"),
+            None => Exec::cmd("bwrap"),
+        };
+        exec = exec
+            .args(&[
+                "--unshare-all",
+                "--die-with-parent",
+                "--new-session",
+                "--dev",
+                "/dev",
+                "--proc",
+                "/proc",
+                "--tmpfs",
+                "/tmp",
+            ])
+            // mounted after /tmp so that the paths inside /tmp stay visible
+            .args(&self.read_only_bind_args(cmd))
+            .arg("--bind")
+            .arg(&self.exercise_dir)
+            .arg(&self.exercise_dir)
+            .arg("--");
+
+        let limits = self.limit_args();
+        if !limits.is_empty() {
+            exec = exec.arg("prlimit").args(&limits).arg("--");
+        }
+        (exec.arg(cmd), scope)
+    }
+
+    fn limit_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(max_processes) = self.limits.max_processes {
+            args.push(format!("--nproc={max_processes}"));
+        }
+        if let Some(cpu_time_s) = self.limits.cpu_time_s {
+            args.push(format!("--cpu={cpu_time_s}"));
+        }
+        args
+    }
+
+    // the arguments for mounting the system directories, the toolchains and the command's directory read-only
+    // missing paths are skipped by bwrap
+    fn read_only_bind_args(&self, cmd: &OsStr) -> Vec<OsString> {
+        let home = env::var_os("HOME").map(PathBuf::from);
+        // directories like / or the home directory itself would expose everything in the home directory
+        let contains_home = |dir: &Path| home.as_ref().is_some_and(|home| home.starts_with(dir));
+
+        let mut paths = SYSTEM_PATHS.iter().map(PathBuf::from).collect::<Vec<_>>();
+        if let Some(path) = env::var_os("PATH") {
+            for dir in env::split_paths(&path) {
+                if dir.is_absolute() && !contains_home(&dir) {
+                    paths.push(dir);
+                }
+            }
+        }
+        for var in TOOLCHAIN_VARS {
+            if let Some(dir) = env::var_os(var).map(PathBuf::from) {
+                if dir.is_absolute() && !contains_home(&dir) {
+                    paths.push(dir);
+                }
+            }
+        }
+        if let Some(home) = &home {
+            paths.extend(HOME_TOOLCHAIN_PATHS.iter().map(|path| home.join(path)));
+        }
+        // runners and other helper files that the plugins store in the cache directory
+        if let Some(cache_dir) = env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home.as_ref().map(|home| home.join(".cache")))
+        {
+            paths.push(cache_dir.join("tmc"));
+        }
+        // commands outside of the toolchains, such as external plugins, may need the other files next to them
+        if let Some(cmd_dir) = Path::new(cmd).parent() {
+            if cmd_dir.is_absolute() && !contains_home(cmd_dir) {
+                paths.push(cmd_dir.to_path_buf());
+            }
+        }
+
+        let mut args = vec![];
+        for path in paths {
+            args.push(OsString::from("--ro-bind-try"));
+            args.push(path.clone().into_os_string());
+            args.push(path.into_os_string());
+        }
+        args
+    }
+}
+
+/// A transient systemd scope with a memory limit that a sandboxed command runs in.
+/// The scope is named so that its result can be checked after the command has exited.
+#[derive(Debug)]
+pub(crate) struct MemoryScope {
+    unit: String,
+    memory_mb: u32,
+    // root uses the system instance of systemd, other users their own instance
+    user: bool,
+}
+
+impl MemoryScope {
+    fn new(memory_mb: u32) -> Self {
+        static SCOPES: AtomicU64 = AtomicU64::new(0);
+
+        let scope = SCOPES.fetch_add(1, Ordering::Relaxed);
+        #[cfg(unix)]
+        // SAFETY: geteuid has no preconditions and cannot fail
+        let user = unsafe { libc::geteuid() } != 0;
+        #[cfg(not(unix))]
+        let user = true;
+        Self {
+            unit: format!("tmc-langs-{}-{scope}.scope", std::process::id()),
+            memory_mb,
+            user,
+        }
+    }
+
+    // the arguments for systemd-run
+    fn args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if self.user {
+            args.push("--user".to_string());
+        }
+        args.extend([
+            "--scope".to_string(),
+            "--quiet".to_string(),
+            format!("--unit={}", self.unit),
+            "--property".to_string(),
+            format!("MemoryMax={}", u64::from(self.memory_mb) * 1024 * 1024),
+            "--property".to_string(),
+            "MemorySwapMax=0".to_string(),
+        ]);
+        args
+    }
+
+    /// Checks whether the command was killed for exceeding the memory limit, meaning that systemd saw an out of memory event in the scope.
+    /// Should be called after the command has exited. A failed scope is kept by systemd until it is reset, so it is reset here.
+    pub(crate) fn out_of_memory(&self) -> bool {
+        // systemd may still be stopping the scope right after the command has exited
+        for _ in 0..SCOPE_CHECKS {
+            let output = match self
+                .systemctl("show")
+                .with(|e| e.arg("--property=ActiveState,Result"))
+                .output_checked()
+            {
+                Ok(output) => output,
+                Err(err) => {
+                    log::warn!("failed to check the result of {}: {err}", self.unit);
+                    return false;
+                }
+            };
+            let output = String::from_utf8_lossy(&output.stdout);
+            let property = |name: &str| {
+                output
+                    .lines()
+                    .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+                    .unwrap_or_default()
+            };
+            match (property("ActiveState"), property("Result")) {
+                ("activating" | "active" | "deactivating", _) => {
+                    std::thread::sleep(SCOPE_CHECK_INTERVAL);
+                }
+                ("failed", result) => {
+                    if let Err(err) = self.systemctl("reset-failed").output_checked() {
+                        log::warn!("failed to reset {}: {err}", self.unit);
+                    }
+                    return result == "oom-kill";
+                }
+                _ => return false,
+            }
+        }
+        log::warn!("{} did not stop after the command exited", self.unit);
+        false
+    }
+
+    fn systemctl(&self, command: &str) -> TmcCommand {
+        TmcCommand::piped("systemctl").with(|e| {
+            let e = if self.user { e.arg("--user") } else { e };
+            e.arg(command).arg(&self.unit)
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    fn init() {
+        use log::*;
+        use simple_logger::*;
+        let _ = SimpleLogger::new().with_level(LevelFilter::Debug).init();
+    }
+
+    #[test]
+    fn reads_limits_from_project_config() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp.path().join(".tmcproject.yml"),
+            "sandbox_limits:\n  memory_mb: 512\n  cpu_time_s: 10\n",
+        )
+        .unwrap();
+
+        let sandbox = Sandbox::for_exercise(temp.path()).unwrap();
+        assert_eq!(
+            sandbox.limits(),
+            SandboxLimits {
+                memory_mb: Some(512),
+                max_processes: None,
+                cpu_time_s: Some(10),
+            }
+        );
+        let scope_args = MemoryScope::new(512).args();
+        assert!(scope_args.contains(&"MemoryMax=536870912".to_string()));
+        assert!(scope_args.contains(&"MemorySwapMax=0".to_string()));
+        assert_eq!(sandbox.limit_args(), ["--cpu=10"]);
+    }
+
+    #[test]
+    fn wraps_commands() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        let sandbox = Sandbox::new(temp.path(), SandboxLimits::default()).unwrap();
+        let mut cmdline = String::new();
+        let _command = TmcCommand::piped_in("echo", Some(&sandbox)).with(|e| {
+            let e = e.arg("hello");
+            cmdline = e.to_cmdline_lossy();
+            e
+        });
+        assert!(cmdline.contains("bwrap --unshare-all"));
+        assert!(!cmdline.contains("--ro-bind / /"));
+        assert!(cmdline.contains("--ro-bind-try /usr /usr"));
+        assert!(cmdline.ends_with("-- echo hello"));
+
+        let _command = TmcCommand::piped_in("echo", None).with(|e| {
+            cmdline = e.to_cmdline_lossy();
+            e
+        });
+        assert!(cmdline.ends_with(" echo"));
+        assert!(!cmdline.contains("bwrap"));
+    }
+
+    #[test]
+    fn limits_memory_with_systemd() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        let limits = SandboxLimits {
+            memory_mb: Some(256),
+            max_processes: Some(100),
+            cpu_time_s: None,
+        };
+        let sandbox = Sandbox::new(temp.path(), limits).unwrap();
+        let mut cmdline = String::new();
+        let _command = TmcCommand::piped_in("java", Some(&sandbox)).with(|e| {
+            cmdline = e.to_cmdline_lossy();
+            e
+        });
+        assert!(cmdline.contains(" systemd-run "));
+        assert!(cmdline.contains("MemoryMax=268435456"));
+        assert!(cmdline.contains(&format!("--unit=tmc-langs-{}-", std::process::id())));
+        assert!(cmdline.contains("-- bwrap --unshare-all"));
+        assert!(cmdline.contains("-- prlimit '--nproc=100' --"));
+        assert!(cmdline.ends_with("-- java"));
+        assert!(!cmdline.contains("--as="));
+    }
+}
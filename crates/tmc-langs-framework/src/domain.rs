@@ -30,6 +30,7 @@ impl TestDesc {
 
 /// The result of a single test case.
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
 #[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
 pub struct TestResult {
     pub name: String,
@@ -39,6 +40,22 @@ pub struct TestResult {
     pub message: String,
     #[serde(default)]
     pub exception: Vec<String>,
+    /// How long the test took to run in milliseconds, if reported by the test runner.
+    /// Read from the Check results of C exercises, and from the results of Python and R runners
+    /// and external plugins that include it. The bundled Java and C# runners do not report it.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u32>,
+    /// What the test wrote to stdout, if captured by the test runner.
+    /// Read from the results of Python runners and external plugins that include it.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stdout: Option<String>,
+    /// What the test wrote to stderr, if captured by the test runner.
+    /// Read from the results of Python runners and external plugins that include it.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
 }
 
 /// A description of an exercise.
@@ -70,6 +87,10 @@ pub struct RunResult {
     /// Logs from the test run.
     /// The key may be an arbitrary string identifying the type of log.
     pub logs: HashMap<String, String>,
+    /// Statistics about the execution of the command that ran the tests.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_stats: Option<ExecutionStats>,
 }
 
 impl RunResult {
@@ -82,10 +103,85 @@ impl RunResult {
             status,
             test_results,
             logs,
+            execution_stats: None,
         }
     }
 }
 
+/// Selects the tests to run from an exercise.
+/// A test is selected if its name contains any of the names, or if it gives any of the points.
+/// An empty filter selects every test.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TestFilter {
+    pub names: Vec<String>,
+    pub points: Vec<String>,
+}
+
+impl TestFilter {
+    pub fn new(names: Vec<String>, points: Vec<String>) -> Self {
+        Self { names, points }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty() && self.points.is_empty()
+    }
+
+    /// Checks whether the filter selects a test with the given name and points.
+    pub fn matches(&self, name: &str, points: &[String]) -> bool {
+        self.is_empty()
+            || self.names.iter().any(|n| name.contains(n.as_str()))
+            || points.iter().any(|p| self.points.contains(p))
+    }
+
+    /// Removes the results of the tests that were not selected.
+    ///
+    /// If none of the results were selected and the run did not pass, the result is returned as is,
+    /// because the failure was not caused by a single test, as is the case with timeouts for example.
+    pub fn filter_run_result(&self, mut run_result: RunResult) -> RunResult {
+        if self.is_empty() {
+            return run_result;
+        }
+
+        let selected = run_result
+            .test_results
+            .iter()
+            .filter(|t| self.matches(&t.name, &t.points))
+            .count();
+        if selected == 0 && run_result.status != RunStatus::Passed {
+            return run_result;
+        }
+
+        run_result
+            .test_results
+            .retain(|t| self.matches(&t.name, &t.points));
+        if run_result.status == RunStatus::TestsFailed
+            && run_result.test_results.iter().all(|t| t.successful)
+        {
+            run_result.status = RunStatus::Passed;
+        }
+        run_result
+    }
+}
+
+/// Statistics about the execution of a command.
+#[derive(Debug, Default, Clone, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub struct ExecutionStats {
+    /// How long the command ran in milliseconds.
+    pub wall_time_ms: u32,
+    /// The CPU time used by the command and the processes it waited for in milliseconds. Only measured on Unix.
+    pub cpu_time_ms: Option<u32>,
+    /// The peak resident set size in kilobytes of the largest process among the command and the processes it waited for. Only measured on Unix.
+    pub peak_rss_kb: Option<u32>,
+    /// The signal that terminated the command, if any.
+    pub exit_signal: Option<u8>,
+    /// Whether the command was stopped because it ran longer than its timeout.
+    pub timed_out: bool,
+    /// Whether the command was killed for exceeding the sandbox's memory limit. Only detected for commands run in a sandbox with a memory limit.
+    pub memory_limit_exceeded: bool,
+}
+
 /// The overall status of a test run.
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
 #[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
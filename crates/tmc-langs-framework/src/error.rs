@@ -1,5 +1,6 @@
 //! Contains the TmcError type.
 
+use crate::ExecutionStats;
 pub use nom_language::error::VerboseError;
 use std::{path::PathBuf, time::Duration};
 pub use subprocess::{ExitStatus, PopenError};
@@ -96,6 +97,7 @@ pub enum CommandError {
         timeout: Duration,
         stdout: String,
         stderr: String,
+        stats: ExecutionStats,
     },
     #[error("Failed to terminate command {0}")]
     Terminate(String, #[source] std::io::Error),
@@ -16,14 +16,22 @@ mod test_helpers;
 
 pub use self::{
     archive::{Archive, ArchiveBuilder, Compression},
-    command::{ExitStatus, Output, TmcCommand},
+    command::{ExitStatus, Output, Sandbox, SandboxLimits, TmcCommand},
     domain::{
-        ExerciseDesc, ExercisePackagingConfiguration, RunResult, RunStatus, StyleValidationError,
-        StyleValidationResult, StyleValidationStrategy, TestDesc, TestResult,
+        ExecutionStats, ExerciseDesc, ExercisePackagingConfiguration, RunResult, RunStatus,
+        StyleValidationError, StyleValidationResult, StyleValidationStrategy, TestDesc, TestFilter,
+        TestResult,
     },
     error::{CommandError, PopenError, TmcError},
-    meta_syntax::{MetaString, MetaSyntaxParser},
-    plugin::{Language, LanguagePlugin},
+    meta_syntax::{
+        AnnotationIssue, AnnotationIssueKind, CommentSyntax, CommentSyntaxes, MetaString,
+        MetaSyntaxParser, lint_annotations,
+    },
+    plugin::{
+        Language, LanguagePlugin, extract_project_with_policy, extract_student_files_with_policy,
+        fail_if_no_tests, get_exercise_packaging_configuration_with_policy,
+        safe_find_project_dir_in_archive_with, tests_timeout,
+    },
     policy::{EverythingIsStudentFilePolicy, NothingIsStudentFilePolicy, StudentFilePolicy},
     tmc_project_yml::{PythonVer, TmcProjectYml},
 };
@@ -3,17 +3,128 @@
 
 use once_cell::sync::Lazy;
 use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
 
 // rules for finding comments in various languages
-static META_SYNTAXES_C: Lazy<[MetaSyntax; 2]> = Lazy::new(|| {
-    [
-        MetaSyntax::new("//", None),
-        MetaSyntax::new(r"/\*", Some(r"\*/")),
-    ]
+static META_SYNTAXES_C: Lazy<Arc<[MetaSyntax]>> =
+    Lazy::new(|| CommentSyntax::new(Some("//"), Some(("/*", "*/"))).compile());
+static META_SYNTAXES_HTML: Lazy<Arc<[MetaSyntax]>> =
+    Lazy::new(|| CommentSyntax::new(None, Some(("<!--", "-->"))).compile());
+static META_SYNTAXES_PY: Lazy<Arc<[MetaSyntax]>> =
+    Lazy::new(|| CommentSyntax::new(Some("#"), None).compile());
+static META_SYNTAXES_SQL: Lazy<Arc<[MetaSyntax]>> =
+    Lazy::new(|| CommentSyntax::new(Some("--"), Some(("/*", "*/"))).compile());
+
+// the variants an annotation applies to, from the text after the annotation
+#[allow(clippy::unwrap_used)]
+static VARIANT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bvariant\s*=\s*([\w-]+(?:\s*,\s*[\w-]+)*)").unwrap());
+
+// assigns each supported file extension with the proper comment syntax
+static BUILTIN_COMMENT_SYNTAXES: Lazy<CommentSyntaxes> = Lazy::new(|| {
+    let mut by_extension = HashMap::new();
+    for extension in [
+        "java", "c", "cpp", "h", "hpp", "js", "ts", "tsx", "mjs", "cjs", "jsx", "css", "scss",
+        "less", "rs", "qml", "cs", "go", "kt", "kts", "scala", "swift", "dart",
+    ] {
+        by_extension.insert(extension.to_string(), Arc::clone(&META_SYNTAXES_C));
+    }
+    for extension in ["xml", "http", "html", "qrc", "md", "svg", "vue"] {
+        by_extension.insert(extension.to_string(), Arc::clone(&META_SYNTAXES_HTML));
+    }
+    for extension in [
+        "properties",
+        "py",
+        "R",
+        "pro",
+        "ipynb",
+        "sh",
+        "bash",
+        "yml",
+        "yaml",
+        "toml",
+        "rb",
+    ] {
+        by_extension.insert(extension.to_string(), Arc::clone(&META_SYNTAXES_PY));
+    }
+    by_extension.insert("sql".to_string(), Arc::clone(&META_SYNTAXES_SQL));
+    CommentSyntaxes { by_extension }
 });
-static META_SYNTAXES_HTML: Lazy<[MetaSyntax; 1]> =
-    Lazy::new(|| [MetaSyntax::new("<!--", Some("-->"))]);
-static META_SYNTAXES_PY: Lazy<[MetaSyntax; 1]> = Lazy::new(|| [MetaSyntax::new("#", None)]);
+
+/// The comment syntax of a language, used to find the annotations in files written in it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub struct CommentSyntax {
+    /// Starts a comment that lasts until the end of the line, e.g. `//`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<String>,
+    /// The start and end of a comment that can span multiple lines, like the block comments in C.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block: Option<(String, String)>,
+}
+
+impl CommentSyntax {
+    pub fn new(line: Option<&str>, block: Option<(&str, &str)>) -> Self {
+        Self {
+            line: line.map(str::to_string),
+            block: block.map(|(start, end)| (start.to_string(), end.to_string())),
+        }
+    }
+
+    fn compile(&self) -> Arc<[MetaSyntax]> {
+        let line = self
+            .line
+            .iter()
+            .map(|line| MetaSyntax::new(&regex::escape(line), None));
+        let block = self
+            .block
+            .iter()
+            .map(|(start, end)| MetaSyntax::new(&regex::escape(start), Some(&regex::escape(end))));
+        line.chain(block).collect()
+    }
+}
+
+/// Maps file extensions to the comment syntaxes used to find annotations in files with them.
+/// Files with other extensions are not parsed for annotations.
+#[derive(Debug, Clone)]
+pub struct CommentSyntaxes {
+    by_extension: HashMap<String, Arc<[MetaSyntax]>>,
+}
+
+impl CommentSyntaxes {
+    /// The built-in syntaxes for common file extensions.
+    pub fn builtin() -> Self {
+        BUILTIN_COMMENT_SYNTAXES.clone()
+    }
+
+    /// Sets the syntax for the extension, replacing any previous syntax.
+    pub fn insert(&mut self, extension: &str, syntax: &CommentSyntax) {
+        self.by_extension
+            .insert(extension.to_string(), syntax.compile());
+    }
+
+    /// Sets the syntax for the extension if it does not already have one.
+    pub fn insert_if_missing(&mut self, extension: &str, syntax: &CommentSyntax) {
+        if !self.contains(extension) {
+            self.insert(extension, syntax);
+        }
+    }
+
+    /// Whether the extension has a syntax.
+    pub fn contains(&self, extension: &str) -> bool {
+        self.by_extension.contains_key(extension)
+    }
+}
+
+impl Default for CommentSyntaxes {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
 
 /// Used to classify lines of code based on the annotations in the file.
 #[derive(Debug, PartialEq, Eq)]
@@ -29,6 +140,7 @@ pub enum MetaString {
 /// Contains the needed regexes for a given comment syntax.
 #[derive(Debug)]
 struct MetaSyntax {
+    comment_start: Regex,
     solution_file: Regex,
     solution_begin: Regex,
     solution_end: Regex,
@@ -41,7 +153,8 @@ struct MetaSyntax {
 
 #[allow(clippy::unwrap_used)]
 impl MetaSyntax {
-    fn new(comment_start: &'static str, comment_end: Option<&'static str>) -> Self {
+    // the comment delimiters are regex patterns
+    fn new(comment_start: &str, comment_end: Option<&str>) -> Self {
         // comment patterns
         let comment_start_pattern = format!(r"^(\s*){comment_start}\s*");
         let comment_end_pattern = match comment_end {
@@ -62,7 +175,10 @@ impl MetaSyntax {
             r"{comment_start_pattern}END\s+SOLUTION{comment_end_pattern}"
         ))
         .unwrap();
-        let stub_begin = Regex::new(&format!(r"{comment_start_pattern}STUB:[\s&&[^\n]]*")).unwrap();
+        let stub_begin = Regex::new(&format!(
+            r"{comment_start_pattern}STUB(\s+variant\s*=\s*[\w-]+(?:\s*,\s*[\w-]+)*)?:[\s&&[^\n]]*"
+        ))
+        .unwrap();
         let stub_end = Regex::new(&comment_end_pattern).unwrap();
         let hidden_file = Regex::new(&format!(
             r"{comment_start_pattern}HIDDEN\s+FILE{comment_end_pattern}"
@@ -78,6 +194,7 @@ impl MetaSyntax {
         .unwrap();
 
         Self {
+            comment_start: Regex::new(&comment_start_pattern).unwrap(),
             solution_file,
             solution_begin,
             solution_end,
@@ -91,39 +208,84 @@ impl MetaSyntax {
 }
 
 /// Parses a given text file into an iterator of `MetaString`s.
+///
+/// Annotations can be limited to named variants of the exercise, such as `BEGIN SOLUTION variant=advanced` or `STUB variant=beginner,advanced:`.
+/// Annotations for variants other than the selected one are left out along with their stubs, while the code in their solution blocks is parsed as strings.
+/// HIDDEN annotations cannot be limited to variants, so that hidden code never ends up in a stub or solution; any variant on them is ignored.
 #[derive(Debug)]
 pub struct MetaSyntaxParser<I> {
-    meta_syntaxes: &'static [MetaSyntax],
+    meta_syntaxes: Arc<[MetaSyntax]>,
     line_iterator: I,
-    // contains the syntax that started the current stub block
+    variant: Option<String>,
+    // contains the index of the syntax that started the current stub block
     // used to make sure only the appropriate terminator ends the block
-    in_stub: Option<&'static MetaSyntax>,
+    in_stub: Option<usize>,
     in_solution: bool,
     in_hidden: bool,
+    // whether the current blocks apply to the selected variant
+    stub_applies: bool,
+    solution_applies: bool,
 }
 
 impl<E, I: Iterator<Item = Result<String, E>>> MetaSyntaxParser<I> {
+    /// Parses the lines using the built-in comment syntax for the extension.
     pub fn new(line_iterator: I, target_extension: &str) -> Self {
-        // assigns each supported file extension with the proper comment syntax
-        // todo: stop checking extension twice here and in submission_processing
-        // NOTE: if you change these extensions make sure to change them in submission_processing.rs as well
-        let meta_syntaxes: &[MetaSyntax] = match target_extension {
-            "java" | "c" | "cpp" | "h" | "hpp" | "js" | "css" | "rs" | "qml" | "cs" => {
-                &*META_SYNTAXES_C
-            }
-            "xml" | "http" | "html" | "qrc" => &*META_SYNTAXES_HTML,
-            "properties" | "py" | "R" | "pro" | "ipynb" => &*META_SYNTAXES_PY,
-            _ => &[],
-        };
+        Self::with_syntaxes(line_iterator, &BUILTIN_COMMENT_SYNTAXES, target_extension)
+    }
+
+    /// Parses the lines using the comment syntax for the extension from the given syntaxes.
+    /// If there is none, all of the lines are parsed as strings.
+    pub fn with_syntaxes(
+        line_iterator: I,
+        comment_syntaxes: &CommentSyntaxes,
+        target_extension: &str,
+    ) -> Self {
+        let meta_syntaxes = comment_syntaxes
+            .by_extension
+            .get(target_extension)
+            .cloned()
+            .unwrap_or_else(|| Arc::new([]));
 
         Self {
             meta_syntaxes,
             line_iterator,
+            variant: None,
             in_stub: None,
             in_solution: false,
             in_hidden: false,
+            stub_applies: true,
+            solution_applies: true,
         }
     }
+
+    /// Selects the variant of the exercise to parse the lines for.
+    /// If a variant is given, the annotations for it are processed in addition to the ones without a variant.
+    /// If none is selected, only the annotations without a variant are processed and `lint_annotations` reports the ones with a variant as errors.
+    pub fn select_variant(mut self, variant: Option<&str>) -> Self {
+        self.variant = variant.map(str::to_string);
+        self
+    }
+}
+
+// the comma-separated variants an annotation followed by the given text is limited to, if any
+fn annotation_variants(caps: Option<Captures>) -> Option<String> {
+    let rest = caps.as_ref().and_then(|caps| caps.get(2))?;
+    VARIANT
+        .captures(rest.as_str())
+        .map(|variants| variants[1].to_string())
+}
+
+// whether an annotation followed by the given text applies to the selected variant
+// annotations without a variant apply to every variant
+fn applies_to_variant(variant: Option<&str>, caps: Option<Captures>) -> bool {
+    match annotation_variants(caps) {
+        Some(variants) => variant.is_some_and(|variant| {
+            variants
+                .split(',')
+                .any(|candidate| candidate.trim() == variant)
+        }),
+        None => true,
+    }
 }
 
 // iterates through the lines in the underlying file, parsing them to MetaStrings
@@ -134,10 +296,14 @@ impl<E, I: Iterator<Item = Result<String, E>>> Iterator for MetaSyntaxParser<I>
         match self.line_iterator.next() {
             Some(Ok(mut s)) => {
                 // check line with each meta syntax
-                for meta_syntax in self.meta_syntaxes {
+                for (index, meta_syntax) in self.meta_syntaxes.iter().enumerate() {
                     // check for stub
                     if self.in_stub.is_none() && meta_syntax.stub_begin.is_match(&s) {
                         log::trace!("stub start: '{s}'");
+                        let applies = applies_to_variant(
+                            self.variant.as_deref(),
+                            meta_syntax.stub_begin.captures(&s),
+                        );
                         // remove stub start
                         s = meta_syntax
                             .stub_begin
@@ -145,24 +311,24 @@ impl<E, I: Iterator<Item = Result<String, E>>> Iterator for MetaSyntaxParser<I>
                             .to_string();
 
                         if s.trim().is_empty() && meta_syntax.stub_end.is_match(&s) {
+                            if !applies {
+                                return self.next();
+                            }
                             // empty oneliner stubs are replaced by a newline
                             return Some(Ok(MetaString::Stub("\n".to_string())));
                         }
 
                         // save the syntax that started the current stub
-                        self.in_stub = Some(meta_syntax);
+                        self.in_stub = Some(index);
+                        self.stub_applies = applies;
 
                         if s.trim().is_empty() {
                             // only metadata, skip
                             return self.next();
                         }
                     }
-                    // if the line matches stub_end and the saved syntax matches
-                    // the start of the current meta syntax, return stub contents if any
-                    if meta_syntax.stub_end.is_match(&s)
-                        && self.in_stub.map(|r| r.stub_begin.as_str())
-                            == Some(meta_syntax.stub_begin.as_str())
-                    {
+                    // if the line matches stub_end and the saved syntax is the current meta syntax, return stub contents if any
+                    if meta_syntax.stub_end.is_match(&s) && self.in_stub == Some(index) {
                         log::trace!("stub end: '{s}'");
                         self.in_stub = None;
                         // remove stub end
@@ -170,8 +336,8 @@ impl<E, I: Iterator<Item = Result<String, E>>> Iterator for MetaSyntaxParser<I>
                             .stub_end
                             .replace(&s, |caps: &Captures| caps[1].to_string())
                             .to_string();
-                        if s.trim().is_empty() {
-                            // only metadata, skip
+                        if s.trim().is_empty() || !self.stub_applies {
+                            // only metadata or a stub for another variant, skip
                             return self.next();
                         }
                         // return the stub contents
@@ -179,16 +345,24 @@ impl<E, I: Iterator<Item = Result<String, E>>> Iterator for MetaSyntaxParser<I>
                     }
 
                     // check for solution, skip solution begin/end markers
+                    // markers for other variants are skipped as well
+                    let variant = self.variant.as_deref();
                     if meta_syntax.solution_file.is_match(&s) {
                         log::trace!("solution file marker");
+                        if !applies_to_variant(variant, meta_syntax.solution_file.captures(&s)) {
+                            return self.next();
+                        }
                         return Some(Ok(MetaString::SolutionFileMarker));
                     } else if meta_syntax.solution_begin.is_match(&s) {
                         self.in_solution = true;
+                        self.solution_applies =
+                            applies_to_variant(variant, meta_syntax.solution_begin.captures(&s));
                         return self.next();
                     } else if meta_syntax.solution_end.is_match(&s) && self.in_solution {
                         self.in_solution = false;
                         return self.next();
                     } else if meta_syntax.hidden_file.is_match(&s) {
+                        // hidden annotations apply to every variant
                         log::trace!("hidden file marker");
                         return Some(Ok(MetaString::HiddenFileMarker));
                     } else if meta_syntax.hidden_begin.is_match(&s) {
@@ -201,10 +375,14 @@ impl<E, I: Iterator<Item = Result<String, E>>> Iterator for MetaSyntaxParser<I>
                 }
                 // after processing the line with each meta syntax,
                 // parse the current line accordingly
-                if self.in_solution {
+                if self.in_solution && self.solution_applies {
                     log::trace!("solution: '{s}'");
                     Some(Ok(MetaString::Solution(s)))
                 } else if self.in_stub.is_some() {
+                    if !self.stub_applies {
+                        log::trace!("stub for another variant: '{s}'");
+                        return self.next();
+                    }
                     log::trace!("stub: '{s}'");
                     Some(Ok(MetaString::Stub(s)))
                 } else if self.in_hidden {
@@ -221,6 +399,290 @@ impl<E, I: Iterator<Item = Result<String, E>>> Iterator for MetaSyntaxParser<I>
     }
 }
 
+/// A problem with the annotations in a file found by `lint_annotations`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub struct AnnotationIssue {
+    /// The line of the annotation, starting from 1.
+    pub line: usize,
+    /// The column where the annotation's comment starts, starting from 1.
+    pub column: usize,
+    pub kind: AnnotationIssueKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub enum AnnotationIssueKind {
+    /// A block that is never closed, so the rest of the file ends up in it.
+    Unclosed,
+    /// A block that begins inside another block.
+    Nested,
+    /// A block end that does not match the innermost open block.
+    Mismatched,
+    /// A block end without a beginning.
+    Unmatched,
+    /// Text that looks like an annotation but is not recognized as one, so it and any solution it marks would remain in the stub.
+    Unrecognized,
+    /// A HIDDEN annotation limited to variants, or an annotation limited to variants when no variant is selected.
+    InvalidVariant,
+}
+
+impl AnnotationIssueKind {
+    /// Whether the issue certainly breaks the stub or solution.
+    /// Unrecognized annotations may be intentional, such as in documentation that describes the annotations.
+    pub fn is_error(self) -> bool {
+        !matches!(self, Self::Unrecognized)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnnotationBlock {
+    Solution,
+    Hidden,
+    // contains the index of the syntax that started the stub
+    Stub(usize),
+}
+
+impl AnnotationBlock {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Solution => "BEGIN SOLUTION",
+            Self::Hidden => "BEGIN HIDDEN",
+            Self::Stub(_) => "STUB:",
+        }
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+static ANNOTATION_LIKE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(BEGIN\s*(SOLUTION|HIDDEN)|END\s*(SOLUTION|HIDDEN)|(SOLUTION|HIDDEN)\s*FILE|STUB(\s+variant\b[^:]*)?\s*:)").unwrap()
+});
+
+// annotations can only be limited to variants when one is selected, and HIDDEN annotations cannot be limited at all
+fn variant_issue(variant: Option<&str>, hidden: bool, caps: Option<Captures>) -> Option<String> {
+    let variants = annotation_variants(caps)?;
+    if hidden {
+        Some(format!(
+            "HIDDEN annotations apply to every variant and cannot be limited to '{variants}'"
+        ))
+    } else if variant.is_none() {
+        Some(format!(
+            "the annotation is limited to '{variants}' but no variant is selected, so it would not be processed"
+        ))
+    } else {
+        None
+    }
+}
+
+/// Checks the annotations in the lines, parsed with the comment syntax for the extension, for unclosed, nested or mismatched blocks.
+/// In files without a comment syntax, any text that looks like an annotation is reported as unrecognized since the file is copied to the stub as is.
+/// Variants on the annotations are checked against the selected variant, see `MetaSyntaxParser::select_variant`.
+pub fn lint_annotations<E>(
+    line_iterator: impl Iterator<Item = Result<String, E>>,
+    comment_syntaxes: &CommentSyntaxes,
+    target_extension: &str,
+    variant: Option<&str>,
+) -> Result<Vec<AnnotationIssue>, E> {
+    let meta_syntaxes = comment_syntaxes.by_extension.get(target_extension);
+    let mut issues = vec![];
+    let mut issue = |line: usize, column: usize, kind: AnnotationIssueKind, message: String| {
+        issues.push(AnnotationIssue {
+            line,
+            column,
+            kind,
+            message,
+        })
+    };
+    // the open blocks along with the line and column where they began
+    let mut open: Vec<(AnnotationBlock, usize, usize)> = vec![];
+
+    for (index, line) in line_iterator.enumerate() {
+        let line = line?;
+        let line_number = index + 1;
+
+        let Some(meta_syntaxes) = meta_syntaxes else {
+            if let Some(found) = ANNOTATION_LIKE.find(&line) {
+                issue(
+                    line_number,
+                    line[..found.start()].chars().count() + 1,
+                    AnnotationIssueKind::Unrecognized,
+                    format!(
+                        "'{}' is not processed in .{target_extension} files and will be copied to the stub as is",
+                        found.as_str()
+                    ),
+                );
+            }
+            continue;
+        };
+
+        let column =
+            |caps: Option<Captures>| caps.map(|caps| caps[1].chars().count() + 1).unwrap_or(1);
+        let mut recognized = false;
+        for (syntax_index, meta_syntax) in meta_syntaxes.iter().enumerate() {
+            let in_stub = open
+                .iter()
+                .any(|(block, _, _)| matches!(block, AnnotationBlock::Stub(_)));
+            if !in_stub && meta_syntax.stub_begin.is_match(&line) {
+                recognized = true;
+                let col = column(meta_syntax.stub_begin.captures(&line));
+                if let Some(message) =
+                    variant_issue(variant, false, meta_syntax.stub_begin.captures(&line))
+                {
+                    issue(
+                        line_number,
+                        col,
+                        AnnotationIssueKind::InvalidVariant,
+                        message,
+                    );
+                }
+                let rest = meta_syntax.stub_begin.replace(&line, "");
+                if !meta_syntax.stub_end.is_match(&rest) {
+                    open.push((AnnotationBlock::Stub(syntax_index), line_number, col));
+                }
+                break;
+            }
+            if open.last().map(|(block, _, _)| *block) == Some(AnnotationBlock::Stub(syntax_index))
+                && meta_syntax.stub_end.is_match(&line)
+            {
+                recognized = true;
+                open.pop();
+                break;
+            }
+
+            let begin = if meta_syntax.solution_begin.is_match(&line) {
+                Some((AnnotationBlock::Solution, &meta_syntax.solution_begin))
+            } else if meta_syntax.hidden_begin.is_match(&line) {
+                Some((AnnotationBlock::Hidden, &meta_syntax.hidden_begin))
+            } else {
+                None
+            };
+            if let Some((block, regex)) = begin {
+                recognized = true;
+                let col = column(regex.captures(&line));
+                let hidden = block == AnnotationBlock::Hidden;
+                if let Some(message) = variant_issue(variant, hidden, regex.captures(&line)) {
+                    issue(
+                        line_number,
+                        col,
+                        AnnotationIssueKind::InvalidVariant,
+                        message,
+                    );
+                }
+                if let Some((outer, outer_line, _)) = open.last() {
+                    issue(
+                        line_number,
+                        col,
+                        AnnotationIssueKind::Nested,
+                        format!(
+                            "{} inside the {} block that began on line {outer_line}",
+                            block.name(),
+                            outer.name()
+                        ),
+                    );
+                }
+                open.push((block, line_number, col));
+                break;
+            }
+
+            let end = if meta_syntax.solution_end.is_match(&line) {
+                Some((
+                    AnnotationBlock::Solution,
+                    "END SOLUTION",
+                    &meta_syntax.solution_end,
+                ))
+            } else if meta_syntax.hidden_end.is_match(&line) {
+                Some((
+                    AnnotationBlock::Hidden,
+                    "END HIDDEN",
+                    &meta_syntax.hidden_end,
+                ))
+            } else {
+                None
+            };
+            if let Some((block, name, regex)) = end {
+                recognized = true;
+                let col = column(regex.captures(&line));
+                match open.iter().rposition(|(b, _, _)| *b == block) {
+                    Some(position) if position + 1 == open.len() => {
+                        open.pop();
+                    }
+                    Some(position) => {
+                        let (inner, inner_line, _) = open[open.len() - 1];
+                        issue(
+                            line_number,
+                            col,
+                            AnnotationIssueKind::Mismatched,
+                            format!(
+                                "{name} while the {} block that began on line {inner_line} is open",
+                                inner.name()
+                            ),
+                        );
+                        open.remove(position);
+                    }
+                    None => issue(
+                        line_number,
+                        col,
+                        AnnotationIssueKind::Unmatched,
+                        format!("{name} without {}", block.name()),
+                    ),
+                }
+                break;
+            }
+
+            let file_marker = if meta_syntax.solution_file.is_match(&line) {
+                Some((false, &meta_syntax.solution_file))
+            } else if meta_syntax.hidden_file.is_match(&line) {
+                Some((true, &meta_syntax.hidden_file))
+            } else {
+                None
+            };
+            if let Some((hidden, regex)) = file_marker {
+                recognized = true;
+                if let Some(message) = variant_issue(variant, hidden, regex.captures(&line)) {
+                    let col = column(regex.captures(&line));
+                    issue(
+                        line_number,
+                        col,
+                        AnnotationIssueKind::InvalidVariant,
+                        message,
+                    );
+                }
+                break;
+            }
+        }
+
+        // only comments are checked as the code may contain similar text
+        let in_comment = meta_syntaxes
+            .iter()
+            .any(|meta_syntax| meta_syntax.comment_start.is_match(&line));
+        if !recognized && in_comment {
+            if let Some(found) = ANNOTATION_LIKE.find(&line) {
+                issue(
+                    line_number,
+                    line[..found.start()].chars().count() + 1,
+                    AnnotationIssueKind::Unrecognized,
+                    format!(
+                        "'{}' looks like an annotation but is not recognized as one",
+                        found.as_str()
+                    ),
+                );
+            }
+        }
+    }
+
+    for (block, line, column) in open {
+        issue(
+            line,
+            column,
+            AnnotationIssueKind::Unclosed,
+            format!("{} is never closed", block.name()),
+        );
+    }
+    Ok(issues)
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod test {
@@ -245,6 +707,10 @@ mod test {
         fn stub(s: &str) -> Self {
             Self::Stub(s.to_string())
         }
+
+        fn hidden(s: &str) -> Self {
+            Self::Hidden(s.to_string())
+        }
     }
 
     #[test]
@@ -406,4 +872,257 @@ print("a")
         let actual = filter.map(|l| l.unwrap()).collect::<Vec<MetaString>>();
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn parse_typescript() {
+        init();
+
+        const TS_FILE: &str = r#"
+export function add(a: number, b: number): number {
+    // BEGIN SOLUTION
+    return a + b;
+    // END SOLUTION
+    // STUB: return 0;
+}
+"#;
+        let expected: Vec<MetaString> = vec![
+            MetaString::str("\n"),
+            MetaString::str("export function add(a: number, b: number): number {\n"),
+            MetaString::solution("    return a + b;\n"),
+            MetaString::stub("    return 0;\n"),
+            MetaString::str("}\n"),
+        ];
+
+        for extension in ["ts", "tsx", "mjs"] {
+            let filter = MetaSyntaxParser::new(
+                TS_FILE
+                    .lines()
+                    .map(|s| Ok::<_, Infallible>(format!("{s}\n"))),
+                extension,
+            );
+            let actual = filter.map(|l| l.unwrap()).collect::<Vec<MetaString>>();
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn parse_custom_syntax() {
+        init();
+
+        const LUA_FILE: &str = r#"
+function add(a, b)
+    -- BEGIN SOLUTION
+    return a + b
+    -- END SOLUTION
+    -- STUB: return 0
+end
+"#;
+        let expected: Vec<MetaString> = vec![
+            MetaString::str("\n"),
+            MetaString::str("function add(a, b)\n"),
+            MetaString::solution("    return a + b\n"),
+            MetaString::stub("    return 0\n"),
+            MetaString::str("end\n"),
+        ];
+
+        let lines = || {
+            LUA_FILE
+                .lines()
+                .map(|s| Ok::<_, Infallible>(format!("{s}\n")))
+        };
+        // not parsed without a syntax for the extension
+        let unparsed = MetaSyntaxParser::new(lines(), "lua")
+            .map(|l| l.unwrap())
+            .collect::<Vec<MetaString>>();
+        assert!(
+            unparsed
+                .iter()
+                .all(|ms| matches!(ms, MetaString::String(_)))
+        );
+
+        let mut syntaxes = CommentSyntaxes::builtin();
+        syntaxes.insert("lua", &CommentSyntax::new(Some("--"), Some(("--[[", "]]"))));
+        let actual = MetaSyntaxParser::with_syntaxes(lines(), &syntaxes, "lua")
+            .map(|l| l.unwrap())
+            .collect::<Vec<MetaString>>();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn parse_variants() {
+        init();
+
+        const JAVA_FILE: &str = r#"
+public class JavaTestCase {
+    // BEGIN SOLUTION variant=advanced
+    private int helper() {
+        return 3;
+    }
+    // END SOLUTION
+
+    public int foo() {
+        // BEGIN SOLUTION
+        return helper();
+        // END SOLUTION
+        // STUB variant=beginner: return helper() - 3;
+        /* STUB variant=advanced, expert:
+        return 0;
+        */
+    }
+    // BEGIN HIDDEN variant=expert
+    public int bar() {}
+    // END HIDDEN
+}
+"#;
+        let lines = || {
+            JAVA_FILE
+                .lines()
+                .map(|s| Ok::<_, Infallible>(format!("{s}\n")))
+        };
+        let parse = |variant| {
+            MetaSyntaxParser::new(lines(), "java")
+                .select_variant(variant)
+                .map(|l| l.unwrap())
+                .collect::<Vec<MetaString>>()
+        };
+        let expected = vec![
+            MetaString::str("\n"),
+            MetaString::str("public class JavaTestCase {\n"),
+            MetaString::str("    private int helper() {\n"),
+            MetaString::str("        return 3;\n"),
+            MetaString::str("    }\n"),
+            MetaString::str("\n"),
+            MetaString::str("    public int foo() {\n"),
+            MetaString::solution("        return helper();\n"),
+            MetaString::stub("        return helper() - 3;\n"),
+            MetaString::str("    }\n"),
+            MetaString::hidden("    public int bar() {}\n"),
+            MetaString::str("}\n"),
+        ];
+        assert_eq!(expected, parse(Some("beginner")));
+
+        let expected = vec![
+            MetaString::str("\n"),
+            MetaString::str("public class JavaTestCase {\n"),
+            MetaString::solution("    private int helper() {\n"),
+            MetaString::solution("        return 3;\n"),
+            MetaString::solution("    }\n"),
+            MetaString::str("\n"),
+            MetaString::str("    public int foo() {\n"),
+            MetaString::solution("        return helper();\n"),
+            MetaString::stub("        return 0;\n"),
+            MetaString::str("    }\n"),
+            MetaString::hidden("    public int bar() {}\n"),
+            MetaString::str("}\n"),
+        ];
+        assert_eq!(expected, parse(Some("advanced")));
+
+        // only the annotations without a variant are processed if none is selected, and hidden ones always are
+        let without_variant = parse(None);
+        assert!(
+            !without_variant
+                .iter()
+                .any(|ms| matches!(ms, MetaString::Stub(_)))
+        );
+        assert!(without_variant.contains(&MetaString::hidden("    public int bar() {}\n")));
+        assert_eq!(
+            without_variant
+                .iter()
+                .filter(|ms| matches!(ms, MetaString::Solution(_)))
+                .count(),
+            1
+        );
+
+        // the variant on the hidden block is an error, as are variants when none is selected
+        let lint = |variant| {
+            lint_annotations(lines(), &CommentSyntaxes::builtin(), "java", variant)
+                .unwrap()
+                .into_iter()
+                .map(|issue| (issue.line, issue.kind))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            lint(Some("expert")),
+            [(18, AnnotationIssueKind::InvalidVariant)]
+        );
+        assert_eq!(
+            lint(None),
+            [
+                (3, AnnotationIssueKind::InvalidVariant),
+                (13, AnnotationIssueKind::InvalidVariant),
+                (14, AnnotationIssueKind::InvalidVariant),
+                (18, AnnotationIssueKind::InvalidVariant),
+            ]
+        );
+    }
+
+    #[test]
+    fn lints_annotations() {
+        init();
+
+        const JAVA_FILE: &str = r#"public class Lint {
+    // BEGIN SOLUTION
+    int a;
+        // BEGIN HIDDEN
+    int b;
+    // END SOLUTION
+    // END HIDDEN
+    // END SOLUTION
+    // begin solution
+    /* STUB: int c;
+    String s = "BEGIN SOLUTION";
+"#;
+        let issues = lint_annotations(
+            JAVA_FILE
+                .lines()
+                .map(|s| Ok::<_, Infallible>(format!("{s}\n"))),
+            &CommentSyntaxes::builtin(),
+            "java",
+            None,
+        )
+        .unwrap();
+        let issues = issues
+            .iter()
+            .map(|i| (i.line, i.column, i.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            issues,
+            [
+                (4, 9, AnnotationIssueKind::Nested),
+                (6, 5, AnnotationIssueKind::Mismatched),
+                (8, 5, AnnotationIssueKind::Unmatched),
+                (9, 8, AnnotationIssueKind::Unrecognized),
+                (10, 5, AnnotationIssueKind::Unclosed),
+            ]
+        );
+    }
+
+    #[test]
+    fn lints_files_without_comment_syntax() {
+        init();
+
+        let issues = lint_annotations(
+            ["first\n", "  BEGIN SOLUTION\n"]
+                .into_iter()
+                .map(|s| Ok::<_, Infallible>(s.to_string())),
+            &CommentSyntaxes::builtin(),
+            "txt",
+            None,
+        )
+        .unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!((issues[0].line, issues[0].column), (2, 3));
+        assert!(!issues[0].kind.is_error());
+
+        let issues = lint_annotations(
+            ["// BEGIN SOLUTION\n", "// END SOLUTION\n"]
+                .into_iter()
+                .map(|s| Ok::<_, Infallible>(s.to_string())),
+            &CommentSyntaxes::builtin(),
+            "java",
+            None,
+        )
+        .unwrap();
+        assert!(issues.is_empty());
+    }
 }
@@ -3,11 +3,13 @@
 use crate::{
     Archive, Compression,
     archive::ArchiveIterator,
+    command::Sandbox,
     domain::{
         ExerciseDesc, ExercisePackagingConfiguration, RunResult, RunStatus, StyleValidationResult,
-        TestResult,
+        TestFilter, TestResult,
     },
     error::TmcError,
+    meta_syntax::CommentSyntax,
     policy::StudentFilePolicy,
 };
 pub use isolang::Language;
@@ -41,6 +43,9 @@ pub trait LanguagePlugin {
     const DEFAULT_SANDBOX_IMAGE: &'static str;
     const LINE_COMMENT: &'static str;
     const BLOCK_COMMENT: Option<(&'static str, &'static str)>;
+    /// The extensions of the language's source files. Files with them are parsed for annotations using Self::LINE_COMMENT and Self::BLOCK_COMMENT,
+    /// unless the extension already has a built-in comment syntax.
+    const SOURCE_FILE_EXTENSIONS: &'static [&'static str] = &[];
     type StudentFilePolicy: StudentFilePolicy;
 
     /// Produces an exercise description of an exercise directory.
@@ -54,38 +59,58 @@ pub trait LanguagePlugin {
 
     /// Runs the tests for the exercise.
     fn run_tests(&self, path: &Path) -> Result<RunResult, TmcError> {
-        let timeout = Self::StudentFilePolicy::new(path)?
-            .get_project_config()
-            .tests_timeout_ms
-            .map(Into::into)
-            .map(Duration::from_millis);
-        let result = self.run_tests_with_timeout(path, timeout)?;
-
-        // override success on no test cases
-        if result.status == RunStatus::Passed && result.test_results.is_empty() {
-            Ok(RunResult {
-                status: RunStatus::TestsFailed,
-                test_results: vec![TestResult {
-                    name: "Tests found test".to_string(),
-                    successful: false,
-                    points: vec![],
-                    message: "No tests found. Did you terminate your program with an exit() command?\nYou can also try submitting the exercise to the server."
-                        .to_string(),
-                    exception: vec![],
-                }],
-                logs: result.logs,
-            })
+        self.run_tests_in_sandbox(path, &TestFilter::default(), None)
+    }
+
+    /// Runs the tests for the exercise that are selected by the filter.
+    fn run_tests_filtered(&self, path: &Path, filter: &TestFilter) -> Result<RunResult, TmcError> {
+        self.run_tests_in_sandbox(path, filter, None)
+    }
+
+    /// Runs the tests for the exercise that are selected by the filter, in the sandbox if one is given.
+    fn run_tests_in_sandbox(
+        &self,
+        path: &Path,
+        filter: &TestFilter,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        let timeout = tests_timeout::<Self::StudentFilePolicy>(path)?;
+        if filter.is_empty() {
+            let result = self.run_tests_with_timeout(path, timeout, sandbox)?;
+            Ok(fail_if_no_tests(
+                result,
+                "No tests found. Did you terminate your program with an exit() command?\nYou can also try submitting the exercise to the server.",
+            ))
         } else {
-            Ok(result)
+            let result = self.run_tests_filtered_with_timeout(path, filter, timeout, sandbox)?;
+            Ok(fail_if_no_tests(result, "No tests matched the filter."))
         }
     }
 
+    /// Runs the tests for the exercise that are selected by the filter with the given timeout.
+    /// Used by run_tests_in_sandbox with the timeout from the project config.
+    ///
+    /// By default, all of the tests are run and the results of the tests that were not selected are removed.
+    /// Plugins whose test runners can select the tests to run should override this.
+    fn run_tests_filtered_with_timeout(
+        &self,
+        path: &Path,
+        filter: &TestFilter,
+        timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        let result = self.run_tests_with_timeout(path, timeout, sandbox)?;
+        Ok(filter.filter_run_result(result))
+    }
+
     /// Runs the tests for the exercise with the given timeout.
-    /// Used by run_tests with the timeout from the project config.
+    /// Used by run_tests_in_sandbox with the timeout from the project config.
+    /// The commands that run the student's code should be created with `TmcCommand::piped_in` so that they are executed in the sandbox.
     fn run_tests_with_timeout(
         &self,
         path: &Path,
         timeout: Option<Duration>,
+        sandbox: Option<&Sandbox>,
     ) -> Result<RunResult, TmcError>;
 
     /// Run checkstyle or similar plugin to project if applicable, no-op by default
@@ -116,104 +141,13 @@ pub trait LanguagePlugin {
         let project_dir = Self::find_project_dir_in_archive(archive)?;
         log::debug!("Project dir in zip: {}", project_dir.display());
 
-        // extract config file if any
-        let tmc_project_yml_path = project_dir.join(".tmcproject.yml");
-        let tmc_project_yml_path_s = tmc_project_yml_path
-            .to_str()
-            .ok_or_else(|| TmcError::ProjectDirInvalidUtf8(project_dir.clone()))?;
-        if let Ok(mut file) = archive.by_path(tmc_project_yml_path_s) {
-            let target_path = target_location.join(".tmcproject.yml");
-            file_util::read_to_file(&mut file, target_path)?;
-        }
-        let policy = Self::StudentFilePolicy::new(target_location)?;
-
-        // used to clean non-student files not in the zip later
-        let mut files_from_archive = HashSet::new();
-        files_from_archive.insert(target_location.join(".tmcproject.yml")); // prevent cleaning .tmcproject.yml
-
-        let mut iter = archive.iter()?;
-        loop {
-            let next = iter.with_next::<(), _>(|mut file| {
-                let file_path = file.path()?;
-                if file_path == Path::new(tmc_project_yml_path_s) {
-                    // already extracted
-                    return Ok(Continue(()));
-                }
-
-                let relative = match file_path.strip_prefix(&project_dir) {
-                    Ok(relative) => relative,
-                    _ => {
-                        log::trace!("skip {}, not in project dir", file_path.display());
-                        return Ok(Continue(()));
-                    }
-                };
-                let path_in_target = target_location.join(relative);
-                log::trace!("processing {file_path:?} -> {path_in_target:?}");
-
-                files_from_archive.insert(path_in_target.clone());
-
-                if !path_in_target.exists() {
-                    // just extract
-                    if file.is_dir() {
-                        file_util::create_dir_all(path_in_target)?;
-                    } else {
-                        file_util::read_to_file(&mut file, path_in_target)?;
-                    }
-                } else if !policy.is_student_file(relative)
-                    || policy.is_updating_forced(relative)?
-                {
-                    // not student file, or forced update
-                    if file.is_file() {
-                        // remove old if dir
-                        if path_in_target.is_dir() {
-                            file_util::remove_dir_all(&path_in_target)?;
-                        }
-                        file_util::read_to_file(&mut file, path_in_target)?;
-                    }
-                }
-                Ok(Continue(()))
-            });
-            match next? {
-                Continue(_) => continue,
-                Break(_) => break,
-            }
-        }
-
-        if clean {
-            // delete non-student files that were not in archive
-            log::debug!("deleting non-student files not in archive");
-            for entry in WalkDir::new(target_location)
-                .into_iter()
-                .filter_map(|e| e.ok())
-            {
-                let relative = entry
-                    .path()
-                    .strip_prefix(target_location)
-                    .expect("all entries are inside target");
-                if !files_from_archive.contains(entry.path())
-                    && (policy.is_updating_forced(entry.path())?
-                        || !policy.is_student_file(relative))
-                {
-                    log::debug!(
-                        "rm {} {}",
-                        entry.path().display(),
-                        target_location.display()
-                    );
-                    if entry.path().is_dir() {
-                        // delete if empty
-                        if WalkDir::new(entry.path()).max_depth(1).into_iter().count() == 1 {
-                            log::debug!("deleting empty directory {}", entry.path().display());
-                            file_util::remove_dir_empty(entry.path())?;
-                        }
-                    } else {
-                        log::debug!("removing file {}", entry.path().display());
-                        file_util::remove_file(entry.path())?;
-                    }
-                }
-            }
-        }
-
-        Ok(())
+        extract_project_with_policy(
+            archive,
+            target_location,
+            clean,
+            &project_dir,
+            Self::StudentFilePolicy::new,
+        )
     }
 
     /// Extracts student files from the compressed project.
@@ -234,44 +168,7 @@ pub trait LanguagePlugin {
         log::debug!("Project directory in archive: {}", project_dir.display());
 
         let policy = Self::StudentFilePolicy::new(target_location)?;
-
-        let mut iter: ArchiveIterator<_> = archive.iter()?;
-        loop {
-            let next = iter.with_next::<(), _>(|mut file| {
-                // get the path where the file should be extracted
-                let file_path = file.path()?;
-                let relative = match file_path.strip_prefix(&project_dir) {
-                    Ok(relative) => relative,
-                    _ => {
-                        log::trace!("skip {}, not in project dir", file_path.display());
-                        return Ok(Continue(()));
-                    }
-                };
-                let path_in_target = target_location.join(relative);
-                log::trace!("processing {file_path:?} -> {path_in_target:?}");
-
-                if policy.is_student_file(relative) {
-                    if file.is_file() {
-                        // for files, everything should be removed out of the way
-                        file_util::remove_all(&path_in_target)?;
-                        file_util::read_to_file(&mut file, &path_in_target)?;
-                    } else {
-                        // for directories, we should keep existing directories but delete files at the same path
-                        if path_in_target.is_file() {
-                            file_util::remove_file(&path_in_target)?;
-                        }
-                        file_util::create_dir_all(&path_in_target)?;
-                    }
-                }
-                Ok(Continue(()))
-            });
-            match next? {
-                Continue(_) => continue,
-                Break(_) => break,
-            }
-        }
-
-        Ok(())
+        extract_student_files_with_policy(&mut archive, &project_dir, target_location, &policy)
     }
 
     /// Searches the zip for a valid project directory.
@@ -291,71 +188,7 @@ pub trait LanguagePlugin {
     /// 3) If archive root has only one folder, use that folder
     /// 4) Archive root (empty path)
     fn safe_find_project_dir_in_archive<R: Read + Seek>(archive: &mut Archive<R>) -> PathBuf {
-        // 1) Try plugin-specific project dir detection first
-        if let Ok(dir) = Self::find_project_dir_in_archive(archive) {
-            return dir;
-        }
-
-        // 2) Try to find the first directory that contains a .tmcproject.yml
-        if let Ok(mut iter) = archive.iter() {
-            loop {
-                let next = iter.with_next(|file| {
-                    let file_path = file.path()?;
-                    if file.is_file()
-                        && file_path
-                            .file_name()
-                            .map(|name| name == OsStr::new(".tmcproject.yml"))
-                            .unwrap_or(false)
-                    {
-                        let parent = file_path
-                            .parent()
-                            .map(PathBuf::from)
-                            .unwrap_or_else(|| PathBuf::from(""));
-                        return Ok(Break(Some(parent)));
-                    }
-                    Ok(Continue(()))
-                });
-                match next {
-                    Ok(Continue(_)) => continue,
-                    Ok(Break(Some(dir))) => return dir,
-                    Ok(Break(None)) => break,
-                    Err(_) => break,
-                }
-            }
-        }
-
-        // 3) Check if archive root has only one folder. This is the format tmc-langs-cli sends submissions, so all official clients should use this format.
-        if let Ok(mut iter) = archive.iter() {
-            let mut root_entries = HashSet::<OsString>::new();
-            loop {
-                let next = iter.with_next::<(), _>(|file| {
-                    let file_path = file.path()?;
-                    if let Some(first_component) = file_path.iter().next() {
-                        root_entries.insert(first_component.to_os_string());
-                    }
-                    Ok(Continue(()))
-                });
-                match next {
-                    Ok(Continue(_)) => continue,
-                    Ok(Break(_)) => break,
-                    Err(_) => break,
-                }
-            }
-
-            // If there's exactly one folder at the root and no files, skip over it
-            // Special case: don't skip over certain folders
-            let excluded_folders = [OsStr::new("src")];
-
-            if root_entries.len() == 1 {
-                let only = root_entries.iter().next().expect("len is 1");
-                if !excluded_folders.contains(&only.as_os_str()) {
-                    return PathBuf::from(only);
-                }
-            }
-        }
-
-        // 4) Default to archive root
-        PathBuf::from("")
+        safe_find_project_dir_in_archive_with(archive, Self::find_project_dir_in_archive)
     }
 
     /// Tells if there's a valid exercise in this archive.
@@ -373,29 +206,7 @@ pub trait LanguagePlugin {
         path: &Path,
     ) -> Result<ExercisePackagingConfiguration, TmcError> {
         let policy = Self::StudentFilePolicy::new(path)?;
-        let mut config = ExercisePackagingConfiguration {
-            student_file_paths: HashSet::new(),
-            exercise_file_paths: HashSet::new(),
-        };
-        for entry in WalkDir::new(path).min_depth(1) {
-            let entry = entry?;
-            if entry.metadata()?.is_dir() {
-                continue;
-            }
-
-            let path = entry
-                .path()
-                .strip_prefix(path)
-                .expect("All entries are within path")
-                .to_path_buf();
-            if policy.is_student_file(&path) {
-                config.student_file_paths.insert(path);
-            } else {
-                config.exercise_file_paths.insert(path);
-            }
-        }
-
-        Ok(config)
+        get_exercise_packaging_configuration_with_policy(path, &policy)
     }
 
     /// Runs clean command e.g `make clean` for make or `mvn clean` for maven.
@@ -405,6 +216,11 @@ pub trait LanguagePlugin {
 
     fn get_default_exercise_file_paths() -> Vec<PathBuf>;
 
+    /// The comment syntax described by Self::LINE_COMMENT and Self::BLOCK_COMMENT.
+    fn comment_syntax() -> CommentSyntax {
+        CommentSyntax::new(Some(Self::LINE_COMMENT), Self::BLOCK_COMMENT)
+    }
+
     /// Parses exercise files using Self::LINE_COMMENT and Self::BLOCK_COMMENT to filter out comments and Self::points_parser to parse points from the actual code.
     fn get_available_points(exercise_path: &Path) -> Result<Vec<String>, TmcError> {
         let config = Self::get_exercise_packaging_configuration(exercise_path)?;
@@ -507,6 +323,297 @@ pub trait LanguagePlugin {
     fn points_parser(i: &str) -> IResult<&str, Vec<&str>, VerboseError<&str>>;
 }
 
+/// Extracts the project at project_dir in the archive to the target location, using the policy created by new_policy to decide which files to overwrite.
+/// The policy is created after the project's .tmcproject.yml has been extracted.
+/// Used by `LanguagePlugin::extract_project`, plugins that are only known at runtime can use it directly.
+pub fn extract_project_with_policy<R: Read + Seek, P: StudentFilePolicy>(
+    archive: &mut Archive<R>,
+    target_location: &Path,
+    clean: bool,
+    project_dir: &Path,
+    new_policy: impl FnOnce(&Path) -> Result<P, TmcError>,
+) -> Result<(), TmcError> {
+    // extract config file if any
+    let tmc_project_yml_path = project_dir.join(".tmcproject.yml");
+    let tmc_project_yml_path_s = tmc_project_yml_path
+        .to_str()
+        .ok_or_else(|| TmcError::ProjectDirInvalidUtf8(project_dir.to_path_buf()))?;
+    if let Ok(mut file) = archive.by_path(tmc_project_yml_path_s) {
+        let target_path = target_location.join(".tmcproject.yml");
+        file_util::read_to_file(&mut file, target_path)?;
+    }
+    let policy = new_policy(target_location)?;
+
+    // used to clean non-student files not in the zip later
+    let mut files_from_archive = HashSet::new();
+    files_from_archive.insert(target_location.join(".tmcproject.yml")); // prevent cleaning .tmcproject.yml
+
+    let mut iter = archive.iter()?;
+    loop {
+        let next = iter.with_next::<(), _>(|mut file| {
+            let file_path = file.path()?;
+            if file_path == Path::new(tmc_project_yml_path_s) {
+                // already extracted
+                return Ok(Continue(()));
+            }
+
+            let relative = match file_path.strip_prefix(project_dir) {
+                Ok(relative) => relative,
+                _ => {
+                    log::trace!("skip {}, not in project dir", file_path.display());
+                    return Ok(Continue(()));
+                }
+            };
+            let path_in_target = target_location.join(relative);
+            log::trace!("processing {file_path:?} -> {path_in_target:?}");
+
+            files_from_archive.insert(path_in_target.clone());
+
+            if !path_in_target.exists() {
+                // just extract
+                if file.is_dir() {
+                    file_util::create_dir_all(path_in_target)?;
+                } else {
+                    file_util::read_to_file(&mut file, path_in_target)?;
+                }
+            } else if !policy.is_student_file(relative) || policy.is_updating_forced(relative)? {
+                // not student file, or forced update
+                if file.is_file() {
+                    // remove old if dir
+                    if path_in_target.is_dir() {
+                        file_util::remove_dir_all(&path_in_target)?;
+                    }
+                    file_util::read_to_file(&mut file, path_in_target)?;
+                }
+            }
+            Ok(Continue(()))
+        });
+        match next? {
+            Continue(_) => continue,
+            Break(_) => break,
+        }
+    }
+
+    if clean {
+        // delete non-student files that were not in archive
+        log::debug!("deleting non-student files not in archive");
+        for entry in WalkDir::new(target_location)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let relative = entry
+                .path()
+                .strip_prefix(target_location)
+                .expect("all entries are inside target");
+            if !files_from_archive.contains(entry.path())
+                && (policy.is_updating_forced(entry.path())? || !policy.is_student_file(relative))
+            {
+                log::debug!(
+                    "rm {} {}",
+                    entry.path().display(),
+                    target_location.display()
+                );
+                if entry.path().is_dir() {
+                    // delete if empty
+                    if WalkDir::new(entry.path()).max_depth(1).into_iter().count() == 1 {
+                        log::debug!("deleting empty directory {}", entry.path().display());
+                        file_util::remove_dir_empty(entry.path())?;
+                    }
+                } else {
+                    log::debug!("removing file {}", entry.path().display());
+                    file_util::remove_file(entry.path())?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Extracts the student files of the project at project_dir in the archive to the target location, overwriting all files.
+/// Used by `LanguagePlugin::extract_student_files`, plugins that are only known at runtime can use it directly.
+pub fn extract_student_files_with_policy<R: Read + Seek, P: StudentFilePolicy>(
+    archive: &mut Archive<R>,
+    project_dir: &Path,
+    target_location: &Path,
+    policy: &P,
+) -> Result<(), TmcError> {
+    let mut iter: ArchiveIterator<_> = archive.iter()?;
+    loop {
+        let next = iter.with_next::<(), _>(|mut file| {
+            // get the path where the file should be extracted
+            let file_path = file.path()?;
+            let relative = match file_path.strip_prefix(project_dir) {
+                Ok(relative) => relative,
+                _ => {
+                    log::trace!("skip {}, not in project dir", file_path.display());
+                    return Ok(Continue(()));
+                }
+            };
+            let path_in_target = target_location.join(relative);
+            log::trace!("processing {file_path:?} -> {path_in_target:?}");
+
+            if policy.is_student_file(relative) {
+                if file.is_file() {
+                    // for files, everything should be removed out of the way
+                    file_util::remove_all(&path_in_target)?;
+                    file_util::read_to_file(&mut file, &path_in_target)?;
+                } else {
+                    // for directories, we should keep existing directories but delete files at the same path
+                    if path_in_target.is_file() {
+                        file_util::remove_file(&path_in_target)?;
+                    }
+                    file_util::create_dir_all(&path_in_target)?;
+                }
+            }
+            Ok(Continue(()))
+        });
+        match next? {
+            Continue(_) => continue,
+            Break(_) => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the project directory in the archive with find_project_dir, falling back to the heuristics described in `LanguagePlugin::safe_find_project_dir_in_archive`.
+pub fn safe_find_project_dir_in_archive_with<R: Read + Seek>(
+    archive: &mut Archive<R>,
+    find_project_dir: impl FnOnce(&mut Archive<R>) -> Result<PathBuf, TmcError>,
+) -> PathBuf {
+    // 1) Try plugin-specific project dir detection first
+    if let Ok(dir) = find_project_dir(archive) {
+        return dir;
+    }
+
+    // 2) Try to find the first directory that contains a .tmcproject.yml
+    if let Ok(mut iter) = archive.iter() {
+        loop {
+            let next = iter.with_next(|file| {
+                let file_path = file.path()?;
+                if file.is_file()
+                    && file_path
+                        .file_name()
+                        .map(|name| name == OsStr::new(".tmcproject.yml"))
+                        .unwrap_or(false)
+                {
+                    let parent = file_path
+                        .parent()
+                        .map(PathBuf::from)
+                        .unwrap_or_else(|| PathBuf::from(""));
+                    return Ok(Break(Some(parent)));
+                }
+                Ok(Continue(()))
+            });
+            match next {
+                Ok(Continue(_)) => continue,
+                Ok(Break(Some(dir))) => return dir,
+                Ok(Break(None)) => break,
+                Err(_) => break,
+            }
+        }
+    }
+
+    // 3) Check if archive root has only one folder. This is the format tmc-langs-cli sends submissions, so all official clients should use this format.
+    if let Ok(mut iter) = archive.iter() {
+        let mut root_entries = HashSet::<OsString>::new();
+        loop {
+            let next = iter.with_next::<(), _>(|file| {
+                let file_path = file.path()?;
+                if let Some(first_component) = file_path.iter().next() {
+                    root_entries.insert(first_component.to_os_string());
+                }
+                Ok(Continue(()))
+            });
+            match next {
+                Ok(Continue(_)) => continue,
+                Ok(Break(_)) => break,
+                Err(_) => break,
+            }
+        }
+
+        // If there's exactly one folder at the root and no files, skip over it
+        // Special case: don't skip over certain folders
+        let excluded_folders = [OsStr::new("src")];
+
+        if root_entries.len() == 1 {
+            let only = root_entries.iter().next().expect("len is 1");
+            if !excluded_folders.contains(&only.as_os_str()) {
+                return PathBuf::from(only);
+            }
+        }
+    }
+
+    // 4) Default to archive root
+    PathBuf::from("")
+}
+
+/// Sorts the files in the exercise directory into student and exercise files using the given policy.
+pub fn get_exercise_packaging_configuration_with_policy<P: StudentFilePolicy>(
+    path: &Path,
+    policy: &P,
+) -> Result<ExercisePackagingConfiguration, TmcError> {
+    let mut config = ExercisePackagingConfiguration {
+        student_file_paths: HashSet::new(),
+        exercise_file_paths: HashSet::new(),
+    };
+    for entry in WalkDir::new(path).min_depth(1) {
+        let entry = entry?;
+        if entry.metadata()?.is_dir() {
+            continue;
+        }
+
+        let path = entry
+            .path()
+            .strip_prefix(path)
+            .expect("All entries are within path")
+            .to_path_buf();
+        if policy.is_student_file(&path) {
+            config.student_file_paths.insert(path);
+        } else {
+            config.exercise_file_paths.insert(path);
+        }
+    }
+
+    Ok(config)
+}
+
+/// Reads the tests timeout from the project config.
+/// Used by `LanguagePlugin::run_tests_in_sandbox`, plugins that are only known at runtime can use it directly.
+pub fn tests_timeout<P: StudentFilePolicy>(path: &Path) -> Result<Option<Duration>, TmcError> {
+    let timeout = P::new(path)?
+        .get_project_config()
+        .tests_timeout_ms
+        .map(Into::into)
+        .map(Duration::from_millis);
+    Ok(timeout)
+}
+
+/// Overrides a passing run result without any test results with a failing one, with the message as the reason.
+/// Used by `LanguagePlugin::run_tests_in_sandbox`, plugins that are only known at runtime can use it directly.
+pub fn fail_if_no_tests(result: RunResult, message: &str) -> RunResult {
+    if result.status == RunStatus::Passed && result.test_results.is_empty() {
+        RunResult {
+            status: RunStatus::TestsFailed,
+            test_results: vec![TestResult {
+                name: "Tests found test".to_string(),
+                successful: false,
+                points: vec![],
+                message: message.to_string(),
+                exception: vec![],
+                duration_ms: None,
+                stdout: None,
+                stderr: None,
+            }],
+            logs: result.logs,
+            execution_stats: result.execution_stats,
+        }
+    } else {
+        result
+    }
+}
+
 #[derive(Debug, Clone)]
 enum Parse {
     LineComment,
@@ -520,7 +627,7 @@ enum Parse {
 mod test {
     use super::*;
     use crate::test_helpers::{MockPlugin, SimpleMockPlugin};
-    use std::io::Write;
+    use std::{collections::HashMap, io::Write};
     use zip::{ZipWriter, write::SimpleFileOptions};
 
     fn init() {
@@ -643,6 +750,59 @@ extra_exercise_files:
         assert_eq!(res.test_results[0].name, "Tests found test")
     }
 
+    #[test]
+    fn filtered_run_without_matches_is_err() {
+        init();
+        let plugin = MockPlugin {};
+        let filter = TestFilter::new(vec!["missing".to_string()], vec![]);
+        let res = plugin.run_tests_filtered(Path::new(""), &filter).unwrap();
+        assert_eq!(res.status, RunStatus::TestsFailed);
+        assert_eq!(res.test_results[0].message, "No tests matched the filter.");
+    }
+
+    #[test]
+    fn filters_run_result() {
+        init();
+
+        let test_result = |name: &str, successful: bool, points: &[&str]| TestResult {
+            name: name.to_string(),
+            successful,
+            points: points.iter().map(|p| p.to_string()).collect(),
+            message: String::new(),
+            exception: vec![],
+            duration_ms: None,
+            stdout: None,
+            stderr: None,
+        };
+        let run_result = || RunResult {
+            status: RunStatus::TestsFailed,
+            test_results: vec![
+                test_result("Tests first", true, &["1.1"]),
+                test_result("Tests second", false, &[]),
+                test_result("Other third", true, &["1.2"]),
+            ],
+            logs: HashMap::new(),
+            execution_stats: None,
+        };
+
+        let filter = TestFilter::new(vec!["first".to_string()], vec!["1.2".to_string()]);
+        let filtered = filter.filter_run_result(run_result());
+        assert_eq!(filtered.status, RunStatus::Passed);
+        let names: Vec<_> = filtered.test_results.iter().map(|t| &t.name).collect();
+        assert_eq!(names, ["Tests first", "Other third"]);
+
+        let filter = TestFilter::new(vec!["Tests".to_string()], vec![]);
+        let filtered = filter.filter_run_result(run_result());
+        assert_eq!(filtered.status, RunStatus::TestsFailed);
+        assert_eq!(filtered.test_results.len(), 2);
+
+        // failures that are not caused by the selected tests are kept
+        let filter = TestFilter::new(vec!["missing".to_string()], vec![]);
+        let filtered = filter.filter_run_result(run_result());
+        assert_eq!(filtered.status, RunStatus::TestsFailed);
+        assert_eq!(filtered.test_results.len(), 3);
+    }
+
     #[test]
     fn gets_available_points() {
         init();
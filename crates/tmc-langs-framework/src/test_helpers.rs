@@ -2,6 +2,7 @@
 
 use crate::{
     Archive, TmcProjectYml,
+    command::Sandbox,
     domain::{ExerciseDesc, RunResult, RunStatus},
     error::TmcError,
     plugin::LanguagePlugin,
@@ -76,11 +77,13 @@ impl LanguagePlugin for MockPlugin {
         &self,
         _path: &Path,
         _timeout: Option<Duration>,
+        _sandbox: Option<&Sandbox>,
     ) -> Result<RunResult, TmcError> {
         Ok(RunResult {
             status: RunStatus::Passed,
             test_results: vec![],
             logs: std::collections::HashMap::new(),
+            execution_stats: None,
         })
     }
 
@@ -182,11 +185,13 @@ impl LanguagePlugin for SimpleMockPlugin {
         &self,
         _path: &Path,
         _timeout: Option<Duration>,
+        _sandbox: Option<&Sandbox>,
     ) -> Result<RunResult, TmcError> {
         Ok(RunResult {
             status: RunStatus::Passed,
             test_results: vec![],
             logs: std::collections::HashMap::new(),
+            execution_stats: None,
         })
     }
 
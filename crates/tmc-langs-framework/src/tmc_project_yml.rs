@@ -1,11 +1,12 @@
 //! Contains a struct that models the .tmcproject.yml file.
 
-use crate::TmcError;
+use crate::{CommentSyntax, SandboxLimits, TmcError};
 use serde::{
     Deserialize, Deserializer, Serialize,
     de::{Error, Visitor},
 };
 use std::{
+    collections::BTreeMap,
     fmt::{self, Display},
     path::{Path, PathBuf},
 };
@@ -63,10 +64,21 @@ pub struct TmcProjectYml {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sandbox_image: Option<String>,
 
+    /// Resource limits for running the tests in a sandbox.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sandbox_limits: Option<SandboxLimits>,
+
     /// Overrides the default archive size limit (500 Mb).
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub submission_size_limit_mb: Option<u32>,
+
+    /// Maps file extensions to the comment syntaxes used to find annotations such as `BEGIN SOLUTION` in files with them.
+    /// Overrides the built-in and language plugin syntaxes for the extension.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub comment_syntaxes: BTreeMap<String, CommentSyntax>,
 }
 
 impl TmcProjectYml {
@@ -126,10 +138,16 @@ impl TmcProjectYml {
             fail_on_valgrind_error: old.fail_on_valgrind_error.or(with.fail_on_valgrind_error),
             minimum_python_version: old.minimum_python_version.or(with.minimum_python_version),
             sandbox_image: old.sandbox_image.or(with.sandbox_image),
+            sandbox_limits: old.sandbox_limits.or(with.sandbox_limits),
             no_tests: old.no_tests.or(with.no_tests),
             submission_size_limit_mb: old
                 .submission_size_limit_mb
                 .or(with.submission_size_limit_mb),
+            comment_syntaxes: if old.comment_syntaxes.is_empty() {
+                with.comment_syntaxes
+            } else {
+                old.comment_syntaxes
+            },
         };
         *self = new;
     }
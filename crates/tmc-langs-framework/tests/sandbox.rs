@@ -0,0 +1,71 @@
+//! Runs commands in the sandbox. Requires bubblewrap, run with `cargo test -- --ignored`.
+//! The directories that should not be visible in the sandbox are created in Cargo's temporary directory for integration tests,
+//! as the sandbox has its own `/tmp`.
+
+#![cfg(target_os = "linux")]
+
+use tempfile::TempDir;
+use tmc_langs_framework::{Sandbox, SandboxLimits, TmcCommand};
+
+fn init() {
+    use log::*;
+    use simple_logger::*;
+    let _ = SimpleLogger::new().with_level(LevelFilter::Debug).init();
+}
+
+// fails instead of skipping the test so that an ignored test is not reported as passed without bwrap
+fn assert_bwrap_available() {
+    let available = TmcCommand::piped("bwrap")
+        .with(|e| e.args(&["--ro-bind", "/", "/", "true"]))
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or_default();
+    assert!(available, "bwrap is not available in this environment");
+}
+
+// a directory outside of the sandbox, removed when dropped
+fn outside_dir() -> TempDir {
+    tempfile::tempdir_in(env!("CARGO_TARGET_TMPDIR")).unwrap()
+}
+
+#[test]
+#[ignore = "requires bwrap"]
+fn restricts_writes() {
+    init();
+    assert_bwrap_available();
+
+    let temp = tempfile::tempdir().unwrap();
+    let outside = outside_dir();
+    let sandbox = Sandbox::new(temp.path(), SandboxLimits::default()).unwrap();
+    TmcCommand::piped_in("sh", Some(&sandbox))
+        .with(|e| {
+            e.arg("-c").arg(format!(
+                "touch {}/inside; touch {}/outside",
+                temp.path().display(),
+                outside.path().display()
+            ))
+        })
+        .output()
+        .unwrap();
+    assert!(temp.path().join("inside").exists());
+    assert!(!outside.path().join("outside").exists());
+}
+
+#[test]
+#[ignore = "requires bwrap"]
+fn hides_files_outside_toolchains() {
+    init();
+    assert_bwrap_available();
+
+    let temp = tempfile::tempdir().unwrap();
+    let outside = outside_dir();
+    let secret = outside.path().join("credentials.json");
+    std::fs::write(&secret, "secret").unwrap();
+    let sandbox = Sandbox::new(temp.path(), SandboxLimits::default()).unwrap();
+    let output = TmcCommand::piped_in("cat", Some(&sandbox))
+        .with(|e| e.arg(&secret))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("secret"));
+}
@@ -12,19 +12,23 @@ use std::{
     path::{Path, PathBuf},
 };
 pub use tmc_langs_csharp::CSharpPlugin;
-use tmc_langs_framework::{Archive, LanguagePlugin, TmcError};
+pub use tmc_langs_external::ExternalPlugin;
+use tmc_langs_framework::{Archive, CommentSyntax, LanguagePlugin, Sandbox, TmcError};
 pub use tmc_langs_framework::{
     Compression, ExerciseDesc, ExercisePackagingConfiguration, Language,
     NothingIsStudentFilePolicy, RunResult, StudentFilePolicy, StyleValidationResult,
-    StyleValidationStrategy,
+    StyleValidationStrategy, TestFilter,
 };
 // the Java plugin is disabled on musl
+pub use tmc_langs_go::GoPlugin;
 #[cfg(not(target_env = "musl"))]
-pub use tmc_langs_java::{AntPlugin, MavenPlugin};
+pub use tmc_langs_java::{AntPlugin, GradlePlugin, MavenPlugin};
 pub use tmc_langs_make::MakePlugin;
+pub use tmc_langs_node::NodePlugin;
 pub use tmc_langs_notests::NoTestsPlugin;
 pub use tmc_langs_python3::Python3Plugin;
 pub use tmc_langs_r::RPlugin;
+pub use tmc_langs_rust::RustPlugin;
 
 /// Finds the correct language plug-in for the given exercise path and calls `LanguagePlugin::extract_project`,
 /// If no language plugin matches, see `extract_project_overwrite`.
@@ -74,13 +78,20 @@ pub fn compress_project(
 /// Enum containing variants for each language plugin.
 pub enum Plugin {
     CSharp(CSharpPlugin),
+    External(&'static ExternalPlugin),
+    Go(GoPlugin),
     Make(MakePlugin),
     // the Java plugin is disabled on musl
     #[cfg(not(target_env = "musl"))]
+    Gradle(GradlePlugin),
+    // the Java plugin is disabled on musl
+    #[cfg(not(target_env = "musl"))]
     Maven(MavenPlugin),
+    Node(NodePlugin),
     NoTests(NoTestsPlugin),
     Python3(Python3Plugin),
     R(RPlugin),
+    Rust(RustPlugin),
     // the Java plugin is disabled on musl
     #[cfg(not(target_env = "musl"))]
     Ant(AntPlugin),
@@ -92,9 +103,16 @@ impl Plugin {
         let plugin = match PluginType::from_exercise(path)? {
             PluginType::NoTests => Plugin::NoTests(NoTestsPlugin::new()),
             PluginType::CSharp => Plugin::CSharp(CSharpPlugin::new()),
+            PluginType::External(plugin) => Plugin::External(plugin),
             PluginType::Make => Plugin::Make(MakePlugin::new()),
             PluginType::Python3 => Plugin::Python3(Python3Plugin::new()),
             PluginType::R => Plugin::R(RPlugin::new()),
+            PluginType::Rust => Plugin::Rust(RustPlugin::new()),
+            PluginType::Node => Plugin::Node(NodePlugin::new()),
+            PluginType::Go => Plugin::Go(GoPlugin::new()),
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
+            PluginType::Gradle => Plugin::Gradle(GradlePlugin::new()?),
             // the Java plugin is disabled on musl
             #[cfg(not(target_env = "musl"))]
             PluginType::Maven => Plugin::Maven(MavenPlugin::new()?),
@@ -108,13 +126,20 @@ impl Plugin {
     pub fn clean(&self, path: &Path) -> Result<(), TmcError> {
         match self {
             Plugin::CSharp(plugin) => plugin.clean(path),
+            Plugin::External(plugin) => plugin.clean(path),
+            Plugin::Go(plugin) => plugin.clean(path),
             Plugin::Make(plugin) => plugin.clean(path),
             // the Java plugin is disabled on musl
             #[cfg(not(target_env = "musl"))]
+            Plugin::Gradle(plugin) => plugin.clean(path),
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
             Plugin::Maven(plugin) => plugin.clean(path),
+            Plugin::Node(plugin) => plugin.clean(path),
             Plugin::NoTests(plugin) => plugin.clean(path),
             Plugin::Python3(plugin) => plugin.clean(path),
             Plugin::R(plugin) => plugin.clean(path),
+            Plugin::Rust(plugin) => plugin.clean(path),
             // the Java plugin is disabled on musl
             #[cfg(not(target_env = "musl"))]
             Plugin::Ant(plugin) => plugin.clean(path),
@@ -128,13 +153,20 @@ impl Plugin {
     ) -> Result<ExerciseDesc, TmcError> {
         match self {
             Plugin::CSharp(plugin) => plugin.scan_exercise(path, exercise_name),
+            Plugin::External(plugin) => plugin.scan_exercise(path, exercise_name),
+            Plugin::Go(plugin) => plugin.scan_exercise(path, exercise_name),
             Plugin::Make(plugin) => plugin.scan_exercise(path, exercise_name),
             // the Java plugin is disabled on musl
             #[cfg(not(target_env = "musl"))]
+            Plugin::Gradle(plugin) => plugin.scan_exercise(path, exercise_name),
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
             Plugin::Maven(plugin) => plugin.scan_exercise(path, exercise_name),
+            Plugin::Node(plugin) => plugin.scan_exercise(path, exercise_name),
             Plugin::NoTests(plugin) => plugin.scan_exercise(path, exercise_name),
             Plugin::Python3(plugin) => plugin.scan_exercise(path, exercise_name),
             Plugin::R(plugin) => plugin.scan_exercise(path, exercise_name),
+            Plugin::Rust(plugin) => plugin.scan_exercise(path, exercise_name),
             // the Java plugin is disabled on musl
             #[cfg(not(target_env = "musl"))]
             Plugin::Ant(plugin) => plugin.scan_exercise(path, exercise_name),
@@ -144,19 +176,82 @@ impl Plugin {
     pub fn run_tests(&self, path: &Path) -> Result<RunResult, TmcError> {
         match self {
             Plugin::CSharp(plugin) => plugin.run_tests(path),
+            Plugin::External(plugin) => plugin.run_tests(path),
+            Plugin::Go(plugin) => plugin.run_tests(path),
             Plugin::Make(plugin) => plugin.run_tests(path),
             // the Java plugin is disabled on musl
             #[cfg(not(target_env = "musl"))]
+            Plugin::Gradle(plugin) => plugin.run_tests(path),
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
             Plugin::Maven(plugin) => plugin.run_tests(path),
+            Plugin::Node(plugin) => plugin.run_tests(path),
             Plugin::NoTests(plugin) => plugin.run_tests(path),
             Plugin::Python3(plugin) => plugin.run_tests(path),
             Plugin::R(plugin) => plugin.run_tests(path),
+            Plugin::Rust(plugin) => plugin.run_tests(path),
             // the Java plugin is disabled on musl
             #[cfg(not(target_env = "musl"))]
             Plugin::Ant(plugin) => plugin.run_tests(path),
         }
     }
 
+    pub fn run_tests_filtered(
+        &self,
+        path: &Path,
+        filter: &TestFilter,
+    ) -> Result<RunResult, TmcError> {
+        match self {
+            Plugin::CSharp(plugin) => plugin.run_tests_filtered(path, filter),
+            Plugin::External(plugin) => plugin.run_tests_filtered(path, filter),
+            Plugin::Go(plugin) => plugin.run_tests_filtered(path, filter),
+            Plugin::Make(plugin) => plugin.run_tests_filtered(path, filter),
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
+            Plugin::Gradle(plugin) => plugin.run_tests_filtered(path, filter),
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
+            Plugin::Maven(plugin) => plugin.run_tests_filtered(path, filter),
+            Plugin::Node(plugin) => plugin.run_tests_filtered(path, filter),
+            Plugin::NoTests(plugin) => plugin.run_tests_filtered(path, filter),
+            Plugin::Python3(plugin) => plugin.run_tests_filtered(path, filter),
+            Plugin::R(plugin) => plugin.run_tests_filtered(path, filter),
+            Plugin::Rust(plugin) => plugin.run_tests_filtered(path, filter),
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
+            Plugin::Ant(plugin) => plugin.run_tests_filtered(path, filter),
+        }
+    }
+
+    /// Runs the tests selected by the filter, in the sandbox if one is given.
+    pub fn run_tests_in_sandbox(
+        &self,
+        path: &Path,
+        filter: &TestFilter,
+        sandbox: Option<&Sandbox>,
+    ) -> Result<RunResult, TmcError> {
+        match self {
+            Plugin::CSharp(plugin) => plugin.run_tests_in_sandbox(path, filter, sandbox),
+            Plugin::External(plugin) => plugin.run_tests_in_sandbox(path, filter, sandbox),
+            Plugin::Go(plugin) => plugin.run_tests_in_sandbox(path, filter, sandbox),
+            Plugin::Make(plugin) => plugin.run_tests_in_sandbox(path, filter, sandbox),
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
+            Plugin::Gradle(plugin) => plugin.run_tests_in_sandbox(path, filter, sandbox),
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
+            Plugin::Maven(plugin) => plugin.run_tests_in_sandbox(path, filter, sandbox),
+            Plugin::Node(plugin) => plugin.run_tests_in_sandbox(path, filter, sandbox),
+            Plugin::NoTests(plugin) => plugin.run_tests_in_sandbox(path, filter, sandbox),
+            Plugin::Python3(plugin) => plugin.run_tests_in_sandbox(path, filter, sandbox),
+            Plugin::R(plugin) => plugin.run_tests_in_sandbox(path, filter, sandbox),
+            Plugin::Rust(plugin) => plugin.run_tests_in_sandbox(path, filter, sandbox),
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
+            Plugin::Ant(plugin) => plugin.run_tests_in_sandbox(path, filter, sandbox),
+        }
+    }
+
     pub fn check_code_style(
         &self,
         path: &Path,
@@ -164,13 +259,20 @@ impl Plugin {
     ) -> Result<Option<StyleValidationResult>, TmcError> {
         match self {
             Plugin::CSharp(plugin) => plugin.check_code_style(path, locale),
+            Plugin::External(plugin) => plugin.check_code_style(path, locale),
+            Plugin::Go(plugin) => plugin.check_code_style(path, locale),
             Plugin::Make(plugin) => plugin.check_code_style(path, locale),
             // the Java plugin is disabled on musl
             #[cfg(not(target_env = "musl"))]
+            Plugin::Gradle(plugin) => plugin.check_code_style(path, locale),
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
             Plugin::Maven(plugin) => plugin.check_code_style(path, locale),
+            Plugin::Node(plugin) => plugin.check_code_style(path, locale),
             Plugin::NoTests(plugin) => plugin.check_code_style(path, locale),
             Plugin::Python3(plugin) => plugin.check_code_style(path, locale),
             Plugin::R(plugin) => plugin.check_code_style(path, locale),
+            Plugin::Rust(plugin) => plugin.check_code_style(path, locale),
             // the Java plugin is disabled on musl
             #[cfg(not(target_env = "musl"))]
             Plugin::Ant(plugin) => plugin.check_code_style(path, locale),
@@ -182,13 +284,20 @@ impl Plugin {
 #[derive(Clone, Copy)]
 pub enum PluginType {
     CSharp,
+    External(&'static ExternalPlugin),
+    Go,
     Make,
     // the Java plugin is disabled on musl
     #[cfg(not(target_env = "musl"))]
+    Gradle,
+    // the Java plugin is disabled on musl
+    #[cfg(not(target_env = "musl"))]
     Maven,
+    Node,
     NoTests,
     Python3,
     R,
+    Rust,
     // the Java plugin is disabled on musl
     #[cfg(not(target_env = "musl"))]
     Ant,
@@ -198,13 +307,20 @@ macro_rules! delegate_plugin_type {
     ($self:ident, $($args:tt)*) => {
         match $self {
             Self::CSharp => CSharpPlugin::$($args)*,
+            Self::External(plugin) => plugin.$($args)*,
+            Self::Go => GoPlugin::$($args)*,
             Self::Make => MakePlugin::$($args)*,
             // the Java plugin is disabled on musl
             #[cfg(not(target_env = "musl"))]
+            Self::Gradle => GradlePlugin::$($args)*,
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
             Self::Maven => MavenPlugin::$($args)*,
+            Self::Node => NodePlugin::$($args)*,
             Self::NoTests => NoTestsPlugin::$($args)*,
             Self::Python3 => Python3Plugin::$($args)*,
             Self::R => RPlugin::$($args)*,
+            Self::Rust => RustPlugin::$($args)*,
             // the Java plugin is disabled on musl
             #[cfg(not(target_env = "musl"))]
             Self::Ant => AntPlugin::$($args)*,
@@ -224,11 +340,24 @@ impl PluginType {
             (Python3Plugin::PLUGIN_NAME, PluginType::Python3)
         } else if RPlugin::is_exercise_type_correct(path) {
             (RPlugin::PLUGIN_NAME, PluginType::R)
+        } else if RustPlugin::is_exercise_type_correct(path) {
+            (RustPlugin::PLUGIN_NAME, PluginType::Rust)
+        } else if NodePlugin::is_exercise_type_correct(path) {
+            (NodePlugin::PLUGIN_NAME, PluginType::Node)
+        } else if GoPlugin::is_exercise_type_correct(path) {
+            (GoPlugin::PLUGIN_NAME, PluginType::Go)
         } else {
+            // external plugins are checked after the built-in ones so that they cannot shadow them,
+            // except for Ant, which recognizes any project with src and test directories
             // the Java plugin is disabled on musl
             #[cfg(not(target_env = "musl"))]
             if MavenPlugin::is_exercise_type_correct(path) {
                 (MavenPlugin::PLUGIN_NAME, PluginType::Maven)
+            } else if GradlePlugin::is_exercise_type_correct(path) {
+                // projects that also have a pom.xml are kept as Maven projects
+                (GradlePlugin::PLUGIN_NAME, PluginType::Gradle)
+            } else if let Some(plugin) = external_plugin_for_exercise(path) {
+                (plugin.name(), PluginType::External(plugin))
             } else if AntPlugin::is_exercise_type_correct(path) {
                 // TODO: currently, ant needs to be last because any project with src and test are recognized as ant
                 (AntPlugin::PLUGIN_NAME, PluginType::Ant)
@@ -236,7 +365,11 @@ impl PluginType {
                 return Err(PluginError::PluginNotFound(path.to_path_buf()));
             }
             #[cfg(target_env = "musl")]
-            return Err(PluginError::PluginNotFound(path.to_path_buf()));
+            if let Some(plugin) = external_plugin_for_exercise(path) {
+                (plugin.name(), PluginType::External(plugin))
+            } else {
+                return Err(PluginError::PluginNotFound(path.to_path_buf()));
+            }
         };
         log::info!("Detected project at {} as {}", path.display(), plugin_name);
         Ok(plugin_type)
@@ -253,11 +386,24 @@ impl PluginType {
             (Python3Plugin::PLUGIN_NAME, PluginType::Python3)
         } else if RPlugin::is_archive_type_correct(archive) {
             (RPlugin::PLUGIN_NAME, PluginType::R)
+        } else if RustPlugin::is_archive_type_correct(archive) {
+            (RustPlugin::PLUGIN_NAME, PluginType::Rust)
+        } else if NodePlugin::is_archive_type_correct(archive) {
+            (NodePlugin::PLUGIN_NAME, PluginType::Node)
+        } else if GoPlugin::is_archive_type_correct(archive) {
+            (GoPlugin::PLUGIN_NAME, PluginType::Go)
         } else {
+            // external plugins are checked after the built-in ones so that they cannot shadow them,
+            // except for Ant, which recognizes any project with src and test directories
             // the Java plugin is disabled on musl
             #[cfg(not(target_env = "musl"))]
             if MavenPlugin::is_archive_type_correct(archive) {
                 (MavenPlugin::PLUGIN_NAME, PluginType::Maven)
+            } else if GradlePlugin::is_archive_type_correct(archive) {
+                // projects that also have a pom.xml are kept as Maven projects
+                (GradlePlugin::PLUGIN_NAME, PluginType::Gradle)
+            } else if let Some(plugin) = external_plugin_for_archive(archive) {
+                (plugin.name(), PluginType::External(plugin))
             } else if AntPlugin::is_archive_type_correct(archive) {
                 // TODO: currently, ant needs to be last because any project with src and test are recognized as ant
                 (AntPlugin::PLUGIN_NAME, PluginType::Ant)
@@ -265,7 +411,11 @@ impl PluginType {
                 return Err(PluginError::PluginNotFoundInArchive);
             }
             #[cfg(target_env = "musl")]
-            return Err(PluginError::PluginNotFoundInArchive);
+            if let Some(plugin) = external_plugin_for_archive(archive) {
+                (plugin.name(), PluginType::External(plugin))
+            } else {
+                return Err(PluginError::PluginNotFoundInArchive);
+            }
         };
         log::info!("Detected project in archive as {plugin_name}");
         Ok(plugin_type)
@@ -319,6 +469,58 @@ impl PluginType {
     pub fn get_available_points(self, exercise_path: &Path) -> Result<Vec<String>, TmcError> {
         delegate_plugin_type!(self, get_available_points(exercise_path))
     }
+
+    /// The extensions of the plugin's source files and the comment syntax used in them, see `LanguagePlugin::SOURCE_FILE_EXTENSIONS`.
+    /// External plugins declare them in their info.
+    pub fn source_file_comment_syntax(self) -> (Vec<&'static str>, CommentSyntax) {
+        fn of<P: LanguagePlugin>() -> (Vec<&'static str>, CommentSyntax) {
+            (P::SOURCE_FILE_EXTENSIONS.to_vec(), P::comment_syntax())
+        }
+
+        match self {
+            Self::CSharp => of::<CSharpPlugin>(),
+            Self::External(plugin) => (
+                plugin
+                    .source_file_extensions()
+                    .iter()
+                    .map(String::as_str)
+                    .collect(),
+                plugin.comment_syntax().clone(),
+            ),
+            Self::Go => of::<GoPlugin>(),
+            Self::Make => of::<MakePlugin>(),
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
+            Self::Gradle => of::<GradlePlugin>(),
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
+            Self::Maven => of::<MavenPlugin>(),
+            Self::Node => of::<NodePlugin>(),
+            Self::NoTests => of::<NoTestsPlugin>(),
+            Self::Python3 => of::<Python3Plugin>(),
+            Self::R => of::<RPlugin>(),
+            Self::Rust => of::<RustPlugin>(),
+            // the Java plugin is disabled on musl
+            #[cfg(not(target_env = "musl"))]
+            Self::Ant => of::<AntPlugin>(),
+        }
+    }
+}
+
+/// The first installed external plugin that recognizes the exercise.
+fn external_plugin_for_exercise(path: &Path) -> Option<&'static ExternalPlugin> {
+    tmc_langs_external::installed_plugins()
+        .iter()
+        .find(|p| p.is_exercise_type_correct(path))
+}
+
+/// The first installed external plugin that finds a project in the archive.
+fn external_plugin_for_archive<R: Read + Seek>(
+    archive: &mut Archive<R>,
+) -> Option<&'static ExternalPlugin> {
+    tmc_langs_external::installed_plugins()
+        .iter()
+        .find(|p| p.is_archive_type_correct(archive))
 }
 
 pub fn get_student_file_policy(path: &Path) -> Result<Box<dyn StudentFilePolicy>, PluginError> {
@@ -329,6 +531,7 @@ pub fn get_student_file_policy(path: &Path) -> Result<Box<dyn StudentFilePolicy>
         PluginType::CSharp => Box::new(<CSharpPlugin as LanguagePlugin>::StudentFilePolicy::new(
             path,
         )?),
+        PluginType::External(plugin) => Box::new(plugin.student_file_policy(path)?),
         PluginType::Make => Box::new(<MakePlugin as LanguagePlugin>::StudentFilePolicy::new(
             path,
         )?),
@@ -336,6 +539,18 @@ pub fn get_student_file_policy(path: &Path) -> Result<Box<dyn StudentFilePolicy>
             path,
         )?),
         PluginType::R => Box::new(<RPlugin as LanguagePlugin>::StudentFilePolicy::new(path)?),
+        PluginType::Rust => Box::new(<RustPlugin as LanguagePlugin>::StudentFilePolicy::new(
+            path,
+        )?),
+        PluginType::Node => Box::new(<NodePlugin as LanguagePlugin>::StudentFilePolicy::new(
+            path,
+        )?),
+        PluginType::Go => Box::new(<GoPlugin as LanguagePlugin>::StudentFilePolicy::new(path)?),
+        // the Java plugin is disabled on musl
+        #[cfg(not(target_env = "musl"))]
+        PluginType::Gradle => Box::new(<GradlePlugin as LanguagePlugin>::StudentFilePolicy::new(
+            path,
+        )?),
         // the Java plugin is disabled on musl
         #[cfg(not(target_env = "musl"))]
         PluginType::Maven => Box::new(<MavenPlugin as LanguagePlugin>::StudentFilePolicy::new(
@@ -1,5 +1,6 @@
 //! Handles the CLI's configuration files and credentials.
 
+pub mod credential_store;
 mod credentials;
 mod projects_config;
 mod tmc_config;
@@ -7,7 +8,7 @@ mod tmc_config;
 pub use self::{
     credentials::Credentials,
     projects_config::{ProjectsConfig, ProjectsDirTmcExercise, TmcCourseConfig},
-    tmc_config::TmcConfig,
+    tmc_config::{Profile, TmcConfig},
 };
 use crate::{TMC_LANGS_CONFIG_DIR_VAR, data::LocalTmcExercise, error::LangsError};
 use std::{
@@ -29,14 +30,15 @@ fn get_tmc_dir(client_name: &str) -> Result<PathBuf, LangsError> {
     Ok(config_dir.join(format!("tmc-{client_name}")))
 }
 
-/// Returns all of the exercises for the given course.
+/// Returns all of the exercises for the given course in the projects directory of the given profile.
 pub fn list_local_tmc_course_exercises(
     client_name: &str,
+    profile: Option<&str>,
     course_slug: &str,
 ) -> Result<Vec<LocalTmcExercise>, LangsError> {
     log::debug!("listing local course exercises of {course_slug} for {client_name}");
 
-    let projects_dir = TmcConfig::load(client_name)?.projects_dir;
+    let projects_dir = crate::get_projects_dir(client_name, profile)?;
     let mut projects_config = ProjectsConfig::load(&projects_dir)?;
 
     let exercises = projects_config
@@ -54,9 +56,10 @@ pub fn list_local_tmc_course_exercises(
     Ok(local_exercises)
 }
 
-/// Migrates an exercise from a location that's not managed by tmc-langs to the projects directory.
+/// Migrates an exercise from a location that's not managed by tmc-langs to the projects directory of the given profile.
 pub fn migrate_exercise(
     tmc_config: TmcConfig,
+    profile: Option<&str>,
     course_slug: &str,
     exercise_slug: &str,
     exercise_id: u32,
@@ -71,7 +74,8 @@ pub fn migrate_exercise(
 
     let mut lock = Lock::dir(exercise_path, LockOptions::Write)?;
     let _guard = lock.lock()?;
-    let mut projects_config = ProjectsConfig::load(&tmc_config.projects_dir)?;
+    let projects_dir = tmc_config.get_profile_projects_dir(profile)?;
+    let mut projects_config = ProjectsConfig::load(projects_dir)?;
     let course_config = projects_config
         .tmc_courses
         .entry(course_slug.to_string())
@@ -80,11 +84,8 @@ pub fn migrate_exercise(
             exercises: BTreeMap::new(),
         });
 
-    let target_dir = ProjectsConfig::get_tmc_exercise_download_target(
-        &tmc_config.projects_dir,
-        course_slug,
-        exercise_slug,
-    );
+    let target_dir =
+        ProjectsConfig::get_tmc_exercise_download_target(projects_dir, course_slug, exercise_slug);
     if target_dir.exists() {
         return Err(LangsError::DirectoryExists(target_dir));
     }
@@ -98,12 +99,17 @@ pub fn migrate_exercise(
     );
 
     super::move_dir(exercise_path, &target_dir)?;
-    course_config.save_to_projects_dir(&tmc_config.projects_dir)?;
+    course_config.save_to_projects_dir(projects_dir)?;
     Ok(())
 }
 
-/// Moves the projects directory from its current location to the target, taking all of the contained exercises with it.
-pub fn move_projects_dir(mut tmc_config: TmcConfig, target: PathBuf) -> Result<(), LangsError> {
+/// Moves the projects directory of the given profile from its current location to the target, taking all of the contained exercises with it.
+/// If the profile does not have its own projects directory, the default one is moved.
+pub fn move_projects_dir(
+    mut tmc_config: TmcConfig,
+    profile: Option<&str>,
+    target: PathBuf,
+) -> Result<(), LangsError> {
     log::debug!("moving projects dir to {}", target.display());
 
     if target.is_file() {
@@ -117,14 +123,14 @@ pub fn move_projects_dir(mut tmc_config: TmcConfig, target: PathBuf) -> Result<(
         .canonicalize()
         .map_err(|e| LangsError::Canonicalize(target.clone(), e))?;
     let prev_dir_canon = tmc_config
-        .projects_dir
+        .get_profile_projects_dir(profile)?
         .canonicalize()
         .map_err(|e| LangsError::Canonicalize(target.clone(), e))?;
     if target_canon == prev_dir_canon {
         return Err(LangsError::MovingProjectsDirToItself);
     }
 
-    let old_projects_dir = tmc_config.set_projects_dir(target.clone())?;
+    let old_projects_dir = tmc_config.set_profile_projects_dir(profile, target.clone())?;
 
     let mut lock = Lock::dir(old_projects_dir.clone(), LockOptions::Write)?;
     let _guard = lock.lock()?;
@@ -168,6 +174,8 @@ mod test {
         let tmc_config = TmcConfig {
             location: PathBuf::new(),
             projects_dir: projects_dir.path().to_path_buf(),
+            profiles: BTreeMap::new(),
+            credential_store: None,
             table: Table::new(),
         };
 
@@ -182,6 +190,7 @@ mod test {
 
         migrate_exercise(
             tmc_config,
+            None,
             "course",
             "exercise",
             0,
@@ -214,6 +223,8 @@ mod test {
         let tmc_config = TmcConfig {
             location: config_location,
             projects_dir: projects_dir.path().to_path_buf(),
+            profiles: BTreeMap::new(),
+            credential_store: None,
             table: Table::new(),
         };
 
@@ -230,7 +241,7 @@ mod test {
                 .exists()
         );
 
-        move_projects_dir(tmc_config, target_dir.path().to_path_buf()).unwrap();
+        move_projects_dir(tmc_config, None, target_dir.path().to_path_buf()).unwrap();
 
         assert!(
             target_dir
@@ -240,4 +251,52 @@ mod test {
         );
         assert!(!projects_dir.path().exists());
     }
+
+    #[test]
+    fn moves_profile_projects_dir() {
+        init();
+
+        let config_dir = tempfile::tempdir().unwrap();
+        let config_location = config_dir.path().join("tmc_config.temp");
+        let projects_dir = tempfile::tempdir().unwrap();
+        let profile_dir = tempfile::tempdir().unwrap();
+        let target_dir = tempfile::tempdir().unwrap();
+
+        let profile = Profile {
+            projects_dir: Some(profile_dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let tmc_config = TmcConfig {
+            location: config_location.clone(),
+            projects_dir: projects_dir.path().to_path_buf(),
+            profiles: BTreeMap::from([("staging".to_string(), profile)]),
+            credential_store: None,
+            table: Table::new(),
+        };
+
+        file_to(
+            profile_dir.path(),
+            "some course/some exercise/some file",
+            "",
+        );
+
+        move_projects_dir(tmc_config, Some("staging"), target_dir.path().to_path_buf()).unwrap();
+
+        assert!(
+            target_dir
+                .path()
+                .join("some course/some exercise/some file")
+                .exists()
+        );
+        assert!(!profile_dir.path().exists());
+        assert!(projects_dir.path().exists());
+        let tmc_config = TmcConfig::load_from("client", config_location).unwrap();
+        assert_eq!(
+            tmc_config
+                .get_profile_projects_dir(Some("staging"))
+                .unwrap(),
+            target_dir.path()
+        );
+        assert_eq!(tmc_config.get_projects_dir(), projects_dir.path());
+    }
 }
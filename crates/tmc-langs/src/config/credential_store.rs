@@ -0,0 +1,506 @@
+//! Contains the stores the credentials can be saved in.
+
+use super::TmcConfig;
+use crate::LangsError;
+use base64::Engine;
+use chacha20poly1305::{
+    AeadCore, ChaCha20Poly1305, KeyInit,
+    aead::{Aead, OsRng, rand_core::RngCore},
+};
+use serde::{Deserialize, Serialize};
+use std::{env, path::PathBuf};
+use tmc_langs_util::{
+    deserialize,
+    file_util::{self, Lock, LockOptions},
+};
+
+/// Selects the credential store, one of `secret-service`, `encrypted-file` or `plaintext-file`, overriding the `credential-store` setting.
+pub const CREDENTIAL_STORE_VAR: &str = "TMC_LANGS_CREDENTIAL_STORE";
+/// The passphrase used to encrypt the credentials file.
+pub const CREDENTIALS_PASSPHRASE_VAR: &str = "TMC_LANGS_CREDENTIALS_PASSPHRASE";
+
+const PLAINTEXT_FILE_NAME: &str = "credentials.json";
+const ENCRYPTED_FILE_NAME: &str = "credentials.enc.json";
+const SALT_LEN: usize = 16;
+
+/// The backends the credentials can be stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub enum CredentialBackend {
+    SecretService,
+    EncryptedFile,
+    PlaintextFile,
+}
+
+impl CredentialBackend {
+    const ALL: [Self; 3] = [
+        Self::SecretService,
+        Self::EncryptedFile,
+        Self::PlaintextFile,
+    ];
+
+    pub fn parse(name: &str) -> Result<Self, LangsError> {
+        match name {
+            "secret-service" => Ok(Self::SecretService),
+            "encrypted-file" => Ok(Self::EncryptedFile),
+            "plaintext-file" => Ok(Self::PlaintextFile),
+            other => Err(LangsError::InvalidCredentialStore(other.to_string())),
+        }
+    }
+
+    // the secure backend that is available, preferring the Secret Service, or the plaintext file if there is none
+    fn detect() -> Self {
+        Self::select(
+            Self::SecretService.is_usable(),
+            Self::EncryptedFile.is_usable(),
+        )
+    }
+
+    fn select(secret_service: bool, passphrase: bool) -> Self {
+        if secret_service {
+            Self::SecretService
+        } else if passphrase {
+            Self::EncryptedFile
+        } else {
+            Self::PlaintextFile
+        }
+    }
+
+    // whether the store can be opened without an error, used to find credentials left in other backends
+    fn is_usable(self) -> bool {
+        match self {
+            #[cfg(target_os = "linux")]
+            Self::SecretService => SecretServiceStore::is_available(),
+            #[cfg(not(target_os = "linux"))]
+            Self::SecretService => false,
+            Self::EncryptedFile => env::var(CREDENTIALS_PASSPHRASE_VAR).is_ok(),
+            Self::PlaintextFile => true,
+        }
+    }
+}
+
+/// Storage for the serialized credentials of a client.
+pub trait CredentialStore {
+    /// Returns the stored secret, if any.
+    fn load(&self) -> Result<Option<Vec<u8>>, LangsError>;
+
+    /// Replaces the stored secret.
+    fn save(&self, secret: &[u8]) -> Result<(), LangsError>;
+
+    /// Removes the stored secret. Does nothing if there is none.
+    fn remove(&self) -> Result<(), LangsError>;
+}
+
+/// Returns the backend the client's credentials are stored in.
+///
+/// The backend is selected by `CREDENTIAL_STORE_VAR` or the `credential-store` setting in the client's config.
+/// If neither is set, the Secret Service is used when it is available, followed by the encrypted file if a passphrase is set,
+/// and the choice is saved in the config so that the credentials are not looked for in a different store later.
+/// If no secure store is available, the plaintext file is used with a warning. This choice is not saved,
+/// so the credentials are migrated into a secure store once one becomes available.
+pub fn selected_backend(client_name: &str) -> Result<CredentialBackend, LangsError> {
+    if let Ok(name) = env::var(CREDENTIAL_STORE_VAR) {
+        return CredentialBackend::parse(&name);
+    }
+    let mut config = TmcConfig::load(client_name)?;
+    if let Some(backend) = config.credential_store {
+        return Ok(backend);
+    }
+    let backend = CredentialBackend::detect();
+    if backend == CredentialBackend::PlaintextFile {
+        log::warn!(
+            "No secure credential store is available, storing the credentials in plaintext. Set {CREDENTIALS_PASSPHRASE_VAR} to encrypt them"
+        );
+        return Ok(backend);
+    }
+    log::info!("Saving the selected credential store {backend:?} in the config");
+    config.credential_store = Some(backend);
+    config.save()?;
+    Ok(backend)
+}
+
+/// Returns the store for the credentials of the client's profile in the selected backend, see `selected_backend`.
+/// The default credentials are used if no profile is given.
+pub fn for_client(
+    client_name: &str,
+    profile: Option<&str>,
+) -> Result<Box<dyn CredentialStore>, LangsError> {
+    let backend = selected_backend(client_name)?;
+    open(backend, client_name, profile)
+}
+
+/// Returns the stores of the other usable backends for the client's profile.
+/// Credentials can be left in them by older versions or when the selected backend is changed.
+pub fn other_stores(
+    client_name: &str,
+    profile: Option<&str>,
+    selected: CredentialBackend,
+) -> Result<Vec<Box<dyn CredentialStore>>, LangsError> {
+    let mut stores = vec![];
+    for backend in CredentialBackend::ALL {
+        if backend != selected && backend.is_usable() {
+            stores.push(open(backend, client_name, profile)?);
+        }
+    }
+    Ok(stores)
+}
+
+/// Returns the store for the credentials of the client's profile in the given backend.
+pub fn open(
+    backend: CredentialBackend,
+    client_name: &str,
+    profile: Option<&str>,
+) -> Result<Box<dyn CredentialStore>, LangsError> {
+    let store: Box<dyn CredentialStore> = match backend {
+        #[cfg(target_os = "linux")]
+        CredentialBackend::SecretService => Box::new(SecretServiceStore::new(client_name, profile)),
+        #[cfg(not(target_os = "linux"))]
+        CredentialBackend::SecretService => {
+            return Err(LangsError::InvalidCredentialStore(
+                "secret-service".to_string(),
+            ));
+        }
+        CredentialBackend::EncryptedFile => {
+            let passphrase = env::var(CREDENTIALS_PASSPHRASE_VAR)
+                .map_err(|_| LangsError::NoCredentialsPassphrase)?;
+            let path =
+                super::get_tmc_dir(client_name)?.join(file_name(ENCRYPTED_FILE_NAME, profile)?);
+            Box::new(EncryptedFileStore::new(path, passphrase))
+        }
+        CredentialBackend::PlaintextFile => {
+            let path =
+                super::get_tmc_dir(client_name)?.join(file_name(PLAINTEXT_FILE_NAME, profile)?);
+            Box::new(PlaintextFileStore::new(path))
+        }
+    };
+    Ok(store)
+}
+
+// the credentials of a profile are stored next to the default ones, e.g. credentials-staging.json
+fn file_name(default: &str, profile: Option<&str>) -> Result<String, LangsError> {
+    let Some(profile) = profile else {
+        return Ok(default.to_string());
+    };
+    if profile.is_empty()
+        || !profile
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(LangsError::InvalidProfileName(profile.to_string()));
+    }
+    let (stem, extension) = default.split_once('.').unwrap_or((default, ""));
+    Ok(format!("{stem}-{profile}.{extension}"))
+}
+
+/// Stores the secret as is in a file.
+pub struct PlaintextFileStore {
+    path: PathBuf,
+}
+
+impl PlaintextFileStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl CredentialStore for PlaintextFileStore {
+    fn load(&self) -> Result<Option<Vec<u8>>, LangsError> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        log::debug!("Loading credentials from {}", self.path.display());
+
+        let mut lock = Lock::file(&self.path, LockOptions::Read)?;
+        let guard = lock.lock()?;
+        let secret = file_util::read_reader(guard.get_file())?;
+        Ok(Some(secret))
+    }
+
+    fn save(&self, secret: &[u8]) -> Result<(), LangsError> {
+        if let Some(parent) = self.path.parent() {
+            file_util::create_dir_all(parent)?;
+        }
+        let mut lock = Lock::file(&self.path, LockOptions::WriteTruncate)?;
+        let mut guard = lock.lock()?;
+        file_util::write_to_writer(secret, guard.get_file_mut())?;
+        Ok(())
+    }
+
+    fn remove(&self) -> Result<(), LangsError> {
+        if self.path.exists() {
+            file_util::remove_file_locked(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Stores the secret in a file encrypted with ChaCha20-Poly1305, using a key derived from a passphrase with Argon2id.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    passphrase: String,
+}
+
+// the contents of the encrypted file, all of the byte fields are base64 encoded
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedFile {
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl EncryptedFileStore {
+    pub fn new(path: PathBuf, passphrase: String) -> Self {
+        Self { path, passphrase }
+    }
+
+    fn cipher(&self, salt: &[u8]) -> Result<ChaCha20Poly1305, LangsError> {
+        let mut key = [0; 32];
+        argon2::Argon2::default()
+            .hash_password_into(self.passphrase.as_bytes(), salt, &mut key)
+            .map_err(LangsError::CredentialsKeyDerivation)?;
+        Ok(ChaCha20Poly1305::new(&key.into()))
+    }
+}
+
+impl CredentialStore for EncryptedFileStore {
+    fn load(&self) -> Result<Option<Vec<u8>>, LangsError> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        log::debug!("Loading encrypted credentials from {}", self.path.display());
+
+        let mut lock = Lock::file(&self.path, LockOptions::Read)?;
+        let guard = lock.lock()?;
+        let file: EncryptedFile = deserialize::json_from_reader(guard.get_file())
+            .map_err(|e| LangsError::DeserializeJson(self.path.clone(), e))?;
+
+        let base64 = base64::engine::general_purpose::STANDARD;
+        let salt = base64.decode(file.salt)?;
+        let nonce = base64.decode(file.nonce)?;
+        let ciphertext = base64.decode(file.ciphertext)?;
+        if nonce.len() != 12 {
+            return Err(LangsError::CredentialsDecryption);
+        }
+        let secret = self
+            .cipher(&salt)?
+            .decrypt(nonce.as_slice().into(), ciphertext.as_slice())
+            .map_err(|_| LangsError::CredentialsDecryption)?;
+        Ok(Some(secret))
+    }
+
+    fn save(&self, secret: &[u8]) -> Result<(), LangsError> {
+        let mut salt = [0; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher(&salt)?
+            .encrypt(&nonce, secret)
+            .map_err(|_| LangsError::CredentialsEncryption)?;
+
+        let base64 = base64::engine::general_purpose::STANDARD;
+        let file = EncryptedFile {
+            salt: base64.encode(salt),
+            nonce: base64.encode(nonce),
+            ciphertext: base64.encode(ciphertext),
+        };
+        if let Some(parent) = self.path.parent() {
+            file_util::create_dir_all(parent)?;
+        }
+        let mut lock = Lock::file(&self.path, LockOptions::WriteTruncate)?;
+        let mut guard = lock.lock()?;
+        serde_json::to_writer(guard.get_file_mut(), &file)?;
+        Ok(())
+    }
+
+    fn remove(&self) -> Result<(), LangsError> {
+        if self.path.exists() {
+            file_util::remove_file_locked(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Stores the secret with the Secret Service API over D-Bus, implemented by e.g. GNOME Keyring and KWallet.
+#[cfg(target_os = "linux")]
+pub struct SecretServiceStore {
+    client_name: String,
+    profile: Option<String>,
+}
+
+#[cfg(target_os = "linux")]
+impl SecretServiceStore {
+    pub fn new(client_name: &str, profile: Option<&str>) -> Self {
+        Self {
+            client_name: client_name.to_string(),
+            profile: profile.map(str::to_string),
+        }
+    }
+
+    /// Checks whether a Secret Service provider is running.
+    pub fn is_available() -> bool {
+        use secret_service::{EncryptionType, blocking::SecretService};
+
+        match SecretService::connect(EncryptionType::Dh)
+            .and_then(|service| service.get_default_collection().map(|_| ()))
+        {
+            Ok(()) => true,
+            Err(err) => {
+                log::debug!("Secret Service is not available: {err}");
+                false
+            }
+        }
+    }
+
+    fn with_items<T>(
+        &self,
+        f: impl FnOnce(
+            &secret_service::blocking::Collection,
+            Vec<secret_service::blocking::Item>,
+        ) -> Result<T, secret_service::Error>,
+    ) -> Result<T, LangsError> {
+        use secret_service::{EncryptionType, blocking::SecretService};
+
+        let service = SecretService::connect(EncryptionType::Dh)?;
+        let collection = service.get_default_collection()?;
+        collection.ensure_unlocked()?;
+        // searching matches any items that have the attributes, so the default credentials would also match the profiles' items
+        let mut items = vec![];
+        for item in collection.search_items(self.attributes())? {
+            if item.get_attributes()?.get("profile") == self.profile.as_ref() {
+                items.push(item);
+            }
+        }
+        Ok(f(&collection, items)?)
+    }
+
+    fn attributes(&self) -> std::collections::HashMap<&str, &str> {
+        let mut attributes = std::collections::HashMap::from([
+            ("application", "tmc-langs"),
+            ("client", self.client_name.as_str()),
+        ]);
+        if let Some(profile) = &self.profile {
+            attributes.insert("profile", profile.as_str());
+        }
+        attributes
+    }
+
+    fn label(&self) -> String {
+        match &self.profile {
+            Some(profile) => format!("TMC credentials ({}, {profile})", self.client_name),
+            None => format!("TMC credentials ({})", self.client_name),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl CredentialStore for SecretServiceStore {
+    fn load(&self) -> Result<Option<Vec<u8>>, LangsError> {
+        log::debug!("Loading credentials from the Secret Service");
+
+        self.with_items(|_, items| match items.first() {
+            Some(item) => {
+                item.ensure_unlocked()?;
+                item.get_secret().map(Some)
+            }
+            None => Ok(None),
+        })
+    }
+
+    fn save(&self, secret: &[u8]) -> Result<(), LangsError> {
+        self.with_items(|collection, _| {
+            collection.create_item(
+                &self.label(),
+                self.attributes(),
+                secret,
+                true,
+                "application/json",
+            )?;
+            Ok(())
+        })
+    }
+
+    fn remove(&self) -> Result<(), LangsError> {
+        self.with_items(|_, items| {
+            for item in items {
+                item.delete()?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encrypts_and_decrypts() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("credentials.enc.json");
+        let store = EncryptedFileStore::new(path.clone(), "passphrase".to_string());
+        assert!(store.load().unwrap().is_none());
+
+        store.save(b"secret token").unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("secret token"));
+        assert_eq!(store.load().unwrap().unwrap(), b"secret token");
+
+        let wrong = EncryptedFileStore::new(path, "wrong".to_string());
+        assert!(matches!(
+            wrong.load(),
+            Err(LangsError::CredentialsDecryption)
+        ));
+
+        store.remove().unwrap();
+        assert!(store.load().unwrap().is_none());
+    }
+
+    #[test]
+    fn parses_backends() {
+        for backend in CredentialBackend::ALL {
+            let name = serde_json::to_value(backend).unwrap();
+            assert_eq!(
+                CredentialBackend::parse(name.as_str().unwrap()).unwrap(),
+                backend
+            );
+        }
+        assert!(matches!(
+            CredentialBackend::parse("keychain"),
+            Err(LangsError::InvalidCredentialStore(_))
+        ));
+    }
+
+    #[test]
+    fn falls_back_to_plaintext_without_secure_store() {
+        assert_eq!(
+            CredentialBackend::select(true, true),
+            CredentialBackend::SecretService
+        );
+        assert_eq!(
+            CredentialBackend::select(false, true),
+            CredentialBackend::EncryptedFile
+        );
+        assert_eq!(
+            CredentialBackend::select(false, false),
+            CredentialBackend::PlaintextFile
+        );
+    }
+
+    #[test]
+    fn names_profile_files() {
+        assert_eq!(
+            file_name(PLAINTEXT_FILE_NAME, None).unwrap(),
+            "credentials.json"
+        );
+        assert_eq!(
+            file_name(ENCRYPTED_FILE_NAME, Some("staging")).unwrap(),
+            "credentials-staging.enc.json"
+        );
+        assert!(matches!(
+            file_name(PLAINTEXT_FILE_NAME, Some("../other")),
+            Err(LangsError::InvalidProfileName(_))
+        ));
+    }
+}
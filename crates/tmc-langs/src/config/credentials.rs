@@ -1,77 +1,277 @@
 //! Contains the Credentials struct for authenticating with tmc-server.
 
-use crate::{LangsError, tmc::Token};
-use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use tmc_langs_util::{
-    deserialize,
-    file_util::{self, Lock, LockOptions},
+use super::credential_store::{self, CredentialStore};
+use crate::{
+    LangsError,
+    tmc::{self, Token},
 };
+use chrono::{DateTime, Utc};
+use oauth2::TokenResponse;
+use serde::{Deserialize, Serialize};
+use tmc_langs_util::deserialize;
 
 /// Credentials for authenticating with tmc-server.
+/// The token is kept in the client's credential store, see `credential_store::for_client`.
+/// Each profile of the client has its own credentials.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Credentials {
-    path: PathBuf,
+    client_name: String,
+    profile: Option<String>,
     token: Token,
+    expires_at: Option<DateTime<Utc>>,
 }
 
-impl Credentials {
-    // path to the credentials file
-    fn get_credentials_path(client_name: &str) -> Result<PathBuf, LangsError> {
-        super::get_tmc_dir(client_name).map(|dir| dir.join("credentials.json"))
-    }
+// the token and its expiry time as they are stored, credentials stored by older versions only contain the token
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredToken {
+    #[serde(flatten)]
+    token: Token,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at: Option<DateTime<Utc>>,
+}
 
+impl Credentials {
+    /// Loads the credentials of the given profile, or the default credentials if there is no profile.
+    /// If the selected credential store is empty, credentials left in the other stores, such as the plaintext file used by older versions,
+    /// are migrated into it. A failed migration is logged and the credentials are used from where they were found.
+    ///
     /// ### Returns
-    /// - Ok(Some) if a credentials file exists and can be deserialized,
-    /// - Ok(None) if no credentials file exists, and
-    /// - Err if a credentials file exists but cannot be deserialized.
+    /// - Ok(Some) if stored credentials exist and can be deserialized,
+    /// - Ok(None) if no credentials are stored, and
+    /// - Err if stored credentials exist but cannot be deserialized.
     ///
-    /// On Err, the file is deleted.
-    pub fn load(client_name: &str) -> Result<Option<Self>, LangsError> {
-        let credentials_path = Self::get_credentials_path(client_name)?;
-        if !credentials_path.exists() {
-            return Ok(None);
-        }
-        log::debug!("Loading credentials from {}", credentials_path.display());
-
-        let mut credentials_lock = Lock::file(&credentials_path, LockOptions::Read)?;
-        let credentials_guard = credentials_lock.lock()?;
-        match deserialize::json_from_reader(credentials_guard.get_file()) {
-            Ok(token) => Ok(Some(Credentials {
-                path: credentials_path,
-                token,
+    /// On Err, the stored credentials are removed.
+    pub fn load(client_name: &str, profile: Option<&str>) -> Result<Option<Self>, LangsError> {
+        let backend = credential_store::selected_backend(client_name)?;
+        let store = credential_store::open(backend, client_name, profile)?;
+        let others = credential_store::other_stores(client_name, profile, backend)?;
+        Self::load_from(client_name, profile, store.as_ref(), &others)
+    }
+
+    fn load_from(
+        client_name: &str,
+        profile: Option<&str>,
+        store: &dyn CredentialStore,
+        others: &[Box<dyn CredentialStore>],
+    ) -> Result<Option<Self>, LangsError> {
+        let secret = match store.load()? {
+            Some(secret) => secret,
+            None => match Self::migrate(store, others)? {
+                Some(secret) => secret,
+                None => return Ok(None),
+            },
+        };
+
+        match deserialize::json_from_slice::<StoredToken>(&secret) {
+            Ok(stored) => Ok(Some(Credentials {
+                client_name: client_name.to_string(),
+                profile: profile.map(str::to_string),
+                token: stored.token,
+                expires_at: stored.expires_at,
             })),
             Err(e) => {
-                log::error!("Failed to deserialize credentials.json due to \"{e}\", deleting");
-                file_util::remove_file(&credentials_path)?;
-                Err(LangsError::DeserializeCredentials(credentials_path, e))
+                log::error!("Failed to deserialize credentials due to \"{e}\", deleting");
+                store.remove()?;
+                Err(LangsError::DeserializeCredentials(e))
             }
         }
     }
 
-    pub fn save(client_name: &str, token: Token) -> Result<(), LangsError> {
-        let credentials_path = Self::get_credentials_path(client_name)?;
-
-        if let Some(p) = credentials_path.parent() {
-            file_util::create_dir_all(p)?;
+    // moves the first credentials found in the other stores into the store
+    fn migrate(
+        store: &dyn CredentialStore,
+        others: &[Box<dyn CredentialStore>],
+    ) -> Result<Option<Vec<u8>>, LangsError> {
+        for other in others {
+            match other.load() {
+                Ok(Some(secret)) => {
+                    log::info!("Migrating credentials into the selected credential store");
+                    match store.save(&secret) {
+                        Ok(()) => {
+                            if let Err(err) = other.remove() {
+                                log::warn!("Failed to remove migrated credentials: {err}");
+                            }
+                        }
+                        Err(err) => log::warn!("Failed to migrate credentials: {err}"),
+                    }
+                    return Ok(Some(secret));
+                }
+                Ok(None) => {}
+                Err(err) => log::warn!("Failed to check another credential store: {err}"),
+            }
         }
-        let mut credentials_lock = Lock::file(&credentials_path, LockOptions::WriteTruncate)?;
-        let mut credentials_guard = credentials_lock.lock()?;
-        // write token
-        if let Err(e) = serde_json::to_writer(credentials_guard.get_file_mut(), &token) {
-            // failed to write token, removing credentials file
-            file_util::remove_file(&credentials_path)?;
-            return Err(LangsError::Json(e));
+        Ok(None)
+    }
+
+    /// Saves a token that was just issued for the given profile. Its expiry time is calculated from its lifetime.
+    pub fn save(
+        client_name: &str,
+        profile: Option<&str>,
+        token: Token,
+    ) -> Result<Self, LangsError> {
+        let backend = credential_store::selected_backend(client_name)?;
+        let stored = StoredToken {
+            expires_at: tmc::token_expiry(&token),
+            token,
+        };
+        let secret = serde_json::to_vec(&stored)?;
+        credential_store::open(backend, client_name, profile)?.save(&secret)?;
+        // don't leave behind old credentials, such as plaintext ones from older versions
+        for other in credential_store::other_stores(client_name, profile, backend)? {
+            if let Err(err) = other.remove() {
+                log::warn!("Failed to remove credentials from another credential store: {err}");
+            }
         }
-        Ok(())
+        Ok(Self {
+            client_name: client_name.to_string(),
+            profile: profile.map(str::to_string),
+            token: stored.token,
+            expires_at: stored.expires_at,
+        })
     }
 
     pub fn remove(self) -> Result<(), LangsError> {
-        file_util::remove_file_locked(self.path)?;
+        credential_store::for_client(&self.client_name, self.profile.as_deref())?.remove()?;
         Ok(())
     }
 
     pub fn token(&self) -> Token {
         self.token.clone()
     }
+
+    /// The time the token expires at, if known.
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expires_at
+    }
+
+    /// Whether the token should be refreshed before it is used: it has expired,
+    /// or its expiry time is unknown, as with tokens saved by older versions, and it has a refresh token.
+    pub fn needs_refresh(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => expires_at <= Utc::now(),
+            None => self.token.refresh_token().is_some(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use crate::config::credential_store::{EncryptedFileStore, PlaintextFileStore};
+
+    #[test]
+    fn migrates_credentials_from_other_stores() {
+        let temp = tempfile::tempdir().unwrap();
+        let legacy = PlaintextFileStore::new(temp.path().join("credentials.json"));
+        let token = r#"{"access_token":"token","token_type":"bearer"}"#;
+        legacy.save(token.as_bytes()).unwrap();
+        let store = EncryptedFileStore::new(
+            temp.path().join("credentials.enc.json"),
+            "passphrase".to_string(),
+        );
+        let others: Vec<Box<dyn CredentialStore>> = vec![Box::new(legacy)];
+
+        let credentials = Credentials::load_from("client", None, &store, &others)
+            .unwrap()
+            .unwrap();
+        assert_eq!(credentials.token().access_token().secret(), "token");
+        assert!(credentials.expires_at().is_none());
+        assert!(others[0].load().unwrap().is_none());
+        assert_eq!(store.load().unwrap().unwrap(), token.as_bytes());
+
+        // switching back to the plaintext store migrates the encrypted credentials into it
+        let plaintext = PlaintextFileStore::new(temp.path().join("credentials.json"));
+        let others: Vec<Box<dyn CredentialStore>> = vec![Box::new(store)];
+        let credentials = Credentials::load_from("client", None, &plaintext, &others)
+            .unwrap()
+            .unwrap();
+        assert_eq!(credentials.token().access_token().secret(), "token");
+        assert!(others[0].load().unwrap().is_none());
+        assert_eq!(plaintext.load().unwrap().unwrap(), token.as_bytes());
+    }
+
+    #[test]
+    fn uses_credentials_when_migration_fails() {
+        let temp = tempfile::tempdir().unwrap();
+        let legacy = PlaintextFileStore::new(temp.path().join("credentials.json"));
+        let token = r#"{"access_token":"token","token_type":"bearer"}"#;
+        legacy.save(token.as_bytes()).unwrap();
+        // the store's directory cannot be created because a file is in the way
+        std::fs::write(temp.path().join("blocked"), "").unwrap();
+        let store = EncryptedFileStore::new(
+            temp.path().join("blocked").join("credentials.enc.json"),
+            "passphrase".to_string(),
+        );
+        let others: Vec<Box<dyn CredentialStore>> = vec![Box::new(legacy)];
+
+        let credentials = Credentials::load_from("client", None, &store, &others)
+            .unwrap()
+            .unwrap();
+        assert_eq!(credentials.token().access_token().secret(), "token");
+        assert_eq!(others[0].load().unwrap().unwrap(), token.as_bytes());
+    }
+
+    #[test]
+    fn loads_expiry_time() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = PlaintextFileStore::new(temp.path().join("credentials.json"));
+        let token = r#"{"access_token":"token","token_type":"bearer","expires_in":7200,"refresh_token":"refresh","expires_at":"2030-01-01T00:00:00Z"}"#;
+        store.save(token.as_bytes()).unwrap();
+
+        let credentials = Credentials::load_from("client", None, &store, &[])
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            credentials.expires_at().unwrap().to_rfc3339(),
+            "2030-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            credentials.token().refresh_token().unwrap().secret(),
+            "refresh"
+        );
+        assert!(!credentials.needs_refresh());
+    }
+
+    #[test]
+    fn refreshes_expired_and_legacy_tokens() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = PlaintextFileStore::new(temp.path().join("credentials.json"));
+        let load = |token: &str| {
+            store.save(token.as_bytes()).unwrap();
+            Credentials::load_from("client", None, &store, &[])
+                .unwrap()
+                .unwrap()
+        };
+
+        let expired = load(
+            r#"{"access_token":"token","token_type":"bearer","refresh_token":"refresh","expires_at":"2020-01-01T00:00:00Z"}"#,
+        );
+        assert!(expired.needs_refresh());
+        let legacy =
+            load(r#"{"access_token":"token","token_type":"bearer","refresh_token":"refresh"}"#);
+        assert!(legacy.needs_refresh());
+        let unrefreshable = load(r#"{"access_token":"token","token_type":"bearer"}"#);
+        assert!(!unrefreshable.needs_refresh());
+    }
+
+    #[test]
+    fn loads_profile_credentials() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = PlaintextFileStore::new(temp.path().join("credentials-staging.json"));
+        assert!(
+            Credentials::load_from("client", Some("staging"), &store, &[])
+                .unwrap()
+                .is_none()
+        );
+
+        let token = r#"{"access_token":"staging token","token_type":"bearer"}"#;
+        store.save(token.as_bytes()).unwrap();
+        let credentials = Credentials::load_from("client", Some("staging"), &store, &[])
+            .unwrap()
+            .unwrap();
+        assert_eq!(credentials.token().access_token().secret(), "staging token");
+        assert_eq!(credentials.profile.as_deref(), Some("staging"));
+    }
 }
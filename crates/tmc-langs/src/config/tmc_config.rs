@@ -1,8 +1,10 @@
 //! Handles the CLI's configuration file.
 
+use super::credential_store::CredentialBackend;
 use crate::error::LangsError;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     env,
     io::Write,
     path::{Path, PathBuf},
@@ -22,11 +24,45 @@ pub struct TmcConfig {
     pub location: PathBuf,
     #[serde(alias = "projects-dir")]
     pub projects_dir: PathBuf,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Where the credentials are stored, see `credential_store::selected_backend`.
+    #[serde(
+        default,
+        alias = "credential-store",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub credential_store: Option<CredentialBackend>,
     #[serde(flatten)]
     #[cfg_attr(feature = "ts-rs", ts(skip))]
     pub table: Table,
 }
 
+/// A named profile, used to work with several servers or accounts from a single client.
+/// Each profile has its own credentials. Unset values fall back to the defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub struct Profile {
+    #[serde(
+        default,
+        alias = "tmc-root-url",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tmc_root_url: Option<String>,
+    #[serde(
+        default,
+        alias = "mooc-root-url",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mooc_root_url: Option<String>,
+    #[serde(
+        default,
+        alias = "projects-dir",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub projects_dir: Option<PathBuf>,
+}
+
 impl TmcConfig {
     /// Reads or initialises the config for the given client.
     pub fn load(client_name: &str) -> Result<TmcConfig, LangsError> {
@@ -84,6 +120,8 @@ impl TmcConfig {
         let config = TmcConfig {
             location: path,
             projects_dir: default_project_dir,
+            profiles: BTreeMap::new(),
+            credential_store: None,
             table: Table::new(),
         };
 
@@ -111,6 +149,48 @@ impl TmcConfig {
         Ok(target)
     }
 
+    /// Sets the projects dir of the given profile if it has its own one, or the default projects dir otherwise.
+    /// Returns the old projects dir.
+    pub fn set_profile_projects_dir(
+        &mut self,
+        profile: Option<&str>,
+        mut target: PathBuf,
+    ) -> Result<PathBuf, LangsError> {
+        let profile_dir = match profile {
+            Some(name) => self
+                .profiles
+                .get_mut(name)
+                .ok_or_else(|| LangsError::UnknownProfile(name.to_string()))?
+                .projects_dir
+                .as_mut(),
+            None => None,
+        };
+        let Some(profile_dir) = profile_dir else {
+            return self.set_projects_dir(target);
+        };
+        if file_util::read_dir(&target)?.next().is_some() {
+            return Err(LangsError::NonEmptyDir(target));
+        }
+        std::mem::swap(profile_dir, &mut target);
+        Ok(target)
+    }
+
+    /// Returns the profile with the given name.
+    pub fn get_profile(&self, name: &str) -> Result<&Profile, LangsError> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| LangsError::UnknownProfile(name.to_string()))
+    }
+
+    /// Returns the projects dir of the given profile, or the default projects dir if there is no profile or it does not set one.
+    pub fn get_profile_projects_dir(&self, profile: Option<&str>) -> Result<&Path, LangsError> {
+        let profile_dir = match profile {
+            Some(name) => self.get_profile(name)?.projects_dir.as_deref(),
+            None => None,
+        };
+        Ok(profile_dir.unwrap_or(&self.projects_dir))
+    }
+
     /// Fetches a value with the given key.
     pub fn get(&self, key: &str) -> Option<&Value> {
         self.table.get(key)
@@ -176,3 +256,58 @@ fn get_client_stub(client: &str) -> &str {
         s => s,
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reads_profiles() {
+        let config = r#"
+projects_dir = "/projects"
+credential-store = "encrypted-file"
+other = "value"
+
+[profiles.staging]
+tmc-root-url = "https://staging.example.com/"
+
+[profiles.second]
+projects_dir = "/second"
+"#;
+        let config = deserialize::toml_from_str::<TmcConfig>(config).unwrap();
+        let staging = config.get_profile("staging").unwrap();
+        assert_eq!(
+            staging.tmc_root_url.as_deref(),
+            Some("https://staging.example.com/")
+        );
+        assert!(staging.mooc_root_url.is_none());
+        assert_eq!(
+            config.get_profile_projects_dir(Some("staging")).unwrap(),
+            Path::new("/projects")
+        );
+        assert_eq!(
+            config.get_profile_projects_dir(Some("second")).unwrap(),
+            Path::new("/second")
+        );
+        assert_eq!(
+            config.get_profile_projects_dir(None).unwrap(),
+            Path::new("/projects")
+        );
+        assert!(matches!(
+            config.get_profile("missing"),
+            Err(LangsError::UnknownProfile(_))
+        ));
+        assert!(!config.table.contains_key("profiles"));
+        assert_eq!(
+            config.credential_store,
+            Some(CredentialBackend::EncryptedFile)
+        );
+        assert!(config.get("other").is_some());
+
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        assert!(serialized.contains("[profiles.staging]"));
+        let config = deserialize::toml_from_str::<TmcConfig>(&serialized).unwrap();
+        assert_eq!(config.profiles.len(), 2);
+    }
+}
@@ -0,0 +1,194 @@
+//! Checking that the exercises in a course repository work as intended.
+
+use crate::{
+    Compression, LangsError, PrepareSubmission, RunResult, RunStatus, TmcParams,
+    find_exercise_directories, grade_submission, parallel,
+};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tmc_langs_util::progress_reporter;
+
+/// The results of checking a single exercise.
+#[derive(Debug, Serialize, JsonSchema)]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub struct ExerciseCheck {
+    /// Path to the exercise relative to the course directory.
+    pub exercise_path: PathBuf,
+    /// Running the tests on the stub. The tests should fail, but some exercises are meant to pass on the stub, so passing is only a warning.
+    pub stub: CheckResult,
+    /// Running the tests on the model solution. The tests should pass.
+    pub solution: CheckResult,
+    /// Grading the model solution packaged as a submission, see `grade_submission`. The tests should pass.
+    pub submission: CheckResult,
+}
+
+impl ExerciseCheck {
+    /// Whether any of the checks had an error.
+    pub fn has_errors(&self) -> bool {
+        [&self.stub, &self.solution, &self.submission]
+            .iter()
+            .any(|c| c.status == CheckStatus::Error)
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub struct CheckResult {
+    pub status: CheckStatus,
+    /// The status of the test run, if the tests could be run.
+    pub run_status: Option<RunStatus>,
+    /// Describes the problem if the status is not ok.
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// Checks each exercise found in the course directory with `find_exercise_directories`:
+/// the tests should fail on the stub, pass on the model solution, and pass when the model solution is packaged and graded as a submission.
+/// The exercises are checked using up to `jobs` threads. The results are in the order the exercises were found in.
+pub fn check_course(course_path: &Path, jobs: usize) -> Result<Vec<ExerciseCheck>, LangsError> {
+    log::debug!("checking course in {}", course_path.display());
+
+    let exercises = find_exercise_directories(course_path)?;
+    let exercise_count = exercises.len();
+    progress_reporter::start_stage::<()>(
+        u32::try_from(exercise_count).unwrap_or(u32::MAX),
+        format!("Checking {exercise_count} exercises"),
+        None,
+    );
+
+    let checks = parallel::map(exercises, jobs, |exercise_path| {
+        let check = check_exercise(course_path, &exercise_path);
+        progress_reporter::progress_stage::<()>(
+            format!("Checked exercise {}", exercise_path.display()),
+            None,
+        );
+        check
+    })
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()?;
+    progress_reporter::finish_stage::<()>(format!("Checked {exercise_count} exercises"), None);
+    Ok(checks)
+}
+
+fn check_exercise(course_path: &Path, exercise_path: &Path) -> Result<ExerciseCheck, LangsError> {
+    log::debug!("checking exercise {}", exercise_path.display());
+
+    let temp = tempfile::tempdir().map_err(LangsError::TempDir)?;
+    let stub_path = temp.path().join("stub");
+    let solution_path = temp.path().join("solution");
+    let submission_archive = temp.path().join("submission.zip");
+
+    let stub = check(false, || {
+        crate::prepare_stub(exercise_path, &stub_path, None)?;
+        crate::run_tests(&stub_path)
+    });
+    let solution = check(true, || {
+        crate::prepare_solution(exercise_path, &solution_path, None)?;
+        crate::run_tests(&solution_path)
+    });
+    let submission = check(true, || {
+        crate::compress_project_to(
+            &solution_path,
+            &submission_archive,
+            Compression::Zip,
+            true,
+            false,
+        )?;
+        let graded = grade_submission(
+            PrepareSubmission {
+                archive: &submission_archive,
+                compression: Compression::Zip,
+                extract_naively: false,
+            },
+            TmcParams::new(),
+            exercise_path,
+        )?;
+        Ok(graded.run_result)
+    });
+
+    Ok(ExerciseCheck {
+        exercise_path: exercise_path
+            .strip_prefix(course_path)
+            .unwrap_or(exercise_path)
+            .to_path_buf(),
+        stub,
+        solution,
+        submission,
+    })
+}
+
+// runs the tests and checks whether they passed as expected, the stub is expected to fail
+fn check(should_pass: bool, run: impl FnOnce() -> Result<RunResult, LangsError>) -> CheckResult {
+    match run() {
+        Ok(run_result) => {
+            let passed = run_result.status == RunStatus::Passed;
+            let (status, message) = match (should_pass, passed) {
+                (true, true) | (false, false) => (CheckStatus::Ok, None),
+                (true, false) => (CheckStatus::Error, Some("the tests failed".to_string())),
+                (false, true) => (CheckStatus::Warning, Some("the tests passed".to_string())),
+            };
+            CheckResult {
+                status,
+                run_status: Some(run_result.status),
+                message,
+            }
+        }
+        Err(error) => CheckResult {
+            status: CheckStatus::Error,
+            run_status: None,
+            message: Some(error.to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use tmc_langs_util::file_util;
+
+    #[test]
+    fn checks_course_exercises() {
+        let temp = tempfile::tempdir().unwrap();
+        // the temporary directory is hidden, which would cause the exercises to be skipped
+        let course = temp.path().join("course");
+        for exercise in ["part1/first", "part1/second", "part2/third"] {
+            file_util::write_to_file(
+                "no-tests:\n  points:\n    - point\n",
+                course.join(exercise).join(".tmcproject.yml"),
+            )
+            .unwrap();
+        }
+
+        let checks = check_course(&course, 2).unwrap();
+        let paths = checks
+            .iter()
+            .map(|c| c.exercise_path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(paths.len(), 3);
+        // no-tests exercises always pass, including the stub
+        for check in &checks {
+            assert_eq!(check.stub.status, CheckStatus::Warning);
+            assert_eq!(check.solution.status, CheckStatus::Ok);
+            assert_eq!(check.submission.status, CheckStatus::Ok);
+            assert!(!check.has_errors());
+        }
+        assert_eq!(
+            paths,
+            find_exercise_directories(&course)
+                .unwrap()
+                .into_iter()
+                .map(|p| p.strip_prefix(&course).unwrap().to_path_buf())
+                .collect::<Vec<_>>()
+        );
+    }
+}
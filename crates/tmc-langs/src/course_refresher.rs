@@ -1,16 +1,18 @@
 //! Course refresher.
 
-use crate::{error::LangsError, progress_reporter};
+use crate::{error::LangsError, lint_exercise, parallel, progress_reporter};
 use md5::Context;
 use serde::{Deserialize, Serialize};
-use serde_yaml::Mapping;
+use serde_yaml::{Mapping, Value};
 use std::{
+    collections::HashMap,
+    ffi::OsStr,
     io::Write,
     path::{Path, PathBuf},
     time::Duration,
 };
-use tmc_langs_framework::{TmcCommand, TmcProjectYml};
-use tmc_langs_util::{deserialize, file_util};
+use tmc_langs_framework::{Output, TmcCommand, TmcProjectYml};
+use tmc_langs_util::{FileError, deserialize, file_util};
 use walkdir::WalkDir;
 use zip::write::SimpleFileOptions;
 
@@ -23,13 +25,20 @@ pub type ModeBits = nix::sys::stat::mode_t;
 #[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
 pub struct RefreshData {
     pub new_cache_path: PathBuf,
+    /// The commit the course was refreshed from, if the source was a git repository.
+    /// None for a local directory with uncommitted changes or untracked files, as they are not part of any commit.
+    pub commit: Option<String>,
+    /// The options in the course's course_options.yml, with the ones under `courses.<course_name>` merged over the general ones.
     #[cfg_attr(feature = "ts-rs", ts(type = "object"))]
     pub course_options: Mapping,
     pub exercises: Vec<RefreshExercise>,
+    /// The names of the exercises that were processed during this refresh.
+    /// The rest were unchanged since the previous refresh and reused from the previous cache.
+    pub rebuilt_exercises: Vec<String>,
 }
 
 /// An exercise from a finished course refresh.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 #[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
 pub struct RefreshExercise {
@@ -43,16 +52,56 @@ pub struct RefreshExercise {
     tmcproject_yml: Option<TmcProjectYml>,
 }
 
+/// Where the course is refreshed from.
+#[derive(Debug, Clone)]
+pub enum RefreshSource {
+    /// A git repository at a URL or path, which is fetched to the previous cache's clone or cloned.
+    /// The ref can be a branch, a tag or a commit SHA that is reachable from one of them.
+    Git { url: String, git_ref: String },
+    /// A git bundle file, which is cloned. The ref is checked out from the bundle, or the bundle's HEAD if none is given.
+    Bundle {
+        path: PathBuf,
+        git_ref: Option<String>,
+    },
+    /// A local directory, such as a teacher's working copy, which is copied including any uncommitted changes.
+    /// If it is a git repository, the .git directory and the files ignored by git are not copied.
+    Directory(PathBuf),
+}
+
+/// Written to each new cache so that the next refresh can tell which exercises have changed.
+#[derive(Debug, Serialize, Deserialize)]
+struct RefreshManifest {
+    /// Exercises are always rebuilt with a different version, as the generated files may differ.
+    tmc_langs_version: String,
+    exercises: Vec<ManifestExercise>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestExercise {
+    path: PathBuf,
+    /// The checksum of the exercise's files in the clone, its merged .tmcproject.yml and the template variant, see `calculate_source_checksum`.
+    source_checksum: String,
+    exercise: RefreshExercise,
+}
+
+const MANIFEST_FILE_NAME: &str = "refresh_manifest.json";
+/// The course option that selects the variant of the exercise templates to prepare the stubs and solutions for.
+const TEMPLATE_VARIANT_OPTION: &str = "template_variant";
+
 /// Used by tmc-server. Refreshes the course.
+/// Exercises that have not changed since the refresh that created `course_cache_path` are not processed again,
+/// their stubs, solutions and zips are hard linked or copied from the previous cache instead.
+/// The exercises are processed using up to `jobs` threads. The results do not depend on the number of threads.
+/// The stubs and solutions are prepared for the variant in the `template_variant` course option, if any.
 pub fn refresh_course(
     course_name: String,
     course_cache_path: PathBuf,
-    source_url: String,
-    git_branch: String,
+    source: RefreshSource,
     cache_root: PathBuf,
+    jobs: usize,
 ) -> Result<RefreshData, LangsError> {
     log::info!("refreshing course {course_name}");
-    start_stage(10, "Refreshing course");
+    start_stage(13, "Refreshing course");
 
     // create new cache path
     let old_version = course_cache_path
@@ -73,17 +122,26 @@ pub fn refresh_course(
     // initialize new clone path and verify directory names
     let new_clone_path = new_cache_path.join("clone");
     let old_clone_path = course_cache_path.join("clone");
-    initialize_new_cache_clone(
-        &new_cache_path,
-        &new_clone_path,
-        &old_clone_path,
-        &source_url,
-        &git_branch,
-    )?;
+    initialize_new_cache_clone(&new_cache_path, &new_clone_path, &old_clone_path, &source)?;
+    // the copy of a local directory does not include its .git directory
+    let commit = match &source {
+        RefreshSource::Directory(path) => resolve_commit(path)?,
+        RefreshSource::Git { .. } | RefreshSource::Bundle { .. } => {
+            resolve_commit(&new_clone_path)?
+        }
+    };
     check_directory_names(&new_clone_path)?;
     progress_stage("Updated repository");
 
     let course_options = get_course_options(&new_clone_path, &course_name)?;
+    let variant = match course_options.get(TEMPLATE_VARIANT_OPTION) {
+        Some(Value::String(variant)) => Some(variant.as_str()),
+        Some(other) => {
+            log::warn!("ignoring invalid {TEMPLATE_VARIANT_OPTION} course option {other:?}");
+            None
+        }
+        None => None,
+    };
     progress_stage("Fetched course options");
 
     let new_solution_path = new_cache_path.join("solution");
@@ -104,40 +162,112 @@ pub fn refresh_course(
         get_and_merge_tmcproject_configs(root_tmcproject_yml, &new_clone_path, exercise_dirs)?;
     progress_stage("Merged .tmcproject.yml files in exercise directories to the root file, if any");
 
-    // make_solutions
-    log::info!("preparing solutions to {}", new_solution_path.display());
-    for (exercise, merged_tmcproject) in &exercise_dirs_and_tmcprojects {
-        // save merged config to solution
-        let dest_root = new_solution_path.join(exercise);
-        super::prepare_solution(&new_clone_path.join(exercise), &dest_root)?;
-        if let Some(merged_tmcproject) = merged_tmcproject {
-            merged_tmcproject.save_to_dir(&dest_root)?;
+    // check the annotations so that broken stubs or solutions are not published
+    let mut annotation_errors = vec![];
+    let lint_results = parallel::map(
+        exercise_dirs_and_tmcprojects.iter().collect(),
+        jobs,
+        |(exercise, _)| lint_exercise(&new_clone_path.join(exercise), variant),
+    );
+    for ((exercise, _), issues) in exercise_dirs_and_tmcprojects.iter().zip(lint_results) {
+        for issue in issues? {
+            let message = format!("{}/{issue}", exercise.display());
+            if issue.is_error() {
+                annotation_errors.push(message);
+            } else {
+                log::warn!("{message}");
+            }
         }
     }
+    if !annotation_errors.is_empty() {
+        return Err(LangsError::InvalidAnnotations(annotation_errors.join("\n")));
+    }
+    progress_stage("Checked annotations");
+
+    // compare the exercises to the previous refresh and reuse the ones that have not changed
+    let source_checksums = parallel::map(
+        exercise_dirs_and_tmcprojects.iter().collect(),
+        jobs,
+        |(exercise, merged_tmcproject)| {
+            calculate_source_checksum(
+                &new_clone_path.join(exercise),
+                merged_tmcproject.as_ref(),
+                variant,
+            )
+        },
+    )
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()?;
+    let previous_manifest = load_manifest(&course_cache_path);
+    let reused_exercises = find_reusable_exercises(
+        previous_manifest,
+        &course_cache_path,
+        &exercise_dirs_and_tmcprojects,
+        &source_checksums,
+    );
+    let exercises_to_rebuild = exercise_dirs_and_tmcprojects
+        .iter()
+        .zip(&reused_exercises)
+        .filter(|(_, reused)| reused.is_none())
+        .map(|(exercise, _)| exercise.clone())
+        .collect::<Vec<_>>();
+    log::info!(
+        "reusing {} exercises from the previous cache, rebuilding {}",
+        reused_exercises.iter().flatten().count(),
+        exercises_to_rebuild.len()
+    );
+    progress_stage("Compared exercises to the previous refresh");
+
+    // make_solutions
+    log::info!("preparing solutions to {}", new_solution_path.display());
+    parallel::map(
+        exercises_to_rebuild.iter().collect(),
+        jobs,
+        |(exercise, merged_tmcproject)| -> Result<(), LangsError> {
+            // save merged config to solution
+            let dest_root = new_solution_path.join(exercise);
+            super::prepare_solution(&new_clone_path.join(exercise), &dest_root, variant)?;
+            if let Some(merged_tmcproject) = merged_tmcproject {
+                merged_tmcproject.save_to_dir(&dest_root)?;
+            }
+            Ok(())
+        },
+    )
+    .into_iter()
+    .collect::<Result<(), _>>()?;
     progress_stage("Prepared solutions");
 
     // make_stubs
     log::info!("preparing stubs to {}", new_stub_path.display());
-    for (exercise, merged_tmcproject) in &exercise_dirs_and_tmcprojects {
-        // save merged config to stub
-        let dest_root = new_stub_path.join(exercise);
-        super::prepare_stub(&new_clone_path.join(exercise), &dest_root)?;
-        if let Some(merged_tmcproject) = merged_tmcproject {
-            merged_tmcproject.save_to_dir(&dest_root)?;
-        }
-    }
+    parallel::map(
+        exercises_to_rebuild.iter().collect(),
+        jobs,
+        |(exercise, merged_tmcproject)| -> Result<(), LangsError> {
+            // save merged config to stub
+            let dest_root = new_stub_path.join(exercise);
+            super::prepare_stub(&new_clone_path.join(exercise), &dest_root, variant)?;
+            if let Some(merged_tmcproject) = merged_tmcproject {
+                merged_tmcproject.save_to_dir(&dest_root)?;
+            }
+            Ok(())
+        },
+    )
+    .into_iter()
+    .collect::<Result<(), _>>()?;
     progress_stage("Prepared stubs");
 
-    let exercises = get_exercises(
-        exercise_dirs_and_tmcprojects,
-        &new_clone_path,
-        &new_stub_path,
-    )?;
+    let rebuilt_exercises =
+        get_exercises(exercises_to_rebuild, &new_clone_path, &new_stub_path, jobs)?;
     progress_stage("Located exercises");
 
     // make_zips_of_solutions
     let new_solution_zip_path = new_cache_path.join("solution_zip");
-    execute_zip(&exercises, &new_solution_path, &new_solution_zip_path)?;
+    execute_zip(
+        &rebuilt_exercises,
+        &new_solution_path,
+        &new_solution_zip_path,
+        jobs,
+    )?;
     progress_stage("Compressed solutions");
 
     // make_zips_of_stubs
@@ -147,33 +277,106 @@ pub fn refresh_course(
         new_stub_path.display(),
         new_stub_zip_path.display()
     );
-    execute_zip(&exercises, &new_stub_path, &new_stub_zip_path)?;
+    execute_zip(&rebuilt_exercises, &new_stub_path, &new_stub_zip_path, jobs)?;
     progress_stage("Compressed stubs");
 
+    // link the unchanged exercises' artifacts from the previous cache
+    parallel::map(
+        reused_exercises.iter().flatten().collect(),
+        jobs,
+        |exercise| -> Result<(), LangsError> {
+            for dir in ["solution", "stub"] {
+                link_or_copy_dir(
+                    &course_cache_path.join(dir).join(&exercise.path),
+                    &new_cache_path.join(dir).join(&exercise.path),
+                )?;
+            }
+            for dir in ["solution_zip", "stub_zip"] {
+                let zip_name = format!("{}.zip", exercise.name);
+                link_or_copy_file(
+                    &course_cache_path.join(dir).join(&zip_name),
+                    &new_cache_path.join(dir).join(&zip_name),
+                )?;
+            }
+            Ok(())
+        },
+    )
+    .into_iter()
+    .collect::<Result<(), _>>()?;
+    progress_stage("Reused unchanged exercises");
+
+    // combine the reused and rebuilt exercises in the original order
+    let rebuilt = rebuilt_exercises
+        .iter()
+        .map(|e| e.name.clone())
+        .collect::<Vec<_>>();
+    let mut rebuilt_exercises = rebuilt_exercises.into_iter();
+    let exercises = reused_exercises
+        .into_iter()
+        .zip(exercise_dirs_and_tmcprojects)
+        .map(|(reused, (_, merged_tmcproject))| match reused {
+            Some(mut exercise) => {
+                exercise.tmcproject_yml = merged_tmcproject;
+                exercise
+            }
+            None => rebuilt_exercises
+                .next()
+                .expect("there is a rebuilt exercise for each exercise that was not reused"),
+        })
+        .collect::<Vec<_>>();
+    save_manifest(&new_cache_path, &exercises, &source_checksums)?;
+
     // make sure the new cache path is readable by anyone
     set_permissions(&new_cache_path)?;
 
     finish_stage("Refreshed course");
     Ok(RefreshData {
         new_cache_path,
+        commit,
         course_options,
         exercises,
+        rebuilt_exercises: rebuilt,
     })
 }
 
-/// Checks old_cache_path/clone for a git repo.
-/// If found, copies it to course_clone_path fetches origin from course_source_url, checks out origin/course_git_branch, cleans and checks out the repo.
-/// If not found or found but one of the git commands causes an error, deletes course_clone_path and clones course_git_branch from course_source_url there.
-/// NOP during testing.
+/// Initializes course_clone_path from the source.
+/// For a git repository, checks old_cache_path/clone for a git repo.
+/// If found, copies it to course_clone_path, fetches origin from the source URL, checks out the ref, cleans and checks out the repo.
+/// If not found or found but one of the git commands causes an error, deletes course_clone_path, clones the source URL there and checks out the ref.
+/// A bundle is always cloned and a local directory always copied.
 fn initialize_new_cache_clone(
     new_course_root: &Path,
     new_clone_path: &Path,
     old_clone_path: &Path,
-    course_source_url: &str,
-    course_git_branch: &str,
+    source: &RefreshSource,
 ) -> Result<(), LangsError> {
     log::info!("initializing repository at {}", new_clone_path.display());
 
+    let (course_source_url, git_ref) = match source {
+        RefreshSource::Git { url, git_ref } => (url, git_ref),
+        RefreshSource::Bundle { path, git_ref } => {
+            log::info!("cloning bundle {}", path.display());
+            run_git(
+                new_course_root,
+                &[
+                    OsStr::new("clone"),
+                    OsStr::new("-q"),
+                    path.as_os_str(),
+                    new_clone_path.as_os_str(),
+                ],
+            )?;
+            if let Some(git_ref) = git_ref {
+                check_out_ref(new_clone_path, git_ref)?;
+            }
+            return Ok(());
+        }
+        RefreshSource::Directory(path) => {
+            log::info!("copying directory {}", path.display());
+            copy_working_tree(path, new_clone_path)?;
+            return Ok(());
+        }
+    };
+
     if old_clone_path.join(".git").exists() {
         log::info!(
             "trying to copy clone from previous cache at {}",
@@ -183,18 +386,12 @@ fn initialize_new_cache_clone(
         // closure to collect any error that occurs during the process
         let copy_and_update_repository = || -> Result<(), LangsError> {
             file_util::copy(old_clone_path, new_course_root)?;
-
-            let run_git = |args: &[&str]| {
-                TmcCommand::piped("git")
-                    .with(|e| e.cwd(new_clone_path).args(args))
-                    .output_with_timeout_checked(Duration::from_secs(60 * 2))
-            };
-
-            run_git(&["remote", "set-url", "origin", course_source_url])?;
-            run_git(&["fetch", "origin"])?;
-            run_git(&["checkout", &format!("origin/{course_git_branch}")])?;
-            run_git(&["clean", "-df"])?;
-            run_git(&["checkout", "."])?;
+            run_git(
+                new_clone_path,
+                &["remote", "set-url", "origin", course_source_url],
+            )?;
+            run_git(new_clone_path, &["fetch", "--tags", "origin"])?;
+            check_out_ref(new_clone_path, git_ref)?;
             Ok(())
         };
         match copy_and_update_repository() {
@@ -213,17 +410,106 @@ fn initialize_new_cache_clone(
     log::info!("could not copy from previous cache, cloning");
 
     // clone_repository
-    TmcCommand::piped("git")
-        .with(|e| {
-            e.args(&["clone", "-q", "-b"])
-                .arg(course_git_branch)
-                .arg(course_source_url)
-                .arg(new_clone_path)
-        })
+    run_git(
+        new_course_root,
+        &[
+            OsStr::new("clone"),
+            OsStr::new("-q"),
+            OsStr::new(course_source_url),
+            new_clone_path.as_os_str(),
+        ],
+    )?;
+    check_out_ref(new_clone_path, git_ref)?;
+    Ok(())
+}
+
+fn run_git(cwd: &Path, args: &[impl AsRef<OsStr>]) -> Result<Output, LangsError> {
+    let output = TmcCommand::piped("git")
+        .with(|e| e.cwd(cwd).args(args))
         .output_with_timeout_checked(Duration::from_secs(60 * 2))?;
+    Ok(output)
+}
+
+/// Checks out the ref in the clone, preferring the remote branch with the name over a local branch, tag or commit.
+fn check_out_ref(clone_path: &Path, git_ref: &str) -> Result<(), LangsError> {
+    let commit = [
+        format!("origin/{git_ref}^{{commit}}"),
+        format!("{git_ref}^{{commit}}"),
+    ]
+    .into_iter()
+    .find_map(|candidate| {
+        run_git(clone_path, &["rev-parse", "-q", "--verify", &candidate])
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    })
+    .ok_or_else(|| LangsError::GitRefNotFound(git_ref.to_string()))?;
+    log::info!("checking out {git_ref} at {commit}");
+    run_git(clone_path, &["checkout", "-q", "--detach", &commit])?;
+    run_git(clone_path, &["clean", "-df"])?;
+    run_git(clone_path, &["checkout", "."])?;
+    Ok(())
+}
+
+/// Copies the files in the working tree at source to target.
+/// If source is a git repository, the .git directory and the files ignored by git are skipped.
+fn copy_working_tree(source: &Path, target: &Path) -> Result<(), LangsError> {
+    file_util::create_dir_all(target)?;
+    if !source.join(".git").exists() {
+        for entry in file_util::read_dir(source)? {
+            let entry = entry.map_err(|e| FileError::DirRead(source.to_path_buf(), e))?;
+            file_util::copy(entry.path(), target)?;
+        }
+        return Ok(());
+    }
+
+    // tracked files, including uncommitted changes, and untracked files that are not ignored
+    let output = run_git(
+        source,
+        &[
+            "ls-files",
+            "-z",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+        ],
+    )?;
+    for relative in output.stdout.split(|b| *b == 0).filter(|p| !p.is_empty()) {
+        // git uses UTF-8 for paths on all platforms
+        let relative = String::from_utf8_lossy(relative);
+        let file = source.join(relative.as_ref());
+        // tracked files that have been deleted in the working tree are still listed
+        if !file.is_file() {
+            continue;
+        }
+        let target_file = target.join(relative.as_ref());
+        if let Some(parent) = target_file.parent() {
+            file_util::create_dir_all(parent)?;
+        }
+        file_util::copy(file, target_file)?;
+    }
     Ok(())
 }
 
+/// The commit checked out in the repository, if it is a git repository whose working tree has no changes.
+/// The commit of a working tree with uncommitted changes or untracked files would not describe its contents.
+fn resolve_commit(repo_path: &Path) -> Result<Option<String>, LangsError> {
+    if !repo_path.join(".git").exists() {
+        return Ok(None);
+    }
+    let status = run_git(repo_path, &["status", "--porcelain"])?;
+    if !status.stdout.is_empty() {
+        log::warn!(
+            "{} has uncommitted changes, not reporting a commit",
+            repo_path.display()
+        );
+        return Ok(None);
+    }
+    let output = run_git(repo_path, &["rev-parse", "HEAD"])?;
+    Ok(Some(
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    ))
+}
+
 /// Makes sure no directory directly under path is an exercise directory containing a dash in the relative path from path to the dir.
 /// A dash is used as a special delimiter.
 fn check_directory_names(path: &Path) -> Result<(), LangsError> {
@@ -278,8 +564,18 @@ fn get_course_options(course_clone_path: &Path, course_name: &str) -> Result<Map
     let options_file = course_clone_path.join("course_options.yml");
     if options_file.exists() {
         let file = file_util::open_file(&options_file)?;
-        let course_options: Mapping = deserialize::yaml_from_reader(file)
+        let mut course_options: Mapping = deserialize::yaml_from_reader(file)
             .map_err(|e| LangsError::DeserializeYaml(options_file, e))?;
+        // the options under courses.<course_name> override the general ones
+        let course_specific = course_options
+            .get("courses")
+            .and_then(|courses| courses.get(course_name))
+            .and_then(Value::as_mapping)
+            .cloned();
+        if let Some(course_specific) = course_specific {
+            log::debug!("merging options specific to {course_name}");
+            course_options.extend(course_specific);
+        }
         Ok(course_options)
     } else {
         Ok(Mapping::new())
@@ -292,12 +588,14 @@ fn get_exercises(
     exercise_dirs_and_tmcprojects: Vec<(PathBuf, Option<TmcProjectYml>)>,
     course_clone_path: &Path,
     course_stub_path: &Path,
+    jobs: usize,
 ) -> Result<Vec<RefreshExercise>, LangsError> {
     log::info!("finding exercise checksums and points");
 
-    let exercises = exercise_dirs_and_tmcprojects
-        .into_iter()
-        .map(|(exercise_dir, tmcproject_yml)| {
+    let exercises = parallel::map(
+        exercise_dirs_and_tmcprojects,
+        jobs,
+        |(exercise_dir, tmcproject_yml)| {
             log::debug!(
                 "processing points and checksum for {}",
                 exercise_dir.display()
@@ -324,8 +622,10 @@ fn get_exercises(
                 sandbox_image,
                 tmcproject_yml,
             })
-        })
-        .collect::<Result<_, LangsError>>()?;
+        },
+    )
+    .into_iter()
+    .collect::<Result<_, LangsError>>()?;
     Ok(exercises)
 }
 
@@ -355,10 +655,171 @@ fn calculate_checksum(exercise_dir: &Path) -> Result<String, LangsError> {
     Ok(format!("{digest:x}"))
 }
 
+/// Calculates a checksum from the exercise's files in the clone, its merged .tmcproject.yml and the template variant,
+/// which together determine the stub, solution and other data generated for the exercise.
+fn calculate_source_checksum(
+    exercise_dir: &Path,
+    tmcproject_yml: Option<&TmcProjectYml>,
+    variant: Option<&str>,
+) -> Result<String, LangsError> {
+    let mut digest = Context::new();
+    digest.consume(calculate_checksum(exercise_dir)?);
+    if let Some(tmcproject_yml) = tmcproject_yml {
+        digest.consume(serde_yaml::to_string(tmcproject_yml)?);
+    }
+    if let Some(variant) = variant {
+        digest.consume(format!("variant={variant}"));
+    }
+    let digest = digest.finalize();
+    Ok(format!("{digest:x}"))
+}
+
+/// Loads the manifest written by the previous refresh, if any.
+/// A missing or invalid manifest or one from a different version of tmc-langs is ignored, causing every exercise to be rebuilt.
+fn load_manifest(cache_path: &Path) -> Option<RefreshManifest> {
+    let manifest_path = cache_path.join(MANIFEST_FILE_NAME);
+    if !manifest_path.exists() {
+        log::info!("no manifest in previous cache at {}", cache_path.display());
+        return None;
+    }
+    let manifest = file_util::open_file(&manifest_path)
+        .map_err(LangsError::from)
+        .and_then(|file| {
+            deserialize::json_from_reader::<RefreshManifest>(file)
+                .map_err(|e| LangsError::DeserializeJson(manifest_path.clone(), e))
+        });
+    match manifest {
+        Ok(manifest) if manifest.tmc_langs_version == env!("CARGO_PKG_VERSION") => Some(manifest),
+        Ok(manifest) => {
+            log::info!(
+                "previous cache was created with tmc-langs {}, rebuilding all exercises",
+                manifest.tmc_langs_version
+            );
+            None
+        }
+        Err(error) => {
+            log::warn!("failed to read manifest from previous cache: {error}");
+            None
+        }
+    }
+}
+
+fn save_manifest(
+    cache_path: &Path,
+    exercises: &[RefreshExercise],
+    source_checksums: &[String],
+) -> Result<(), LangsError> {
+    let manifest = RefreshManifest {
+        tmc_langs_version: env!("CARGO_PKG_VERSION").to_string(),
+        exercises: exercises
+            .iter()
+            .zip(source_checksums)
+            .map(|(exercise, source_checksum)| ManifestExercise {
+                path: exercise.path.clone(),
+                source_checksum: source_checksum.clone(),
+                exercise: exercise.clone(),
+            })
+            .collect(),
+    };
+    let manifest = serde_json::to_vec(&manifest)?;
+    file_util::write_to_file(manifest, cache_path.join(MANIFEST_FILE_NAME))?;
+    Ok(())
+}
+
+/// Finds the exercises whose source checksum matches the one in the previous manifest and whose files exist in the previous cache.
+/// Returns the previous data of each reusable exercise, in the same order as the given exercises.
+fn find_reusable_exercises(
+    previous_manifest: Option<RefreshManifest>,
+    previous_cache_path: &Path,
+    exercise_dirs_and_tmcprojects: &[(PathBuf, Option<TmcProjectYml>)],
+    source_checksums: &[String],
+) -> Vec<Option<RefreshExercise>> {
+    let mut previous_exercises = previous_manifest
+        .map(|m| m.exercises)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|e| (e.path.clone(), e))
+        .collect::<HashMap<_, _>>();
+
+    exercise_dirs_and_tmcprojects
+        .iter()
+        .zip(source_checksums)
+        .map(|((exercise_dir, _), source_checksum)| {
+            let previous = previous_exercises.remove(exercise_dir)?;
+            if &previous.source_checksum != source_checksum {
+                log::debug!("exercise {} has changed", exercise_dir.display());
+                return None;
+            }
+            let mut exercise = previous.exercise;
+            exercise.path = previous.path;
+            let zip_name = format!("{}.zip", exercise.name);
+            let artifacts_exist = previous_cache_path
+                .join("solution")
+                .join(&exercise.path)
+                .is_dir()
+                && previous_cache_path
+                    .join("stub")
+                    .join(&exercise.path)
+                    .is_dir()
+                && previous_cache_path
+                    .join("solution_zip")
+                    .join(&zip_name)
+                    .is_file()
+                && previous_cache_path
+                    .join("stub_zip")
+                    .join(&zip_name)
+                    .is_file();
+            if !artifacts_exist {
+                log::warn!(
+                    "files for unchanged exercise {} missing from previous cache",
+                    exercise_dir.display()
+                );
+                return None;
+            }
+            Some(exercise)
+        })
+        .collect()
+}
+
+/// Hard links the directory's files to the target, creating the directories as needed.
+fn link_or_copy_dir(source: &Path, target: &Path) -> Result<(), LangsError> {
+    for entry in WalkDir::new(source) {
+        let entry = entry?;
+        let relative = entry
+            .path()
+            .strip_prefix(source)
+            .expect("entries are inside source");
+        let target_path = target.join(relative);
+        if entry.file_type().is_dir() {
+            file_util::create_dir_all(&target_path)?;
+        } else {
+            link_or_copy_file(entry.path(), &target_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Hard links the file to the target, copying it instead if linking fails, e.g. when the caches are on different file systems.
+fn link_or_copy_file(source: &Path, target: &Path) -> Result<(), LangsError> {
+    if let Some(parent) = target.parent() {
+        file_util::create_dir_all(parent)?;
+    }
+    if let Err(error) = std::fs::hard_link(source, target) {
+        log::debug!(
+            "failed to link {} to {}, copying instead: {error}",
+            source.display(),
+            target.display()
+        );
+        file_util::copy(source, target)?;
+    }
+    Ok(())
+}
+
 fn execute_zip(
     course_exercises: &[RefreshExercise],
     root_path: &Path,
     zip_dir: &Path,
+    jobs: usize,
 ) -> Result<(), LangsError> {
     log::info!(
         "compressing exercises from from {} to {}",
@@ -367,36 +828,42 @@ fn execute_zip(
     );
 
     file_util::create_dir_all(zip_dir)?;
-    for exercise in course_exercises {
-        let exercise_root = root_path.join(&exercise.path);
-        let zip_file_path = zip_dir.join(format!("{}.zip", exercise.name));
-
-        let mut writer = zip::ZipWriter::new(file_util::create_file(zip_file_path)?);
-        for entry in WalkDir::new(exercise_root) {
-            let entry = entry?;
-            let relative_path = entry
-                .path()
-                .strip_prefix(root_path)
-                .expect("entries are inside root_path");
-
-            if entry.path().is_file() {
-                writer.start_file(
-                    relative_path.to_string_lossy(),
-                    SimpleFileOptions::default().unix_permissions(0o755),
-                )?;
-                let bytes = file_util::read_file(entry.path())?;
-                writer.write_all(&bytes).map_err(LangsError::ZipWrite)?;
-            } else {
-                // java-langs expects directories to have their own entries
-                writer.start_file(
-                    relative_path.join("").to_string_lossy(), // java-langs expects directory entries to have a trailing slash
-                    SimpleFileOptions::default().unix_permissions(0o755),
-                )?;
+    parallel::map(
+        course_exercises.iter().collect(),
+        jobs,
+        |exercise| -> Result<(), LangsError> {
+            let exercise_root = root_path.join(&exercise.path);
+            let zip_file_path = zip_dir.join(format!("{}.zip", exercise.name));
+
+            let mut writer = zip::ZipWriter::new(file_util::create_file(zip_file_path)?);
+            for entry in WalkDir::new(exercise_root) {
+                let entry = entry?;
+                let relative_path = entry
+                    .path()
+                    .strip_prefix(root_path)
+                    .expect("entries are inside root_path");
+
+                if entry.path().is_file() {
+                    writer.start_file(
+                        relative_path.to_string_lossy(),
+                        SimpleFileOptions::default().unix_permissions(0o755),
+                    )?;
+                    let bytes = file_util::read_file(entry.path())?;
+                    writer.write_all(&bytes).map_err(LangsError::ZipWrite)?;
+                } else {
+                    // java-langs expects directories to have their own entries
+                    writer.start_file(
+                        relative_path.join("").to_string_lossy(), // java-langs expects directory entries to have a trailing slash
+                        SimpleFileOptions::default().unix_permissions(0o755),
+                    )?;
+                }
             }
-        }
-        writer.finish()?;
-    }
-    Ok(())
+            writer.finish()?;
+            Ok(())
+        },
+    )
+    .into_iter()
+    .collect()
 }
 
 #[cfg(not(unix))]
@@ -444,7 +911,6 @@ fn finish_stage(message: impl Into<String>) {
 mod test {
     use super::*;
     use crate::find_exercise_directories;
-    use serde_yaml::Value;
     use std::io::Read;
     use tempfile::tempdir;
 
@@ -501,6 +967,28 @@ mod test {
         )
     }
 
+    #[test]
+    fn merges_course_specific_options() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        file_to(
+            &temp,
+            "course_options.yml",
+            "option: 1
+other: 2
+courses:
+  some course:
+    option: 3
+  other course:
+    other: 4
+",
+        );
+        let options = get_course_options(temp.path(), "some course").unwrap();
+        assert_eq!(options.get("option").unwrap().as_u64(), Some(3));
+        assert_eq!(options.get("other").unwrap().as_u64(), Some(2));
+    }
+
     #[test]
     fn gets_exercises() {
         init();
@@ -528,6 +1016,7 @@ mod test {
             exercise_dirs,
             &temp.path().join("course"),
             &temp.path().join("course"),
+            1,
         )
         .unwrap();
         assert_eq!(exercises.len(), 1);
@@ -572,10 +1061,11 @@ mod test {
             exercise_dirs,
             &temp.path().join("clone"),
             &temp.path().join("stub"),
+            2,
         )
         .unwrap();
 
-        execute_zip(&exercises, &temp.path().join("stub"), temp.path()).unwrap();
+        execute_zip(&exercises, &temp.path().join("stub"), temp.path(), 2).unwrap();
 
         let zip = temp.path().join("part1-ex1.zip");
         assert!(zip.exists());
@@ -640,6 +1130,276 @@ mod test {
         set_permissions(temp.path()).unwrap();
     }
 
+    #[test]
+    fn reuses_unchanged_exercises() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        let old_cache = temp.path().join("course-1");
+        for dir in ["solution", "stub"] {
+            file_to(&old_cache, format!("{dir}/part1/ex1/src/file"), "");
+            file_to(&old_cache, format!("{dir}/part1/ex2/src/file"), "");
+        }
+        for dir in ["solution_zip", "stub_zip"] {
+            file_to(&old_cache, format!("{dir}/part1-ex1.zip"), "");
+            file_to(&old_cache, format!("{dir}/part1-ex2.zip"), "");
+        }
+        let exercise = |name: &str, path: &str| RefreshExercise {
+            name: name.to_string(),
+            checksum: "stub checksum".to_string(),
+            points: vec!["1".to_string()],
+            path: PathBuf::from(path),
+            sandbox_image: "image".to_string(),
+            tmcproject_yml: None,
+        };
+        save_manifest(
+            &old_cache,
+            &[
+                exercise("part1-ex1", "part1/ex1"),
+                exercise("part1-ex2", "part1/ex2"),
+            ],
+            &["1".to_string(), "2".to_string()],
+        )
+        .unwrap();
+
+        let manifest = load_manifest(&old_cache).unwrap();
+        let exercises = [
+            (PathBuf::from("part1/ex1"), None),
+            (PathBuf::from("part1/ex2"), None),
+            (PathBuf::from("part1/ex3"), None),
+        ];
+        let reused = find_reusable_exercises(
+            Some(manifest),
+            &old_cache,
+            &exercises,
+            &["1".to_string(), "changed".to_string(), "3".to_string()],
+        );
+        assert_eq!(reused.len(), 3);
+        let reused_exercise = reused[0].as_ref().unwrap();
+        assert_eq!(reused_exercise.path, Path::new("part1/ex1"));
+        assert_eq!(reused_exercise.checksum, "stub checksum");
+        assert_eq!(reused_exercise.points, ["1"]);
+        assert!(reused[1].is_none());
+        assert!(reused[2].is_none());
+
+        let new_cache = temp.path().join("course-2");
+        link_or_copy_dir(
+            &old_cache.join("stub/part1/ex1"),
+            &new_cache.join("stub/part1/ex1"),
+        )
+        .unwrap();
+        assert!(new_cache.join("stub/part1/ex1/src/file").is_file());
+    }
+
+    #[test]
+    fn ignores_manifest_from_other_version() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        file_to(
+            &temp,
+            MANIFEST_FILE_NAME,
+            r#"{"tmc_langs_version":"0.0.0","exercises":[]}"#,
+        );
+        assert!(load_manifest(temp.path()).is_none());
+        assert!(load_manifest(&temp.path().join("missing")).is_none());
+    }
+
+    fn git(cwd: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .args(["-c", "user.name=tmc", "-c", "user.email=tmc@example.com"])
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    #[test]
+    fn refreshes_from_git_refs_bundles_and_directories() {
+        init();
+
+        let temp = tempdir().unwrap();
+        let repo = temp.path().join("repo");
+        file_to(&repo, "part1/ex1/setup.py", "");
+        file_to(&repo, "part1/ex1/test/test.py", "@points('1')");
+        git(&repo, &["init", "-q", "-b", "main"]);
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-q", "-m", "first"]);
+        git(&repo, &["tag", "v1"]);
+        let first = git(&repo, &["rev-parse", "HEAD"]);
+        file_to(&repo, "part1/ex2/setup.py", "");
+        file_to(&repo, "part1/ex2/test/test.py", "@points('2')");
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-q", "-m", "second"]);
+        let second = git(&repo, &["rev-parse", "HEAD"]);
+
+        // each refresh creates the next version of the cache from the previous one
+        let cache_root = temp.path().join("cache");
+        let refresh = |version: u32, source: RefreshSource| {
+            refresh_course(
+                "course".to_string(),
+                cache_root.join(format!("course-{version}")),
+                source,
+                cache_root.clone(),
+                1,
+            )
+        };
+        let git_source = |git_ref: &str| RefreshSource::Git {
+            url: repo.to_str().unwrap().to_string(),
+            git_ref: git_ref.to_string(),
+        };
+
+        let data = refresh(0, git_source("v1")).unwrap();
+        assert_eq!(data.commit.as_deref(), Some(first.as_str()));
+        assert_eq!(data.exercises.len(), 1);
+
+        let data = refresh(1, git_source(&second)).unwrap();
+        assert_eq!(data.commit.as_deref(), Some(second.as_str()));
+        assert_eq!(data.exercises.len(), 2);
+        assert_eq!(data.rebuilt_exercises, ["part1-ex2"]);
+
+        let data = refresh(2, git_source("main")).unwrap();
+        assert_eq!(data.commit.as_deref(), Some(second.as_str()));
+        assert!(data.rebuilt_exercises.is_empty());
+
+        let bundle = temp.path().join("course.bundle");
+        git(
+            &repo,
+            &["bundle", "create", "-q", bundle.to_str().unwrap(), "--all"],
+        );
+        let data = refresh(
+            3,
+            RefreshSource::Bundle {
+                path: bundle,
+                git_ref: Some("v1".to_string()),
+            },
+        )
+        .unwrap();
+        assert_eq!(data.commit.as_deref(), Some(first.as_str()));
+        assert_eq!(data.exercises.len(), 1);
+
+        // a clean working tree has a commit, the .git directory and ignored files are not copied
+        file_to(&repo, ".gitignore", "ignored/\n");
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-q", "-m", "third"]);
+        let third = git(&repo, &["rev-parse", "HEAD"]);
+        file_to(&repo, "ignored/ex4/setup.py", "");
+        file_to(&repo, "ignored/ex4/test/test.py", "@points('4')");
+        let data = refresh(4, RefreshSource::Directory(repo.clone())).unwrap();
+        assert_eq!(data.commit.as_deref(), Some(third.as_str()));
+        assert_eq!(data.exercises.len(), 2);
+        assert!(!cache_root.join("course-5/clone/.git").exists());
+        assert!(!cache_root.join("course-5/clone/ignored").exists());
+
+        // uncommitted changes are included, but they are not part of any commit
+        file_to(&repo, "part1/ex3/setup.py", "");
+        file_to(&repo, "part1/ex3/test/test.py", "@points('3')");
+        let data = refresh(5, RefreshSource::Directory(repo.clone())).unwrap();
+        assert_eq!(data.commit, None);
+        assert_eq!(data.exercises.len(), 3);
+
+        let error = refresh(6, git_source("missing")).unwrap_err();
+        assert!(matches!(error, LangsError::GitRefNotFound(_)));
+    }
+
+    #[test]
+    fn refresh_fails_on_invalid_annotations() {
+        init();
+
+        let temp = tempdir().unwrap();
+        let course = temp.path().join("course");
+        file_to(&course, "part1/ex1/setup.py", "");
+        file_to(&course, "part1/ex1/test/test.py", "@points('1')");
+        file_to(
+            &course,
+            "part1/ex1/src/main.py",
+            "# BEGIN SOLUTION\nprint('hello')\n",
+        );
+
+        let cache_root = temp.path().join("cache");
+        let error = refresh_course(
+            "course".to_string(),
+            cache_root.join("course-0"),
+            RefreshSource::Directory(course),
+            cache_root,
+            1,
+        )
+        .unwrap_err();
+        let LangsError::InvalidAnnotations(errors) = error else {
+            panic!("unexpected error {error}");
+        };
+        assert_eq!(
+            errors,
+            "part1/ex1/src/main.py:1:1: BEGIN SOLUTION is never closed"
+        );
+    }
+
+    #[test]
+    fn refreshes_template_variant_from_course_options() {
+        init();
+
+        let temp = tempdir().unwrap();
+        let course = temp.path().join("course");
+        file_to(
+            &course,
+            "course_options.yml",
+            "courses:\n  beginner:\n    template_variant: beginner\n  advanced:\n    template_variant: advanced\n",
+        );
+        file_to(&course, "part1/ex1/setup.py", "");
+        file_to(&course, "part1/ex1/test/test.py", "@points('1')");
+        file_to(
+            &course,
+            "part1/ex1/src/main.py",
+            "# BEGIN SOLUTION variant=advanced\ndef helper():\n    return 1\n# END SOLUTION\n",
+        );
+
+        let cache_root = temp.path().join("cache");
+        for (course_name, stub) in [
+            ("beginner", "def helper():\n    return 1\n"),
+            ("advanced", ""),
+        ] {
+            let refresh = refresh_course(
+                course_name.to_string(),
+                cache_root.join(format!("{course_name}-0")),
+                RefreshSource::Directory(course.clone()),
+                cache_root.clone(),
+                1,
+            )
+            .unwrap();
+            assert_eq!(
+                file_util::read_file_to_string(
+                    refresh.new_cache_path.join("stub/part1/ex1/src/main.py")
+                )
+                .unwrap(),
+                stub
+            );
+            assert_eq!(
+                file_util::read_file_to_string(
+                    refresh.new_cache_path.join("solution/part1/ex1/src/main.py")
+                )
+                .unwrap(),
+                "def helper():\n    return 1\n"
+            );
+        }
+
+        // the annotations limited to a variant would not be processed without one
+        let error = refresh_course(
+            "other".to_string(),
+            cache_root.join("other-0"),
+            RefreshSource::Directory(course.clone()),
+            cache_root.clone(),
+            1,
+        )
+        .unwrap_err();
+        assert!(matches!(error, LangsError::InvalidAnnotations(_)));
+    }
+
     #[test]
     fn checksum_matches_old_implementation() {
         init();
@@ -0,0 +1,329 @@
+//! Exporting the results of every student in a course as a table with a row for each student and exercise.
+
+use crate::{LangsError, tmc::TestMyCodeClient};
+use chrono::{DateTime, FixedOffset};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    path::Path,
+    str::FromStr,
+};
+use tmc_langs_util::file_util;
+
+/// A student's results for a single exercise.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub struct CourseResult {
+    pub user_id: u32,
+    pub exercise_id: u32,
+    pub exercise_name: String,
+    /// The number of submissions the student has made to the exercise.
+    pub submissions: u32,
+    /// Whether any of the student's submissions passed all of the tests.
+    pub all_tests_passed: bool,
+    /// The points awarded to the student for the exercise, sorted by name.
+    pub points: Vec<String>,
+    #[cfg_attr(feature = "ts-rs", ts(type = "string | null"))]
+    pub last_submission_at: Option<DateTime<FixedOffset>>,
+}
+
+/// The format the course results are exported in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row. The points are separated by semicolons.
+    Csv,
+    /// A JSON object on each line.
+    Ndjson,
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Csv => write!(f, "csv"),
+            Self::Ndjson => write!(f, "ndjson"),
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let format = match s {
+            "csv" => Self::Csv,
+            "ndjson" => Self::Ndjson,
+            _ => return Err("invalid format"),
+        };
+        Ok(format)
+    }
+}
+
+/// Fetches the course's submissions and awarded points and combines them into a row for each student and exercise they have submitted to or have points for.
+/// If `since` is given, only the rows that have new submissions or points after it are returned. The returned rows still contain all of the student's results for the exercise.
+/// The rows are sorted by the user and exercise ids.
+///
+/// Students without any submissions or points are not included, as the server does not list the students of a course.
+/// The server returns all of the submissions and points at once, so the whole course is fetched even when `since` is given.
+pub fn export_course_results(
+    client: &TestMyCodeClient,
+    course_id: u32,
+    since: Option<DateTime<FixedOffset>>,
+) -> Result<Vec<CourseResult>, LangsError> {
+    log::debug!("exporting results for course {course_id}");
+
+    let exercises = client.get_course_exercises(course_id)?;
+    let exercise_ids = exercises
+        .iter()
+        .map(|e| (e.name.as_str(), e.id))
+        .collect::<HashMap<_, _>>();
+    let exercise_names = exercises
+        .iter()
+        .map(|e| (e.id, e.name.as_str()))
+        .collect::<HashMap<_, _>>();
+
+    let mut results = BTreeMap::<(u32, u32), CourseResult>::new();
+    let mut changed = BTreeMap::<(u32, u32), bool>::new();
+    let new_row = |user_id: u32, exercise_id: u32, exercise_name: &str| CourseResult {
+        user_id,
+        exercise_id,
+        exercise_name: exercise_name.to_string(),
+        submissions: 0,
+        all_tests_passed: false,
+        points: vec![],
+        last_submission_at: None,
+    };
+
+    for submission in client.get_course_submissions(course_id)? {
+        let Some(&exercise_id) = exercise_ids.get(submission.exercise_name.as_str()) else {
+            log::warn!(
+                "Skipping submission {} to unknown exercise {}",
+                submission.id,
+                submission.exercise_name
+            );
+            continue;
+        };
+        let key = (submission.user_id, exercise_id);
+        let result = results
+            .entry(key)
+            .or_insert_with(|| new_row(submission.user_id, exercise_id, &submission.exercise_name));
+        result.submissions += 1;
+        result.all_tests_passed |= submission.all_tests_passed;
+        if result
+            .last_submission_at
+            .is_none_or(|last| last < submission.created_at)
+        {
+            result.last_submission_at = Some(submission.created_at);
+        }
+        *changed.entry(key).or_default() |= since.is_none_or(|since| submission.created_at > since);
+    }
+
+    for point in client.get_course_points(course_id)? {
+        let Some(exercise_name) = exercise_names.get(&point.exercise_id) else {
+            log::warn!(
+                "Skipping point {} for unknown exercise {}",
+                point.awarded_point.name,
+                point.exercise_id
+            );
+            continue;
+        };
+        let key = (point.awarded_point.user_id, point.exercise_id);
+        let result = results.entry(key).or_insert_with(|| {
+            new_row(
+                point.awarded_point.user_id,
+                point.exercise_id,
+                exercise_name,
+            )
+        });
+        result.points.push(point.awarded_point.name);
+        *changed.entry(key).or_default() |=
+            since.is_none_or(|since| point.awarded_point.created_at > since);
+    }
+
+    let results = results
+        .into_iter()
+        .filter(|(key, _)| changed.get(key).copied().unwrap_or_default())
+        .map(|(_, mut result)| {
+            result.points.sort();
+            result.points.dedup();
+            result
+        })
+        .collect();
+    Ok(results)
+}
+
+/// Writes the course results to the given path in the given format.
+pub fn write_course_results(
+    results: &[CourseResult],
+    format: ExportFormat,
+    output_path: &Path,
+) -> Result<(), LangsError> {
+    let mut output = String::new();
+    match format {
+        ExportFormat::Csv => {
+            output.push_str(
+                "user_id,exercise_id,exercise_name,submissions,all_tests_passed,points,last_submission_at\n",
+            );
+            for result in results {
+                let row = [
+                    result.user_id.to_string(),
+                    result.exercise_id.to_string(),
+                    csv_field(&result.exercise_name),
+                    result.submissions.to_string(),
+                    result.all_tests_passed.to_string(),
+                    csv_field(&result.points.join(";")),
+                    result
+                        .last_submission_at
+                        .map(|at| at.to_rfc3339())
+                        .unwrap_or_default(),
+                ];
+                output.push_str(&row.join(","));
+                output.push('\n');
+            }
+        }
+        ExportFormat::Ndjson => {
+            for result in results {
+                output.push_str(&serde_json::to_string(result)?);
+                output.push('\n');
+            }
+        }
+    }
+    file_util::write_to_file(output.as_bytes(), output_path)?;
+    Ok(())
+}
+
+// quotes the field if it contains characters that have a special meaning in CSV
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use crate::tmc;
+    use mockito::{Matcher, Server};
+    use oauth2::{AccessToken, EmptyExtraTokenFields, basic::BasicTokenType};
+
+    fn client(root_url: &str) -> TestMyCodeClient {
+        let mut client = TestMyCodeClient::new(
+            root_url.parse().unwrap(),
+            "client".to_string(),
+            "version".to_string(),
+        )
+        .unwrap();
+        let token = tmc::Token::new(
+            AccessToken::new("".to_string()),
+            BasicTokenType::Bearer,
+            EmptyExtraTokenFields {},
+        );
+        client.set_token(token, None);
+        client
+    }
+
+    fn exercise(id: u32, name: &str) -> String {
+        format!(
+            r#"{{"id":{id},"available_points":[],"awarded_points":[],"name":"{name}","publish_time":null,"solution_visible_after":null,"deadline":null,"soft_deadline":null,"disabled":false,"unlocked":true}}"#
+        )
+    }
+
+    fn submission(id: u32, user_id: u32, exercise_name: &str, passed: bool, at: &str) -> String {
+        format!(
+            r#"{{"id":{id},"user_id":{user_id},"pretest_error":null,"created_at":"{at}","exercise_name":"{exercise_name}","course_id":1,"processed":true,"all_tests_passed":{passed},"points":null,"processing_tried_at":null,"processing_began_at":null,"processing_completed_at":null,"times_sent_to_sandbox":1,"processing_attempts_started_at":"{at}","params_json":null,"requires_review":false,"requests_review":false,"reviewed":false,"message_for_reviewer":"","newer_submission_reviewed":false,"review_dismissed":false,"paste_available":false,"message_for_paste":"","paste_key":null}}"#
+        )
+    }
+
+    fn point(user_id: u32, exercise_id: u32, name: &str, at: &str) -> String {
+        format!(
+            r#"{{"awarded_point":{{"id":1,"course_id":1,"user_id":{user_id},"submission_id":1,"name":"{name}","created_at":"{at}"}},"exercise_id":{exercise_id}}}"#
+        )
+    }
+
+    fn mock_course(server: &mut Server) -> Vec<mockito::Mock> {
+        let exercises = format!(
+            "[{},{}]",
+            exercise(1, "first"),
+            exercise(2, "second, part 1")
+        );
+        let submissions = format!(
+            "[{},{},{},{}]",
+            submission(1, 10, "first", false, "2026-01-01T10:00:00+00:00"),
+            submission(2, 10, "first", true, "2026-01-02T10:00:00+00:00"),
+            submission(3, 11, "second, part 1", true, "2026-01-01T10:00:00+00:00"),
+            submission(4, 11, "removed", true, "2026-01-03T10:00:00+00:00"),
+        );
+        let points = format!(
+            "[{},{},{}]",
+            point(10, 1, "1.2", "2026-01-02T10:00:00+00:00"),
+            point(10, 1, "1.1", "2026-01-02T10:00:00+00:00"),
+            point(11, 2, "2.1", "2026-01-01T10:00:00+00:00"),
+        );
+        [
+            ("/api/v8/courses/1/exercises", exercises),
+            ("/api/v8/courses/1/submissions", submissions),
+            ("/api/v8/courses/1/points", points),
+        ]
+        .into_iter()
+        .map(|(path, body)| {
+            server
+                .mock("GET", path)
+                .match_query(Matcher::Any)
+                .with_body(body)
+                .create()
+        })
+        .collect()
+    }
+
+    #[test]
+    fn exports_course_results() {
+        let mut server = Server::new();
+        let _mocks = mock_course(&mut server);
+        let client = client(&server.url());
+
+        let results = export_course_results(&client, 1, None).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].user_id, 10);
+        assert_eq!(results[0].submissions, 2);
+        assert!(results[0].all_tests_passed);
+        assert_eq!(results[0].points, ["1.1", "1.2"]);
+        assert_eq!(
+            results[0].last_submission_at.unwrap().to_rfc3339(),
+            "2026-01-02T10:00:00+00:00"
+        );
+        assert_eq!(results[1].exercise_id, 2);
+
+        let since = "2026-01-01T12:00:00+00:00".parse().unwrap();
+        let results = export_course_results(&client, 1, Some(since)).unwrap();
+        assert_eq!(results.len(), 1);
+        // the row contains the submissions made before the timestamp
+        assert_eq!(results[0].submissions, 2);
+
+        let temp = tempfile::tempdir().unwrap();
+        let csv = temp.path().join("results.csv");
+        let all = export_course_results(&client, 1, None).unwrap();
+        write_course_results(&all, ExportFormat::Csv, &csv).unwrap();
+        let csv = std::fs::read_to_string(csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[1],
+            "10,1,first,2,true,1.1;1.2,2026-01-02T10:00:00+00:00"
+        );
+        assert_eq!(
+            lines[2],
+            r#"11,2,"second, part 1",1,true,2.1,2026-01-01T10:00:00+00:00"#
+        );
+
+        let ndjson = temp.path().join("results.ndjson");
+        write_course_results(&all, ExportFormat::Ndjson, &ndjson).unwrap();
+        let ndjson = std::fs::read_to_string(ndjson).unwrap();
+        assert_eq!(ndjson.lines().count(), 2);
+        let first: serde_json::Value =
+            serde_json::from_str(ndjson.lines().next().unwrap()).unwrap();
+        assert_eq!(first["points"], serde_json::json!(["1.1", "1.2"]));
+    }
+}
@@ -39,9 +39,23 @@ pub enum LangsError {
     #[error("Failed to parse file {0}")]
     SubmissionParse(PathBuf, #[source] Box<Self>),
     #[error(
-        "Failed to deserialize credentials file at {0}. The file has been removed, please try again"
+        "Failed to deserialize the stored credentials. They have been removed, please try again"
     )]
-    DeserializeCredentials(PathBuf, #[source] JsonError),
+    DeserializeCredentials(#[source] JsonError),
+    #[error(
+        "Invalid credential store {0}, expected secret-service, encrypted-file or plaintext-file"
+    )]
+    InvalidCredentialStore(String),
+    #[error(
+        "The encrypted credential store requires a passphrase in TMC_LANGS_CREDENTIALS_PASSPHRASE"
+    )]
+    NoCredentialsPassphrase,
+    #[error("Failed to derive the key for encrypting credentials")]
+    CredentialsKeyDerivation(#[source] argon2::Error),
+    #[error("Failed to encrypt credentials")]
+    CredentialsEncryption,
+    #[error("Failed to decrypt credentials, the passphrase may be incorrect")]
+    CredentialsDecryption,
     #[error("Failed to deserialize JSON from file at {0}")]
     DeserializeJson(PathBuf, #[source] JsonError),
     #[error("Failed to deserialize YAML from file at {0}")]
@@ -56,10 +70,20 @@ pub enum LangsError {
     DirectoryExists(PathBuf),
     #[error("The value for projects-dir must be a string.")]
     ProjectsDirNotString,
+    #[error("No profile named {0} in the config")]
+    UnknownProfile(String),
+    #[error("Invalid profile name {0}, only letters, numbers, '-' and '_' are allowed")]
+    InvalidProfileName(String),
+    #[error("Invalid root URL {0} in profile")]
+    InvalidProfileUrl(String, #[source] url::ParseError),
+    #[error("Invalid value for profiles")]
+    InvalidProfiles(#[source] toml::de::Error),
     #[error("Attempted to move the projects-dir to the directory it's already in")]
     MovingProjectsDirToItself,
     #[error("No projects-dir found")]
     NoProjectsDir,
+    #[error("No queued submission with the id {0}")]
+    QueuedSubmissionNotFound(uuid::Uuid),
     #[error("Exercise in project-config not found")]
     NoProjectExercise,
     #[error("Decoded password was not valid UTF-8")]
@@ -86,6 +110,10 @@ pub enum LangsError {
         "Cache path {0} was invalid. Not a valid UTF-8 string or did not contain a cache version after a dash"
     )]
     InvalidCachePath(PathBuf),
+    #[error("The exercises contain invalid annotations:\n{0}")]
+    InvalidAnnotations(String),
+    #[error("Git ref {0} was not found in the repository")]
+    GitRefNotFound(String),
     #[error("Path {0} contained a dash '-' which is currently not allowed")]
     InvalidDirectory(PathBuf),
 
@@ -132,6 +160,9 @@ pub enum LangsError {
     MoocClient(#[from] Box<tmc_mooc_client::MoocClientError>),
     #[error(transparent)]
     PersistTempFile(#[from] tempfile::PersistError),
+    #[cfg(target_os = "linux")]
+    #[error(transparent)]
+    SecretService(#[from] secret_service::Error),
 }
 
 /// Error validating TMC params values.
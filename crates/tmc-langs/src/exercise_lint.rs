@@ -0,0 +1,254 @@
+//! Checking the annotations in exercise templates before they are turned into stubs and solutions.
+
+use crate::{
+    LangsError,
+    submission_processing::{
+        comment_syntaxes, contains_tmcignore, is_hidden_dir, notebook_cell_extension,
+        notebook_cell_source, on_skip_list,
+    },
+};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    convert::Infallible,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+use tmc_langs_framework::{
+    AnnotationIssue, AnnotationIssueKind, CommentSyntaxes, lint_annotations,
+};
+use tmc_langs_util::{deserialize, file_util};
+use walkdir::WalkDir;
+
+/// A problem with the annotations in an exercise file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub struct LintIssue {
+    /// Path to the file relative to the exercise directory.
+    pub path: PathBuf,
+    /// The notebook cell of the annotation, starting from 1, if the file is a notebook.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cell: Option<usize>,
+    /// The line of the annotation, starting from 1. In notebooks, the line is counted from the start of the cell.
+    pub line: usize,
+    /// The column where the annotation's comment starts, starting from 1.
+    pub column: usize,
+    pub kind: AnnotationIssueKind,
+    pub message: String,
+}
+
+impl LintIssue {
+    fn new(path: &Path, cell: Option<usize>, issue: AnnotationIssue) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            cell,
+            line: issue.line,
+            column: issue.column,
+            kind: issue.kind,
+            message: issue.message,
+        }
+    }
+
+    /// Whether the issue certainly breaks the stub or solution, see `AnnotationIssueKind::is_error`.
+    pub fn is_error(&self) -> bool {
+        self.kind.is_error()
+    }
+}
+
+impl Display for LintIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some(cell) = self.cell {
+            write!(f, " cell {cell}")?;
+        }
+        write!(f, ":{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Checks the annotations in the exercise's files for unclosed, nested or mismatched blocks and for annotations that would remain in the stub.
+/// The same files are checked as are processed by `prepare_stub` and `prepare_solution`, using the same comment syntaxes.
+/// The code and markdown cells of notebooks are checked separately, like they are processed.
+/// Annotations limited to a variant are errors if no variant is given, see `MetaSyntaxParser::select_variant`.
+pub fn lint_exercise(
+    exercise_path: &Path,
+    variant: Option<&str>,
+) -> Result<Vec<LintIssue>, LangsError> {
+    log::debug!("linting exercise {}", exercise_path.display());
+
+    let comment_syntaxes = comment_syntaxes(exercise_path)?;
+    let mut issues = vec![];
+    for entry in WalkDir::new(exercise_path)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !is_hidden_dir(e) && !on_skip_list(e) && !contains_tmcignore(e))
+        .filter_map(|e| e.ok())
+    {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        // files without an extension are not included in the stub
+        let Some(extension) = entry.path().extension().and_then(|o| o.to_str()) else {
+            continue;
+        };
+        let relative_path = entry
+            .path()
+            .strip_prefix(exercise_path)
+            .expect("entries are inside the exercise path");
+        if extension == "ipynb" {
+            issues.extend(lint_notebook(
+                entry.path(),
+                relative_path,
+                &comment_syntaxes,
+                extension,
+                variant,
+            )?);
+            continue;
+        }
+
+        let bytes = file_util::read_file(entry.path())?;
+        let contents = if comment_syntaxes.contains(extension) {
+            String::from_utf8_lossy(&bytes).into_owned()
+        } else if let Ok(contents) = String::from_utf8(bytes) {
+            contents
+        } else {
+            // binary files are copied as is
+            continue;
+        };
+        let file_issues = lint_lines(&contents, &comment_syntaxes, extension, variant);
+        issues.extend(
+            file_issues
+                .into_iter()
+                .map(|issue| LintIssue::new(relative_path, None, issue)),
+        );
+    }
+    Ok(issues)
+}
+
+// each code and markdown cell is checked on its own, with the extension it is processed with
+fn lint_notebook(
+    path: &Path,
+    relative_path: &Path,
+    comment_syntaxes: &CommentSyntaxes,
+    extension: &str,
+    variant: Option<&str>,
+) -> Result<Vec<LintIssue>, LangsError> {
+    let file = file_util::open_file(path)?;
+    let json: Value = deserialize::json_from_reader(file)
+        .map_err(|e| LangsError::DeserializeJson(path.to_path_buf(), e))?;
+    let cells =
+        json.get("cells")
+            .and_then(|cs| cs.as_array())
+            .ok_or(LangsError::InvalidNotebook(
+                "Invalid or missing value for 'cells'",
+            ))?;
+
+    let mut issues = vec![];
+    for (index, cell) in cells.iter().enumerate() {
+        let Some(cell_extension) = notebook_cell_extension(cell, extension) else {
+            continue;
+        };
+        let source = notebook_cell_source(cell)?.concat();
+        let cell_issues = lint_lines(&source, comment_syntaxes, cell_extension, variant);
+        issues.extend(
+            cell_issues
+                .into_iter()
+                .map(|issue| LintIssue::new(relative_path, Some(index + 1), issue)),
+        );
+    }
+    Ok(issues)
+}
+
+fn lint_lines(
+    contents: &str,
+    comment_syntaxes: &CommentSyntaxes,
+    extension: &str,
+    variant: Option<&str>,
+) -> Vec<AnnotationIssue> {
+    lint_annotations(
+        contents
+            .lines()
+            .map(|line| Ok::<_, Infallible>(line.to_string())),
+        comment_syntaxes,
+        extension,
+        variant,
+    )
+    .unwrap_or_else(|never| match never {})
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lints_exercise() {
+        let temp = tempfile::tempdir().unwrap();
+        let exercise = temp.path().join("exercise");
+        file_util::write_to_file(
+            "class A {\n    // BEGIN SOLUTION\n    int a;\n}\n",
+            exercise.join("src/A.java"),
+        )
+        .unwrap();
+        file_util::write_to_file("fine\n", exercise.join("src/B.java")).unwrap();
+        file_util::write_to_file("BEGIN SOLUTION\n", exercise.join("notes.txt")).unwrap();
+        file_util::write_to_file("// END SOLUTION\n", exercise.join("private/C.java")).unwrap();
+
+        let issues = lint_exercise(&exercise, None).unwrap();
+        assert_eq!(issues.len(), 2);
+        assert_eq!(
+            issues[0].to_string(),
+            "notes.txt:1:1: 'BEGIN SOLUTION' is not processed in .txt files and will be copied to the stub as is"
+        );
+        assert!(!issues[0].is_error());
+        assert_eq!(issues[1].path, Path::new("src/A.java"));
+        assert_eq!((issues[1].line, issues[1].column), (2, 5));
+        assert_eq!(issues[1].kind, AnnotationIssueKind::Unclosed);
+    }
+
+    #[test]
+    fn lints_notebook_cells() {
+        let temp = tempfile::tempdir().unwrap();
+        let exercise = temp.path().join("exercise");
+        file_util::write_to_file(
+            serde_json::json!({
+                "cells": [
+                    {
+                        "cell_type": "markdown",
+                        "source": ["<!-- BEGIN SOLUTION -->\n", "answer\n", "<!-- END SOLUTION -->\n"]
+                    },
+                    {
+                        "cell_type": "code",
+                        "source": "x = 1\n# BEGIN SOLUTION\nx = 2\n"
+                    },
+                    {
+                        "cell_type": "raw",
+                        "source": ["# BEGIN SOLUTION\n"]
+                    },
+                    {
+                        "cell_type": "markdown",
+                        "source": ["<!-- END SOLUTION -->\n"]
+                    }
+                ]
+            })
+            .to_string(),
+            exercise.join("notebook.ipynb"),
+        )
+        .unwrap();
+
+        let issues = lint_exercise(&exercise, None).unwrap();
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].cell, Some(2));
+        assert_eq!((issues[0].line, issues[0].column), (2, 1));
+        assert_eq!(issues[0].kind, AnnotationIssueKind::Unclosed);
+        assert!(
+            issues[0]
+                .to_string()
+                .starts_with("notebook.ipynb cell 2:2:1: ")
+        );
+        // markdown cells are parsed with the markdown comment syntax and raw cells are not parsed
+        assert_eq!(issues[1].cell, Some(4));
+        assert_eq!(issues[1].kind, AnnotationIssueKind::Unmatched);
+    }
+}
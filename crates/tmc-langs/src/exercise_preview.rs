@@ -0,0 +1,285 @@
+//! Previewing the stub and model solution generated from an exercise template without writing them to disk.
+
+use crate::{
+    LangsError,
+    submission_processing::{
+        ProcessedFile, comment_syntaxes, contains_tmcignore, is_hidden_dir, on_skip_list,
+        process_file_contents, solution_file_filter, solution_line_filter, stub_file_filter,
+        stub_line_filter,
+    },
+};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use similar::TextDiff;
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+use tmc_langs_util::{deserialize, file_util};
+use walkdir::WalkDir;
+
+/// How the stub and model solution differ from a file in the exercise template.
+/// The diffs are unified diffs, and a file that is left out of the stub or solution is diffed against `/dev/null`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub struct FilePreview {
+    /// Path to the file relative to the exercise directory.
+    pub path: PathBuf,
+    /// Whether the file is included in the stub.
+    pub in_stub: bool,
+    /// Whether the file is included in the model solution.
+    pub in_solution: bool,
+    /// The diff from the template to the stub, if they differ.
+    pub stub_diff: Option<String>,
+    /// The diff from the template to the model solution, if they differ.
+    pub solution_diff: Option<String>,
+    /// The diff from the stub to the model solution, if they differ.
+    pub stub_to_solution_diff: Option<String>,
+}
+
+/// Processes the exercise's files in memory the same way as `prepare_stub` and `prepare_solution` and returns a preview of each file
+/// where the stub or model solution differs from the template, including files that are left out of either.
+/// Files that are copied as is, such as binary files, are not included.
+/// The previews are for the given variant of the exercise, see `MetaSyntaxParser::select_variant`.
+pub fn preview_exercise(
+    exercise_path: &Path,
+    variant: Option<&str>,
+) -> Result<Vec<FilePreview>, LangsError> {
+    log::debug!("previewing exercise {}", exercise_path.display());
+
+    let comment_syntaxes = comment_syntaxes(exercise_path)?;
+    let mut previews = vec![];
+    for entry in WalkDir::new(exercise_path)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !is_hidden_dir(e) && !on_skip_list(e) && !contains_tmcignore(e))
+        .filter_map(|e| e.ok())
+    {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let stub = process_file_contents(
+            entry.path(),
+            &comment_syntaxes,
+            variant,
+            &mut stub_line_filter,
+            &mut stub_file_filter,
+        )?;
+        let solution = process_file_contents(
+            entry.path(),
+            &comment_syntaxes,
+            variant,
+            &mut solution_line_filter,
+            &mut solution_file_filter,
+        )?;
+        let (stub, solution) = match (stub, solution) {
+            (ProcessedFile::Unprocessed, ProcessedFile::Unprocessed) => continue,
+            (stub, solution) => (into_text(stub), into_text(solution)),
+        };
+
+        let relative_path = entry
+            .path()
+            .strip_prefix(exercise_path)
+            .expect("entries are inside the exercise path");
+        let template = template_text(entry.path())?;
+        let path = relative_path.to_string_lossy().replace('\\', "/");
+        let stub_diff = diff(
+            ("template", Some(&template)),
+            ("stub", stub.as_ref()),
+            &path,
+        );
+        let solution_diff = diff(
+            ("template", Some(&template)),
+            ("solution", solution.as_ref()),
+            &path,
+        );
+        let stub_to_solution_diff = diff(
+            ("stub", stub.as_ref()),
+            ("solution", solution.as_ref()),
+            &path,
+        );
+        if stub_diff.is_none() && solution_diff.is_none() {
+            continue;
+        }
+        previews.push(FilePreview {
+            path: relative_path.to_path_buf(),
+            in_stub: stub.is_some(),
+            in_solution: solution.is_some(),
+            stub_diff,
+            solution_diff,
+            stub_to_solution_diff,
+        });
+    }
+    Ok(previews)
+}
+
+/// Renders the previews as an HTML fragment with a collapsible section for each file.
+/// Only elements that are allowed in GitHub comments are used, so the fragment can be pasted into a pull request.
+pub fn preview_to_html(previews: &[FilePreview]) -> String {
+    if previews.is_empty() {
+        return "<p>The stub and model solution are identical to the exercise template.</p>\n"
+            .to_string();
+    }
+
+    let mut html = String::new();
+    for preview in previews {
+        let mut notes = vec![];
+        if !preview.in_stub {
+            notes.push("left out of the stub");
+        }
+        if !preview.in_solution {
+            notes.push("left out of the model solution");
+        }
+        let path = escape_html(&preview.path.to_string_lossy());
+        let _ = write!(html, "<details>\n<summary><code>{path}</code>");
+        if !notes.is_empty() {
+            let _ = write!(html, " ({})", notes.join(", "));
+        }
+        html.push_str("</summary>\n");
+        for (title, diff) in [
+            ("Stub", &preview.stub_diff),
+            ("Model solution", &preview.solution_diff),
+            (
+                "From stub to model solution",
+                &preview.stub_to_solution_diff,
+            ),
+        ] {
+            if let Some(diff) = diff {
+                let _ = write!(html, "<p>{title}</p>\n<pre>");
+                diff_to_html(&mut html, diff);
+                html.push_str("</pre>\n");
+            }
+        }
+        html.push_str("</details>\n");
+    }
+    html
+}
+
+fn into_text(processed: ProcessedFile) -> Option<String> {
+    match processed {
+        ProcessedFile::Processed(contents) => Some(String::from_utf8_lossy(&contents).into_owned()),
+        ProcessedFile::Unprocessed | ProcessedFile::Skipped => None,
+    }
+}
+
+// the template file as text, with notebooks formatted the same way as in the processed files so that only the changes to the cells show up in the diff
+fn template_text(path: &Path) -> Result<String, LangsError> {
+    let bytes = file_util::read_file(path)?;
+    if path.extension().and_then(|e| e.to_str()) == Some("ipynb") {
+        let json: Value = deserialize::json_from_reader(bytes.as_slice())
+            .map_err(|e| LangsError::DeserializeJson(path.to_path_buf(), e))?;
+        let bytes = serde_json::to_vec_pretty(&json)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    } else {
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+// a unified diff between the two versions of the file, or None if they are the same
+// a missing version is a file that was left out
+fn diff(
+    (old_name, old): (&str, Option<&String>),
+    (new_name, new): (&str, Option<&String>),
+    path: &str,
+) -> Option<String> {
+    if old == new {
+        return None;
+    }
+    let old_header = old
+        .map(|_| format!("{old_name}/{path}"))
+        .unwrap_or_else(|| "/dev/null".to_string());
+    let new_header = new
+        .map(|_| format!("{new_name}/{path}"))
+        .unwrap_or_else(|| "/dev/null".to_string());
+    let old = old.map(String::as_str).unwrap_or_default();
+    let new = new.map(String::as_str).unwrap_or_default();
+    let diff = TextDiff::from_lines(old, new)
+        .unified_diff()
+        .header(&old_header, &new_header)
+        .to_string();
+    Some(diff)
+}
+
+fn diff_to_html(html: &mut String, diff: &str) {
+    for line in diff.split_inclusive('\n') {
+        let escaped = escape_html(line);
+        if line.starts_with('+') && !line.starts_with("+++ ") {
+            let _ = write!(html, "<ins>{escaped}</ins>");
+        } else if line.starts_with('-') && !line.starts_with("--- ") {
+            let _ = write!(html, "<del>{escaped}</del>");
+        } else {
+            html.push_str(&escaped);
+        }
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn previews_exercise() {
+        let temp = tempfile::tempdir().unwrap();
+        let exercise = temp.path().join("exercise");
+        file_util::write_to_file(
+            "class A {\n    int a() {\n        // BEGIN SOLUTION\n        return 1;\n        // END SOLUTION\n        // STUB: return 0;\n    }\n}\n",
+            exercise.join("src/A.java"),
+        )
+        .unwrap();
+        file_util::write_to_file(
+            "// SOLUTION FILE\nclass B {}\n",
+            exercise.join("src/B.java"),
+        )
+        .unwrap();
+        file_util::write_to_file("class C {}\n", exercise.join("src/C.java")).unwrap();
+        file_util::write_to_file([0, 159, 146, 150], exercise.join("data.bin")).unwrap();
+
+        let previews = preview_exercise(&exercise, None).unwrap();
+        assert_eq!(previews.len(), 2);
+
+        let a = &previews[0];
+        assert_eq!(a.path, Path::new("src/A.java"));
+        assert!(a.in_stub && a.in_solution);
+        let stub_diff = a.stub_diff.as_ref().unwrap();
+        assert!(stub_diff.starts_with("--- template/src/A.java\n+++ stub/src/A.java\n"));
+        assert!(stub_diff.contains("\n-        return 1;\n"));
+        assert!(stub_diff.contains("\n+        return 0;\n"));
+        let stub_to_solution_diff = a.stub_to_solution_diff.as_ref().unwrap();
+        assert!(stub_to_solution_diff.contains("\n-        return 0;\n"));
+        assert!(stub_to_solution_diff.contains("\n+        return 1;\n"));
+
+        let b = &previews[1];
+        assert_eq!(b.path, Path::new("src/B.java"));
+        assert!(!b.in_stub && b.in_solution);
+        assert!(
+            b.stub_diff
+                .as_ref()
+                .unwrap()
+                .starts_with("--- template/src/B.java\n+++ /dev/null\n")
+        );
+
+        let html = preview_to_html(&previews);
+        assert!(html.contains("<summary><code>src/B.java</code> (left out of the stub)</summary>"));
+        assert!(!html.contains("<ins>+++"));
+        assert!(html.contains("<ins>+        return 0;\n</ins>"));
+        assert!(html.contains("<del>-class B {}\n</del>"));
+    }
+}
@@ -3,27 +3,46 @@
 //! The main tmc-langs library. Provides a convenient API to all of the functionality provided by the tmc-langs project.
 
 mod config;
+mod course_check;
 mod course_refresher;
+mod course_results;
 mod data;
 mod error;
+mod exercise_lint;
+mod exercise_preview;
+mod parallel;
+mod submission_grading;
 mod submission_packaging;
 mod submission_processing;
+mod submission_queue;
+mod watch;
 
 use crate::data::{DownloadTarget, DownloadTargetKind};
 pub use crate::{
     config::{
-        Credentials, ProjectsConfig, ProjectsDirTmcExercise, TmcConfig, TmcCourseConfig,
-        list_local_tmc_course_exercises, migrate_exercise, move_projects_dir,
+        Credentials, Profile, ProjectsConfig, ProjectsDirTmcExercise, TmcConfig, TmcCourseConfig,
+        credential_store, list_local_tmc_course_exercises, migrate_exercise, move_projects_dir,
     },
-    course_refresher::{RefreshData, RefreshExercise, refresh_course},
+    course_check::{CheckResult, CheckStatus, ExerciseCheck, check_course},
+    course_refresher::{RefreshData, RefreshExercise, RefreshSource, refresh_course},
+    course_results::{CourseResult, ExportFormat, export_course_results, write_course_results},
     data::{
         CombinedCourseData, ConfigValue, DownloadOrUpdateMoocCourseExercisesResult,
         DownloadOrUpdateTmcCourseExercisesResult, DownloadResult, LocalExercise, LocalMoocExercise,
         LocalTmcExercise, MoocExerciseDownload, TmcExerciseDownload, TmcParams,
     },
     error::{LangsError, ParamError},
+    exercise_lint::{LintIssue, lint_exercise},
+    exercise_preview::{FilePreview, preview_exercise, preview_to_html},
+    submission_grading::{GradedSubmission, grade_submission},
     submission_packaging::{PrepareSubmission, prepare_submission},
     submission_processing::prepare_solution,
+    submission_queue::{
+        QueuedSubmission, SubmissionTarget, SyncResult, SyncedSubmission, list_queued_submissions,
+        queue_mooc_submission, queue_tmc_submission, remove_queued_submission,
+        sync_mooc_submissions, sync_tmc_submissions,
+    },
+    watch::watch_exercise,
 };
 use hmac::{Hmac, Mac};
 // use heim::disk;
@@ -44,12 +63,14 @@ use std::{
 };
 use tmc_langs_framework::Archive;
 pub use tmc_langs_framework::{
-    CommandError, Compression, ExerciseDesc, ExercisePackagingConfiguration, Language,
-    LanguagePlugin, PythonVer, RunResult, RunStatus, StyleValidationError, StyleValidationResult,
-    StyleValidationStrategy, TestDesc, TestResult, TmcProjectYml,
+    AnnotationIssueKind, CommandError, CommentSyntax, Compression, ExecutionStats, ExerciseDesc,
+    ExercisePackagingConfiguration, Language, LanguagePlugin, PythonVer, RunResult, RunStatus,
+    Sandbox, SandboxLimits, StyleValidationError, StyleValidationResult, StyleValidationStrategy,
+    TestDesc, TestFilter, TestResult, TmcProjectYml,
 };
 use tmc_langs_plugins::{
-    CSharpPlugin, MakePlugin, NoTestsPlugin, Plugin, PluginType, Python3Plugin, RPlugin,
+    CSharpPlugin, GoPlugin, MakePlugin, NoTestsPlugin, NodePlugin, Plugin, PluginType,
+    Python3Plugin, RPlugin, RustPlugin,
 };
 use tmc_langs_util::file_util::LOCK_FILE_NAME;
 // the Java plugin is disabled on musl
@@ -63,7 +84,7 @@ use walkdir::WalkDir;
 #[cfg(not(target_env = "musl"))]
 use {
     tmc_langs_framework::TmcError,
-    tmc_langs_plugins::{AntPlugin, MavenPlugin},
+    tmc_langs_plugins::{AntPlugin, GradlePlugin, MavenPlugin},
 };
 
 const TMC_LANGS_CONFIG_DIR_VAR: &str = "TMC_LANGS_CONFIG_DIR";
@@ -95,10 +116,15 @@ pub fn sign_with_jwt<T: Serialize>(value: T, secret: &[u8]) -> Result<String, La
     Ok(token)
 }
 
-/// Returns the projects directory for the given client name.
+/// Returns the projects directory for the given client name and profile.
 /// The return value for `my-client` might look something like `/home/username/.local/share/tmc/my-client` on Linux.
-pub fn get_projects_dir(client_name: &str) -> Result<PathBuf, LangsError> {
-    let projects_dir = TmcConfig::load(client_name)?.projects_dir;
+/// Profiles use the default projects directory unless they set their own.
+pub fn get_projects_dir(client_name: &str, profile: Option<&str>) -> Result<PathBuf, LangsError> {
+    let tmc_config = TmcConfig::load(client_name)?;
+    let projects_dir = tmc_config.get_profile_projects_dir(profile)?.to_path_buf();
+    if !projects_dir.exists() {
+        file_util::create_dir_all(&projects_dir)?;
+    }
     Ok(projects_dir)
 }
 
@@ -579,44 +605,135 @@ pub fn login_with_password(
 }
 
 /// Initializes a TestMyCodeClient, using and returning the stored credentials, if any.
+/// A token that has expired, or whose expiry time is unknown as with tokens saved by older versions, is refreshed if possible.
+/// If a profile is given, its credentials are used, and its TMC root URL instead of the given one if it sets one.
 pub fn init_testmycode_client_with_credentials(
     root_url: Url,
     client_name: &str,
     client_version: &str,
+    profile: Option<&str>,
 ) -> Result<(tmc::TestMyCodeClient, Option<Credentials>), LangsError> {
-    // create client
-    let mut client = tmc::TestMyCodeClient::new(
-        root_url,
-        client_name.to_string(),
-        client_version.to_string(),
-    )?;
+    let mut client = new_testmycode_client(root_url, client_name, client_version, profile)?;
 
     // set token from the credentials file if one exists
-    let credentials = Credentials::load(client_name)?;
-    if let Some(credentials) = &credentials {
-        client.set_token(credentials.token());
+    let mut credentials = Credentials::load(client_name, profile)?;
+    if let Some(stored) = &credentials {
+        client.set_token(stored.token(), stored.expires_at());
+        if stored.needs_refresh() {
+            // if the token can't be refreshed, the client will report it as expired when it is needed
+            match refresh_credentials(&mut client, client_name, profile) {
+                Ok(refreshed) => credentials = Some(refreshed),
+                Err(err) => log::warn!("Failed to refresh token: {err}"),
+            }
+        }
     }
 
     Ok((client, credentials))
 }
 
+/// Refreshes the client's token and saves it as the credentials of the profile.
+/// Also used when the server rejects a token, which may have been revoked or expired early.
+pub fn refresh_credentials(
+    client: &mut tmc::TestMyCodeClient,
+    client_name: &str,
+    profile: Option<&str>,
+) -> Result<Credentials, LangsError> {
+    let token = client.refresh_token()?;
+    log::debug!("refreshed token");
+    Credentials::save(client_name, profile, token)
+}
+
 /// Initializes a MoocClient, using and returning the stored credentials, if any.
+/// The tokens are issued by the TestMyCode server, so they are refreshed with it like in `init_testmycode_client_with_credentials`.
+/// If a profile is given, its credentials are used, and its root URLs instead of the given ones if it sets them.
 pub fn init_mooc_client_with_credentials(
     root_url: Url,
+    tmc_root_url: Url,
     client_name: &str,
+    client_version: &str,
+    profile: Option<&str>,
 ) -> Result<(mooc::MoocClient, Option<Credentials>), LangsError> {
+    let root_url = match profile {
+        Some(profile) => profile_root_url(client_name, profile, |p| &p.mooc_root_url)?,
+        None => None,
+    }
+    .unwrap_or(root_url);
+
     // create client
     let mut client = mooc::MoocClient::new(root_url);
 
     // set token from the credentials file if one exists
-    let credentials = Credentials::load(client_name)?;
-    if let Some(credentials) = &credentials {
-        client.set_token(credentials.token());
+    let mut credentials = Credentials::load(client_name, profile)?;
+    if let Some(stored) = &credentials {
+        if stored.needs_refresh() {
+            // if the token can't be refreshed, the client will report it as expired when it is needed
+            match refresh_mooc_credentials(
+                tmc_root_url,
+                client_name,
+                client_version,
+                profile,
+                stored,
+            ) {
+                Ok(refreshed) => credentials = Some(refreshed),
+                Err(err) => log::warn!("Failed to refresh token: {err}"),
+            }
+        }
+    }
+    if let Some(stored) = &credentials {
+        client.set_token(stored.token(), stored.expires_at());
     }
 
     Ok((client, credentials))
 }
 
+/// Refreshes the token of the credentials with the TestMyCode server and saves it as the credentials of the profile.
+pub fn refresh_mooc_credentials(
+    tmc_root_url: Url,
+    client_name: &str,
+    client_version: &str,
+    profile: Option<&str>,
+    credentials: &Credentials,
+) -> Result<Credentials, LangsError> {
+    let mut client = new_testmycode_client(tmc_root_url, client_name, client_version, profile)?;
+    client.set_token(credentials.token(), credentials.expires_at());
+    refresh_credentials(&mut client, client_name, profile)
+}
+
+// creates a TestMyCodeClient for the profile's TMC root URL, if it sets one
+fn new_testmycode_client(
+    root_url: Url,
+    client_name: &str,
+    client_version: &str,
+    profile: Option<&str>,
+) -> Result<tmc::TestMyCodeClient, LangsError> {
+    let root_url = match profile {
+        Some(profile) => profile_root_url(client_name, profile, |p| &p.tmc_root_url)?,
+        None => None,
+    }
+    .unwrap_or(root_url);
+
+    let client = tmc::TestMyCodeClient::new(
+        root_url,
+        client_name.to_string(),
+        client_version.to_string(),
+    )?;
+    Ok(client)
+}
+
+// the root URL set in the profile, if any
+fn profile_root_url(
+    client_name: &str,
+    profile: &str,
+    get_url: impl FnOnce(&Profile) -> &Option<String>,
+) -> Result<Option<Url>, LangsError> {
+    let tmc_config = TmcConfig::load(client_name)?;
+    let url = get_url(tmc_config.get_profile(profile)?)
+        .as_deref()
+        .map(|url| Url::parse(url).map_err(|e| LangsError::InvalidProfileUrl(url.to_string(), e)))
+        .transpose()?;
+    Ok(url)
+}
+
 /// Updates the tmc exercises in the local projects directory.
 // TODO: parallel downloads
 pub fn update_tmc_exercises(
@@ -775,6 +892,16 @@ pub fn get_setting(client_name: &str, key: &str) -> Result<ConfigValue, LangsErr
     let tmc_config = get_settings(client_name)?;
     let value = match key {
         "projects-dir" => ConfigValue::Path(tmc_config.get_projects_dir().to_path_buf()),
+        "profiles" if !tmc_config.profiles.is_empty() => {
+            ConfigValue::Value(Some(TomlValue::try_from(&tmc_config.profiles)?))
+        }
+        "profiles" => ConfigValue::Value(None),
+        "credential-store" => ConfigValue::Value(
+            tmc_config
+                .credential_store
+                .map(TomlValue::try_from)
+                .transpose()?,
+        ),
         other => ConfigValue::Value(tmc_config.get(other).cloned()),
     };
     Ok(value)
@@ -801,6 +928,15 @@ pub fn set_setting<T: Serialize>(client_name: &str, key: &str, value: T) -> Resu
             };
             tmc_config.set_projects_dir(PathBuf::from(value))?;
         }
+        "profiles" => {
+            tmc_config.profiles = value.try_into().map_err(LangsError::InvalidProfiles)?;
+        }
+        "credential-store" => {
+            let TomlValue::String(value) = value else {
+                return Err(LangsError::InvalidCredentialStore(value.to_string()));
+            };
+            tmc_config.credential_store = Some(credential_store::CredentialBackend::parse(&value)?);
+        }
         other => {
             tmc_config.insert(other.to_string(), value);
         }
@@ -823,7 +959,17 @@ pub fn unset_setting(client_name: &str, key: &str) -> Result<Option<TomlValue>,
     log::debug!("unsetting setting {key} in {client_name}");
 
     let mut tmc_config = TmcConfig::load(client_name)?;
-    let old_value = tmc_config.remove(key);
+    let old_value = match key {
+        "profiles" if !tmc_config.profiles.is_empty() => Some(TomlValue::try_from(
+            std::mem::take(&mut tmc_config.profiles),
+        )?),
+        "credential-store" => tmc_config
+            .credential_store
+            .take()
+            .map(TomlValue::try_from)
+            .transpose()?,
+        other => tmc_config.remove(other),
+    };
     tmc_config.save()?;
 
     Ok(old_value)
@@ -1016,14 +1162,19 @@ pub fn get_exercise_packaging_configuration(
 }
 
 /// Prepares the exercise stub, copying tmc-junit-runner for Ant exercises.
-pub fn prepare_stub(exercise_path: &Path, dest_path: &Path) -> Result<(), LangsError> {
+/// The variant is handled like in `submission_processing::prepare_stub`.
+pub fn prepare_stub(
+    exercise_path: &Path,
+    dest_path: &Path,
+    variant: Option<&str>,
+) -> Result<(), LangsError> {
     log::debug!(
         "preparing stub for {} in {}",
         exercise_path.display(),
         dest_path.display()
     );
 
-    submission_processing::prepare_stub(exercise_path, dest_path)?;
+    submission_processing::prepare_stub(exercise_path, dest_path, variant)?;
 
     // The Ant plugin needs some additional files to be copied over.
     // the Java plugin is disabled on musl
@@ -1041,6 +1192,23 @@ pub fn run_tests(path: &Path) -> Result<RunResult, LangsError> {
     Ok(Plugin::from_exercise(path)?.run_tests(path)?)
 }
 
+/// Runs the tests for the exercise that are selected by the filter.
+/// The test runners select the tests themselves where possible, otherwise all of the tests are run and the other results are removed.
+pub fn run_tests_filtered(path: &Path, filter: &TestFilter) -> Result<RunResult, LangsError> {
+    log::debug!("running tests matching {filter:?} in {}", path.display());
+
+    Ok(Plugin::from_exercise(path)?.run_tests_filtered(path, filter)?)
+}
+
+/// Runs the tests for an exercise that are selected by the filter in a sandbox with the limits from the exercise's .tmcproject.yml, see `Sandbox`.
+pub fn run_tests_in_sandbox(path: &Path, filter: &TestFilter) -> Result<RunResult, LangsError> {
+    log::debug!("running tests in a sandbox in {}", path.display());
+
+    let plugin = Plugin::from_exercise(path)?;
+    let sandbox = Sandbox::for_exercise(path)?;
+    Ok(plugin.run_tests_in_sandbox(path, filter, Some(&sandbox))?)
+}
+
 /// Scans the exercise.
 pub fn scan_exercise(path: &Path, exercise_name: String) -> Result<ExerciseDesc, LangsError> {
     log::debug!("scanning exercise in {}", path.display());
@@ -1184,6 +1352,8 @@ fn extract_project_overwrite(
 fn get_default_sandbox_image(path: &Path) -> Result<&'static str, LangsError> {
     let img = match PluginType::from_exercise(path)? {
         PluginType::CSharp => CSharpPlugin::DEFAULT_SANDBOX_IMAGE,
+        PluginType::External(plugin) => plugin.default_sandbox_image(),
+        PluginType::Go => GoPlugin::DEFAULT_SANDBOX_IMAGE,
         PluginType::Make => MakePlugin::DEFAULT_SANDBOX_IMAGE,
         // the Java plugin is disabled on musl
         #[cfg(not(target_env = "musl"))]
@@ -1191,9 +1361,14 @@ fn get_default_sandbox_image(path: &Path) -> Result<&'static str, LangsError> {
         // the Java plugin is disabled on musl
         #[cfg(not(target_env = "musl"))]
         PluginType::Ant => AntPlugin::DEFAULT_SANDBOX_IMAGE,
+        // the Java plugin is disabled on musl
+        #[cfg(not(target_env = "musl"))]
+        PluginType::Gradle => GradlePlugin::DEFAULT_SANDBOX_IMAGE,
+        PluginType::Node => NodePlugin::DEFAULT_SANDBOX_IMAGE,
         PluginType::NoTests => NoTestsPlugin::DEFAULT_SANDBOX_IMAGE,
         PluginType::Python3 => Python3Plugin::DEFAULT_SANDBOX_IMAGE,
         PluginType::R => RPlugin::DEFAULT_SANDBOX_IMAGE,
+        PluginType::Rust => RustPlugin::DEFAULT_SANDBOX_IMAGE,
     };
     Ok(img)
 }
@@ -1243,7 +1418,7 @@ mod test {
             BasicTokenType::Bearer,
             EmptyExtraTokenFields {},
         );
-        client.set_token(token);
+        client.set_token(token, None);
         client
     }
 
@@ -1264,7 +1439,7 @@ mod test {
     fn gets_projects_dir() {
         init();
 
-        let projects_dir = get_projects_dir("client").unwrap();
+        let projects_dir = get_projects_dir("client", None).unwrap();
         assert!(projects_dir.ends_with("client"));
         let parent = projects_dir.parent().unwrap();
         assert!(parent.ends_with("tmc"));
@@ -0,0 +1,60 @@
+//! Running work on a bounded number of threads.
+
+use std::{sync::Mutex, thread};
+
+/// Calls the function on each item using up to `jobs` threads, at least one.
+/// The results are returned in the order of the items regardless of the order they finished in.
+pub(crate) fn map<T, R, F>(items: Vec<T>, jobs: usize, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let item_count = items.len();
+    if jobs <= 1 || item_count <= 1 {
+        return items.into_iter().map(f).collect();
+    }
+
+    // the remaining items along with their index, popped by each thread until none are left
+    let queue = Mutex::new(items.into_iter().enumerate().rev().collect::<Vec<_>>());
+    let results = Mutex::new(Vec::with_capacity(item_count));
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(item_count) {
+            scope.spawn(|| {
+                loop {
+                    let next = queue.lock().expect("the threads should never panic").pop();
+                    let Some((index, item)) = next else {
+                        break;
+                    };
+                    let result = f(item);
+                    results
+                        .lock()
+                        .expect("the threads should never panic")
+                        .push((index, result));
+                }
+            });
+        }
+    });
+
+    let mut results = results
+        .into_inner()
+        .expect("the threads should never panic");
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keeps_order() {
+        let items = (0..32).collect::<Vec<u64>>();
+        let results = map(items, 8, |i| {
+            // finish the items in a different order than they were started in
+            thread::sleep(std::time::Duration::from_millis(32 - i));
+            i * 2
+        });
+        assert_eq!(results, (0..32).map(|i| i * 2).collect::<Vec<_>>());
+    }
+}
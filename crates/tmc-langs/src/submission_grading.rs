@@ -0,0 +1,152 @@
+//! Grading submissions locally the same way the server does.
+
+use crate::{
+    Compression, LangsError, PrepareSubmission, RunResult, TmcParams, TmcProjectYml,
+    extract_project_overwrite, prepare_submission,
+};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::{collections::BTreeSet, path::Path};
+use tmc_langs_util::file_util;
+
+/// The result of grading a submission.
+#[derive(Debug, Serialize, JsonSchema)]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub struct GradedSubmission {
+    /// The sandbox image the server would run the tests in.
+    pub sandbox_image: String,
+    /// The points the server would award for the submission, sorted by name.
+    pub awarded_points: Vec<String>,
+    pub run_result: RunResult,
+}
+
+/// Grades the submission like the server does: the submission is prepared with the clone's test files and .tmcparams like in `prepare_submission`,
+/// the tests are run with the timeout from the exercise's .tmcproject.yml and the points are awarded.
+/// A point is awarded if all of the tests that give it passed. The `no-tests` points from .tmcproject.yml are always awarded.
+///
+/// The tests are run in a temporary directory, not in a sandbox.
+pub fn grade_submission(
+    submission: PrepareSubmission,
+    tmc_params: TmcParams,
+    clone_path: &Path,
+) -> Result<GradedSubmission, LangsError> {
+    log::debug!(
+        "grading submission {} for {}",
+        submission.archive.display(),
+        clone_path.display()
+    );
+
+    let temp = tempfile::tempdir().map_err(LangsError::TempDir)?;
+    let prepared_archive = temp.path().join("submission.tar");
+    let sandbox_image = prepare_submission(
+        submission,
+        &prepared_archive,
+        true,
+        tmc_params,
+        clone_path,
+        None,
+        Compression::Tar,
+    )?;
+
+    let exercise_path = temp.path().join("exercise");
+    file_util::create_dir(&exercise_path)?;
+    let archive = file_util::open_file(&prepared_archive)?;
+    extract_project_overwrite(archive, &exercise_path, Compression::Tar)?;
+
+    let run_result = crate::run_tests(&exercise_path)?;
+    let mut awarded_points = awarded_points(&run_result);
+    if let Some(no_tests) = TmcProjectYml::load(&exercise_path)?
+        .and_then(|c| c.no_tests)
+        .filter(|no_tests| no_tests.flag)
+    {
+        awarded_points.extend(no_tests.points);
+    }
+    Ok(GradedSubmission {
+        sandbox_image,
+        awarded_points: awarded_points.into_iter().collect(),
+        run_result,
+    })
+}
+
+// the points for which every test passed
+fn awarded_points(run_result: &RunResult) -> BTreeSet<String> {
+    let mut passed = BTreeSet::new();
+    let mut failed = BTreeSet::new();
+    for test_result in &run_result.test_results {
+        let points = test_result.points.iter().cloned();
+        if test_result.successful {
+            passed.extend(points);
+        } else {
+            failed.extend(points);
+        }
+    }
+    passed.retain(|point| !failed.contains(point));
+    passed
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use crate::{RunStatus, TestResult};
+    use std::collections::HashMap;
+
+    fn test_result(successful: bool, points: &[&str]) -> TestResult {
+        TestResult {
+            name: "test".to_string(),
+            successful,
+            points: points.iter().map(|p| p.to_string()).collect(),
+            message: String::new(),
+            exception: vec![],
+            duration_ms: None,
+            stdout: None,
+            stderr: None,
+        }
+    }
+
+    #[test]
+    fn awards_points_whose_tests_all_passed() {
+        let run_result = RunResult {
+            status: RunStatus::TestsFailed,
+            test_results: vec![
+                test_result(true, &["1", "2"]),
+                test_result(false, &["2"]),
+                test_result(true, &["3"]),
+            ],
+            logs: HashMap::new(),
+            execution_stats: None,
+        };
+        assert_eq!(
+            awarded_points(&run_result).into_iter().collect::<Vec<_>>(),
+            ["1", "3"]
+        );
+    }
+
+    #[test]
+    fn grades_no_tests_submission() {
+        let temp = tempfile::tempdir().unwrap();
+        let tmc_project_yml = "no-tests:\n  points:\n    - no-tests-point\n";
+        let clone = temp.path().join("course/exercise");
+        file_util::write_to_file(tmc_project_yml, clone.join(".tmcproject.yml")).unwrap();
+        file_util::write_to_file(b"template", clone.join("src/answer.txt")).unwrap();
+
+        let student = temp.path().join("student/exercise");
+        file_util::write_to_file(tmc_project_yml, student.join(".tmcproject.yml")).unwrap();
+        file_util::write_to_file(b"answer", student.join("src/answer.txt")).unwrap();
+        let archive = temp.path().join("submission.zip");
+        crate::compress_project_to(&student, &archive, Compression::Zip, false, false).unwrap();
+
+        let graded = grade_submission(
+            PrepareSubmission {
+                archive: &archive,
+                compression: Compression::Zip,
+                extract_naively: false,
+            },
+            TmcParams::new(),
+            &clone,
+        )
+        .unwrap();
+        assert_eq!(graded.awarded_points, ["no-tests-point"]);
+        assert_eq!(graded.run_result.status, RunStatus::Passed);
+    }
+}
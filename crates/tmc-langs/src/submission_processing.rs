@@ -6,10 +6,11 @@ use regex::Regex;
 use serde_json::Value;
 use std::{
     fs::File,
-    io::{BufRead, BufReader, BufWriter, Write},
+    io::{BufRead, BufReader},
     path::Path,
 };
-use tmc_langs_framework::{MetaString, MetaSyntaxParser};
+use tmc_langs_framework::{CommentSyntaxes, MetaString, MetaSyntaxParser, TmcProjectYml};
+use tmc_langs_plugins::PluginType;
 use tmc_langs_util::{FileError, deserialize, file_util};
 use walkdir::{DirEntry, WalkDir};
 
@@ -24,23 +25,25 @@ static FILES_TO_SKIP_ALWAYS: Lazy<Regex> =
 /// files matching patterns defined in ```FILES_TO_SKIP_ALWAYS``` and directories and files named ```private```.
 ///
 /// Binary files are copied without extra processing, while text files are parsed to remove solution tags and stubs.
-pub fn prepare_solution(exercise_path: &Path, dest_root: &Path) -> Result<(), LangsError> {
+/// The solution is prepared for the variant, see `MetaSyntaxParser::select_variant`.
+pub fn prepare_solution(
+    exercise_path: &Path,
+    dest_root: &Path,
+    variant: Option<&str>,
+) -> Result<(), LangsError> {
     log::debug!(
         "preparing solution from {} to {}",
         exercise_path.display(),
         dest_root.display()
     );
 
-    let line_filter = |meta: &MetaString| {
-        !matches!(meta, MetaString::Stub(_)) && !matches!(meta, MetaString::Hidden(_))
-        // hide stub and hidden lines
-    };
-    let file_filter = |metas: &[MetaString]| {
-        !metas
-            .iter()
-            .any(|ms| matches!(ms, MetaString::HiddenFileMarker)) // exclude hidden files
-    };
-    process_files(exercise_path, dest_root, line_filter, file_filter)?;
+    process_files(
+        exercise_path,
+        dest_root,
+        variant,
+        solution_line_filter,
+        solution_file_filter,
+    )?;
     Ok(())
 }
 
@@ -52,51 +55,104 @@ pub fn prepare_solution(exercise_path: &Path, dest_root: &Path) -> Result<(), La
 /// Binary files are copied without extra processing, while text files are parsed to remove stub tags and solutions.
 ///
 /// Additionally, copies any shared files with the corresponding language plugins.
-pub fn prepare_stub(exercise_path: &Path, dest_root: &Path) -> Result<(), LangsError> {
+/// The stub is prepared for the variant, see `MetaSyntaxParser::select_variant`.
+pub fn prepare_stub(
+    exercise_path: &Path,
+    dest_root: &Path,
+    variant: Option<&str>,
+) -> Result<(), LangsError> {
     log::debug!(
         "preparing stub from {} to {}",
         exercise_path.display(),
         dest_root.display()
     );
 
-    let line_filter = |meta: &MetaString| {
-        !matches!(meta, MetaString::Solution(_)) && !matches!(meta, MetaString::Hidden(_))
-        // exclude solution and hidden lines
-    };
-    let file_filter = |metas: &[MetaString]| {
-        !metas.iter().any(|ms| {
-            matches!(ms, MetaString::SolutionFileMarker) // exclude solution files
-                || matches!(ms, MetaString::HiddenFileMarker) // exclude hidden files
-        })
-    };
-    process_files(exercise_path, dest_root, line_filter, file_filter)?;
+    process_files(
+        exercise_path,
+        dest_root,
+        variant,
+        stub_line_filter,
+        stub_file_filter,
+    )?;
     Ok(())
 }
 
+pub(crate) fn solution_line_filter(meta: &MetaString) -> bool {
+    !matches!(meta, MetaString::Stub(_)) && !matches!(meta, MetaString::Hidden(_))
+    // hide stub and hidden lines
+}
+
+pub(crate) fn solution_file_filter(metas: &[MetaString]) -> bool {
+    !metas
+        .iter()
+        .any(|ms| matches!(ms, MetaString::HiddenFileMarker)) // exclude hidden files
+}
+
+pub(crate) fn stub_line_filter(meta: &MetaString) -> bool {
+    !matches!(meta, MetaString::Solution(_)) && !matches!(meta, MetaString::Hidden(_))
+    // exclude solution and hidden lines
+}
+
+pub(crate) fn stub_file_filter(metas: &[MetaString]) -> bool {
+    !metas.iter().any(|ms| {
+        matches!(ms, MetaString::SolutionFileMarker) // exclude solution files
+            || matches!(ms, MetaString::HiddenFileMarker) // exclude hidden files
+    })
+}
+
 // Processes all files in path, copying files in directories that are not skipped.
 fn process_files(
     source: &Path,
     dest_root: &Path,
+    variant: Option<&str>,
     mut line_filter: impl Fn(&MetaString) -> bool,
     mut file_filter: impl Fn(&[MetaString]) -> bool,
 ) -> Result<(), LangsError> {
     log::info!("Project: {source:?}");
 
+    let comment_syntaxes = comment_syntaxes(source)?;
     let walker = WalkDir::new(source).min_depth(1).into_iter();
     // silently skips over errors, for example when there's a directory we don't have permissions for
     for entry in walker
         .filter_entry(|e| !is_hidden_dir(e) && !on_skip_list(e) && !contains_tmcignore(e))
         .filter_map(|e| e.ok())
     {
-        process_file(entry, source, dest_root, &mut line_filter, &mut file_filter)?;
+        process_file(
+            entry,
+            source,
+            dest_root,
+            &comment_syntaxes,
+            variant,
+            &mut line_filter,
+            &mut file_filter,
+        )?;
     }
     Ok(())
 }
 
+// the built-in comment syntaxes, extended with the exercise's language plugin's and overridden by the ones in its .tmcproject.yml
+pub(crate) fn comment_syntaxes(exercise_path: &Path) -> Result<CommentSyntaxes, LangsError> {
+    let mut comment_syntaxes = CommentSyntaxes::builtin();
+    if let Ok(plugin) = PluginType::from_exercise(exercise_path) {
+        let (extensions, comment_syntax) = plugin.source_file_comment_syntax();
+        for extension in extensions {
+            comment_syntaxes.insert_if_missing(extension, &comment_syntax);
+        }
+    }
+    if let Some(config) = TmcProjectYml::load(exercise_path)? {
+        for (extension, comment_syntax) in &config.comment_syntaxes {
+            comment_syntaxes.insert(extension, comment_syntax);
+        }
+    }
+    Ok(comment_syntaxes)
+}
+
 fn process_file(
     entry: DirEntry,
     source: &Path,
     dest_root: &Path,
+    comment_syntaxes: &CommentSyntaxes,
+    variant: Option<&str>,
     line_filter: &mut impl Fn(&MetaString) -> bool,
     file_filter: &mut impl Fn(&[MetaString]) -> bool,
 ) -> Result<(), LangsError> {
@@ -109,91 +165,193 @@ fn process_file(
         .strip_prefix(source)
         .unwrap_or_else(|_| Path::new(""));
     let dest_path = dest_root.join(relative_path);
-    if let Some(extension) = entry.path().extension().and_then(|o| o.to_str()) {
-        // todo: stop checking extension twice here and in meta_syntax
-        // NOTE: if you change these extensions make sure to change them in meta_syntax.rs as well
-        match extension {
-            "java" | "c" | "cpp" | "h" | "hpp" | "js" | "css" | "rs" | "qml" | "cs" | "xml"
-            | "http" | "html" | "qrc" | "properties" | "py" | "R" | "pro" => {
-                // process line by line
-                let source_file = file_util::open_file(entry.path())?;
-                let iter = LossyFileIterator {
-                    file: BufReader::new(source_file),
+    match process_file_contents(
+        entry.path(),
+        comment_syntaxes,
+        variant,
+        line_filter,
+        file_filter,
+    )? {
+        ProcessedFile::Processed(contents) => {
+            file_util::write_to_file(contents, &dest_path)?;
+            log::trace!(
+                "filtered file {} to {}",
+                entry.path().display(),
+                dest_path.display()
+            );
+        }
+        ProcessedFile::Unprocessed => {
+            // copy other files as is
+            file_util::copy(entry.path(), dest_path)?;
+        }
+        ProcessedFile::Skipped => {}
+    }
+    Ok(())
+}
+
+/// The result of processing a single file.
+pub(crate) enum ProcessedFile {
+    /// The filtered contents of the file.
+    Processed(Vec<u8>),
+    /// The file is copied as is.
+    Unprocessed,
+    /// The file is left out.
+    Skipped,
+}
+
+/// Processes the file at the given path in memory according to the filters.
+pub(crate) fn process_file_contents(
+    path: &Path,
+    comment_syntaxes: &CommentSyntaxes,
+    variant: Option<&str>,
+    line_filter: &mut impl Fn(&MetaString) -> bool,
+    file_filter: &mut impl Fn(&[MetaString]) -> bool,
+) -> Result<ProcessedFile, LangsError> {
+    // files without an extension are not included
+    let Some(extension) = path.extension().and_then(|o| o.to_str()) else {
+        return Ok(ProcessedFile::Skipped);
+    };
+    match extension {
+        "ipynb" => {
+            // process each cell in the notebook
+            let file = file_util::open_file(path)?;
+            let mut json: Value = deserialize::json_from_reader(file)
+                .map_err(|e| LangsError::DeserializeJson(path.to_path_buf(), e))?;
+            let cells = json
+                .get_mut("cells")
+                .and_then(|cs| cs.as_array_mut())
+                .ok_or(LangsError::InvalidNotebook(
+                    "Invalid or missing value for 'cells'",
+                ))?;
+
+            let mut processed_cells = vec![];
+            for mut cell in std::mem::take(cells) {
+                // cells tagged as solution or hidden are filtered like the lines in solution and hidden blocks
+                if let Some(tagged) = notebook_cell_tag(&cell) {
+                    if !line_filter(&tagged) {
+                        continue;
+                    }
+                }
+
+                let Some(cell_extension) = notebook_cell_extension(&cell, extension) else {
+                    processed_cells.push(cell);
+                    continue;
                 };
-                if let Some(lines) = process_lines(iter, line_filter, file_filter, extension)
-                    .map_err(|e| FileError::FileRead(entry.path().to_path_buf(), e))?
-                {
-                    // write all lines to target file
-                    if let Some(parent) = dest_path.parent() {
-                        file_util::create_dir_all(parent)?;
+
+                // read the source for each cell
+                let source = notebook_cell_source(&cell)?;
+                let source_is_string = cell.get("source").is_some_and(Value::is_string);
+
+                let lines: Option<Vec<String>> = process_lines(
+                    source.into_iter().map(Ok::<_, LangsError>),
+                    comment_syntaxes,
+                    variant,
+                    line_filter,
+                    file_filter,
+                    cell_extension,
+                )?
+                .map(|i| i.collect());
+                if let Some(lines) = lines {
+                    // replace cell source with filtered output, keeping its format
+                    cell["source"] = if source_is_string {
+                        Value::String(lines.concat())
+                    } else {
+                        Value::Array(lines.into_iter().map(Value::String).collect())
+                    };
+                } else {
+                    // file should be skipped
+                    return Ok(ProcessedFile::Skipped);
+                }
+
+                // the outputs were produced by running the solution, so they are left out along with it
+                if !line_filter(&MetaString::Solution(String::new())) {
+                    if let Some(outputs) = cell.get_mut("outputs") {
+                        *outputs = Value::Array(vec![]);
                     }
-                    let mut file = BufWriter::new(file_util::create_file(&dest_path)?);
-                    for line in lines {
-                        file.write_all(line.as_bytes())
-                            .map_err(|e| FileError::FileWrite(dest_path.to_path_buf(), e))?;
+                    if let Some(execution_count) = cell.get_mut("execution_count") {
+                        *execution_count = Value::Null;
                     }
                 }
+                processed_cells.push(cell);
             }
-            "ipynb" => {
-                // process each cell in the notebook
-                let file = file_util::open_file(entry.path())?;
-                let mut json: Value = deserialize::json_from_reader(file)
-                    .map_err(|e| LangsError::DeserializeJson(entry.path().to_path_buf(), e))?;
-                let cells = json
-                    .get_mut("cells")
-                    .and_then(|cs| cs.as_array_mut())
-                    .ok_or(LangsError::InvalidNotebook(
-                        "Invalid or missing value for 'cells'",
-                    ))?;
-
-                for cell in cells {
-                    let is_cell_type_code = cell
-                        .get("cell_type")
-                        .and_then(|c| c.as_str())
-                        .map(|c| c == "code")
-                        .unwrap_or_default();
-
-                    if is_cell_type_code {
-                        // read the source for each code cell
-                        let cell_source = cell
-                            .get_mut("source")
-                            .and_then(|s| s.as_array_mut())
-                            .ok_or(LangsError::InvalidNotebook(
-                                "Invalid or missing value for 'source'",
-                            ))?;
-                        let source = cell_source.iter().map(|v| {
-                            v.as_str()
-                                .map(String::from)
-                                .ok_or(LangsError::InvalidNotebook("Invalid value in 'source'"))
-                        });
-
-                        let lines: Option<Vec<Value>> =
-                            process_lines(source, line_filter, file_filter, extension)?
-                                .map(|i| i.map(Value::String).collect());
-                        if let Some(lines) = lines {
-                            // replace cell source with filtered output
-                            *cell_source = lines;
-                        } else {
-                            // file should be skipped
-                            return Ok(());
-                        }
-                    }
+            *cells = processed_cells;
+            // the JSON with filtered sources
+            Ok(ProcessedFile::Processed(serde_json::to_vec_pretty(&json)?))
+        }
+        _ if comment_syntaxes.contains(extension) => {
+            // process line by line
+            let source_file = file_util::open_file(path)?;
+            let iter = LossyFileIterator {
+                file: BufReader::new(source_file),
+            };
+            if let Some(lines) = process_lines(
+                iter,
+                comment_syntaxes,
+                variant,
+                line_filter,
+                file_filter,
+                extension,
+            )
+            .map_err(|e| FileError::FileRead(path.to_path_buf(), e))?
+            {
+                let mut contents = vec![];
+                for line in lines {
+                    contents.extend_from_slice(line.as_bytes());
                 }
-                // writes the JSON with filtered sources to the target path
-                file_util::write_to_file(serde_json::to_vec_pretty(&json)?, &dest_path)?;
-                log::trace!(
-                    "filtered file {} to {}",
-                    entry.path().display(),
-                    dest_path.display()
-                );
-            }
-            _ => {
-                // copy other files as is
-                file_util::copy(entry.path(), dest_path)?;
+                Ok(ProcessedFile::Processed(contents))
+            } else {
+                Ok(ProcessedFile::Skipped)
             }
         }
+        _ => Ok(ProcessedFile::Unprocessed),
+    }
+}
+
+// the source of a cell is either a list of lines or a single string, which is split into lines
+pub(crate) fn notebook_cell_source(cell: &Value) -> Result<Vec<String>, LangsError> {
+    match cell.get("source") {
+        Some(Value::String(source)) => Ok(source.split_inclusive('\n').map(String::from).collect()),
+        Some(Value::Array(lines)) => lines
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(String::from)
+                    .ok_or(LangsError::InvalidNotebook("Invalid value in 'source'"))
+            })
+            .collect(),
+        _ => Err(LangsError::InvalidNotebook(
+            "Invalid or missing value for 'source'",
+        )),
+    }
+}
+
+// code cells are parsed with the notebook's comment syntax and markdown cells with the markdown one, other cells are not parsed
+pub(crate) fn notebook_cell_extension<'a>(
+    cell: &Value,
+    notebook_extension: &'a str,
+) -> Option<&'a str> {
+    match cell.get("cell_type").and_then(|c| c.as_str()) {
+        Some("code") => Some(notebook_extension),
+        Some("markdown") => Some("md"),
+        _ => None,
+    }
+}
+
+// the lines of a cell tagged as solution or hidden in its metadata are treated as if they were in a solution or hidden block
+fn notebook_cell_tag(cell: &Value) -> Option<MetaString> {
+    let tags = cell
+        .get("metadata")
+        .and_then(|m| m.get("tags"))
+        .and_then(|t| t.as_array())?;
+    let has_tag = |name: &str| tags.iter().any(|tag| tag.as_str() == Some(name));
+    // hidden cells are left out of the solution as well, so the hidden tag takes precedence
+    if has_tag("hidden") {
+        Some(MetaString::Hidden(String::new()))
+    } else if has_tag("solution") {
+        Some(MetaString::Solution(String::new()))
+    } else {
+        None
     }
-    Ok(())
 }
 
 // Filter for hidden directories (directories with names starting with '.')
@@ -211,7 +369,7 @@ pub fn is_hidden_dir(entry: &DirEntry) -> bool {
 }
 
 // Filter for skipping directories on `FILES_TO_SKIP_ALWAYS` or named 'private', and files in a 'test' directory that contain 'Hidden' in their name.
-fn on_skip_list(entry: &DirEntry) -> bool {
+pub(crate) fn on_skip_list(entry: &DirEntry) -> bool {
     // check if entry's filename matchees the skip list or is 'private'
     let entry_file_name = entry.file_name().to_str();
     let on_skip_list = entry_file_name
@@ -280,6 +438,8 @@ impl Iterator for LossyFileIterator {
 /// Returns None if the file should be skipped.
 fn process_lines<'a, 'b, I, E>(
     line_iterator: I,
+    comment_syntaxes: &CommentSyntaxes,
+    variant: Option<&str>,
     line_filter: &'b mut impl Fn(&MetaString) -> bool,
     file_filter: &'b mut impl Fn(&[MetaString]) -> bool,
     extension: &str,
@@ -288,7 +448,8 @@ where
     I: Iterator<Item = Result<String, E>>,
     'b: 'a,
 {
-    let parser = MetaSyntaxParser::new(line_iterator, extension);
+    let parser = MetaSyntaxParser::with_syntaxes(line_iterator, comment_syntaxes, extension)
+        .select_variant(variant);
     let parse_result: Result<Vec<_>, _> = parser.collect();
     let parsed = parse_result?;
 
@@ -313,7 +474,7 @@ where
 mod test {
     use super::*;
     use std::{fs::File, io::Write, path::PathBuf};
-    use tmc_langs_framework::TmcProjectYml;
+    use tmc_langs_framework::{RunStatus, TmcProjectYml};
 
     fn init() {
         use log::*;
@@ -344,7 +505,7 @@ mod test {
 
         let temp_target = tempfile::tempdir().unwrap();
 
-        prepare_solution(temp_source.path(), temp_target.path()).unwrap();
+        prepare_solution(temp_source.path(), temp_target.path(), None).unwrap();
 
         assert!(temp_target.path().join("inner/binary.bin").exists());
         assert!(temp_target.path().join("File.java").exists());
@@ -383,7 +544,7 @@ mod test {
 
         let temp_target = tempfile::tempdir().unwrap();
 
-        prepare_solution(temp_source.path(), temp_target.path()).unwrap();
+        prepare_solution(temp_source.path(), temp_target.path(), None).unwrap();
 
         let s = file_util::read_file_to_string(temp_target.path().join("Test.java")).unwrap();
         let expected = r#"public class JavaTestCase {
@@ -436,7 +597,7 @@ mod test {
 
         let temp_target = tempfile::tempdir().unwrap();
 
-        prepare_stub(temp_source.path(), temp_target.path()).unwrap();
+        prepare_stub(temp_source.path(), temp_target.path(), None).unwrap();
 
         let s = file_util::read_file_to_string(temp_target.path().join("Test.bin")).unwrap();
 
@@ -465,7 +626,7 @@ class SomeClass {}
 
         let temp_target = tempfile::tempdir().unwrap();
 
-        prepare_solution(temp_source.path(), temp_target.path()).unwrap();
+        prepare_solution(temp_source.path(), temp_target.path(), None).unwrap();
 
         assert!(dbg!(temp_source.path().join("Solution.java")).exists());
         assert!(dbg!(temp_source.path().join("NonSolution.java")).exists());
@@ -504,7 +665,7 @@ class SomeClass {}
 
         let temp_target = tempfile::tempdir().unwrap();
 
-        prepare_stub(temp_source.path(), temp_target.path()).unwrap();
+        prepare_stub(temp_source.path(), temp_target.path(), None).unwrap();
 
         let s = file_util::read_file_to_string(temp_target.path().join("Test.java")).unwrap();
         let expected = r#"public class JavaTestCase {
@@ -522,6 +683,60 @@ class SomeClass {}
         assert_eq!(s, expected, "expected:\n{expected:#}\nfound:\n{s:#}");
     }
 
+    #[test]
+    fn prepares_stubs_with_configured_comment_syntaxes() {
+        init();
+
+        let temp_source = tempfile::tempdir().unwrap();
+        file_to(
+            &temp_source,
+            ".tmcproject.yml",
+            r#"comment_syntaxes:
+  lua:
+    line: "--"
+"#,
+        );
+        file_to(
+            &temp_source,
+            "src/query.sql",
+            r"SELECT
+-- BEGIN SOLUTION
+  name
+-- END SOLUTION
+  -- STUB: *
+FROM students;
+",
+        );
+        file_to(
+            &temp_source,
+            "src/add.lua",
+            r"function add(a, b)
+  -- BEGIN SOLUTION
+  return a + b
+  -- END SOLUTION
+end
+",
+        );
+        file_to(
+            &temp_source,
+            "src/notes.txt",
+            "# BEGIN SOLUTION
+",
+        );
+
+        let temp_target = tempfile::tempdir().unwrap();
+
+        prepare_stub(temp_source.path(), temp_target.path(), None).unwrap();
+
+        let s = file_util::read_file_to_string(temp_target.path().join("src/query.sql")).unwrap();
+        assert_eq!(s, "SELECT\n  *\nFROM students;\n");
+        let s = file_util::read_file_to_string(temp_target.path().join("src/add.lua")).unwrap();
+        assert_eq!(s, "function add(a, b)\nend\n");
+        // files without a comment syntax are copied as is
+        let s = file_util::read_file_to_string(temp_target.path().join("src/notes.txt")).unwrap();
+        assert_eq!(s, "# BEGIN SOLUTION\n");
+    }
+
     #[test]
     fn prepare_stubs_filters_solution_files() {
         init();
@@ -532,7 +747,7 @@ class SomeClass {}
 
         let temp_target = tempfile::tempdir().unwrap();
 
-        prepare_stub(temp_source.path(), temp_target.path()).unwrap();
+        prepare_stub(temp_source.path(), temp_target.path(), None).unwrap();
 
         assert!(temp_target.path().join("NonSolution.java").exists());
         assert!(!temp_target.path().join("SolutionFile.java").exists());
@@ -568,7 +783,7 @@ extra_student_files:
 
         let temp_target = tempfile::tempdir().unwrap();
 
-        prepare_solution(temp_source.path(), temp_target.path()).unwrap();
+        prepare_solution(temp_source.path(), temp_target.path(), None).unwrap();
 
         assert!(dbg!(temp_source.path().join("NotHidden")).exists());
         assert!(!dbg!(temp_source.path().join("ActuallyHidden")).exists());
@@ -589,7 +804,7 @@ etc etc",
 
         let temp_target = tempfile::tempdir().unwrap();
 
-        prepare_solution(temp_source.path(), temp_target.path()).unwrap();
+        prepare_solution(temp_source.path(), temp_target.path(), None).unwrap();
 
         assert!(!temp_target.path().join("H.java").exists());
         assert!(temp_target.path().join("NonH.java").exists());
@@ -610,7 +825,7 @@ etc etc",
 
         let temp_target = tempfile::tempdir().unwrap();
 
-        prepare_stub(temp_source.path(), temp_target.path()).unwrap();
+        prepare_stub(temp_source.path(), temp_target.path(), None).unwrap();
 
         assert!(!temp_target.path().join("H.java").exists());
         assert!(temp_target.path().join("NonH.java").exists());
@@ -633,7 +848,7 @@ etc etc",
 
         let temp_target = tempfile::tempdir().unwrap();
 
-        prepare_solution(temp_source.path(), temp_target.path()).unwrap();
+        prepare_solution(temp_source.path(), temp_target.path(), None).unwrap();
 
         let s =
             file_util::read_file_to_string(temp_target.path().join("ContainsHidden.java")).unwrap();
@@ -662,7 +877,7 @@ etc etc",
 
         let temp_target = tempfile::tempdir().unwrap();
 
-        prepare_stub(temp_source.path(), temp_target.path()).unwrap();
+        prepare_stub(temp_source.path(), temp_target.path(), None).unwrap();
 
         let s =
             file_util::read_file_to_string(temp_target.path().join("ContainsHidden.java")).unwrap();
@@ -739,7 +954,7 @@ etc etc"
 
         let temp_target = tempfile::tempdir().unwrap();
 
-        prepare_stub(temp_source.path(), temp_target.path()).unwrap();
+        prepare_stub(temp_source.path(), temp_target.path(), None).unwrap();
 
         assert!(!temp_target.path().join("hidden.ipynb").exists());
 
@@ -778,4 +993,155 @@ etc etc"
             })
         );
     }
+
+    #[test]
+    fn filters_notebook_cells() {
+        init();
+
+        let temp_source = tempfile::tempdir().unwrap();
+        file_to(
+            &temp_source,
+            "notebook.ipynb",
+            serde_json::json!({
+                "cells": [
+                    {
+                        "cell_type": "markdown",
+                        "metadata": {},
+                        // the source can be a single string instead of a list of lines
+                        "source": "Write a function.\n<!-- BEGIN SOLUTION -->\nThe solution uses recursion.\n<!-- END SOLUTION -->\n"
+                    },
+                    {
+                        "cell_type": "code",
+                        "execution_count": 1,
+                        "metadata": { "tags": ["solution"] },
+                        "outputs": [],
+                        "source": ["def helper():\n", "    return 1\n"]
+                    },
+                    {
+                        "cell_type": "code",
+                        "execution_count": 2,
+                        "metadata": {},
+                        "outputs": [{ "output_type": "stream", "name": "stdout", "text": ["1\n"] }],
+                        "source": "# BEGIN SOLUTION\nprint(helper())\n# END SOLUTION\n"
+                    },
+                    {
+                        "cell_type": "markdown",
+                        "metadata": { "tags": ["solution", "hidden"] },
+                        "source": ["Notes for the teachers."]
+                    },
+                ],
+            })
+            .to_string(),
+        );
+
+        let read_cells = |path: &Path| {
+            let val: serde_json::Value =
+                deserialize::json_from_reader(file_util::open_file(path).unwrap()).unwrap();
+            val["cells"].as_array().unwrap().clone()
+        };
+
+        let temp_stub = tempfile::tempdir().unwrap();
+        prepare_stub(temp_source.path(), temp_stub.path(), None).unwrap();
+        let cells = read_cells(&temp_stub.path().join("notebook.ipynb"));
+        assert_eq!(cells.len(), 2);
+        assert_eq!(cells[0]["source"], "Write a function.\n");
+        assert_eq!(cells[1]["source"], "");
+        assert_eq!(cells[1]["outputs"], serde_json::json!([]));
+        assert_eq!(cells[1]["execution_count"], serde_json::Value::Null);
+
+        let temp_solution = tempfile::tempdir().unwrap();
+        prepare_solution(temp_source.path(), temp_solution.path(), None).unwrap();
+        let cells = read_cells(&temp_solution.path().join("notebook.ipynb"));
+        assert_eq!(cells.len(), 3);
+        assert_eq!(
+            cells[0]["source"],
+            "Write a function.\nThe solution uses recursion.\n"
+        );
+        assert_eq!(cells[1]["execution_count"], 1);
+        assert_eq!(
+            cells[1]["source"],
+            serde_json::json!(["def helper():\n", "    return 1\n"])
+        );
+        assert_eq!(cells[2]["source"], "print(helper())\n");
+        assert_eq!(cells[2]["outputs"][0]["text"], serde_json::json!(["1\n"]));
+    }
+
+    #[test]
+    fn runs_tests_for_prepared_notebook() {
+        init();
+
+        let temp = tempfile::tempdir().unwrap();
+        // the temporary directory is hidden, which would cause the exercise to be skipped
+        let exercise = temp.path().join("exercise");
+        file_util::copy("tests/data/some_course/PythonExercise/tmc", &exercise).unwrap();
+        file_to(&exercise, "test/__init__.py", "");
+        file_to(
+            &exercise,
+            "src/exercise.ipynb",
+            serde_json::json!({
+                "cells": [
+                    {
+                        "cell_type": "markdown",
+                        "metadata": {},
+                        "source": "Write a function that adds two numbers."
+                    },
+                    {
+                        "cell_type": "code",
+                        "execution_count": 1,
+                        "metadata": { "tags": ["solution"] },
+                        "outputs": [],
+                        "source": ["def add(a, b):\n", "    return a + b\n"]
+                    },
+                    {
+                        "cell_type": "code",
+                        "execution_count": null,
+                        "metadata": {},
+                        "outputs": [],
+                        "source": "# STUB: def add(a, b):\n# STUB:     return 0\n"
+                    },
+                ],
+                "metadata": {},
+                "nbformat": 4,
+                "nbformat_minor": 5,
+            })
+            .to_string(),
+        );
+        file_to(
+            &exercise,
+            "test/test_notebook.py",
+            r#"
+import json
+import unittest
+from tmc import points
+
+def load_notebook(path):
+    with open(path) as f:
+        notebook = json.load(f)
+    namespace = {}
+    for cell in notebook["cells"]:
+        if cell["cell_type"] == "code":
+            exec("".join(cell["source"]), namespace)
+    return namespace
+
+@points('1.1')
+class TestNotebook(unittest.TestCase):
+    def test_add(self):
+        add = load_notebook("src/exercise.ipynb")["add"]
+        self.assertEqual(add(1, 2), 3)
+"#,
+        );
+
+        let solution = temp.path().join("solution");
+        prepare_solution(&exercise, &solution, None).unwrap();
+        let run_result = crate::run_tests(&solution).unwrap();
+        assert_eq!(run_result.status, RunStatus::Passed);
+        assert_eq!(run_result.test_results[0].name, "TestNotebook: test_add");
+        assert!(run_result.test_results[0].points.contains(&"1.1".into()));
+
+        let stub = temp.path().join("stub");
+        prepare_stub(&exercise, &stub, None).unwrap();
+        let run_result = crate::run_tests(&stub).unwrap();
+        assert_eq!(run_result.status, RunStatus::TestsFailed);
+        assert!(!run_result.test_results[0].successful);
+    }
 }
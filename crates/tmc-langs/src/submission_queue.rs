@@ -0,0 +1,534 @@
+//! A queue in the projects directory for submissions that could not be sent because the server was unreachable.
+//! The queued submissions are sent later in the order they were queued in.
+//! Submissions are only removed from the queue once they have been sent or with `remove_queued_submission`.
+
+use crate::{LangsError, mooc, tmc};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tmc_langs_framework::{Compression, Language};
+use tmc_langs_util::{
+    deserialize,
+    file_util::{self, Lock, LockOptions},
+};
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+// the directory inside the projects dir that contains the queued submissions
+const QUEUE_DIR: &str = ".submission-queue";
+
+/// A submission waiting to be sent. Stored in the queue directory as a JSON file next to the archive.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub struct QueuedSubmission {
+    pub id: Uuid,
+    pub target: SubmissionTarget,
+    pub queued_at: DateTime<Utc>,
+    /// The BLAKE3 hash of the archive, checked before the archive is sent.
+    pub hash: String,
+}
+
+/// The exercise a queued submission is sent to.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub enum SubmissionTarget {
+    Tmc {
+        exercise_id: u32,
+        /// ISO 639-3 code of the locale the submission was made with.
+        locale: Option<String>,
+    },
+    Mooc {
+        exercise_id: Uuid,
+        slide_id: Uuid,
+        task_id: Uuid,
+    },
+}
+
+/// The result of trying to send a queued submission.
+#[derive(Debug, Serialize, JsonSchema)]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub struct SyncedSubmission<T> {
+    pub submission: QueuedSubmission,
+    pub result: SyncResult<T>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(tag = "status", content = "data", rename_all = "kebab-case")]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+pub enum SyncResult<T> {
+    /// The submission was sent and removed from the queue.
+    Sent(T),
+    /// The server rejected the submission or its archive is corrupted. It is kept in the queue.
+    Failed(String),
+    /// The server could not be reached or failed to handle a request, so the submission was not attempted. It is kept in the queue.
+    Pending,
+}
+
+/// Compresses the exercise at the submission path and adds it to the queue of TMC submissions.
+pub fn queue_tmc_submission(
+    projects_dir: &Path,
+    exercise_id: u32,
+    submission_path: &Path,
+    locale: Option<Language>,
+) -> Result<QueuedSubmission, LangsError> {
+    let target = SubmissionTarget::Tmc {
+        exercise_id,
+        locale: locale.map(|l| l.to_639_3().to_string()),
+    };
+    // the server expects a zip like the one created by TestMyCodeClient::submit
+    queue(projects_dir, target, submission_path, Compression::Zip)
+}
+
+/// Compresses the exercise at the submission path and adds it to the queue of MOOC submissions.
+pub fn queue_mooc_submission(
+    projects_dir: &Path,
+    exercise_id: Uuid,
+    slide_id: Uuid,
+    task_id: Uuid,
+    submission_path: &Path,
+) -> Result<QueuedSubmission, LangsError> {
+    let target = SubmissionTarget::Mooc {
+        exercise_id,
+        slide_id,
+        task_id,
+    };
+    queue(projects_dir, target, submission_path, Compression::TarZstd)
+}
+
+fn queue(
+    projects_dir: &Path,
+    target: SubmissionTarget,
+    submission_path: &Path,
+    compression: Compression,
+) -> Result<QueuedSubmission, LangsError> {
+    log::debug!("queuing submission from {}", submission_path.display());
+
+    let queue_dir = projects_dir.join(QUEUE_DIR);
+    let mut lock = Lock::dir(&queue_dir, LockOptions::WriteCreate)?;
+    let _guard = lock.lock()?;
+    let id = Uuid::new_v4();
+    let archive = archive_path(&queue_dir, id);
+    crate::compress_project_to(submission_path, &archive, compression, false, false)?;
+    let submission = QueuedSubmission {
+        id,
+        target,
+        queued_at: Utc::now(),
+        hash: hash_archive(&archive)?,
+    };
+    // the metadata is written last so that an interrupted write does not leave a submission without an archive
+    write_metadata(&queue_dir, &submission)?;
+    Ok(submission)
+}
+
+/// Returns the queued submissions in the order they were queued in.
+pub fn list_queued_submissions(projects_dir: &Path) -> Result<Vec<QueuedSubmission>, LangsError> {
+    let queue_dir = projects_dir.join(QUEUE_DIR);
+    if !queue_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut lock = Lock::dir(&queue_dir, LockOptions::Read)?;
+    let _guard = lock.lock()?;
+    list(&queue_dir)
+}
+
+/// Removes a submission from the queue without sending it.
+pub fn remove_queued_submission(
+    projects_dir: &Path,
+    id: Uuid,
+) -> Result<QueuedSubmission, LangsError> {
+    log::debug!("removing queued submission {id}");
+
+    let queue_dir = projects_dir.join(QUEUE_DIR);
+    let not_found = || LangsError::QueuedSubmissionNotFound(id);
+    if !queue_dir.exists() {
+        return Err(not_found());
+    }
+
+    let mut lock = Lock::dir(&queue_dir, LockOptions::Write)?;
+    let _guard = lock.lock()?;
+    let submission = list(&queue_dir)?
+        .into_iter()
+        .find(|s| s.id == id)
+        .ok_or_else(not_found)?;
+    file_util::remove_file(metadata_path(&queue_dir, id))?;
+    let archive = archive_path(&queue_dir, id);
+    if archive.exists() {
+        file_util::remove_file(archive)?;
+    }
+    Ok(submission)
+}
+
+fn list(queue_dir: &Path) -> Result<Vec<QueuedSubmission>, LangsError> {
+    let mut submissions = vec![];
+    for entry in WalkDir::new(queue_dir).min_depth(1).max_depth(1) {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let file = file_util::open_file(path)?;
+        let submission: QueuedSubmission = deserialize::json_from_reader(file)
+            .map_err(|e| LangsError::DeserializeJson(path.to_path_buf(), e))?;
+        submissions.push(submission);
+    }
+    submissions.sort_by_key(|s| s.queued_at);
+    Ok(submissions)
+}
+
+/// Sends the queued TMC submissions in order and removes the ones that are sent successfully.
+/// Once the server cannot be reached or fails with a server error, the rest of the submissions are left pending in the queue.
+/// If the token is rejected, the sync is stopped with the error so that the token can be refreshed.
+pub fn sync_tmc_submissions(
+    client: &tmc::TestMyCodeClient,
+    projects_dir: &Path,
+) -> Result<Vec<SyncedSubmission<tmc::response::NewSubmission>>, LangsError> {
+    log::debug!("sending queued TMC submissions");
+
+    sync(
+        projects_dir,
+        |target| match target {
+            SubmissionTarget::Tmc {
+                exercise_id,
+                locale,
+            } => Some((
+                *exercise_id,
+                locale.as_deref().and_then(Language::from_639_3),
+            )),
+            SubmissionTarget::Mooc { .. } => None,
+        },
+        |(exercise_id, locale), archive| {
+            let archive = file_util::read_file(archive)?;
+            let result = client
+                .submit_archive(exercise_id, archive, locale)
+                .map_err(|e| {
+                    let status = match e.as_ref() {
+                        tmc::TestMyCodeClientError::HttpError { status, .. } => {
+                            Some(status.as_u16())
+                        }
+                        _ => None,
+                    };
+                    let unauthorized = matches!(
+                        e.as_ref(),
+                        tmc::TestMyCodeClientError::NotAuthenticated
+                            | tmc::TestMyCodeClientError::TokenExpired
+                            | tmc::TestMyCodeClientError::NoRefreshToken
+                    );
+                    SendError::classify(e.is_connection_error(), unauthorized, status, e.into())
+                });
+            Ok(result)
+        },
+    )
+}
+
+/// Sends the queued MOOC submissions in order and removes the ones that are sent successfully.
+/// Once the server cannot be reached or fails with a server error, the rest of the submissions are left pending in the queue.
+/// If the token is rejected, the sync is stopped with the error so that the token can be refreshed.
+pub fn sync_mooc_submissions(
+    client: &mooc::MoocClient,
+    projects_dir: &Path,
+) -> Result<Vec<SyncedSubmission<mooc::ExerciseTaskSubmissionResult>>, LangsError> {
+    log::debug!("sending queued MOOC submissions");
+
+    sync(
+        projects_dir,
+        |target| match target {
+            SubmissionTarget::Mooc {
+                exercise_id,
+                slide_id,
+                task_id,
+            } => Some((*exercise_id, *slide_id, *task_id)),
+            SubmissionTarget::Tmc { .. } => None,
+        },
+        |(exercise_id, slide_id, task_id), archive| {
+            let result = client
+                .submit(exercise_id, slide_id, task_id, archive)
+                .map_err(|e| {
+                    let status = match e.as_ref() {
+                        mooc::MoocClientError::HttpError { status, .. } => Some(status.as_u16()),
+                        _ => None,
+                    };
+                    let unauthorized = matches!(
+                        e.as_ref(),
+                        mooc::MoocClientError::NotAuthenticated
+                            | mooc::MoocClientError::TokenExpired
+                    );
+                    SendError::classify(e.is_connection_error(), unauthorized, status, e.into())
+                });
+            Ok(result)
+        },
+    )
+}
+
+// why a queued submission could not be sent
+enum SendError {
+    // the server could not be reached or failed with a server error, so the rest of the queue is left pending
+    Unavailable(LangsError),
+    // the token was rejected or cannot be used, so the sync is stopped to let the caller refresh it
+    Unauthorized(LangsError),
+    // the server rejected the submission itself
+    Rejected(LangsError),
+}
+
+impl SendError {
+    fn classify(
+        connection_error: bool,
+        unauthorized: bool,
+        status: Option<u16>,
+        error: LangsError,
+    ) -> Self {
+        if unauthorized || status == Some(401) {
+            Self::Unauthorized(error)
+        } else if connection_error || status.is_some_and(|s| s >= 500) {
+            Self::Unavailable(error)
+        } else {
+            Self::Rejected(error)
+        }
+    }
+}
+
+// sends the queued submissions the filter returns the parameters for, skipping the ones for other servers
+fn sync<P, T>(
+    projects_dir: &Path,
+    filter: impl Fn(&SubmissionTarget) -> Option<P>,
+    mut send: impl FnMut(P, &Path) -> Result<Result<T, SendError>, LangsError>,
+) -> Result<Vec<SyncedSubmission<T>>, LangsError> {
+    let queue_dir = projects_dir.join(QUEUE_DIR);
+    if !queue_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    // held while sending so that concurrent syncs don't send the same submissions
+    let mut lock = Lock::dir(&queue_dir, LockOptions::Write)?;
+    let _guard = lock.lock()?;
+    let mut synced = vec![];
+    let mut unavailable = false;
+    for submission in list(&queue_dir)? {
+        let Some(params) = filter(&submission.target) else {
+            continue;
+        };
+        let archive = archive_path(&queue_dir, submission.id);
+        let result = if unavailable {
+            SyncResult::Pending
+        } else if !hash_archive(&archive).is_ok_and(|hash| hash == submission.hash) {
+            log::warn!(
+                "Queued submission {} has a missing or corrupted archive",
+                submission.id
+            );
+            SyncResult::Failed("The queued archive is missing or corrupted".to_string())
+        } else {
+            match send(params, &archive)? {
+                Ok(sent) => {
+                    log::debug!("sent queued submission {}", submission.id);
+                    remove(&queue_dir, submission.id)?;
+                    SyncResult::Sent(sent)
+                }
+                Err(SendError::Unavailable(error)) => {
+                    log::warn!("Server unavailable, leaving submissions in the queue: {error}");
+                    unavailable = true;
+                    SyncResult::Pending
+                }
+                Err(SendError::Unauthorized(error)) => {
+                    log::warn!("Token rejected, stopping the sync: {error}");
+                    return Err(error);
+                }
+                Err(SendError::Rejected(error)) => SyncResult::Failed(error.to_string()),
+            }
+        };
+        synced.push(SyncedSubmission { submission, result });
+    }
+    Ok(synced)
+}
+
+fn hash_archive(archive: &Path) -> Result<String, LangsError> {
+    let data = file_util::read_file(archive)?;
+    Ok(blake3::hash(&data).to_string())
+}
+
+fn write_metadata(queue_dir: &Path, submission: &QueuedSubmission) -> Result<(), LangsError> {
+    let metadata = serde_json::to_vec_pretty(submission)?;
+    file_util::write_to_file(metadata, metadata_path(queue_dir, submission.id))?;
+    Ok(())
+}
+
+fn remove(queue_dir: &Path, id: Uuid) -> Result<(), LangsError> {
+    file_util::remove_file(metadata_path(queue_dir, id))?;
+    file_util::remove_file(archive_path(queue_dir, id))?;
+    Ok(())
+}
+
+fn metadata_path(queue_dir: &Path, id: Uuid) -> PathBuf {
+    queue_dir.join(format!("{id}.json"))
+}
+
+fn archive_path(queue_dir: &Path, id: Uuid) -> PathBuf {
+    queue_dir.join(format!("{id}.archive"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use oauth2::{AccessToken, EmptyExtraTokenFields, basic::BasicTokenType};
+    use tmc_server_mock::mockito::{Matcher, Mock, Server};
+
+    fn client(root_url: &str) -> tmc::TestMyCodeClient {
+        let mut client = tmc::TestMyCodeClient::new(
+            root_url.parse().unwrap(),
+            "client".to_string(),
+            "version".to_string(),
+        )
+        .unwrap();
+        let token = tmc::Token::new(
+            AccessToken::new("".to_string()),
+            BasicTokenType::Bearer,
+            EmptyExtraTokenFields {},
+        );
+        client.set_token(token, None);
+        client
+    }
+
+    fn exercise(dir: &Path) {
+        std::fs::write(dir.join(".tmcproject.yml"), "no-tests: true\n").unwrap();
+        std::fs::write(dir.join("answer.txt"), "answer").unwrap();
+    }
+
+    // created before the other mocks so that it takes precedence
+    fn reject_submission(server: &mut Server, exercise_id: u32, status: usize) -> Mock {
+        server
+            .mock(
+                "POST",
+                format!("/api/v8/core/exercises/{exercise_id}/submissions").as_str(),
+            )
+            .match_query(Matcher::Any)
+            .with_status(status)
+            .with_body(r#"{"errors":["The deadline has passed"]}"#)
+            .create()
+    }
+
+    #[test]
+    fn syncs_queued_submissions_in_order() {
+        let projects_dir = tempfile::tempdir().unwrap();
+        let exercise_dir = tempfile::tempdir().unwrap();
+        exercise(exercise_dir.path());
+
+        let first =
+            queue_tmc_submission(projects_dir.path(), 1, exercise_dir.path(), None).unwrap();
+        let second = queue_tmc_submission(
+            projects_dir.path(),
+            2,
+            exercise_dir.path(),
+            Language::from_639_3("fin"),
+        )
+        .unwrap();
+        let queued = list_queued_submissions(projects_dir.path()).unwrap();
+        assert_eq!(queued.len(), 2);
+        assert_eq!(queued[0].id, first.id);
+        assert_eq!(queued[1].id, second.id);
+
+        // the server is unreachable, so nothing is sent
+        let unreachable = client("http://127.0.0.1:1/");
+        let synced = sync_tmc_submissions(&unreachable, projects_dir.path()).unwrap();
+        assert_eq!(synced.len(), 2);
+        assert!(
+            synced
+                .iter()
+                .all(|s| matches!(s.result, SyncResult::Pending))
+        );
+        assert_eq!(
+            list_queued_submissions(projects_dir.path()).unwrap().len(),
+            2
+        );
+
+        let mut server = Server::new();
+        let m2 = reject_submission(&mut server, 2, 403);
+        let m1 = tmc_server_mock::core::submit_exercise(&mut server);
+        let synced = sync_tmc_submissions(&client(&server.url()), projects_dir.path()).unwrap();
+        m1.assert();
+        m2.assert();
+        assert!(matches!(&synced[0].result, SyncResult::Sent(s) if s.submission_url == "url"));
+        assert!(matches!(&synced[1].result, SyncResult::Failed(_)));
+
+        // the rejected submission is kept
+        let queued = list_queued_submissions(projects_dir.path()).unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].id, second.id);
+        assert!(
+            matches!(&queued[0].target, SubmissionTarget::Tmc { exercise_id: 2, locale } if locale.as_deref() == Some("fin"))
+        );
+
+        remove_queued_submission(projects_dir.path(), second.id).unwrap();
+        assert!(
+            list_queued_submissions(projects_dir.path())
+                .unwrap()
+                .is_empty()
+        );
+        assert!(matches!(
+            remove_queued_submission(projects_dir.path(), second.id),
+            Err(LangsError::QueuedSubmissionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn keeps_corrupted_archives() {
+        let projects_dir = tempfile::tempdir().unwrap();
+        let exercise_dir = tempfile::tempdir().unwrap();
+        exercise(exercise_dir.path());
+
+        let queued =
+            queue_tmc_submission(projects_dir.path(), 1, exercise_dir.path(), None).unwrap();
+        let queue_dir = projects_dir.path().join(QUEUE_DIR);
+        std::fs::write(archive_path(&queue_dir, queued.id), "corrupted").unwrap();
+
+        let mut server = Server::new();
+        let m = tmc_server_mock::core::submit_exercise(&mut server).expect(0);
+        let synced = sync_tmc_submissions(&client(&server.url()), projects_dir.path()).unwrap();
+        m.assert();
+        assert!(matches!(&synced[0].result, SyncResult::Failed(_)));
+        assert_eq!(
+            list_queued_submissions(projects_dir.path()).unwrap().len(),
+            1
+        );
+
+        remove_queued_submission(projects_dir.path(), queued.id).unwrap();
+        assert!(!archive_path(&queue_dir, queued.id).exists());
+        assert!(!metadata_path(&queue_dir, queued.id).exists());
+    }
+
+    #[test]
+    fn keeps_submissions_on_server_and_token_errors() {
+        let projects_dir = tempfile::tempdir().unwrap();
+        let exercise_dir = tempfile::tempdir().unwrap();
+        exercise(exercise_dir.path());
+        queue_tmc_submission(projects_dir.path(), 1, exercise_dir.path(), None).unwrap();
+        queue_tmc_submission(projects_dir.path(), 2, exercise_dir.path(), None).unwrap();
+
+        // a server error leaves the rest of the queue pending
+        let mut server = Server::new();
+        let m1 = reject_submission(&mut server, 1, 500).expect(1);
+        let m2 = reject_submission(&mut server, 2, 500).expect(0);
+        let synced = sync_tmc_submissions(&client(&server.url()), projects_dir.path()).unwrap();
+        m1.assert();
+        m2.assert();
+        assert!(
+            synced
+                .iter()
+                .all(|s| matches!(s.result, SyncResult::Pending))
+        );
+
+        // a rejected token stops the sync with the error so that it can be refreshed
+        let mut server = Server::new();
+        let m1 = reject_submission(&mut server, 1, 401).expect(1);
+        let m2 = reject_submission(&mut server, 2, 401).expect(0);
+        let error = sync_tmc_submissions(&client(&server.url()), projects_dir.path()).unwrap_err();
+        m1.assert();
+        m2.assert();
+        assert!(matches!(error, LangsError::TestMyCodeClient(_)));
+        assert_eq!(
+            list_queued_submissions(projects_dir.path()).unwrap().len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Watching exercises for changes.
+
+use crate::{LangsError, RunResult};
+use std::{
+    collections::BTreeMap,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+use tmc_langs_framework::StudentFilePolicy;
+use walkdir::WalkDir;
+
+// how often the student files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// dependency, build and version control directories that are not watched
+const PRUNED_DIRS: &[&str] = &[
+    ".git",
+    ".gradle",
+    ".venv",
+    "__pycache__",
+    "bin",
+    "build",
+    "node_modules",
+    "obj",
+    "target",
+];
+
+// the modification time and length of each student file
+type Snapshot = BTreeMap<PathBuf, (Option<SystemTime>, u64)>;
+
+/// Runs the exercise's tests and then re-runs them every time its student files change, as classified by its `StudentFilePolicy`.
+/// The tests are only re-run once the files have not changed for the debounce duration, so that saving several files at once only causes a single run.
+/// The result of each run is passed to the callback, which decides whether to keep watching.
+///
+/// Files are polled for changes. Changes to existing files made while the tests are running cause another run.
+/// Files that appear during a run are considered to be generated by the tests and are not watched until they are changed between runs.
+/// Dependency, build and version control directories such as `node_modules`, `target` and `.git` are never watched.
+pub fn watch_exercise(
+    exercise_path: &Path,
+    debounce: Duration,
+    mut on_run: impl FnMut(Result<RunResult, LangsError>) -> ControlFlow<()>,
+) -> Result<(), LangsError> {
+    log::debug!("watching exercise in {}", exercise_path.display());
+
+    let policy = tmc_langs_plugins::get_student_file_policy(exercise_path)?;
+    let mut generated = Snapshot::new();
+    loop {
+        // generated files that changed since the previous run are included and watched from now on
+        let mut snapshot = snapshot_student_files(exercise_path, policy.as_ref(), &generated);
+        let result = crate::run_tests(exercise_path);
+        let after_run = snapshot_student_files(exercise_path, policy.as_ref(), &Snapshot::new());
+        generated = after_run
+            .into_iter()
+            .filter(|(path, _)| !snapshot.contains_key(path))
+            .collect();
+        if on_run(result).is_break() {
+            return Ok(());
+        }
+
+        // wait for a change
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let current = snapshot_student_files(exercise_path, policy.as_ref(), &generated);
+            if current != snapshot {
+                snapshot = current;
+                break;
+            }
+        }
+        // wait for the changes to settle
+        loop {
+            thread::sleep(debounce);
+            let current = snapshot_student_files(exercise_path, policy.as_ref(), &generated);
+            if current == snapshot {
+                break;
+            }
+            snapshot = current;
+        }
+        log::debug!("student files changed in {}", exercise_path.display());
+    }
+}
+
+// generated files are skipped while they are unchanged
+fn snapshot_student_files(
+    exercise_path: &Path,
+    policy: &dyn StudentFilePolicy,
+    generated: &Snapshot,
+) -> Snapshot {
+    let mut snapshot = Snapshot::new();
+    // files can be removed while walking, so errors are skipped
+    for entry in WalkDir::new(exercise_path)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| {
+            !(e.file_type().is_dir()
+                && e.file_name()
+                    .to_str()
+                    .is_some_and(|name| PRUNED_DIRS.contains(&name)))
+        })
+        .filter_map(Result::ok)
+    {
+        let Ok(relative) = entry.path().strip_prefix(exercise_path) else {
+            continue;
+        };
+        if !entry.file_type().is_file() || !policy.is_student_file(relative) {
+            continue;
+        }
+        if let Ok(metadata) = entry.metadata() {
+            let state = (metadata.modified().ok(), metadata.len());
+            if generated.get(relative) == Some(&state) {
+                continue;
+            }
+            snapshot.insert(relative.to_path_buf(), state);
+        }
+    }
+    snapshot
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use crate::RunStatus;
+
+    #[test]
+    fn reruns_tests_on_changes() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join(".tmcproject.yml"), "no-tests: true\n").unwrap();
+        std::fs::write(temp.path().join("answer.txt"), "first").unwrap();
+
+        let mut runs = 0;
+        watch_exercise(temp.path(), Duration::from_millis(50), |result| {
+            assert_eq!(result.unwrap().status, RunStatus::Passed);
+            runs += 1;
+            if runs == 1 {
+                std::fs::write(temp.path().join("answer.txt"), "second answer").unwrap();
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        })
+        .unwrap();
+        assert_eq!(runs, 2);
+    }
+
+    #[test]
+    fn skips_pruned_and_ignored_files() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join(".tmcproject.yml"), "no-tests: true\n").unwrap();
+        for file in [
+            "src/answer.txt",
+            "src/generated.txt",
+            "src/node_modules/dep/index.js",
+            "src/target/debug/out",
+            "src/.git/HEAD",
+        ] {
+            let path = temp.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+
+        let policy = tmc_langs_plugins::get_student_file_policy(temp.path()).unwrap();
+        let all = snapshot_student_files(temp.path(), policy.as_ref(), &Snapshot::new());
+        let generated: Snapshot = all
+            .into_iter()
+            .filter(|(path, _)| path.ends_with("generated.txt"))
+            .collect();
+        let snapshot = snapshot_student_files(temp.path(), policy.as_ref(), &generated);
+        assert_eq!(
+            snapshot.into_keys().collect::<Vec<_>>(),
+            [PathBuf::from("src/answer.txt")]
+        );
+
+        // once changed, generated files are watched like any other student file
+        std::fs::write(temp.path().join("src/generated.txt"), "edited").unwrap();
+        let snapshot = snapshot_student_files(temp.path(), policy.as_ref(), &generated);
+        assert_eq!(
+            snapshot.into_keys().collect::<Vec<_>>(),
+            [
+                PathBuf::from("src/answer.txt"),
+                PathBuf::from("src/generated.txt")
+            ]
+        );
+    }
+}
@@ -21,6 +21,8 @@ pub enum MoocClientError {
     UrlParse(String, #[source] url::ParseError),
     #[error("Authentication required")]
     NotAuthenticated,
+    #[error("The authentication token has expired")]
+    TokenExpired,
     #[error("Failed to attach file to submission form: {error}")]
     AttachFileToForm { error: Box<dyn Error + Send + Sync> },
     #[error("Failed to send {method} request to {url}: {error}.")]
@@ -43,3 +45,10 @@ pub enum MoocClientError {
     #[error(transparent)]
     JsonError(#[from] tmc_langs_util::JsonError),
 }
+
+impl MoocClientError {
+    /// Whether the server could not be reached at all, for example due to a missing network connection.
+    pub fn is_connection_error(&self) -> bool {
+        matches!(self, Self::ConnectionError(..))
+    }
+}
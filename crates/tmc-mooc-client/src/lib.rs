@@ -39,6 +39,7 @@ struct MoocClientInner {
     client: Client,
     root_url: Url,
     token: Option<api::Token>,
+    token_expires_at: Option<DateTime<Utc>>,
 }
 
 /// Non-API methods.
@@ -56,6 +57,7 @@ impl MoocClient {
             client: Client::new(),
             root_url,
             token: None,
+            token_expires_at: None,
         }))
     }
 
@@ -68,8 +70,11 @@ impl MoocClient {
             .map(|d| trusted_domains.contains(&d))
             .unwrap_or_default();
         let mut builder = self.0.client.request(method.clone(), url.clone());
+        let token_expired = self.is_token_expired();
         if let Some(token) = self.0.token.as_ref() {
-            if is_trusted_domain {
+            if token_expired {
+                log::debug!("leaving out expired bearer token");
+            } else if is_trusted_domain {
                 log::debug!("setting bearer token");
                 builder = builder.bearer_auth(token.access_token().secret());
             } else {
@@ -82,13 +87,26 @@ impl MoocClient {
             url,
             method,
             builder,
+            token_expired,
         }
     }
 
-    pub fn set_token(&mut self, token: api::Token) {
-        Arc::get_mut(&mut self.0)
-            .expect("called when multiple clones exist")
-            .token = Some(token);
+    /// Sets the authentication token. If the expiry time is known,
+    /// requests fail with `MoocClientError::TokenExpired` without being sent once the token has expired.
+    pub fn set_token(&mut self, token: api::Token, expires_at: Option<DateTime<Utc>>) {
+        let inner = Arc::get_mut(&mut self.0).expect("called when multiple clones exist");
+        inner.token = Some(token);
+        inner.token_expires_at = expires_at;
+    }
+
+    /// Checks whether the client has a token that has expired.
+    pub fn is_token_expired(&self) -> bool {
+        self.0.token.is_some()
+            && self
+                .0
+                .token_expires_at
+                .map(|expires_at| expires_at <= Utc::now())
+                .unwrap_or_default()
     }
 }
 
@@ -220,6 +238,7 @@ struct MoocRequest {
     url: Url,
     method: Method,
     builder: RequestBuilder,
+    token_expired: bool,
 }
 
 impl MoocRequest {
@@ -234,6 +253,9 @@ impl MoocRequest {
     }
 
     fn send(self) -> MoocClientResult<Response> {
+        if self.token_expired {
+            return Err(Box::new(MoocClientError::TokenExpired));
+        }
         match self.builder.send() {
             Ok(res) => {
                 let status = res.status();
@@ -467,7 +489,7 @@ mod test {
             BasicTokenType::Bearer,
             EmptyExtraTokenFields {},
         );
-        client.set_token(token);
+        client.set_token(token, None);
         client
     }
 
@@ -639,4 +661,26 @@ mod test {
             ExerciseTaskSubmissionStatus::Grading { .. }
         ));
     }
+
+    #[test]
+    fn fails_with_expired_token() {
+        init();
+        let mut server = Server::new();
+        let mut client = MoocClient::new(server.url().parse().unwrap());
+        let token = Token::new(
+            AccessToken::new("".to_string()),
+            BasicTokenType::Bearer,
+            EmptyExtraTokenFields {},
+        );
+        client.set_token(token, Some(Utc::now() - chrono::Duration::hours(1)));
+        let m = server
+            .mock("GET", "/api/v0/langs/course-instances")
+            .expect(0)
+            .create();
+        assert!(matches!(
+            *client.course_instances().unwrap_err(),
+            MoocClientError::TokenExpired
+        ));
+        m.assert();
+    }
 }
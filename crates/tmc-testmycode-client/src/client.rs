@@ -6,9 +6,10 @@ use self::api_v8::{PasteData, ReviewData};
 use crate::{
     TestMyCodeClientResult, error::TestMyCodeClientError, request::FeedbackAnswer, response::*,
 };
+use chrono::{DateTime, Utc};
 use oauth2::{
-    AuthUrl, ClientId, ClientSecret, ResourceOwnerPassword, ResourceOwnerUsername, TokenUrl,
-    basic::BasicClient,
+    AuthUrl, ClientId, ClientSecret, EndpointNotSet, EndpointSet, ResourceOwnerPassword,
+    ResourceOwnerUsername, TokenResponse, TokenUrl, basic::BasicClient,
 };
 use reqwest::{
     Url,
@@ -32,6 +33,12 @@ use tmc_langs_util::progress_reporter;
 pub type Token =
     oauth2::StandardTokenResponse<oauth2::EmptyExtraTokenFields, oauth2::basic::BasicTokenType>;
 
+/// Returns the time the token expires at, assuming it was just issued.
+pub fn token_expiry(token: &Token) -> Option<DateTime<Utc>> {
+    let expires_in = chrono::Duration::from_std(token.expires_in()?).ok()?;
+    Utc::now().checked_add_signed(expires_in)
+}
+
 /// Updated exercises.
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 #[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
@@ -49,6 +56,7 @@ struct TmcCore {
     oauth_client: Client,
     root_url: Url,
     token: Option<Token>,
+    token_expires_at: Option<DateTime<Utc>>,
     client_name: String,
     client_version: String,
 }
@@ -57,13 +65,25 @@ struct TmcCore {
 impl TestMyCodeClient {
     /// Convenience function for checking authentication.
     pub fn require_authentication(&self) -> Result<(), TestMyCodeClientError> {
-        if self.0.token.is_some() {
-            Ok(())
-        } else {
+        if self.0.token.is_none() {
             Err(TestMyCodeClientError::NotAuthenticated)
+        } else if self.is_token_expired() {
+            Err(TestMyCodeClientError::TokenExpired)
+        } else {
+            Ok(())
         }
     }
 
+    /// Checks whether the client has a token that has expired.
+    pub fn is_token_expired(&self) -> bool {
+        self.0.token.is_some()
+            && self
+                .0
+                .token_expires_at
+                .map(|expires_at| expires_at <= Utc::now())
+                .unwrap_or_default()
+    }
+
     /// Creates a new TestMyCodeClient with the given config directory and root URL.
     ///
     /// # Panics
@@ -97,6 +117,7 @@ impl TestMyCodeClient {
             oauth_client,
             root_url,
             token: None,
+            token_expires_at: None,
             client_name,
             client_version,
         }));
@@ -104,13 +125,14 @@ impl TestMyCodeClient {
     }
 
     /// Sets the authentication token, which may for example have been read from a file.
+    /// If the expiry time is known, an expired token is not sent to the server and requests that require authentication fail with `TestMyCodeClientError::TokenExpired`.
     ///
     /// # Panics
     /// If called when multiple clones of the client exist. Call this function before cloning.
-    pub fn set_token(&mut self, token: Token) {
-        Arc::get_mut(&mut self.0)
-            .expect("called when multiple clones exist")
-            .token = Some(token);
+    pub fn set_token(&mut self, token: Token, expires_at: Option<DateTime<Utc>>) {
+        let core = Arc::get_mut(&mut self.0).expect("called when multiple clones exist");
+        core.token = Some(token);
+        core.token_expires_at = expires_at;
     }
 
     /// Attempts to log in with the given credentials, returns an error if an authentication token is already present.
@@ -140,6 +162,56 @@ impl TestMyCodeClient {
             return Err(Box::new(TestMyCodeClientError::AlreadyAuthenticated));
         }
 
+        let client = self.oauth2_client()?;
+        let token = client
+            .exchange_password(
+                &ResourceOwnerUsername::new(email),
+                &ResourceOwnerPassword::new(password),
+            )
+            .request(&self.0.oauth_client)
+            .map_err(TestMyCodeClientError::Token)?;
+        self.set_token(token.clone(), token_expiry(&token));
+        log::debug!("authenticated");
+        Ok(token)
+    }
+
+    /// Exchanges the current token's refresh token for a new token, which replaces the current one.
+    ///
+    /// # Errors
+    /// If the client has no token, the token has no refresh token, or if the server rejects the refresh.
+    ///
+    /// # Panics
+    /// If called when multiple clones exist. Call this function before cloning.
+    pub fn refresh_token(&mut self) -> TestMyCodeClientResult<Token> {
+        let refresh_token = self
+            .0
+            .token
+            .as_ref()
+            .ok_or(TestMyCodeClientError::NotAuthenticated)?
+            .refresh_token()
+            .ok_or(TestMyCodeClientError::NoRefreshToken)?
+            .clone();
+
+        let client = self.oauth2_client()?;
+        let mut token = client
+            .exchange_refresh_token(&refresh_token)
+            .request(&self.0.oauth_client)
+            .map_err(TestMyCodeClientError::Token)?;
+        if token.refresh_token().is_none() {
+            // the server may keep using the old refresh token
+            token.set_refresh_token(Some(refresh_token));
+        }
+        self.set_token(token.clone(), token_expiry(&token));
+        log::debug!("refreshed token");
+        Ok(token)
+    }
+
+    // creates an OAuth2 client with the credentials of the client application
+    fn oauth2_client(
+        &self,
+    ) -> TestMyCodeClientResult<
+        BasicClient<EndpointSet, EndpointNotSet, EndpointNotSet, EndpointNotSet, EndpointSet>,
+    > {
         let auth_url = self.0.root_url.join("/oauth/token").map_err(|e| {
             TestMyCodeClientError::UrlParse(self.0.root_url.to_string() + "/oauth/token", e)
         })?;
@@ -151,19 +223,7 @@ impl TestMyCodeClient {
             .set_client_secret(ClientSecret::new(credentials.secret))
             .set_auth_uri(AuthUrl::from_url(auth_url.clone()))
             .set_token_uri(TokenUrl::from_url(auth_url));
-
-        let token = client
-            .exchange_password(
-                &ResourceOwnerUsername::new(email),
-                &ResourceOwnerPassword::new(password),
-            )
-            .request(&self.0.oauth_client)
-            .map_err(TestMyCodeClientError::Token)?;
-        Arc::get_mut(&mut self.0)
-            .expect("called when multiple clones exist")
-            .token = Some(token.clone());
-        log::debug!("authenticated");
-        Ok(token)
+        Ok(client)
     }
 
     /// Fetches the course's information. Does not require authentication.
@@ -221,6 +281,29 @@ impl TestMyCodeClient {
         Ok(result)
     }
 
+    /// Sends an already compressed submission to the server. The archive should be a zip, as compressed by `submit`. Requires authentication.
+    ///
+    /// # Errors
+    /// If not authenticated, there's some problem reaching the API, or if the API returns an error.
+    pub fn submit_archive(
+        &self,
+        exercise_id: u32,
+        archive: Vec<u8>,
+        locale: Option<Language>,
+    ) -> TestMyCodeClientResult<NewSubmission> {
+        self.require_authentication()?;
+
+        let result = api_v8::core::submit_exercise(
+            self,
+            exercise_id,
+            Cursor::new(archive),
+            None,
+            None,
+            locale,
+        )?;
+        Ok(result)
+    }
+
     /// Downloads an old submission. Requires authentication.
     ///
     /// # Errors
@@ -317,6 +400,32 @@ impl TestMyCodeClient {
         Ok(res.into_iter().collect())
     }
 
+    /// Fetches all of the points awarded in the course. Requires authentication as a teacher or an admin.
+    ///
+    /// # Errors
+    /// If not authenticated, there's some problem reaching the API, or if the API returns an error.
+    pub fn get_course_points(
+        &self,
+        course_id: u32,
+    ) -> TestMyCodeClientResult<Vec<CourseDataExercisePoint>> {
+        self.require_authentication()?;
+        let res = api_v8::point::get_course_points_by_id(self, course_id)?;
+        Ok(res)
+    }
+
+    /// Fetches all of the submissions to the course visible to the user. Requires authentication.
+    ///
+    /// # Errors
+    /// If not authenticated, there's some problem reaching the API, or if the API returns an error.
+    pub fn get_course_submissions(
+        &self,
+        course_id: u32,
+    ) -> TestMyCodeClientResult<Vec<Submission>> {
+        self.require_authentication()?;
+        let res = api_v8::submission::get_course_submissions_by_id(self, course_id)?;
+        Ok(res)
+    }
+
     /// Request code review. Requires authentication.
     ///
     /// # Errors
@@ -742,7 +851,7 @@ mod test {
             BasicTokenType::Bearer,
             EmptyExtraTokenFields {},
         );
-        client.set_token(token);
+        client.set_token(token, None);
         client
     }
 
@@ -910,6 +1019,53 @@ mod test {
         m.assert();
     }
 
+    #[test]
+    fn refreshes_expired_token() {
+        init();
+        let mut server = Server::new();
+        let mut client = make_client(&server);
+        let mut token = Token::new(
+            AccessToken::new("old".to_string()),
+            BasicTokenType::Bearer,
+            EmptyExtraTokenFields {},
+        );
+        token.set_refresh_token(Some(oauth2::RefreshToken::new("refresh".to_string())));
+        client.set_token(token, Some(Utc::now() - chrono::Duration::hours(1)));
+        assert!(matches!(
+            client.require_authentication(),
+            Err(TestMyCodeClientError::TokenExpired)
+        ));
+
+        let _credentials = server
+            .mock("GET", "/api/v8/application/some_client/credentials")
+            .match_query(Matcher::Any)
+            .with_body(r#"{"application_id":"id","secret":"secret"}"#)
+            .create();
+        let m = server
+            .mock("POST", "/oauth/token")
+            .match_body(Matcher::Regex("refresh_token=refresh".to_string()))
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"access_token":"new","token_type":"bearer","expires_in":3600}"#)
+            .create();
+
+        let token = client.refresh_token().unwrap();
+        m.assert();
+        assert_eq!(token.access_token().secret(), "new");
+        assert_eq!(token.refresh_token().unwrap().secret(), "refresh");
+        assert!(client.require_authentication().is_ok());
+    }
+
+    #[test]
+    fn cannot_refresh_without_refresh_token() {
+        init();
+        let server = Server::new();
+        let mut client = make_client(&server);
+        assert!(matches!(
+            *client.refresh_token().unwrap_err(),
+            TestMyCodeClientError::NoRefreshToken
+        ));
+    }
+
     #[test]
     fn asd() {}
 }
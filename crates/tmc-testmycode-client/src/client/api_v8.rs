@@ -52,10 +52,10 @@ fn prepare_tmc_request(client: &TestMyCodeClient, method: Method, url: Url) -> R
         ("client", &client.0.client_name),
         ("client_version", &client.0.client_version),
     ]);
-    if let Some(token) = &client.0.token {
-        req.bearer_auth(token.access_token().secret())
-    } else {
-        req
+    match &client.0.token {
+        // an expired token would be rejected, so the request is made without it
+        Some(token) if !client.is_token_expired() => req.bearer_auth(token.access_token().secret()),
+        _ => req,
     }
 }
 
@@ -25,7 +25,7 @@ pub enum TestMyCodeClientError {
     },
     #[error("Connection error trying to {0} {1}")]
     ConnectionError(Method, Url, #[source] reqwest::Error),
-    #[error("OAuth2 password exchange error")]
+    #[error("OAuth2 token exchange error")]
     Token(#[source] TokenError),
     #[error("Failed to parse as URL: {0}")]
     UrlParse(String, #[source] url::ParseError),
@@ -42,6 +42,10 @@ pub enum TestMyCodeClientError {
     AlreadyAuthenticated,
     #[error("Authentication required")]
     NotAuthenticated,
+    #[error("The authentication token has expired")]
+    TokenExpired,
+    #[error("The authentication token cannot be refreshed because it has no refresh token")]
+    NoRefreshToken,
 
     #[error(transparent)]
     SystemTime(#[from] std::time::SystemTimeError),
@@ -52,3 +56,10 @@ pub enum TestMyCodeClientError {
     #[error(transparent)]
     Plugin(#[from] tmc_langs_plugins::PluginError),
 }
+
+impl TestMyCodeClientError {
+    /// Whether the server could not be reached at all, for example due to a missing network connection.
+    pub fn is_connection_error(&self) -> bool {
+        matches!(self, Self::ConnectionError(..))
+    }
+}
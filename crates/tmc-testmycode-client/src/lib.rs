@@ -17,7 +17,7 @@ pub mod request;
 pub mod response;
 
 pub use self::{
-    client::{ClientUpdateData, TestMyCodeClient, Token, UpdateResult, api_v8},
+    client::{ClientUpdateData, TestMyCodeClient, Token, UpdateResult, api_v8, token_expiry},
     error::{TestMyCodeClientError, TestMyCodeClientResult},
 };
 // these types are part of tmc-testmycode-client's API and thus re-exported
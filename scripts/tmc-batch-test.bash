@@ -5,5 +5,6 @@
 # 1. The tests fail on the template (some exercises are intended to pass on the template, so failing this is just a warning)
 # 2. The tests pass on the example solution
 # 3. The tests pass when the example solution is packaged like a submission and extracted over the template
+# To run the same checks on a local course repository, use `tmc-langs-cli check-course --course-path <path>` instead
 
 cargo test test_policies_on_course_exercises -- --ignored --nocapture | tee test-cache/out.log